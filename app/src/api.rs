@@ -1,9 +1,9 @@
 use crate::error_utils::{
     approve_rewrite_rule, create_client_from_session, create_storage, deserialize_session,
-    remove_pending_edit, remove_pending_rule, with_timeout, ToBoxError,
+    decide_pending_edit, load_pending_edit, remove_pending_rule, with_timeout, ToBoxError,
 };
 use lastfm_edit::{LastFmEditClient, Track};
-use scrobble_scrubber::persistence::{PendingEdit, PendingRewriteRule};
+use scrobble_scrubber::persistence::{EditDecision, PendingEdit, PendingRewriteRule};
 
 pub async fn login_to_lastfm(
     username: String,
@@ -426,7 +426,8 @@ pub async fn approve_pending_edit(
     edit_id: String,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let storage = create_storage().await?;
-    let approved_edit = remove_pending_edit(&storage, &edit_id).await?;
+    // The edit stays pending until Last.fm accepts it
+    let approved_edit = load_pending_edit(&storage, &edit_id).await?;
 
     // Deserialize session
     let session = deserialize_session(&session_str)?;
@@ -443,6 +444,7 @@ pub async fn approve_pending_edit(
         })?;
 
     log::info!("Successfully applied edit to Last.fm: {result:?}");
+    decide_pending_edit(&storage, &edit_id, EditDecision::Approved).await?;
     Ok("Edit approved and applied to Last.fm".to_string())
 }
 
@@ -450,7 +452,7 @@ pub async fn reject_pending_edit(
     edit_id: String,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let storage = create_storage().await?;
-    remove_pending_edit(&storage, &edit_id).await?;
+    decide_pending_edit(&storage, &edit_id, EditDecision::Rejected).await?;
     Ok("Edit rejected and removed".to_string())
 }
 
@@ -1,9 +1,10 @@
 use crate::types::AppState;
 use dioxus::prelude::*;
 use scrobble_scrubber::config::{
-    CompilationToCanonicalConfig, JsonLoggingConfig, LastFmConfig, MusicBrainzProviderConfig,
-    OpenAIProviderConfig, ProvidersConfig, ScrobbleScrubberConfig, ScrubberConfig, StorageConfig,
-    TrackProviderType,
+    CompilationToCanonicalConfig, ConfidenceThresholds, ConflictPolicy, GreatestHitsPolicy,
+    JsonLoggingConfig, LastFmConfig, MajorityAlbumConfig, MusicBrainzConfig,
+    MusicBrainzProviderConfig, OpenAIProviderConfig, ProvidersConfig, ScrobbleScrubberConfig,
+    ScrubberConfig, StorageBackend, StorageConfig, TrackProviderType,
 };
 
 #[component]
@@ -14,6 +15,7 @@ pub fn ConfigPage(state: Signal<AppState>) -> Element {
     let providers_config = use_signal(|| config.providers.clone());
     let storage_config = use_signal(|| config.storage.clone());
     let lastfm_config = use_signal(|| config.lastfm.clone());
+    let musicbrainz_config = use_signal(|| config.musicbrainz.clone());
 
     let mut save_status = use_signal(|| None::<String>);
 
@@ -24,6 +26,7 @@ pub fn ConfigPage(state: Signal<AppState>) -> Element {
                 providers: providers_config.read().clone(),
                 storage: storage_config.read().clone(),
                 lastfm: lastfm_config.read().clone(),
+                musicbrainz: musicbrainz_config.read().clone(),
             };
 
             match save_config_to_file(&new_config).await {
@@ -95,6 +98,9 @@ pub fn ConfigPage(state: Signal<AppState>) -> Element {
                 // Last.fm Configuration Section
                 LastFmConfigSection { config: lastfm_config }
 
+                // MusicBrainz Cache and Rate Limit Section
+                MusicBrainzRequestsConfigSection { config: musicbrainz_config }
+
                 // Save Button at Bottom
                 div {
                     style: "margin-top: 2rem; padding-top: 2rem; border-top: 1px solid #e5e7eb;",
@@ -149,6 +155,85 @@ fn ScrubberConfigSection(config: Signal<ScrubberConfig>) -> Element {
                 onchange: move |value| config.with_mut(|c| c.track_provider = value),
             }
 
+            SelectInput {
+                label: "Conflict Resolution",
+                value: match config.read().conflict_policy {
+                    ConflictPolicy::ProviderPriority => "provider_priority",
+                    ConflictPolicy::HighestConfidence => "highest_confidence",
+                    ConflictPolicy::PendingReview => "pending_review",
+                }
+                .to_string(),
+                options: vec![
+                    ("provider_priority".to_string(), "Provider priority".to_string()),
+                    ("highest_confidence".to_string(), "Highest confidence".to_string()),
+                    ("pending_review".to_string(), "Send to pending review".to_string()),
+                ],
+                onchange: move |value: String| {
+                    let policy = match value.as_str() {
+                        "highest_confidence" => ConflictPolicy::HighestConfidence,
+                        "pending_review" => ConflictPolicy::PendingReview,
+                        _ => ConflictPolicy::ProviderPriority,
+                    };
+                    config.with_mut(|c| c.conflict_policy = policy);
+                },
+                help: "What to do when providers suggest different values for the same field; highest confidence falls back to provider priority for suggestions without a confidence"
+            }
+
+            TextInput {
+                label: "Provider Priority",
+                value: config.read().provider_priority.join(", "),
+                onchange: move |value: String| {
+                    config.with_mut(|c| {
+                        c.provider_priority = value
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|name| !name.is_empty())
+                            .map(str::to_string)
+                            .collect();
+                    })
+                },
+                help: "Comma-separated provider names, highest priority first (e.g. RewriteRules, CompilationToCanonical)"
+            }
+
+            CheckboxInput {
+                label: "Confidence-Based Auto-Apply",
+                checked: config.read().confidence_thresholds.is_some(),
+                onchange: move |checked: bool| {
+                    config.with_mut(|c| {
+                        c.confidence_thresholds = checked.then(ConfidenceThresholds::default);
+                    })
+                },
+                help: "Decide by confidence whether suggestions are applied, sent to pending review or dropped (per-provider thresholds can be set in the config file)"
+            }
+
+            if let Some(thresholds) = config.read().confidence_thresholds {
+                NumberInput {
+                    label: "Auto-Apply Confidence (%)",
+                    value: (thresholds.auto_apply * 100.0) as u64,
+                    onchange: move |value| {
+                        config.with_mut(|c| {
+                            if let Some(thresholds) = c.confidence_thresholds.as_mut() {
+                                thresholds.auto_apply = (value as f32) / 100.0;
+                            }
+                        })
+                    },
+                    help: "Suggestions at least this confident are applied without confirmation"
+                }
+
+                NumberInput {
+                    label: "Pending Review Confidence (%)",
+                    value: (thresholds.pending * 100.0) as u64,
+                    onchange: move |value| {
+                        config.with_mut(|c| {
+                            if let Some(thresholds) = c.confidence_thresholds.as_mut() {
+                                thresholds.pending = (value as f32) / 100.0;
+                            }
+                        })
+                    },
+                    help: "Less confident suggestions are dropped; the rest need confirmation"
+                }
+            }
+
             JsonLoggingSection { config: config.read().json_logging.clone(), onchange: move |new_config| config.with_mut(|c| c.json_logging = new_config) }
         }
     }
@@ -210,6 +295,27 @@ fn ProvidersConfigSection(config: Signal<ProvidersConfig>) -> Element {
                     onchange: move |new_config| config.with_mut(|c| c.compilation_to_canonical = Some(new_config))
                 }
             }
+
+            CheckboxInput {
+                label: "Enable Majority Album Provider",
+                checked: config.read().enable_majority_album,
+                onchange: move |checked| config.with_mut(|c| c.enable_majority_album = checked),
+                help: "Move new scrobbles to the album most of the track's earlier plays are on"
+            }
+
+            if config.read().enable_majority_album {
+                MajorityAlbumConfigSection {
+                    config: config.read().majority_album.clone().unwrap_or_default(),
+                    onchange: move |new_config| config.with_mut(|c| c.majority_album = Some(new_config))
+                }
+            }
+
+            CheckboxInput {
+                label: "Chain Providers",
+                checked: config.read().chain_providers,
+                onchange: move |checked| config.with_mut(|c| c.chain_providers = checked),
+                help: "Run providers in order, each seeing the previous providers' output, and merge their changes into one edit per track"
+            }
         }
     }
 }
@@ -224,6 +330,85 @@ fn StorageConfigSection(config: Signal<StorageConfig>) -> Element {
                 onchange: move |value| config.with_mut(|c| c.state_file = value),
                 help: "Path to the file where application state is stored"
             }
+
+            SelectInput {
+                label: "Storage Backend",
+                value: match config.read().backend {
+                    StorageBackend::Pickledb => "pickledb",
+                    StorageBackend::Sqlite => "sqlite",
+                }
+                .to_string(),
+                options: vec![
+                    ("pickledb".to_string(), "PickleDB state file".to_string()),
+                    ("sqlite".to_string(), "SQLite database".to_string()),
+                ],
+                onchange: move |value: String| {
+                    let backend = match value.as_str() {
+                        "sqlite" => StorageBackend::Sqlite,
+                        _ => StorageBackend::Pickledb,
+                    };
+                    config.with_mut(|c| c.backend = backend);
+                },
+                help: "Switching to SQLite migrates the existing state file on first start"
+            }
+
+            TextInput {
+                label: "SQLite Database Path",
+                value: config.read().sqlite_file.clone().unwrap_or_default(),
+                onchange: move |value: String| {
+                    config.with_mut(|c| c.sqlite_file = (!value.is_empty()).then_some(value))
+                },
+                help: "Leave empty to use the state file path with a .sqlite extension"
+            }
+        }
+    }
+}
+
+#[component]
+fn MusicBrainzRequestsConfigSection(config: Signal<MusicBrainzConfig>) -> Element {
+    rsx! {
+        ConfigSection { title: "MusicBrainz Requests",
+            CheckboxInput {
+                label: "Cache MusicBrainz Responses",
+                checked: config.read().cache.enabled,
+                onchange: move |checked| config.with_mut(|c| c.cache.enabled = checked),
+                help: "Keep MusicBrainz search and release lookups on disk between runs"
+            }
+
+            NumberInput {
+                label: "Cache TTL (hours)",
+                value: config.read().cache.ttl_hours,
+                onchange: move |value| config.with_mut(|c| c.cache.ttl_hours = value),
+                help: "How long cached responses are used before being fetched again"
+            }
+
+            NumberInput {
+                label: "Maximum Cached Responses",
+                value: config.read().cache.max_entries as u64,
+                onchange: move |value: u64| config.with_mut(|c| c.cache.max_entries = value as usize),
+                help: "The oldest responses are evicted once the cache grows past this size"
+            }
+
+            TextInput {
+                label: "Cache File Path (optional)",
+                value: config.read().cache.path.clone().unwrap_or_default(),
+                onchange: move |value: String| config.with_mut(|c| c.cache.path = if value.is_empty() { None } else { Some(value) }),
+                help: "Defaults to musicbrainz_cache.json next to the state file"
+            }
+
+            NumberInput {
+                label: "Burst Size",
+                value: config.read().rate_limit.burst as u64,
+                onchange: move |value: u64| config.with_mut(|c| c.rate_limit.burst = value.max(1) as u32),
+                help: "Requests sent back to back before MusicBrainz lookups are paced to the configured rate"
+            }
+
+            NumberInput {
+                label: "Retries When Throttled",
+                value: config.read().rate_limit.max_retries as u64,
+                onchange: move |value: u64| config.with_mut(|c| c.rate_limit.max_retries = value as u32),
+                help: "How often a request MusicBrainz rejects with 503 is retried, with exponential backoff"
+            }
         }
     }
 }
@@ -367,7 +552,9 @@ impl PartialEq for MusicBrainzConfigSectionProps {
     fn eq(&self, other: &Self) -> bool {
         self.config.confidence_threshold == other.config.confidence_threshold
             && self.config.max_results == other.config.max_results
-            && self.config.api_delay_ms == other.config.api_delay_ms
+            && self.config.base_url == other.config.base_url
+            && self.config.user_agent == other.config.user_agent
+            && self.config.contact == other.config.contact
     }
 }
 
@@ -402,11 +589,25 @@ fn MusicBrainzConfigSection(props: MusicBrainzConfigSectionProps) -> Element {
                 help: "Maximum number of search results to examine"
             }
 
-            NumberInput {
-                label: "API Delay (milliseconds)",
-                value: local_config.read().api_delay_ms,
-                onchange: move |value| local_config.with_mut(|c| c.api_delay_ms = value),
-                help: "Delay between API requests to be respectful to MusicBrainz"
+            TextInput {
+                label: "Server URL (optional)",
+                value: local_config.read().base_url.clone().unwrap_or_default(),
+                onchange: move |value: String| local_config.with_mut(|c| c.base_url = if value.is_empty() { None } else { Some(value) }),
+                help: "Local MusicBrainz mirror, e.g. http://localhost:5000 (defaults to https://musicbrainz.org); mirrors are not limited to one request per second"
+            }
+
+            TextInput {
+                label: "User Agent (optional)",
+                value: local_config.read().user_agent.clone().unwrap_or_default(),
+                onchange: move |value: String| local_config.with_mut(|c| c.user_agent = if value.is_empty() { None } else { Some(value) }),
+                help: "Application name sent to MusicBrainz (defaults to scrobble-scrubber/<version>)"
+            }
+
+            TextInput {
+                label: "Contact (optional)",
+                value: local_config.read().contact.clone().unwrap_or_default(),
+                onchange: move |value: String| local_config.with_mut(|c| c.contact = if value.is_empty() { None } else { Some(value) }),
+                help: "URL or email MusicBrainz can reach you at, added to the user agent"
             }
         }
     }
@@ -422,6 +623,7 @@ impl PartialEq for CompilationToCanonicalConfigSectionProps {
     fn eq(&self, other: &Self) -> bool {
         self.config.confidence_threshold == other.config.confidence_threshold
             && self.config.enabled == other.config.enabled
+            && self.config.greatest_hits_policy == other.config.greatest_hits_policy
     }
 }
 
@@ -455,6 +657,106 @@ fn CompilationToCanonicalConfigSection(props: CompilationToCanonicalConfigSectio
                 onchange: move |checked| local_config.with_mut(|c| c.enabled = checked),
                 help: "Enable or disable the provider"
             }
+
+            SelectInput {
+                label: "Greatest Hits Albums",
+                value: match local_config.read().greatest_hits_policy {
+                    GreatestHitsPolicy::Keep => "keep",
+                    GreatestHitsPolicy::MapToOriginal => "map_to_original",
+                    GreatestHitsPolicy::MapIfOlder { .. } => "map_if_older",
+                }
+                .to_string(),
+                options: vec![
+                    ("keep".to_string(), "Keep tracks on greatest hits albums".to_string()),
+                    ("map_to_original".to_string(), "Move tracks to their original album".to_string()),
+                    ("map_if_older".to_string(), "Move tracks only if the original is much older".to_string()),
+                ],
+                onchange: move |value: String| {
+                    let policy = match value.as_str() {
+                        "keep" => GreatestHitsPolicy::Keep,
+                        "map_if_older" => GreatestHitsPolicy::MapIfOlder { years: 10 },
+                        _ => GreatestHitsPolicy::MapToOriginal,
+                    };
+                    local_config.with_mut(|c| c.greatest_hits_policy = policy);
+                },
+                help: "What to do with tracks on single-artist best-of albums"
+            }
+
+            if matches!(local_config.read().greatest_hits_policy, GreatestHitsPolicy::MapIfOlder { .. }) {
+                NumberInput {
+                    label: "Minimum Age Gap (years)",
+                    value: match local_config.read().greatest_hits_policy {
+                        GreatestHitsPolicy::MapIfOlder { years } => u64::from(years),
+                        _ => 0,
+                    },
+                    onchange: move |value: u64| local_config.with_mut(|c| {
+                        c.greatest_hits_policy = GreatestHitsPolicy::MapIfOlder {
+                            years: u32::try_from(value).unwrap_or(u32::MAX),
+                        }
+                    }),
+                    help: "Only move a track when its original release is at least this much older than the compilation"
+                }
+            }
+        }
+    }
+}
+
+#[derive(Props, Clone)]
+struct MajorityAlbumConfigSectionProps {
+    config: MajorityAlbumConfig,
+    onchange: EventHandler<MajorityAlbumConfig>,
+}
+
+impl PartialEq for MajorityAlbumConfigSectionProps {
+    fn eq(&self, other: &Self) -> bool {
+        self.config == other.config
+    }
+}
+
+#[component]
+fn MajorityAlbumConfigSection(props: MajorityAlbumConfigSectionProps) -> Element {
+    let MajorityAlbumConfigSectionProps { config, onchange } = props;
+    let mut local_config = use_signal(|| config.clone());
+
+    use_effect(move || {
+        onchange.call(local_config.read().clone());
+    });
+
+    rsx! {
+        div {
+            style: "margin-top: 1rem; padding: 1rem; background-color: #f9fafb; border-radius: 0.5rem;",
+            h4 {
+                style: "font-weight: 600; margin-bottom: 1rem; color: #374151;",
+                "Majority Album Configuration"
+            }
+
+            NumberInput {
+                label: "Majority Threshold",
+                value: (local_config.read().majority_threshold * 100.0) as u64,
+                onchange: move |value| local_config.with_mut(|c| c.majority_threshold = (value as f32) / 100.0),
+                help: "Percentage of a track's plays an album needs before other scrobbles are moved to it (0-100)"
+            }
+
+            NumberInput {
+                label: "Minimum History",
+                value: local_config.read().min_history,
+                onchange: move |value| local_config.with_mut(|c| c.min_history = value),
+                help: "Minimum number of plays of a track before suggesting its majority album"
+            }
+
+            CheckboxInput {
+                label: "Fetch Missing History",
+                checked: local_config.read().fetch_missing_history,
+                onchange: move |checked| local_config.with_mut(|c| c.fetch_missing_history = checked),
+                help: "Load an artist's library tracks from Last.fm when they aren't cached yet"
+            }
+
+            CheckboxInput {
+                label: "Require Confirmation",
+                checked: local_config.read().require_confirmation,
+                onchange: move |checked| local_config.with_mut(|c| c.require_confirmation = checked),
+                help: "Queue majority album suggestions for review instead of applying them"
+            }
         }
     }
 }
@@ -724,12 +1026,26 @@ fn check_if_scrubber_restart_needed(
     old_config.scrubber.interval != new_config.scrubber.interval
         || old_config.scrubber.dry_run != new_config.scrubber.dry_run
         || old_config.scrubber.track_provider != new_config.scrubber.track_provider
+        || old_config.scrubber.conflict_policy != new_config.scrubber.conflict_policy
+        || old_config.scrubber.provider_priority != new_config.scrubber.provider_priority
+        || old_config.scrubber.confidence_thresholds != new_config.scrubber.confidence_thresholds
+        || old_config.scrubber.provider_confidence_thresholds
+            != new_config.scrubber.provider_confidence_thresholds
         || old_config.providers.enable_rewrite_rules != new_config.providers.enable_rewrite_rules
         || old_config.providers.enable_openai != new_config.providers.enable_openai
         || old_config.providers.enable_musicbrainz != new_config.providers.enable_musicbrainz
         || old_config.providers.openai != new_config.providers.openai
         || old_config.providers.musicbrainz != new_config.providers.musicbrainz
+        || old_config.providers.enable_compilation_to_canonical
+            != new_config.providers.enable_compilation_to_canonical
+        || old_config.providers.compilation_to_canonical
+            != new_config.providers.compilation_to_canonical
+        || old_config.providers.enable_majority_album != new_config.providers.enable_majority_album
+        || old_config.providers.majority_album != new_config.providers.majority_album
+        || old_config.providers.chain_providers != new_config.providers.chain_providers
         || old_config.storage.state_file != new_config.storage.state_file
+        || old_config.storage.backend != new_config.storage.backend
+        || old_config.storage.sqlite_file != new_config.storage.sqlite_file
         || old_config.lastfm.username != new_config.lastfm.username
         || old_config.lastfm.password != new_config.lastfm.password
         || old_config.lastfm.base_url != new_config.lastfm.base_url
@@ -126,6 +126,9 @@ pub fn PendingEditsPage(state: Signal<AppState>) -> Element {
                                 new_track_name: edit.new_track_name.clone(),
                                 new_artist_name: edit.new_artist_name.clone(),
                                 new_album_name: edit.new_album_name.clone(),
+                                conflicts: edit.conflicts.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                                confidence: edit.confidence,
+                                explanation: edit.explanation.clone(),
                                 on_approve: {
                                     let edit_id = edit.id.clone();
                                     let handler = create_operation_handler(
@@ -174,6 +177,9 @@ fn PendingEditCard(
     new_track_name: Option<String>,
     new_artist_name: Option<String>,
     new_album_name: Option<String>,
+    conflicts: Vec<String>,
+    confidence: Option<f32>,
+    explanation: Option<String>,
     on_approve: EventHandler<()>,
     on_reject: EventHandler<()>,
 ) -> Element {
@@ -210,6 +216,32 @@ fn PendingEditCard(
                             }
                         }
                     }
+
+                    if !conflicts.is_empty() {
+                        div { style: "margin-top: 0.75rem; padding: 0.5rem; background: #fef3c7; border-radius: 0.25rem; font-size: 0.875rem; color: #92400e;",
+                            strong { "Providers disagree:" }
+                            for conflict in conflicts.iter() {
+                                div { "{conflict}" }
+                            }
+                        }
+                    }
+
+                    if confidence.is_some() || explanation.is_some() {
+                        div { style: "margin-top: 0.75rem; font-size: 0.875rem; color: #4b5563;",
+                            if let Some(confidence) = confidence {
+                                div {
+                                    strong { "Confidence: " }
+                                    {format!("{:.0}%", confidence * 100.0)}
+                                }
+                            }
+                            if let Some(explanation) = &explanation {
+                                div {
+                                    strong { "Reason: " }
+                                    "{explanation}"
+                                }
+                            }
+                        }
+                    }
                 }
 
                 div { style: "display: flex; gap: 0.5rem;",
@@ -48,6 +48,7 @@ pub fn RewriteRulesPage(mut state: Signal<AppState>) -> Element {
                     // Rules list
                     {
                         let saved_rules = state.read().saved_rules.clone();
+                        let rule_stats = state.read().rule_stats.clone();
                         if saved_rules.is_empty() {
                             rsx! {
                                 div { style: "text-center; color: #6b7280; padding: 2rem;",
@@ -103,6 +104,24 @@ pub fn RewriteRulesPage(mut state: Signal<AppState>) -> Element {
                                                             }
                                                         }
                                                     }
+
+                                                    if let Some(stats) = rule_stats.for_rule(rule) {
+                                                        div { style: "font-size: 0.75rem; color: #6b7280; margin-top: 0.5rem;",
+                                                            div {
+                                                                "Matched {stats.matched} · Produced {stats.produced} · Applied {stats.applied} · Rejected by MusicBrainz {stats.rejected_by_musicbrainz}"
+                                                            }
+                                                            if let Some(last_fired) = stats.last_fired {
+                                                                div { "Last fired: {last_fired.format(\"%Y-%m-%d %H:%M\")}" }
+                                                            }
+                                                            if let Some(track) = stats.last_matched_track.as_ref() {
+                                                                div { "Last matched: {track}" }
+                                                            }
+                                                        }
+                                                    } else {
+                                                        div { style: "font-size: 0.75rem; color: #9ca3af; margin-top: 0.5rem;",
+                                                            "Never matched"
+                                                        }
+                                                    }
                                                 }
                                                 button {
                                                     style: "background: #dc2626; color: white; padding: 0.5rem 1rem; border: none; border-radius: 0.375rem; cursor: pointer; font-size: 0.875rem; margin-top: 0.5rem;",
@@ -1,6 +1,6 @@
 use crate::types::AppState;
 use crate::utils::{copy_to_clipboard, save_current_rule};
-use ::scrobble_scrubber::rewrite::{RewriteRule, SdRule};
+use ::scrobble_scrubber::rewrite::{ReplacementMode, RewriteRule, SdRule};
 use dioxus::prelude::*;
 
 #[component]
@@ -15,6 +15,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
     let mut album_replace = use_signal(String::new);
     let mut album_artist_find = use_signal(String::new);
     let mut album_artist_replace = use_signal(String::new);
+    let mut replacement_mode = use_signal(ReplacementMode::default);
 
     rsx! {
         div { style: "display: flex; flex-direction: column; gap: 1.5rem;",
@@ -29,11 +30,28 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                     oninput: move |e| {
                         rule_name.set(e.value());
                         update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
-                                       &album_find, &album_replace, &album_artist_find, &album_artist_replace);
+                                       &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
                     }
                 }
             }
 
+            // Replacement Mode
+            div { style: "border: 1px solid #e5e7eb; border-radius: 0.5rem; padding: 1rem;",
+                h3 { style: "font-weight: 600; margin-bottom: 1rem; color: #374151;", "Replacement Mode" }
+                select {
+                    style: "width: 100%; padding: 0.5rem; border: 1px solid #d1d5db; border-radius: 0.375rem;",
+                    value: mode_value(*replacement_mode.read()),
+                    onchange: move |e| {
+                        replacement_mode.set(parse_mode(&e.value()));
+                        update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
+                                       &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
+                    },
+                    option { value: "whole_string", "Whole string - replace the entire field when the pattern matches" }
+                    option { value: "first_match", "First match - replace only the first matching text" }
+                    option { value: "all_matches", "All matches - replace every matching text" }
+                }
+            }
+
             // Track Name
             div { style: "border: 1px solid #e5e7eb; border-radius: 0.5rem; padding: 1rem;",
                 h3 { style: "font-weight: 600; margin-bottom: 1rem; color: #374151;", "Track Name" }
@@ -47,7 +65,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                             oninput: move |e| {
                                 track_find.set(e.value());
                                 update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
-                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace);
+                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
                             }
                         }
                     }
@@ -60,7 +78,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                             oninput: move |e| {
                                 track_replace.set(e.value());
                                 update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
-                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace);
+                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
                             }
                         }
                     }
@@ -80,7 +98,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                             oninput: move |e| {
                                 artist_find.set(e.value());
                                 update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
-                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace);
+                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
                             }
                         }
                     }
@@ -93,7 +111,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                             oninput: move |e| {
                                 artist_replace.set(e.value());
                                 update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
-                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace);
+                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
                             }
                         }
                     }
@@ -113,7 +131,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                             oninput: move |e| {
                                 album_find.set(e.value());
                                 update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
-                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace);
+                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
                             }
                         }
                     }
@@ -126,7 +144,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                             oninput: move |e| {
                                 album_replace.set(e.value());
                                 update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
-                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace);
+                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
                             }
                         }
                     }
@@ -146,7 +164,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                             oninput: move |e| {
                                 album_artist_find.set(e.value());
                                 update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
-                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace);
+                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
                             }
                         }
                     }
@@ -159,7 +177,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                             oninput: move |e| {
                                 album_artist_replace.set(e.value());
                                 update_all_rules(state, &rule_name, &track_find, &track_replace, &artist_find, &artist_replace,
-                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace);
+                                               &album_find, &album_replace, &album_artist_find, &album_artist_replace, &replacement_mode);
                             }
                         }
                     }
@@ -222,6 +240,7 @@ pub fn RuleEditor(mut state: Signal<AppState>) -> Element {
                         album_replace.set(String::new());
                         album_artist_find.set(String::new());
                         album_artist_replace.set(String::new());
+                        replacement_mode.set(ReplacementMode::default());
                         state.with_mut(|s| s.current_rule = RewriteRule::new());
                     },
                     "Clear All Rules"
@@ -243,8 +262,10 @@ fn update_all_rules(
     album_replace: &Signal<String>,
     album_artist_find: &Signal<String>,
     album_artist_replace: &Signal<String>,
+    replacement_mode: &Signal<ReplacementMode>,
 ) {
     let mut rule = RewriteRule::new();
+    let mode = *replacement_mode.read();
 
     // Add rule name if provided
     let rule_name_str = rule_name.read();
@@ -256,7 +277,7 @@ fn update_all_rules(
     let track_find_str = track_find.read();
     let track_replace_str = track_replace.read();
     if !track_find_str.is_empty() {
-        let sd_rule = SdRule::new(&track_find_str, &track_replace_str);
+        let sd_rule = SdRule::new(&track_find_str, &track_replace_str).with_mode(mode);
         rule = rule.with_track_name(sd_rule);
     }
 
@@ -264,7 +285,7 @@ fn update_all_rules(
     let artist_find_str = artist_find.read();
     let artist_replace_str = artist_replace.read();
     if !artist_find_str.is_empty() {
-        let sd_rule = SdRule::new(&artist_find_str, &artist_replace_str);
+        let sd_rule = SdRule::new(&artist_find_str, &artist_replace_str).with_mode(mode);
         rule = rule.with_artist_name(sd_rule);
     }
 
@@ -272,7 +293,7 @@ fn update_all_rules(
     let album_find_str = album_find.read();
     let album_replace_str = album_replace.read();
     if !album_find_str.is_empty() {
-        let sd_rule = SdRule::new(&album_find_str, &album_replace_str);
+        let sd_rule = SdRule::new(&album_find_str, &album_replace_str).with_mode(mode);
         rule = rule.with_album_name(sd_rule);
     }
 
@@ -280,9 +301,28 @@ fn update_all_rules(
     let album_artist_find_str = album_artist_find.read();
     let album_artist_replace_str = album_artist_replace.read();
     if !album_artist_find_str.is_empty() {
-        let sd_rule = SdRule::new(&album_artist_find_str, &album_artist_replace_str);
+        let sd_rule =
+            SdRule::new(&album_artist_find_str, &album_artist_replace_str).with_mode(mode);
         rule = rule.with_album_artist_name(sd_rule);
     }
 
     state.with_mut(|s| s.current_rule = rule);
 }
+
+/// Select option value for a replacement mode
+fn mode_value(mode: ReplacementMode) -> &'static str {
+    match mode {
+        ReplacementMode::WholeString => "whole_string",
+        ReplacementMode::FirstMatch => "first_match",
+        ReplacementMode::AllMatches => "all_matches",
+    }
+}
+
+/// Parse a select option value back into a replacement mode
+fn parse_mode(value: &str) -> ReplacementMode {
+    match value {
+        "first_match" => ReplacementMode::FirstMatch,
+        "all_matches" => ReplacementMode::AllMatches,
+        _ => ReplacementMode::WholeString,
+    }
+}
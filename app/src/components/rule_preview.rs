@@ -3,7 +3,7 @@ use crate::types::{AppState, PreviewType};
 use crate::utils::get_current_tracks;
 use dioxus::prelude::*;
 use lastfm_edit::{ScrobbleEdit, Track};
-use scrobble_scrubber::rewrite::{any_rules_match, apply_all_rules, create_no_op_edit};
+use scrobble_scrubber::rewrite::{create_no_op_edit, CompiledRuleSet};
 use std::collections::HashMap;
 
 #[component]
@@ -23,6 +23,9 @@ pub fn RulePreview(state: Signal<AppState>, rules_type: PreviewType) -> Element
         PreviewType::AllSavedRules => state_read.saved_rules.clone(),
     };
 
+    // Compile once for all tracks; rules that fail to compile are skipped
+    let (rule_set, _invalid_rules) = CompiledRuleSet::compile_valid(rules_to_apply);
+
     // Compute matches and prepare tracks for display based on toggle
     let mut tracks_to_display = Vec::new();
     let mut matching_count = 0;
@@ -32,12 +35,12 @@ pub fn RulePreview(state: Signal<AppState>, rules_type: PreviewType) -> Element
         let track: Track = strack.clone();
 
         // Use pattern matching to check if rules match (regardless of whether they would change anything)
-        let rules_match = any_rules_match(&rules_to_apply, &track).unwrap_or(false);
+        let rules_match = rule_set.any_rule_matches(&track);
 
         // Create edit to show the result (whether changed or not)
         let mut edit = create_no_op_edit(&track);
         if rules_match {
-            let _rule_applied = apply_all_rules(&rules_to_apply, &mut edit).unwrap_or_default();
+            let _rule_applied = rule_set.apply_all(&mut edit);
         }
 
         // Show as matching if patterns match, regardless of whether output changes
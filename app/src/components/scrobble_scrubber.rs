@@ -223,7 +223,7 @@ pub async fn set_timestamp_anchor(mut state: Signal<AppState>, track: Track) {
 }
 
 async fn set_timestamp_anchor_direct(
-    storage: Arc<tokio::sync::Mutex<::scrobble_scrubber::persistence::FileStorage>>,
+    storage: Arc<tokio::sync::Mutex<::scrobble_scrubber::persistence::ConfiguredStorage>>,
     track: Track,
     state: &mut Signal<AppState>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -368,7 +368,7 @@ async fn run_scrubber_with_shared_instance(
 async fn run_scrubber_with_instance(
     mut state: Signal<AppState>,
     mut scrubber: ::scrobble_scrubber::scrubber::ScrobbleScrubber<
-        ::scrobble_scrubber::persistence::FileStorage,
+        ::scrobble_scrubber::persistence::ConfiguredStorage,
         ::scrobble_scrubber::scrub_action_provider::OrScrubActionProvider,
     >,
     sender: Arc<broadcast::Sender<ScrubberEvent>>,
@@ -466,7 +466,7 @@ async fn process_with_shared_scrubber(
 
 async fn process_with_scrubber(
     scrubber: &mut ::scrobble_scrubber::scrubber::ScrobbleScrubber<
-        ::scrobble_scrubber::persistence::FileStorage,
+        ::scrobble_scrubber::persistence::ConfiguredStorage,
         ::scrobble_scrubber::scrub_action_provider::OrScrubActionProvider,
     >,
     sender: &Arc<broadcast::Sender<ScrubberEvent>>,
@@ -516,6 +516,7 @@ async fn process_with_scrubber(
                     ::scrobble_scrubber::events::ScrubberEventType::CycleCompleted { .. } => {
                         has_cycle_completed = true;
                         state.with_mut(|s| s.scrubber_state.events.push(lib_event.clone()));
+                        crate::utils::refresh_rule_stats(state).await;
                     }
                     ::scrobble_scrubber::events::ScrubberEventType::ProcessingBatchStarted {
                         tracks,
@@ -556,10 +556,10 @@ pub async fn handle_scrubber_event(
     if let Some(rate_limit_state) =
         crate::types::event_formatting::detect_rate_limit_from_event(&lib_event)
     {
+        log::warn!("Rate limiting detected: {}", rate_limit_state.message);
         state.with_mut(|s| {
             s.scrubber_state.rate_limit_state = Some(rate_limit_state);
         });
-        log::warn!("Rate limiting detected from LastFM API");
     }
 
     // Check if rate limiting has ended
@@ -4,6 +4,25 @@ use dioxus::prelude::*;
 #[component]
 pub fn ScrubberStatisticsSection(state: Signal<AppState>) -> Element {
     let scrubber_state = state.read().scrubber_state.clone();
+    let (rules_fired, unused_rules) = {
+        let state = state.read();
+        let fired = state
+            .saved_rules
+            .iter()
+            .filter(|rule| {
+                state
+                    .rule_stats
+                    .for_rule(rule)
+                    .is_some_and(|stats| stats.produced > 0)
+            })
+            .count();
+        let unused = state
+            .saved_rules
+            .iter()
+            .filter(|rule| state.rule_stats.for_rule(rule).is_none())
+            .count();
+        (fired, unused)
+    };
 
     rsx! {
         div { style: "background: white; border-radius: 0.5rem; box-shadow: 0 4px 6px rgba(0,0,0,0.1); padding: 1.5rem;",
@@ -25,6 +44,16 @@ pub fn ScrubberStatisticsSection(state: Signal<AppState>) -> Element {
                     label: "Total Events",
                     color: "#dc2626"
                 }
+                StatCard {
+                    value: rules_fired,
+                    label: "Rules That Fired",
+                    color: "#7c3aed"
+                }
+                StatCard {
+                    value: unused_rules,
+                    label: "Rules Never Matched",
+                    color: "#6b7280"
+                }
             }
         }
     }
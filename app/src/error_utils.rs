@@ -58,17 +58,18 @@ where
 /// Helper for storage operations - creates storage and loads config
 #[allow(dead_code)] // Used in #[server] macro-generated code
 pub async fn create_storage() -> Result<
-    std::sync::Arc<tokio::sync::Mutex<scrobble_scrubber::persistence::FileStorage>>,
+    std::sync::Arc<tokio::sync::Mutex<scrobble_scrubber::persistence::ConfiguredStorage>>,
     Box<dyn std::error::Error + Send + Sync>,
 > {
     use scrobble_scrubber::config::ScrobbleScrubberConfig;
-    use scrobble_scrubber::persistence::FileStorage;
+    use scrobble_scrubber::persistence::ConfiguredStorage;
     use std::sync::Arc;
     use tokio::sync::Mutex;
 
     let config = ScrobbleScrubberConfig::load().to_box_error("Failed to load config")?;
 
-    let storage = FileStorage::new(&config.storage.state_file)
+    let storage = ConfiguredStorage::open(&config.storage)
+        .await
         .to_box_error("Failed to initialize storage")?;
 
     Ok(Arc::new(Mutex::new(storage)))
@@ -116,65 +117,59 @@ pub async fn apply_edit_with_timeout(
     }
 }
 
-/// Helper to find and remove an edit by ID
+/// Helper to find a pending edit by ID, leaving it pending
 #[allow(dead_code)] // Used in #[server] macro-generated code
-pub async fn remove_pending_edit(
-    storage: &std::sync::Arc<tokio::sync::Mutex<scrobble_scrubber::persistence::FileStorage>>,
+pub async fn load_pending_edit(
+    storage: &std::sync::Arc<tokio::sync::Mutex<scrobble_scrubber::persistence::ConfiguredStorage>>,
     edit_id: &str,
 ) -> Result<scrobble_scrubber::persistence::PendingEdit, Box<dyn std::error::Error + Send + Sync>> {
     use scrobble_scrubber::persistence::StateStorage;
 
-    log::info!("Removing pending edit with ID: {edit_id}");
-
-    let mut pending_edits_state = {
-        let storage_guard = storage.lock().await;
-        storage_guard
-            .load_pending_edits_state()
-            .await
-            .to_box_error("Failed to load pending edits")?
-    };
-
-    log::debug!(
-        "Loaded {} pending edits from storage",
-        pending_edits_state.pending_edits.len()
-    );
-
-    let edit_index = pending_edits_state
-        .pending_edits
-        .iter()
-        .position(|e| e.id == edit_id)
+    storage
+        .lock()
+        .await
+        .load_pending_edit(edit_id)
+        .await
+        .to_box_error("Failed to load pending edit")?
         .ok_or_else(|| {
-            log::warn!(
-                "Edit with ID '{}' not found in {} pending edits",
-                edit_id,
-                pending_edits_state.pending_edits.len()
-            );
+            log::warn!("Edit with ID '{edit_id}' not found in pending edits");
             Box::<dyn std::error::Error + Send + Sync>::from("Edit not found")
-        })?;
+        })
+}
 
-    let removed_edit = pending_edits_state.pending_edits.remove(edit_index);
-    log::info!(
-        "Removed edit for track '{}', {} edits remaining",
-        removed_edit.original_track_name,
-        pending_edits_state.pending_edits.len()
-    );
+/// Helper to remove a pending edit and remember the user's decision on it, so it isn't
+/// suggested for review again
+#[allow(dead_code)] // Used in #[server] macro-generated code
+pub async fn decide_pending_edit(
+    storage: &std::sync::Arc<tokio::sync::Mutex<scrobble_scrubber::persistence::ConfiguredStorage>>,
+    edit_id: &str,
+    decision: scrobble_scrubber::persistence::EditDecision,
+) -> Result<scrobble_scrubber::persistence::PendingEdit, Box<dyn std::error::Error + Send + Sync>> {
+    use scrobble_scrubber::persistence::{EditDecision, StateStorage};
 
-    {
-        let mut storage_guard = storage.lock().await;
-        storage_guard
-            .save_pending_edits_state(&pending_edits_state)
-            .await
-            .to_box_error("Failed to save pending edits")?;
-    }
+    log::info!("Removing pending edit with ID {edit_id} as {decision:?}");
 
-    log::info!("Saved pending edits state to disk");
+    let mut storage = storage.lock().await;
+    let decided_edit = match decision {
+        EditDecision::Approved => storage.approve_pending_edit(edit_id).await,
+        EditDecision::Rejected => storage.reject_pending_edit(edit_id).await,
+    }
+    .to_box_error("Failed to save edit decision")?
+    .ok_or_else(|| {
+        log::warn!("Edit with ID '{edit_id}' not found in pending edits");
+        Box::<dyn std::error::Error + Send + Sync>::from("Edit not found")
+    })?;
+    log::info!(
+        "Removed edit for track '{}'",
+        decided_edit.original_track_name
+    );
 
-    Ok(removed_edit)
+    Ok(decided_edit)
 }
 
 /// Helper to find and remove a rule by ID
 pub async fn remove_pending_rule(
-    storage: &std::sync::Arc<tokio::sync::Mutex<scrobble_scrubber::persistence::FileStorage>>,
+    storage: &std::sync::Arc<tokio::sync::Mutex<scrobble_scrubber::persistence::ConfiguredStorage>>,
     rule_id: &str,
 ) -> Result<
     scrobble_scrubber::persistence::PendingRewriteRule,
@@ -233,10 +228,16 @@ pub async fn remove_pending_rule(
 /// Helper to approve a rewrite rule (remove from pending and add to active)
 #[allow(dead_code)] // Used in #[server] macro-generated code
 pub async fn approve_rewrite_rule(
-    storage: &std::sync::Arc<tokio::sync::Mutex<scrobble_scrubber::persistence::FileStorage>>,
+    storage: &std::sync::Arc<tokio::sync::Mutex<scrobble_scrubber::persistence::ConfiguredStorage>>,
     rule_id: &str,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use scrobble_scrubber::config::ScrobbleScrubberConfig;
     use scrobble_scrubber::persistence::StateStorage;
+    use scrobble_scrubber::rules_dir::ensure_rules_editable;
+
+    // Approved rules would be lost when the rules directory is reloaded
+    let config = ScrobbleScrubberConfig::load().to_box_error("Failed to load config")?;
+    ensure_rules_editable(config.storage.rules_dir.as_ref())?;
 
     // Remove from pending rules
     let approved_rule = remove_pending_rule(storage, rule_id).await?;
@@ -1,7 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use ::scrobble_scrubber::config::{ScrobbleScrubberConfig, StorageConfig};
-use ::scrobble_scrubber::persistence::{FileStorage, StateStorage};
+use ::scrobble_scrubber::persistence::{ConfiguredStorage, StateStorage};
 use ::scrobble_scrubber::rewrite::RewriteRule;
 use ::scrobble_scrubber::track_cache::TrackCache;
 use dioxus::prelude::*;
@@ -48,7 +48,7 @@ pub enum Route {
 async fn initialize_app_state() -> Result<
     (
         ScrobbleScrubberConfig,
-        Option<Arc<Mutex<FileStorage>>>,
+        Option<Arc<Mutex<ConfiguredStorage>>>,
         Vec<RewriteRule>,
     ),
     String,
@@ -62,10 +62,15 @@ async fn initialize_app_state() -> Result<
             StorageConfig::get_default_state_file_path_for_user(Some(&config.lastfm.username));
     }
 
-    let state_file = config.storage.state_file.clone();
+    let mut storage = ConfiguredStorage::open(&config.storage)
+        .await
+        .map_err(|e| format!("Failed to initialize storage: {e}"))?;
 
-    let storage =
-        FileStorage::new(&state_file).map_err(|e| format!("Failed to initialize storage: {e}"))?;
+    if let Some(rules_dir) = &config.storage.rules_dir {
+        ::scrobble_scrubber::rules_dir::sync_storage_from_dir(rules_dir, &mut storage)
+            .await
+            .map_err(|e| format!("Failed to load rules directory {}: {e}", rules_dir.path))?;
+    }
 
     let saved_rules = storage
         .load_rewrite_rules_state()
@@ -96,6 +101,7 @@ async fn initialize_app_state_signal(mut state: Signal<AppState>) {
                     s.current_page = 1;
                 }
             });
+            utils::refresh_rule_stats(state).await;
         }
         Err(e) => {
             log::error!("Failed to initialize app: {e}");
@@ -1,8 +1,13 @@
 use crate::types::{AppState, GlobalScrubber};
-use ::scrobble_scrubber::config::{ScrobbleScrubberConfig, TrackProviderType};
+use ::scrobble_scrubber::config::{RulesDirConfig, ScrobbleScrubberConfig, TrackProviderType};
+use ::scrobble_scrubber::http_provider::HttpScrubActionProvider;
+use ::scrobble_scrubber::majority_album_provider::MajorityAlbumProvider;
 use ::scrobble_scrubber::musicbrainz::CompilationToCanonicalProvider;
+use ::scrobble_scrubber::musicbrainz::MusicBrainzCache;
+use ::scrobble_scrubber::musicbrainz::MusicBrainzClient;
+use ::scrobble_scrubber::musicbrainz::MusicBrainzRateLimiter;
 use ::scrobble_scrubber::musicbrainz::MusicBrainzScrubActionProvider;
-use ::scrobble_scrubber::persistence::FileStorage;
+use ::scrobble_scrubber::persistence::{ConfiguredStorage, StateStorage};
 use ::scrobble_scrubber::rewrite::RewriteRule;
 use ::scrobble_scrubber::scrub_action_provider::{
     OrScrubActionProvider, RewriteRulesScrubActionProvider,
@@ -16,7 +21,7 @@ use tokio::sync::Mutex;
 /// Creates a new scrubber instance with the given configuration
 pub async fn create_scrubber_instance(
     session_json: String,
-    storage: Arc<Mutex<FileStorage>>,
+    storage: Arc<Mutex<ConfiguredStorage>>,
     saved_rules: Vec<RewriteRule>,
     config: ScrobbleScrubberConfig,
 ) -> Result<GlobalScrubber, Box<dyn std::error::Error + Send + Sync>> {
@@ -27,14 +32,37 @@ pub async fn create_scrubber_instance(
     let http_client = http_client::native::NativeClient::new();
     let client = LastFmEditClientImpl::from_session(Box::new(http_client), session);
 
+    // MusicBrainz server, cache and rate limit are process-wide; the first scrubber instance
+    // sets them up
+    MusicBrainzClient::init_server(&config);
+    MusicBrainzCache::init_shared(&config);
+    MusicBrainzRateLimiter::init_shared(&config);
+
     // Create action provider chain with enabled providers
     let mut action_provider = OrScrubActionProvider::new();
 
     // Add rewrite rules provider if enabled
-    if config.providers.enable_rewrite_rules && !saved_rules.is_empty() {
-        let rewrite_provider = RewriteRulesScrubActionProvider::from_rules(saved_rules);
-        action_provider = action_provider.add_provider(rewrite_provider);
-        log::info!("Enabled rewrite rules provider");
+    if config.providers.enable_rewrite_rules {
+        let alias_rules = storage
+            .lock()
+            .await
+            .load_rewrite_rules_state()
+            .await
+            .map(|rules_state| rules_state.alias_rules)
+            .unwrap_or_default();
+
+        // A watched rules directory may gain rules while the scrubber runs
+        let watches_rules_dir = config
+            .storage
+            .rules_dir
+            .as_ref()
+            .is_some_and(RulesDirConfig::watches);
+        if !saved_rules.is_empty() || !alias_rules.is_empty() || watches_rules_dir {
+            let rewrite_provider = RewriteRulesScrubActionProvider::from_rules(saved_rules)
+                .with_alias_rules(&alias_rules);
+            action_provider = action_provider.add_provider(rewrite_provider);
+            log::info!("Enabled rewrite rules provider");
+        }
     }
 
     // Add MusicBrainz provider if enabled
@@ -59,6 +87,7 @@ pub async fn create_scrubber_instance(
                     comp_config.confidence_threshold,
                 )
                 .with_enabled(comp_config.enabled)
+                .with_greatest_hits_policy(comp_config.greatest_hits_policy)
             } else {
                 CompilationToCanonicalProvider::new()
             };
@@ -66,6 +95,29 @@ pub async fn create_scrubber_instance(
         log::info!("Enabled Compilation to Canonical provider");
     }
 
+    // Add HTTP provider if enabled and configured
+    if config.providers.enable_http {
+        if let Some(http_config) = &config.providers.http {
+            let http_provider = HttpScrubActionProvider::new(http_config)?;
+            action_provider = action_provider.add_provider(http_provider);
+            log::info!("Enabled HTTP provider");
+        } else {
+            log::warn!("HTTP provider enabled but not configured");
+        }
+    }
+
+    // Add majority album provider if enabled
+    if config.providers.enable_majority_album {
+        let majority_config = config.providers.majority_album.clone().unwrap_or_default();
+        action_provider = action_provider.add_provider(MajorityAlbumProvider::new(majority_config));
+        log::info!("Enabled majority album provider");
+    }
+
+    if config.providers.chain_providers {
+        action_provider = action_provider.chained();
+        log::info!("Chaining providers");
+    }
+
     // Create scrubber instance with configured track provider
     let scrubber = match config.scrubber.track_provider {
         TrackProviderType::Cached => ScrobbleScrubber::with_cached_provider(
@@ -1,7 +1,7 @@
 use crate::components::TrackProgressState;
 use ::scrobble_scrubber::config::ScrobbleScrubberConfig;
 use ::scrobble_scrubber::events::ScrubberEvent;
-use ::scrobble_scrubber::persistence::FileStorage;
+use ::scrobble_scrubber::persistence::{ConfiguredStorage, RuleStatsState};
 use ::scrobble_scrubber::rewrite::RewriteRule;
 use ::scrobble_scrubber::scrub_action_provider::OrScrubActionProvider;
 use ::scrobble_scrubber::scrubber::ScrobbleScrubber;
@@ -112,6 +112,14 @@ pub mod event_formatting {
             ScrubberEventType::TrackSkipped { track, reason, .. } => {
                 format!("Skipped '{}' by '{}': {}", track.artist, track.name, reason)
             }
+            ScrubberEventType::EditApplying { track, origin, .. } => {
+                format!(
+                    "Applying edit from {} to '{}' by '{}'",
+                    origin.providers.join(", "),
+                    track.name,
+                    track.artist
+                )
+            }
             ScrubberEventType::ClientEvent(client_event) => {
                 // Try to extract meaningful information from client events
                 let debug_str = format!("{client_event:?}");
@@ -175,6 +183,16 @@ pub mod event_formatting {
                     result.summary()
                 )
             }
+            ScrubberEventType::MusicBrainzRateLimited {
+                delay_seconds,
+                attempt,
+                ..
+            } => {
+                format!("⚠️ Rate Limited: MusicBrainz throttled a request, retry {attempt} in {delay_seconds}s")
+            }
+            ScrubberEventType::MusicBrainzRateLimitEnded => {
+                "MusicBrainz rate limit ended".to_string()
+            }
         }
     }
 
@@ -228,6 +246,25 @@ pub mod event_formatting {
 
     /// Detect rate limiting and create rate limit state from ClientEvent
     pub fn detect_rate_limit_from_event(event: &ScrubberEvent) -> Option<RateLimitState> {
+        if let ScrubberEventType::MusicBrainzRateLimited {
+            delay_seconds,
+            attempt,
+            message,
+        } = &event.event_type
+        {
+            let detected_at = event.timestamp;
+            return Some(RateLimitState {
+                is_rate_limited: true,
+                detected_at,
+                retry_after: Some(detected_at + chrono::Duration::seconds(*delay_seconds as i64)),
+                message: format!(
+                    "Rate limited by MusicBrainz - retry {attempt} in {delay_seconds} seconds ({message})"
+                ),
+                delay_seconds: *delay_seconds,
+                rate_limit_type: Some("MusicBrainz".to_string()),
+            });
+        }
+
         if let ScrubberEventType::ClientEvent(client_event) = &event.event_type {
             // Check for the new structured rate limiting events first
             match client_event {
@@ -281,7 +318,12 @@ pub mod event_formatting {
 
     /// Check if rate limiting has ended from ClientEvent
     pub fn detect_rate_limit_ended_from_event(event: &ScrubberEvent) -> bool {
-        if let ScrubberEventType::ClientEvent(client_event) = &event.event_type {
+        if matches!(
+            event.event_type,
+            ScrubberEventType::MusicBrainzRateLimitEnded
+        ) {
+            true
+        } else if let ScrubberEventType::ClientEvent(client_event) = &event.event_type {
             matches!(
                 client_event,
                 lastfm_edit::ClientEvent::RateLimitEnded { .. }
@@ -308,11 +350,14 @@ pub mod event_formatting {
             ScrubberEventType::TrackEdited { .. } => "track_edited",
             ScrubberEventType::TrackEditFailed { .. } => "track_edit_failed",
             ScrubberEventType::TrackSkipped { .. } => "track_skipped",
+            ScrubberEventType::EditApplying { .. } => "edit_applying",
             ScrubberEventType::ClientEvent(_) => "client_event",
             ScrubberEventType::PendingEditCreated { .. } => "pending_edit_created",
             ScrubberEventType::ProcessingBatchStarted { .. } => "processing_batch_started",
             ScrubberEventType::TrackProcessingStarted { .. } => "track_processing_started",
             ScrubberEventType::TrackProcessingCompleted { .. } => "track_processing_completed",
+            ScrubberEventType::MusicBrainzRateLimited { .. } => "musicbrainz_rate_limited",
+            ScrubberEventType::MusicBrainzRateLimitEnded => "musicbrainz_rate_limit_ended",
         }
     }
 }
@@ -341,7 +386,7 @@ impl PartialEq for ScrubberState {
     }
 }
 
-pub type GlobalScrubber = ScrobbleScrubber<FileStorage, OrScrubActionProvider>;
+pub type GlobalScrubber = ScrobbleScrubber<ConfiguredStorage, OrScrubActionProvider>;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -353,8 +398,9 @@ pub struct AppState {
     pub show_all_tracks: bool, // Toggle to show all tracks or only matching ones
     pub current_page: u32,     // Current page for pagination (for recent tracks)
     pub config: Option<ScrobbleScrubberConfig>, // Loaded configuration
-    pub storage: Option<Arc<Mutex<FileStorage>>>, // Persistence storage
+    pub storage: Option<Arc<Mutex<ConfiguredStorage>>>, // Persistence storage
     pub saved_rules: Vec<RewriteRule>, // Rules loaded from storage
+    pub rule_stats: RuleStatsState, // Per-rule hit statistics
     pub scrubber_state: ScrubberState, // Scrobble scrubber state and observability
     pub scrubber_instance: Option<Arc<tokio::sync::Mutex<GlobalScrubber>>>, // Global scrubber instance
     pub track_progress_state: TrackProgressState, // Track processing progress for UI
@@ -375,6 +421,7 @@ impl Default for AppState {
             config: None,
             storage: None,
             saved_rules: Vec::new(),
+            rule_stats: RuleStatsState::default(),
             scrubber_state: ScrubberState {
                 status: ScrubberStatus::Stopped,
                 events: Vec::new(),
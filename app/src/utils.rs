@@ -1,7 +1,8 @@
 use crate::types::AppState;
 pub use ::scrobble_scrubber::default_rules::{load_default_remaster_rules, DefaultRule};
-use ::scrobble_scrubber::persistence::{RewriteRulesState, StateStorage};
+use ::scrobble_scrubber::persistence::StateStorage;
 use ::scrobble_scrubber::rewrite::RewriteRule;
+use ::scrobble_scrubber::rules_dir::ensure_rules_editable;
 use dioxus::document::eval;
 use dioxus::prelude::*;
 use lastfm_edit::Track;
@@ -26,6 +27,17 @@ pub fn get_current_tracks(state: &AppState) -> Vec<Track> {
     all_tracks
 }
 
+// Refuse rule changes the configured rules directory would overwrite
+fn ensure_rules_editable_in(state: &Signal<AppState>) -> Result<(), Box<dyn std::error::Error>> {
+    let rules_dir = state
+        .read()
+        .config
+        .as_ref()
+        .and_then(|config| config.storage.rules_dir.clone());
+    ensure_rules_editable(rules_dir.as_ref())?;
+    Ok(())
+}
+
 pub async fn save_current_rule(
     mut state: Signal<AppState>,
     rule: RewriteRule,
@@ -39,6 +51,8 @@ pub async fn save_current_rule(
         return Err("Cannot save empty rule".into());
     }
 
+    ensure_rules_editable_in(&state)?;
+
     let storage = state.read().storage.clone();
     if let Some(storage) = storage {
         let mut storage_lock = storage.lock().await;
@@ -68,6 +82,7 @@ pub async fn remove_rule_at_index(
     mut state: Signal<AppState>,
     index: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    ensure_rules_editable_in(&state)?;
     let storage = state.read().storage.clone();
     if let Some(storage) = storage {
         let mut storage_lock = storage.lock().await;
@@ -98,15 +113,18 @@ pub async fn remove_rule_at_index(
 pub async fn clear_all_rules(
     mut state: Signal<AppState>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    ensure_rules_editable_in(&state)?;
     let storage = state.read().storage.clone();
     if let Some(storage) = storage {
         let mut storage_lock = storage.lock().await;
 
-        // Clear all rules
-        let empty_rules_state = RewriteRulesState::default();
-        storage_lock
-            .save_rewrite_rules_state(&empty_rules_state)
-            .await?;
+        // Clear all rules, keeping alias tables
+        let mut rules_state = storage_lock
+            .load_rewrite_rules_state()
+            .await
+            .unwrap_or_default();
+        rules_state.rewrite_rules.clear();
+        storage_lock.save_rewrite_rules_state(&rules_state).await?;
 
         // Update local state
         drop(storage_lock);
@@ -122,6 +140,7 @@ pub async fn update_rule_confirmation(
     index: usize,
     requires_confirmation: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    ensure_rules_editable_in(&state)?;
     let storage = state.read().storage.clone();
     if let Some(storage) = storage {
         let mut storage_lock = storage.lock().await;
@@ -149,6 +168,17 @@ pub async fn update_rule_confirmation(
     Ok(())
 }
 
+// Reload per-rule hit statistics from storage
+pub async fn refresh_rule_stats(mut state: Signal<AppState>) {
+    let storage = state.read().storage.clone();
+    if let Some(storage) = storage {
+        match storage.lock().await.load_rule_stats_state().await {
+            Ok(rule_stats) => state.with_mut(|s| s.rule_stats = rule_stats),
+            Err(e) => log::warn!("Failed to load rule statistics: {e}"),
+        }
+    }
+}
+
 // Helper function to copy text to clipboard
 pub fn copy_to_clipboard(text: String) {
     spawn(async move {
@@ -168,6 +198,8 @@ pub async fn import_default_rules(
         return Ok(0);
     }
 
+    ensure_rules_editable_in(&state)?;
+
     let storage = state.read().storage.clone();
     if let Some(storage) = storage {
         let mut storage_lock = storage.lock().await;
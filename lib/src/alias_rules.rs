@@ -0,0 +1,338 @@
+//! Alias-table rules for canonicalizing artist, album and track names
+//!
+//! An alias rule maps many spellings of a value to one canonical value for a single field.
+//! Lookups are done on a normalized key, so large tables stay fast and readable compared
+//! to the equivalent regex rules.
+//!
+//! Alias tables can be written directly as JSON (`{"Canonical": ["alias", ...]}`) or as
+//! TSV where every line maps an alias to its canonical value:
+//!
+//! ```text
+//! # alias<TAB>canonical
+//! Beyonce<TAB>Beyoncé
+//! Beyonce Knowles<TAB>Beyoncé
+//! ```
+
+use crate::rewrite::RuleField;
+use crate::rewrite_template::TemplateFilter;
+use lastfm_edit::{ScrobbleEdit, Track};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// How field values are compared with alias keys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum AliasMatching {
+    /// Keys must match exactly
+    #[default]
+    Exact,
+    /// Keys are compared ignoring case
+    CaseInsensitive,
+    /// Keys are compared ignoring case, diacritics and surrounding whitespace
+    Normalized,
+}
+
+impl AliasMatching {
+    /// The lookup key for a value
+    #[must_use]
+    pub fn key(self, value: &str) -> String {
+        match self {
+            Self::Exact => value.to_string(),
+            Self::CaseInsensitive => value.to_lowercase(),
+            Self::Normalized => TemplateFilter::StripDiacritics
+                .apply(value.trim())
+                .to_lowercase(),
+        }
+    }
+}
+
+impl std::fmt::Display for AliasMatching {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exact => write!(f, "exact"),
+            Self::CaseInsensitive => write!(f, "case-insensitive"),
+            Self::Normalized => write!(f, "normalized"),
+        }
+    }
+}
+
+/// Errors that can occur while loading or compiling alias tables
+#[derive(Debug, thiserror::Error)]
+pub enum AliasError {
+    #[error("Failed to read alias file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid alias JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid alias TSV line {line}: '{content}' (expected alias<TAB>canonical)")]
+    InvalidLine { line: usize, content: String },
+    #[error("Unsupported alias file format '{0}' (expected .tsv or .json)")]
+    UnsupportedFormat(String),
+    #[error("Alias '{alias}' maps to both '{first}' and '{second}'")]
+    ConflictingAlias {
+        alias: String,
+        first: String,
+        second: String,
+    },
+}
+
+/// A lookup table mapping alternative spellings of a field value to a canonical value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AliasRule {
+    /// Optional name for this rule
+    pub name: Option<String>,
+    /// The field this table applies to
+    pub field: RuleField,
+    /// How values are compared with the aliases
+    #[serde(default)]
+    pub matching: AliasMatching,
+    /// Canonical value → alternative spellings
+    pub aliases: BTreeMap<String, Vec<String>>,
+    /// Whether changes from this table require user confirmation
+    #[serde(default)]
+    pub requires_confirmation: bool,
+}
+
+impl AliasRule {
+    /// Create an empty alias table for a field
+    #[must_use]
+    pub const fn new(field: RuleField) -> Self {
+        Self {
+            name: None,
+            field,
+            matching: AliasMatching::Exact,
+            aliases: BTreeMap::new(),
+            requires_confirmation: false,
+        }
+    }
+
+    /// Set name for this rule
+    #[must_use]
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set how values are compared with the aliases
+    #[must_use]
+    pub const fn with_matching(mut self, matching: AliasMatching) -> Self {
+        self.matching = matching;
+        self
+    }
+
+    /// Add an alias for a canonical value
+    #[must_use]
+    pub fn with_alias(mut self, canonical: &str, alias: &str) -> Self {
+        self.aliases
+            .entry(canonical.to_string())
+            .or_default()
+            .push(alias.to_string());
+        self
+    }
+
+    /// Set whether this rule requires confirmation
+    #[must_use]
+    pub const fn with_confirmation_required(mut self, requires_confirmation: bool) -> Self {
+        self.requires_confirmation = requires_confirmation;
+        self
+    }
+
+    /// Total number of aliases in the table
+    #[must_use]
+    pub fn alias_count(&self) -> usize {
+        self.aliases.values().map(Vec::len).sum()
+    }
+
+    /// Parse a TSV table where every line is `alias<TAB>canonical`
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_tsv(field: RuleField, content: &str) -> Result<Self, AliasError> {
+        let mut rule = Self::new(field);
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            match line.split_once('\t') {
+                Some((alias, canonical))
+                    if !alias.trim().is_empty() && !canonical.trim().is_empty() =>
+                {
+                    rule = rule.with_alias(canonical.trim(), alias.trim());
+                }
+                _ => {
+                    return Err(AliasError::InvalidLine {
+                        line: index + 1,
+                        content: line.to_string(),
+                    })
+                }
+            }
+        }
+        Ok(rule)
+    }
+
+    /// Parse a JSON table of the form `{"Canonical": ["alias", ...]}`
+    pub fn from_json(field: RuleField, content: &str) -> Result<Self, AliasError> {
+        Ok(Self {
+            aliases: serde_json::from_str(content)?,
+            ..Self::new(field)
+        })
+    }
+
+    /// Load a table from a `.tsv` or `.json` file
+    pub fn load_file(field: RuleField, path: &Path) -> Result<Self, AliasError> {
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("tsv") => Self::from_tsv(field, &content),
+            Some("json") => Self::from_json(field, &content),
+            other => Err(AliasError::UnsupportedFormat(
+                other.unwrap_or_default().to_string(),
+            )),
+        }
+    }
+
+    /// Build the lookup table, failing if an alias maps to more than one canonical value
+    pub fn compile(&self) -> Result<CompiledAliasRule, AliasError> {
+        let mut lookup: HashMap<String, String> = HashMap::new();
+
+        for (canonical, aliases) in &self.aliases {
+            // The canonical spelling maps to itself so differently cased or accented
+            // variants of it get normalized too
+            for value in std::iter::once(canonical).chain(aliases) {
+                let key = self.matching.key(value);
+                match lookup.get(&key) {
+                    Some(existing) if existing != canonical => {
+                        return Err(AliasError::ConflictingAlias {
+                            alias: value.clone(),
+                            first: existing.clone(),
+                            second: canonical.clone(),
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        lookup.insert(key, canonical.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(CompiledAliasRule {
+            rule: self.clone(),
+            lookup,
+        })
+    }
+}
+
+/// An `AliasRule` with its lookup table built
+#[derive(Debug, Clone)]
+pub struct CompiledAliasRule {
+    rule: AliasRule,
+    /// Normalized key → canonical value
+    lookup: HashMap<String, String>,
+}
+
+impl CompiledAliasRule {
+    /// The rule this was compiled from
+    #[must_use]
+    pub const fn rule(&self) -> &AliasRule {
+        &self.rule
+    }
+
+    /// The canonical value for `value`, if it differs from `value`
+    #[must_use]
+    pub fn canonical_for(&self, value: &str) -> Option<&str> {
+        self.lookup
+            .get(&self.rule.matching.key(value))
+            .map(String::as_str)
+            .filter(|canonical| *canonical != value)
+    }
+
+    /// Check if the table would rewrite the track's field
+    #[must_use]
+    pub fn matches(&self, track: &Track) -> bool {
+        self.canonical_for(self.rule.field.track_value(track))
+            .is_some()
+    }
+
+    /// Rewrite the edit's field to its canonical value, returning true if it changed
+    pub fn apply(&self, edit: &mut ScrobbleEdit) -> bool {
+        let Some(canonical) = self
+            .rule
+            .field
+            .edit_value(edit)
+            .and_then(|value| self.canonical_for(value))
+            .map(str::to_string)
+        else {
+            return false;
+        };
+
+        log::debug!(
+            "Alias rule '{}' rewrites {} to '{canonical}'",
+            self.rule.name.as_deref().unwrap_or("unnamed"),
+            self.rule.field
+        );
+        self.rule.field.set_edit_value(edit, canonical);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beyonce_rule(matching: AliasMatching) -> CompiledAliasRule {
+        AliasRule::new(RuleField::ArtistName)
+            .with_matching(matching)
+            .with_alias("Beyoncé", "Beyonce Knowles")
+            .compile()
+            .unwrap()
+    }
+
+    #[test_log::test]
+    fn should_look_up_aliases_by_matching_mode() {
+        let exact = beyonce_rule(AliasMatching::Exact);
+        assert_eq!(exact.canonical_for("Beyonce Knowles"), Some("Beyoncé"));
+        assert_eq!(exact.canonical_for("beyonce knowles"), None);
+        assert_eq!(exact.canonical_for("Beyoncé"), None);
+
+        let case_insensitive = beyonce_rule(AliasMatching::CaseInsensitive);
+        assert_eq!(case_insensitive.canonical_for("BEYONCÉ"), Some("Beyoncé"));
+        assert_eq!(case_insensitive.canonical_for("Beyonce"), None);
+
+        let normalized = beyonce_rule(AliasMatching::Normalized);
+        assert_eq!(normalized.canonical_for(" beyonce "), Some("Beyoncé"));
+        assert_eq!(normalized.canonical_for("BEYONCE KNOWLES"), Some("Beyoncé"));
+    }
+
+    #[test_log::test]
+    fn should_parse_tsv_and_json_tables() {
+        let tsv = "# alias\tcanonical\nBeyonce\tBeyoncé\n\nBey\tBeyoncé\n";
+        let rule = AliasRule::from_tsv(RuleField::ArtistName, tsv).unwrap();
+        assert_eq!(rule.alias_count(), 2);
+        assert_eq!(rule.aliases["Beyoncé"], vec!["Beyonce", "Bey"]);
+
+        let json = r#"{"Beyoncé": ["Beyonce", "Bey"]}"#;
+        assert_eq!(
+            AliasRule::from_json(RuleField::ArtistName, json).unwrap(),
+            rule
+        );
+
+        assert!(matches!(
+            AliasRule::from_tsv(RuleField::ArtistName, "no tab here"),
+            Err(AliasError::InvalidLine { line: 1, .. })
+        ));
+    }
+
+    #[test_log::test]
+    fn should_reject_conflicting_aliases() {
+        let rule = AliasRule::new(RuleField::AlbumName)
+            .with_matching(AliasMatching::CaseInsensitive)
+            .with_alias("Abbey Road", "abbey rd")
+            .with_alias("Abbey Road (Remastered)", "ABBEY RD");
+        assert!(matches!(
+            rule.compile(),
+            Err(AliasError::ConflictingAlias { .. })
+        ));
+    }
+}
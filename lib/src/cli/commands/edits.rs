@@ -0,0 +1,163 @@
+use crate::config::{ScrobbleScrubberConfig, StorageConfig};
+use crate::persistence::StateStorage;
+use crate::scrub_action_provider::ScrubActionProvider;
+use crate::scrubber::ScrobbleScrubber;
+use crate::undo::{plan_undo, read_edit_log, UndoEdit, UndoFilter};
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand};
+use lastfm_edit::{LastFmError, Result};
+use std::io::{self, Write};
+use std::path::Path;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum EditsCommands {
+    /// Revert edits recorded in the edit log
+    Undo(UndoArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct UndoArgs {
+    /// Only edits from this processing run (as shown in the preview and the edit log)
+    #[arg(long)]
+    pub run_id: Option<String>,
+    /// Only edits made at or after this time (ISO 8601, e.g. "2025-07-22T07:08:00Z")
+    #[arg(long)]
+    pub since: Option<String>,
+    /// Only edits made before this time (ISO 8601)
+    #[arg(long)]
+    pub until: Option<String>,
+    /// Only edits produced by the rewrite rule with this name
+    #[arg(long)]
+    pub rule: Option<String>,
+    /// Only edits of this artist's scrobbles
+    #[arg(long)]
+    pub artist: Option<String>,
+    /// Dry run mode - show what would be reverted without making changes
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Create pending edits for the reverts instead of applying them
+    #[arg(long)]
+    pub require_confirmation: bool,
+    /// Disable all confirmation prompts (overrides config and --require-confirmation)
+    #[arg(long)]
+    pub no_confirmation: bool,
+    /// Apply without asking after the preview
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+impl UndoArgs {
+    fn filter(&self) -> Result<UndoFilter> {
+        let parse_time = |value: &Option<String>| -> Result<Option<DateTime<Utc>>> {
+            value
+                .as_deref()
+                .map(|value| {
+                    DateTime::parse_from_rfc3339(value)
+                        .map(|time| time.with_timezone(&Utc))
+                        .map_err(|e| {
+                            LastFmError::Io(std::io::Error::other(format!(
+                                "Invalid time '{value}': {e}"
+                            )))
+                        })
+                })
+                .transpose()
+        };
+
+        let mut filter =
+            UndoFilter::new().with_time_range(parse_time(&self.since)?, parse_time(&self.until)?);
+        if let Some(run_id) = &self.run_id {
+            filter = filter.with_run_id(run_id);
+        }
+        if let Some(rule) = &self.rule {
+            filter = filter.with_rule(rule);
+        }
+        if let Some(artist) = &self.artist {
+            filter = filter.with_artist(artist);
+        }
+        Ok(filter)
+    }
+}
+
+/// Preview the edits selected from the edit log and revert them with `scrubber`
+pub async fn undo_logged_edits<S: StateStorage, P: ScrubActionProvider>(
+    scrubber: &mut ScrobbleScrubber<S, P>,
+    args: &UndoArgs,
+    config: &ScrobbleScrubberConfig,
+) -> Result<()> {
+    let filter = args.filter()?;
+    if filter.is_empty() {
+        println!("❌ Select the edits to undo with --run-id, --since, --until, --rule or --artist");
+        return Ok(());
+    }
+
+    let log_path = StorageConfig::get_edit_log_path(&config.storage.state_file);
+    let entries = read_edit_log(Path::new(&log_path)).map_err(|e| {
+        LastFmError::Io(std::io::Error::other(format!(
+            "Failed to read edit log {log_path}: {e}"
+        )))
+    })?;
+    let plan = plan_undo(entries, &filter);
+
+    if plan.is_empty() {
+        println!("No logged edits match");
+        return Ok(());
+    }
+
+    print_undo_plan(&plan);
+
+    if !config.scrubber.dry_run && !args.yes {
+        print!("Revert these {} edits? (y/N): ", plan.len());
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).map_err(|e| {
+            LastFmError::Io(std::io::Error::other(format!("Failed to read input: {e}")))
+        })?;
+
+        if input.trim().to_lowercase() != "y" && input.trim().to_lowercase() != "yes" {
+            println!("❌ Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    let applied = scrubber.undo_edits(&plan).await?;
+    if config.scrubber.dry_run {
+        println!("🔍 Dry run: no edits were reverted");
+    } else {
+        println!("✅ Reverted {applied} of {} edits", plan.len());
+        println!("   Reverted changes are remembered as rejected and won't be made again");
+        if applied < plan.len() {
+            println!("   The rest were created as pending edits or skipped, see the log");
+        }
+    }
+    Ok(())
+}
+
+fn print_undo_plan(plan: &[UndoEdit]) {
+    println!("↩️ Edits to Revert");
+    println!("==================");
+    for (index, undo) in plan.iter().enumerate() {
+        let origin = undo.entry.origin.as_ref();
+        println!(
+            "{}. {} ({})",
+            index + 1,
+            undo.describe(),
+            undo.entry.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        if let Some(origin) = origin {
+            let source = if origin.rules.is_empty() {
+                origin.providers.join(", ")
+            } else {
+                format!(
+                    "{}: {}",
+                    origin.providers.join(", "),
+                    origin.rules.join(", ")
+                )
+            };
+            println!("   run {} - {source}", origin.run_id);
+        }
+        if !undo.entry.edit.edit_all {
+            println!("   only the scrobble at {}", undo.entry.edit.timestamp);
+        }
+    }
+}
@@ -1,11 +1,15 @@
 pub mod cache;
+pub mod edits;
 pub mod musicbrainz;
 pub mod pending;
 pub mod rules;
+pub mod state;
 pub mod timestamp;
 
 pub use cache::*;
+pub use edits::*;
 pub use musicbrainz::*;
 pub use pending::*;
 pub use rules::*;
+pub use state::*;
 pub use timestamp::*;
@@ -1,4 +1,7 @@
-use crate::musicbrainz::MusicBrainzScrubActionProvider;
+use crate::config::ScrobbleScrubberConfig;
+use crate::musicbrainz::{
+    MusicBrainzCache, MusicBrainzClient, MusicBrainzRateLimiter, MusicBrainzScrubActionProvider,
+};
 use crate::scrub_action_provider::ScrubActionProvider;
 use clap::Subcommand;
 use lastfm_edit::Track;
@@ -101,10 +104,30 @@ pub enum MusicBrainzCommands {
         #[arg(short = 'f', long, default_value = "text")]
         format: String,
     },
+
+    /// Inspect or purge the on-disk MusicBrainz response cache
+    #[command(subcommand)]
+    Cache(MusicBrainzCacheCommands),
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum MusicBrainzCacheCommands {
+    /// Show cached entry counts and hit/miss statistics
+    Stats,
+
+    /// Remove cached MusicBrainz responses
+    Purge {
+        /// Only remove entries older than the configured TTL
+        #[arg(long)]
+        expired: bool,
+    },
 }
 
 impl MusicBrainzCommands {
-    pub async fn execute(self) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn execute(
+        self,
+        config: &ScrobbleScrubberConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         match self {
             Self::AlbumSearch {
                 artist,
@@ -135,10 +158,62 @@ impl MusicBrainzCommands {
                 title,
                 album,
                 format,
-            } => Self::rank_releases(&artist, &title, album.as_deref(), &format).await,
+            } => Self::rank_releases(&artist, &title, album.as_deref(), &format, config).await,
+            Self::Cache(MusicBrainzCacheCommands::Stats) => Self::show_cache_stats(config),
+            Self::Cache(MusicBrainzCacheCommands::Purge { expired }) => Self::purge_cache(expired),
         }
     }
 
+    /// Show the contents and hit rate of the MusicBrainz response cache
+    fn show_cache_stats(config: &ScrobbleScrubberConfig) -> Result<(), Box<dyn std::error::Error>> {
+        let cache = MusicBrainzCache::shared();
+        let stats = cache.stats();
+        let cache_config = &config.musicbrainz.cache;
+
+        println!("📂 MusicBrainz Cache");
+        println!("===================");
+        match cache.path() {
+            Some(path) => println!("File: {}", path.display()),
+            None => println!("File: none (in-memory only)"),
+        }
+        if !cache_config.enabled {
+            println!("⚠️  Caching is disabled in the configuration");
+        }
+        println!(
+            "TTL: {}h, size cap: {} entries",
+            cache_config.ttl_hours, cache_config.max_entries
+        );
+
+        println!("\nEntries:");
+        println!("  Recording searches: {}", stats.recording_searches);
+        println!("  Release searches:   {}", stats.release_searches);
+        println!("  Releases:           {}", stats.releases);
+        println!("  Release groups:     {}", stats.release_groups);
+        println!(
+            "  Total:              {} ({} expired)",
+            stats.total_entries(),
+            stats.expired
+        );
+
+        println!("\nLookups:");
+        println!("  Hits:     {}", stats.hits);
+        println!("  Misses:   {}", stats.misses);
+        println!("  Hit rate: {:.1}%", stats.hit_rate() * 100.0);
+
+        Ok(())
+    }
+
+    /// Remove entries from the MusicBrainz response cache
+    fn purge_cache(expired_only: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let removed = MusicBrainzCache::shared().purge(expired_only)?;
+        if expired_only {
+            println!("🗑️  Removed {removed} expired MusicBrainz cache entries");
+        } else {
+            println!("🗑️  Removed all {removed} MusicBrainz cache entries");
+        }
+        Ok(())
+    }
+
     /// Search for albums using the provider's search method
     async fn search_albums(
         artist: &str,
@@ -248,9 +323,6 @@ impl MusicBrainzCommands {
 
         // For detailed output, use the provider's search to show what was selected
         if show_tracks || show_all {
-            use musicbrainz_rs::entity::release::Release;
-            use musicbrainz_rs::Fetch;
-
             // Use the provider's search method
             let releases =
                 MusicBrainzScrubActionProvider::search_album_releases(artist, Some(album))
@@ -283,11 +355,10 @@ impl MusicBrainzCommands {
 
                 if show_tracks {
                     // Fetch the full release with recordings
-                    let full_release = Release::fetch()
-                        .id(&canonical.id)
-                        .with_recordings()
-                        .execute()
-                        .await?;
+                    let full_release =
+                        MusicBrainzClient::fetch_release_with_recordings(&canonical.id)
+                            .await
+                            .map_err(|e| format!("MusicBrainz error: {e}"))?;
 
                     println!("📀 TRACK LIST:");
                     if let Some(media) = &full_release.media {
@@ -503,12 +574,17 @@ impl MusicBrainzCommands {
         println!("🆔 Release UUID: {}", canonical.id);
 
         // Fetch additional metadata
-        let full_release = Release::fetch()
-            .id(&canonical.id)
-            .with_recordings()
-            .with_artist_credits()
-            .with_labels()
-            .execute()
+        let release_id = canonical.id.as_str();
+        let full_release = MusicBrainzRateLimiter::shared()
+            .execute("release lookup", move || async move {
+                Release::fetch()
+                    .id(release_id)
+                    .with_recordings()
+                    .with_artist_credits()
+                    .with_labels()
+                    .execute_with_client(MusicBrainzClient::api_client())
+                    .await
+            })
             .await?;
 
         // Show status
@@ -638,9 +714,17 @@ impl MusicBrainzCommands {
         title: &str,
         current_album: Option<&str>,
         format: &str,
+        config: &ScrobbleScrubberConfig,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // Create the compilation provider
-        let provider = CompilationToCanonicalProvider::new();
+        // Create the compilation provider with the configured greatest hits policy
+        let greatest_hits_policy = config
+            .providers
+            .compilation_to_canonical
+            .as_ref()
+            .map(|comp_config| comp_config.greatest_hits_policy)
+            .unwrap_or_default();
+        let provider =
+            CompilationToCanonicalProvider::new().with_greatest_hits_policy(greatest_hits_policy);
 
         println!("🔍 Ranking releases for '{title}' by '{artist}'");
         if let Some(album) = current_album {
@@ -721,6 +805,10 @@ impl MusicBrainzCommands {
                         println!("   ⚠️  VARIOUS ARTISTS");
                     }
 
+                    if release.is_greatest_hits {
+                        println!("   ⚠️  GREATEST HITS");
+                    }
+
                     if let Some(disamb) = &release.disambiguation {
                         println!("   Note: {disamb}");
                     }
@@ -735,21 +823,23 @@ impl MusicBrainzCommands {
 
                 // What would the provider suggest?
                 if let Some(current) = current_album {
-                    let current_is_compilation = ranked_releases
+                    let current_release = ranked_releases
                         .iter()
-                        .find(|r| r.title.eq_ignore_ascii_case(current))
-                        .map(|r| r.is_compilation)
-                        .unwrap_or(false);
+                        .find(|r| r.title.eq_ignore_ascii_case(current));
+                    let current_is_compilation =
+                        current_release.map(|r| r.is_compilation).unwrap_or(false);
+                    let current_is_greatest_hits =
+                        current_release.map(|r| r.is_greatest_hits).unwrap_or(false);
+
+                    if current_is_greatest_hits {
+                        println!(
+                            "🎯 Greatest hits policy: {}",
+                            provider.greatest_hits_policy()
+                        );
+                    }
 
                     if current_is_compilation {
-                        // Find the best non-compilation
-                        let best_non_compilation = ranked_releases.iter().find(|r| {
-                            !r.is_compilation
-                                && !r.is_various_artists
-                                && !r.title.eq_ignore_ascii_case(current)
-                        });
-
-                        if let Some(best) = best_non_compilation {
+                        if let Some(best) = provider.select_replacement(&ranked_releases, current) {
                             println!(
                                 "✅ Provider would suggest: '{}' → '{}'",
                                 current, best.title
@@ -761,6 +851,17 @@ impl MusicBrainzCommands {
                                     .map(|pt| format!("{pt:?}"))
                                     .unwrap_or_else(|| "studio release".to_string())
                             );
+                        } else if current_is_greatest_hits
+                            && ranked_releases.iter().any(|r| {
+                                !r.is_compilation
+                                    && !r.is_various_artists
+                                    && !r.title.eq_ignore_ascii_case(current)
+                            })
+                        {
+                            println!("✅ Provider would NOT suggest a change");
+                            println!(
+                                "   Reason: The greatest hits policy keeps tracks on '{current}'"
+                            );
                         } else {
                             println!("❌ Provider would NOT suggest a change");
                             println!("   Reason: No suitable non-compilation release found");
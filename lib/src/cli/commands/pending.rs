@@ -1,7 +1,6 @@
-use crate::config::ScrobbleScrubberConfig;
-use crate::persistence::{FileStorage, PendingEdit, PendingEditsState, StateStorage};
+use crate::config::{ScrobbleScrubberConfig, StorageConfig};
+use crate::persistence::{ConfiguredStorage, PendingEdit, PendingEditsState, StateStorage};
 use clap::{Args, Subcommand};
-use std::path::PathBuf;
 
 #[derive(Args, Debug, Clone)]
 pub struct PendingArgs {
@@ -12,13 +11,17 @@ pub struct PendingArgs {
 #[derive(Subcommand, Debug, Clone)]
 pub enum PendingCommands {
     /// List all pending edits
-    List,
+    List {
+        /// Only list edits of this artist's scrobbles
+        #[arg(long)]
+        artist: Option<String>,
+    },
     /// Apply a pending edit by ID
     Apply {
         /// ID of the pending edit to apply
         id: String,
     },
-    /// Reject a pending edit by ID (remove without applying)
+    /// Reject a pending edit by ID (remove without applying and never suggest it again)
     Reject {
         /// ID of the pending edit to reject
         id: String,
@@ -29,12 +32,12 @@ pub enum PendingCommands {
 
 pub async fn handle_pending_command(
     args: PendingArgs,
-    data_dir: PathBuf,
+    storage_config: &StorageConfig,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut storage = FileStorage::new(data_dir)?;
+    let mut storage = ConfiguredStorage::open(storage_config).await?;
 
     match args.command {
-        PendingCommands::List => list_pending_edits(&storage).await,
+        PendingCommands::List { artist } => list_pending_edits(&storage, artist.as_deref()).await,
         PendingCommands::Apply { id } => apply_pending_edit(&mut storage, &id).await,
         PendingCommands::Reject { id } => reject_pending_edit(&mut storage, &id).await,
         PendingCommands::Clear => clear_pending_edits(&mut storage).await,
@@ -42,22 +45,23 @@ pub async fn handle_pending_command(
 }
 
 async fn list_pending_edits(
-    storage: &FileStorage,
+    storage: &ConfiguredStorage,
+    artist: Option<&str>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let pending_edits_state = storage.load_pending_edits_state().await?;
+    let pending_edits = match artist {
+        Some(artist) => storage.load_pending_edits_for_artist(artist).await?,
+        None => storage.load_pending_edits_state().await?.pending_edits,
+    };
 
-    if pending_edits_state.pending_edits.is_empty() {
+    if pending_edits.is_empty() {
         println!("No pending edits found.");
         return Ok(());
     }
 
-    println!(
-        "Pending Edits ({}):",
-        pending_edits_state.pending_edits.len()
-    );
+    println!("Pending Edits ({}):", pending_edits.len());
     println!("{}", "=".repeat(80));
 
-    for edit in &pending_edits_state.pending_edits {
+    for edit in &pending_edits {
         print_pending_edit(edit);
         println!("{}", "-".repeat(80));
     }
@@ -66,71 +70,43 @@ async fn list_pending_edits(
 }
 
 async fn apply_pending_edit(
-    storage: &mut FileStorage,
+    storage: &mut ConfiguredStorage,
     id: &str,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut pending_edits_state = storage.load_pending_edits_state().await?;
-
-    let edit_index = pending_edits_state
-        .pending_edits
-        .iter()
-        .position(|edit| edit.id == id)
+    let pending_edit = storage
+        .load_pending_edit(id)
+        .await?
         .ok_or_else(|| format!("Pending edit with ID '{id}' not found"))?;
 
-    let pending_edit = pending_edits_state.pending_edits.remove(edit_index);
-
-    // Save the updated pending edits state
-    storage
-        .save_pending_edits_state(&pending_edits_state)
-        .await?;
-
-    println!("Applied pending edit:");
-    print_pending_edit(&pending_edit);
-
     let scrobble_edit = pending_edit.to_scrobble_edit();
 
     // Apply the edit directly with the authenticated client
     let config = ScrobbleScrubberConfig::load()?;
     let client = crate::cli::auth::create_authenticated_client(&config).await?;
 
-    match client.edit_scrobble(&scrobble_edit).await {
-        Ok(_) => {
-            println!("✓ Successfully applied edit to Last.fm");
-        }
-        Err(e) => {
-            // Re-add the edit back to pending if it failed
-            let mut pending_edits_state = storage.load_pending_edits_state().await?;
-            pending_edits_state.pending_edits.push(pending_edit);
-            storage
-                .save_pending_edits_state(&pending_edits_state)
-                .await?;
-
-            return Err(format!("Failed to apply edit to Last.fm: {e}").into());
-        }
-    }
+    // The edit stays pending if applying it fails
+    client
+        .edit_scrobble(&scrobble_edit)
+        .await
+        .map_err(|e| format!("Failed to apply edit to Last.fm: {e}"))?;
+    storage.approve_pending_edit(id).await?;
+
+    println!("Applied pending edit:");
+    print_pending_edit(&pending_edit);
+    println!("✓ Successfully applied edit to Last.fm");
 
     Ok(())
 }
 
 async fn reject_pending_edit(
-    storage: &mut FileStorage,
+    storage: &mut ConfiguredStorage,
     id: &str,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut pending_edits_state = storage.load_pending_edits_state().await?;
-
-    let edit_index = pending_edits_state
-        .pending_edits
-        .iter()
-        .position(|edit| edit.id == id)
+    let pending_edit = storage
+        .reject_pending_edit(id)
+        .await?
         .ok_or_else(|| format!("Pending edit with ID '{id}' not found"))?;
 
-    let pending_edit = pending_edits_state.pending_edits.remove(edit_index);
-
-    // Save the updated pending edits state
-    storage
-        .save_pending_edits_state(&pending_edits_state)
-        .await?;
-
     println!("Rejected pending edit:");
     print_pending_edit(&pending_edit);
 
@@ -138,7 +114,7 @@ async fn reject_pending_edit(
 }
 
 async fn clear_pending_edits(
-    storage: &mut FileStorage,
+    storage: &mut ConfiguredStorage,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let pending_edits_state = storage.load_pending_edits_state().await?;
     let count = pending_edits_state.pending_edits.len();
@@ -199,6 +175,18 @@ fn print_pending_edit(edit: &PendingEdit) {
         println!("(No changes - removal edit)");
     }
 
+    for conflict in &edit.conflicts {
+        println!("⚠️  Providers disagree on {conflict}");
+    }
+
+    if let Some(confidence) = edit.confidence {
+        println!("Confidence: {:.0}%", confidence * 100.0);
+    }
+
+    if let Some(ref explanation) = edit.explanation {
+        println!("Reason: {explanation}");
+    }
+
     if let Some(timestamp) = edit.timestamp {
         if let Some(datetime) = chrono::DateTime::from_timestamp(timestamp as i64, 0) {
             println!("Timestamp: {}", datetime.format("%Y-%m-%d %H:%M:%S UTC"));
@@ -1,14 +1,25 @@
+use crate::alias_rules::{AliasMatching, AliasRule};
+use crate::config::RuleFileFormat;
+use crate::default_rules::default_rule_example_tracks;
 use crate::persistence::StateStorage;
-use crate::rewrite::{load_comprehensive_default_rules, RewriteRule, SdRule};
+use crate::rewrite::{
+    load_comprehensive_default_rules, FieldExclusions, ReplacementMode, RewriteRule, RuleField,
+    SdRule,
+};
+use crate::rule_examples::{check_rule_examples, example_count, RuleExample};
+use crate::rule_lint::{lint_rules, LintSeverity};
+use crate::rules_dir::{export_rules_dir, load_rules_dir};
+use crate::track_cache::TrackCache;
 use lastfm_edit::{LastFmError, Result};
 use std::collections::HashSet;
 use std::io::{self, Write};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 /// Show current active rewrite rules
 pub async fn show_active_rules(
-    storage: &Arc<Mutex<crate::persistence::FileStorage>>,
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
 ) -> Result<()> {
     println!("📝 Active Rewrite Rules");
     println!("=====================");
@@ -24,11 +35,22 @@ pub async fn show_active_rules(
             )))
         })?;
 
-    if rules_state.rewrite_rules.is_empty() {
+    if rules_state.rewrite_rules.is_empty() && rules_state.alias_rules.is_empty() {
         println!("No rewrite rules configured");
         return Ok(());
     }
 
+    let rule_stats = storage
+        .lock()
+        .await
+        .load_rule_stats_state()
+        .await
+        .map_err(|e| {
+            LastFmError::Io(std::io::Error::other(format!(
+                "Failed to load rule statistics: {e}"
+            )))
+        })?;
+
     println!("Found {} rewrite rules:", rules_state.rewrite_rules.len());
 
     for (i, rule) in rules_state.rewrite_rules.iter().enumerate() {
@@ -40,28 +62,75 @@ pub async fn show_active_rules(
 
         if let Some(track_rule) = &rule.track_name {
             println!(
-                "    Track: '{}' → '{}'",
-                track_rule.find, track_rule.replace
+                "    Track: '{}' → '{}'{}",
+                track_rule.find,
+                track_rule.replace,
+                mode_suffix(track_rule)
             );
         }
         if let Some(artist_rule) = &rule.artist_name {
             println!(
-                "    Artist: '{}' → '{}'",
-                artist_rule.find, artist_rule.replace
+                "    Artist: '{}' → '{}'{}",
+                artist_rule.find,
+                artist_rule.replace,
+                mode_suffix(artist_rule)
             );
         }
         if let Some(album_rule) = &rule.album_name {
             println!(
-                "    Album: '{}' → '{}'",
-                album_rule.find, album_rule.replace
+                "    Album: '{}' → '{}'{}",
+                album_rule.find,
+                album_rule.replace,
+                mode_suffix(album_rule)
             );
         }
         if let Some(album_artist_rule) = &rule.album_artist_name {
             println!(
-                "    Album Artist: '{}' → '{}'",
-                album_artist_rule.find, album_artist_rule.replace
+                "    Album Artist: '{}' → '{}'{}",
+                album_artist_rule.find,
+                album_artist_rule.replace,
+                mode_suffix(album_artist_rule)
             );
         }
+        for field in RuleField::ALL {
+            if let Some(pattern) = rule.unless.get(field) {
+                println!("    Unless {field} matches: '{pattern}'");
+            }
+        }
+        for condition in &rule.conditions {
+            println!("    Only if {condition}");
+        }
+        for example in &rule.examples {
+            println!("    Example: {example}");
+        }
+
+        if let Err(e) = rule.compile() {
+            println!("    ⚠️  Invalid, skipped by the scrubber: {e}");
+        }
+
+        match rule_stats.for_rule(rule) {
+            Some(stats) => {
+                println!(
+                    "    Stats: matched {}, produced {}, applied {}, rejected by MusicBrainz {}",
+                    stats.matched, stats.produced, stats.applied, stats.rejected_by_musicbrainz
+                );
+                if let Some(last_fired) = stats.last_fired {
+                    println!(
+                        "    Last fired: {}",
+                        last_fired.format("%Y-%m-%d %H:%M:%S UTC")
+                    );
+                }
+                if let (Some(last_matched), Some(track)) =
+                    (stats.last_matched, &stats.last_matched_track)
+                {
+                    println!(
+                        "    Last matched: {track} ({})",
+                        last_matched.format("%Y-%m-%d %H:%M:%S UTC")
+                    );
+                }
+            }
+            None => println!("    Stats: never matched"),
+        }
 
         if rule.requires_confirmation || rule.requires_musicbrainz_confirmation {
             println!("    Options:");
@@ -76,13 +145,53 @@ pub async fn show_active_rules(
         println!();
     }
 
+    if !rules_state.alias_rules.is_empty() {
+        println!("Found {} alias tables:", rules_state.alias_rules.len());
+        for (i, alias_rule) in rules_state.alias_rules.iter().enumerate() {
+            println!(
+                "  Aliases {}: {}",
+                i + 1,
+                alias_rule
+                    .name
+                    .as_deref()
+                    .unwrap_or(&format!("Aliases #{}", i + 1))
+            );
+            println!(
+                "    Field: {} ({} matching)",
+                alias_rule.field, alias_rule.matching
+            );
+            println!(
+                "    {} canonical values, {} aliases",
+                alias_rule.aliases.len(),
+                alias_rule.alias_count()
+            );
+            if let Err(e) = alias_rule.compile() {
+                println!("    ⚠️  Invalid, skipped by the scrubber: {e}");
+            }
+            if alias_rule.requires_confirmation {
+                println!("    Options:");
+                println!("      - Requires user confirmation");
+            }
+            println!();
+        }
+    }
+
     Ok(())
 }
 
+/// Describe a non-default replacement mode for display
+fn mode_suffix(rule: &SdRule) -> String {
+    if rule.mode.is_whole_string() {
+        String::new()
+    } else {
+        format!(" [{}]", rule.mode)
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 /// Add a new rewrite rule
 pub async fn add_rewrite_rule(
-    storage: &Arc<Mutex<crate::persistence::FileStorage>>,
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
     name: Option<&str>,
     track_find: Option<&str>,
     track_replace: Option<&str>,
@@ -93,8 +202,10 @@ pub async fn add_rewrite_rule(
     album_artist_find: Option<&str>,
     album_artist_replace: Option<&str>,
     flags: Option<&str>,
+    mode: ReplacementMode,
     require_confirmation: bool,
     require_musicbrainz_confirmation: bool,
+    examples: &[String],
 ) -> Result<()> {
     println!("📝 Adding New Rewrite Rule");
     println!("==========================");
@@ -144,9 +255,31 @@ pub async fn add_rewrite_rule(
             find: find.to_string(),
             replace: replace.to_string(),
             flags: flags.map(|f| f.to_string()),
+            mode,
         }
     };
 
+    // Examples are written for the first field the rule rewrites
+    let example_field = [
+        (RuleField::TrackName, has_track_rule),
+        (RuleField::ArtistName, has_artist_rule),
+        (RuleField::AlbumName, has_album_rule),
+        (RuleField::AlbumArtistName, has_album_artist_rule),
+    ]
+    .into_iter()
+    .find_map(|(field, has_rule)| has_rule.then_some(field))
+    .unwrap_or(RuleField::TrackName);
+    let mut rule_examples = Vec::new();
+    for example in examples {
+        match RuleExample::parse(example_field, example) {
+            Some(rule_example) => rule_examples.push(rule_example),
+            None => {
+                println!("❌ Error: Invalid example '{example}' (expected 'input -> expected')");
+                return Ok(());
+            }
+        }
+    }
+
     // Create the new rule
     let new_rule = RewriteRule {
         name: name.map(|n| n.to_string()),
@@ -189,8 +322,27 @@ pub async fn add_rewrite_rule(
         requires_confirmation: require_confirmation,
         requires_musicbrainz_confirmation: require_musicbrainz_confirmation,
         musicbrainz_release_filters: None, // Use default filters, can be configured later if needed
+        unless: FieldExclusions::new(),
+        conditions: Vec::new(),
+        examples: rule_examples,
     };
 
+    // Reject rules that would fail to compile instead of storing them
+    if let Err(e) = new_rule.compile() {
+        println!("❌ Error: Invalid rule pattern: {e}");
+        return Ok(());
+    }
+
+    // Reject rules that don't produce their own examples
+    let failures = check_rule_examples(std::slice::from_ref(&new_rule));
+    if !failures.is_empty() {
+        println!("❌ Error: The rule doesn't produce its examples:");
+        for failure in &failures {
+            println!("   {failure}");
+        }
+        return Ok(());
+    }
+
     // Load existing rules
     let mut rules_state = storage
         .lock()
@@ -242,19 +394,25 @@ pub async fn add_rewrite_rule(
     if let Some(flags) = flags {
         println!("   Flags: {flags}");
     }
+    if !mode.is_whole_string() {
+        println!("   Mode: {mode}");
+    }
     if require_confirmation {
         println!("   Requires confirmation: yes");
     }
     if require_musicbrainz_confirmation {
         println!("   Requires MusicBrainz confirmation: yes");
     }
+    for example in &new_rule.examples {
+        println!("   Example: {example}");
+    }
 
     Ok(())
 }
 
 /// Remove a rewrite rule
 pub async fn remove_rewrite_rule(
-    storage: &Arc<Mutex<crate::persistence::FileStorage>>,
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
     index: Option<usize>,
     name: Option<&str>,
     all: bool,
@@ -353,21 +511,345 @@ pub async fn remove_rewrite_rule(
     Ok(())
 }
 
-fn rule_signature(rule: &RewriteRule) -> String {
-    format!(
-        "track:{:?}|artist:{:?}|album:{:?}|album_artist:{:?}",
-        rule.track_name.as_ref().map(|r| (&r.find, &r.replace)),
-        rule.artist_name.as_ref().map(|r| (&r.find, &r.replace)),
-        rule.album_name.as_ref().map(|r| (&r.find, &r.replace)),
-        rule.album_artist_name
-            .as_ref()
-            .map(|r| (&r.find, &r.replace))
-    )
+/// Import an alias table from a TSV or JSON file
+pub async fn add_alias_rule(
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
+    name: Option<&str>,
+    field: RuleField,
+    file: &Path,
+    matching: AliasMatching,
+    require_confirmation: bool,
+) -> Result<()> {
+    println!("📝 Adding Alias Table");
+    println!("=====================");
+
+    let mut alias_rule = match AliasRule::load_file(field, file) {
+        Ok(rule) => rule
+            .with_matching(matching)
+            .with_confirmation_required(require_confirmation),
+        Err(e) => {
+            println!("❌ Error: Failed to load {}: {e}", file.display());
+            return Ok(());
+        }
+    };
+    alias_rule.name = Some(name.map_or_else(
+        || {
+            file.file_stem().map_or_else(
+                || "aliases".to_string(),
+                |s| s.to_string_lossy().into_owned(),
+            )
+        },
+        str::to_string,
+    ));
+
+    if let Err(e) = alias_rule.compile() {
+        println!("❌ Error: Invalid alias table: {e}");
+        return Ok(());
+    }
+
+    let mut rules_state = storage
+        .lock()
+        .await
+        .load_rewrite_rules_state()
+        .await
+        .map_err(|e| {
+            LastFmError::Io(std::io::Error::other(format!(
+                "Failed to load rewrite rules: {e}"
+            )))
+        })?;
+
+    println!("✅ Successfully added alias table:");
+    println!(
+        "   Name: {}",
+        alias_rule.name.as_deref().unwrap_or_default()
+    );
+    println!("   Field: {field}");
+    println!("   Matching: {matching}");
+    println!(
+        "   {} canonical values, {} aliases",
+        alias_rule.aliases.len(),
+        alias_rule.alias_count()
+    );
+    if require_confirmation {
+        println!("   Requires confirmation: yes");
+    }
+
+    rules_state.alias_rules.push(alias_rule);
+    storage
+        .lock()
+        .await
+        .save_rewrite_rules_state(&rules_state)
+        .await
+        .map_err(|e| {
+            LastFmError::Io(std::io::Error::other(format!(
+                "Failed to save rewrite rules: {e}"
+            )))
+        })?;
+
+    Ok(())
+}
+
+/// Remove an alias table
+pub async fn remove_alias_rule(
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
+    index: Option<usize>,
+    name: Option<&str>,
+) -> Result<()> {
+    println!("🗑️ Removing Alias Table");
+    println!("=======================");
+
+    let mut rules_state = storage
+        .lock()
+        .await
+        .load_rewrite_rules_state()
+        .await
+        .map_err(|e| {
+            LastFmError::Io(std::io::Error::other(format!(
+                "Failed to load rewrite rules: {e}"
+            )))
+        })?;
+
+    if rules_state.alias_rules.is_empty() {
+        println!("❌ No alias tables found to remove");
+        return Ok(());
+    }
+
+    if let Some(table_index) = index {
+        if table_index == 0 || table_index > rules_state.alias_rules.len() {
+            println!("❌ Invalid alias table index: {table_index}");
+            println!("   Valid range: 1-{}", rules_state.alias_rules.len());
+            return Ok(());
+        }
+
+        let removed = rules_state.alias_rules.remove(table_index - 1);
+        println!(
+            "✅ Removed alias table #{table_index}: {}",
+            removed.name.as_deref().unwrap_or("(unnamed)")
+        );
+    } else if let Some(table_name) = name {
+        let original_len = rules_state.alias_rules.len();
+        rules_state
+            .alias_rules
+            .retain(|rule| rule.name.as_deref() != Some(table_name));
+
+        if original_len == rules_state.alias_rules.len() {
+            println!("❌ No alias table found with name: '{table_name}'");
+            return Ok(());
+        }
+        println!("✅ Removed alias table: '{table_name}'");
+    } else {
+        println!("❌ Error: Must specify --index or --name");
+        println!("   Use 'rules show' to see alias table indices and names");
+        return Ok(());
+    }
+
+    storage
+        .lock()
+        .await
+        .save_rewrite_rules_state(&rules_state)
+        .await
+        .map_err(|e| {
+            LastFmError::Io(std::io::Error::other(format!(
+                "Failed to save rewrite rules: {e}"
+            )))
+        })?;
+
+    println!("Remaining alias tables: {}", rules_state.alias_rules.len());
+    Ok(())
+}
+
+/// Write the active rules to a directory, one file per rule and per alias table
+pub async fn export_rules_to_dir(
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
+    dir: &Path,
+    format: RuleFileFormat,
+    clean: bool,
+) -> Result<()> {
+    let rules_state = storage
+        .lock()
+        .await
+        .load_rewrite_rules_state()
+        .await
+        .map_err(|e| {
+            LastFmError::Io(std::io::Error::other(format!(
+                "Failed to load rewrite rules: {e}"
+            )))
+        })?;
+
+    let written = export_rules_dir(dir, &rules_state, format, clean)
+        .map_err(|e| LastFmError::Io(std::io::Error::other(e.to_string())))?;
+
+    println!(
+        "📤 Exported {} rewrite rules and {} alias tables to {} ({} files)",
+        rules_state.rewrite_rules.len(),
+        rules_state.alias_rules.len(),
+        dir.display(),
+        written.len()
+    );
+    Ok(())
+}
+
+/// Replace the active rules with the rules in a directory of rule files
+pub async fn import_rules_from_dir(
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
+    dir: &Path,
+) -> Result<()> {
+    let rules_state =
+        load_rules_dir(dir).map_err(|e| LastFmError::Io(std::io::Error::other(e.to_string())))?;
+
+    storage
+        .lock()
+        .await
+        .save_rewrite_rules_state(&rules_state)
+        .await
+        .map_err(|e| {
+            LastFmError::Io(std::io::Error::other(format!(
+                "Failed to save rewrite rules: {e}"
+            )))
+        })?;
+
+    println!(
+        "📥 Imported {} rewrite rules and {} alias tables from {}",
+        rules_state.rewrite_rules.len(),
+        rules_state.alias_rules.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+/// Check the active (or default) rewrite rules against their examples
+///
+/// Returns an error when any example fails so scripts can use this as a regression test.
+pub async fn test_rule_examples(
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
+    defaults: bool,
+) -> Result<()> {
+    println!("🧪 Testing Rewrite Rule Examples");
+    println!("================================");
+
+    let rules = if defaults {
+        load_comprehensive_default_rules()
+    } else {
+        storage
+            .lock()
+            .await
+            .load_rewrite_rules_state()
+            .await
+            .map_err(|e| {
+                LastFmError::Io(std::io::Error::other(format!(
+                    "Failed to load rewrite rules: {e}"
+                )))
+            })?
+            .rewrite_rules
+    };
+
+    let total = example_count(&rules);
+    let rules_with_examples = rules.iter().filter(|r| !r.examples.is_empty()).count();
+    println!(
+        "Checking {total} examples of {rules_with_examples} rules ({} rules have no examples)",
+        rules.len() - rules_with_examples
+    );
+
+    let failures = check_rule_examples(&rules);
+    if failures.is_empty() {
+        println!("✅ All examples passed");
+        return Ok(());
+    }
+
+    let mut last_rule = None;
+    for failure in &failures {
+        let rule_index = failure.rule_index();
+        if last_rule != Some(rule_index) {
+            last_rule = Some(rule_index);
+            println!(
+                "❌ Rule {}: {}",
+                rule_index + 1,
+                rules[rule_index].name.as_deref().unwrap_or("(unnamed)")
+            );
+        }
+        println!("   {failure}");
+    }
+
+    Err(LastFmError::Io(std::io::Error::other(format!(
+        "{} rule examples failed",
+        failures.len()
+    ))))
+}
+
+/// Lint the active rewrite rules
+///
+/// Rules are exercised with the default rule examples and up to `cached_tracks` tracks
+/// from the local track cache.
+pub async fn lint_active_rules(
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
+    cached_tracks: usize,
+) -> Result<()> {
+    println!("🔍 Linting Rewrite Rules");
+    println!("========================");
+
+    let rules_state = storage
+        .lock()
+        .await
+        .load_rewrite_rules_state()
+        .await
+        .map_err(|e| {
+            LastFmError::Io(std::io::Error::other(format!(
+                "Failed to load rewrite rules: {e}"
+            )))
+        })?;
+    let rules = &rules_state.rewrite_rules;
+
+    let mut samples = default_rule_example_tracks();
+    if cached_tracks > 0 {
+        samples.extend(TrackCache::load().get_recent_tracks_limited(cached_tracks));
+    }
+
+    println!(
+        "Checking {} rules against {} sample tracks",
+        rules.len(),
+        samples.len()
+    );
+
+    let lints = lint_rules(rules, &samples);
+    if lints.is_empty() {
+        println!("✅ No problems found");
+        return Ok(());
+    }
+
+    for lint in &lints {
+        let icon = match lint.severity() {
+            LintSeverity::Error => "❌",
+            LintSeverity::Warning => "⚠️ ",
+        };
+        println!("{icon} {lint}");
+        for index in lint.rule_indices() {
+            if let Some(rule) = rules.get(index) {
+                println!(
+                    "     #{}: {}",
+                    index + 1,
+                    rule.name.as_deref().unwrap_or("(unnamed)")
+                );
+            }
+        }
+    }
+
+    let errors = lints
+        .iter()
+        .filter(|lint| lint.severity() == LintSeverity::Error)
+        .count();
+    println!();
+    println!(
+        "Found {} problems ({errors} errors, {} warnings)",
+        lints.len(),
+        lints.len() - errors
+    );
+
+    Ok(())
 }
 
 /// Enable all default rewrite rules, avoiding duplicates
 pub async fn enable_default_rules(
-    storage: &Arc<Mutex<crate::persistence::FileStorage>>,
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
 ) -> Result<()> {
     println!("📝 Enabling Default Rewrite Rules");
     println!("=================================");
@@ -388,14 +870,14 @@ pub async fn enable_default_rules(
     let existing_signatures: HashSet<String> = rules_state
         .rewrite_rules
         .iter()
-        .map(rule_signature)
+        .map(RewriteRule::signature)
         .collect();
 
     let mut added_count = 0;
     let mut skipped_count = 0;
 
     for rule in default_rules {
-        let signature = rule_signature(&rule);
+        let signature = rule.signature();
         if existing_signatures.contains(&signature) {
             log::debug!(
                 "Skipping duplicate rule: {}",
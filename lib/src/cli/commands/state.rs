@@ -0,0 +1,167 @@
+use crate::config::ScrobbleScrubberConfig;
+use crate::persistence::bundle::StateBundle;
+use crate::persistence::ConfiguredStorage;
+use crate::session_manager::SessionManager;
+use crate::track_cache::TrackCache;
+use clap::Subcommand;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum StateCommands {
+    /// Export rules, pending items, settings and the timestamp anchor to a single file
+    Export {
+        /// File to write the bundle to
+        path: PathBuf,
+        /// Also include the track cache
+        #[arg(long)]
+        include_cache: bool,
+        /// Also include the saved Last.fm session (grants access to the account, keep the
+        /// file private)
+        #[arg(long)]
+        include_session: bool,
+    },
+    /// Replace the current state with the contents of an exported bundle
+    Import {
+        /// Bundle file to import
+        path: PathBuf,
+        /// Also restore the Last.fm session, if the bundle has one
+        #[arg(long)]
+        include_session: bool,
+        /// Replace the current state without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+impl StateCommands {
+    pub async fn execute(
+        self,
+        config: &ScrobbleScrubberConfig,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            Self::Export {
+                path,
+                include_cache,
+                include_session,
+            } => export_state(config, &path, include_cache, include_session).await,
+            Self::Import {
+                path,
+                include_session,
+                yes,
+            } => import_state(config, &path, include_session, yes).await,
+        }
+    }
+}
+
+async fn export_state(
+    config: &ScrobbleScrubberConfig,
+    path: &Path,
+    include_cache: bool,
+    include_session: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let storage = ConfiguredStorage::open(&config.storage).await?;
+    let mut bundle = StateBundle::export(&storage).await?;
+
+    if include_cache {
+        bundle = bundle.with_track_cache(TrackCache::load());
+    }
+    if include_session {
+        match SessionManager::new(&config.lastfm.username).load_session() {
+            Some(session) => bundle = bundle.with_session(session),
+            None => println!("⚠️  No saved session found, exporting without one"),
+        }
+    }
+
+    bundle.write_to(path)?;
+
+    println!("📦 Exported state to {}", path.display());
+    print_bundle_summary(&bundle);
+    if bundle.session.is_some() {
+        println!("🔑 The bundle contains your Last.fm session - keep it private");
+    }
+    Ok(())
+}
+
+async fn import_state(
+    config: &ScrobbleScrubberConfig,
+    path: &Path,
+    include_session: bool,
+    yes: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let bundle = StateBundle::read_from(path)?;
+
+    if !yes {
+        println!("📦 {} contains:", path.display());
+        print_bundle_summary(&bundle);
+        print!("Replace the current state with it? (y/N): ");
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        if input.trim().to_lowercase() != "y" && input.trim().to_lowercase() != "yes" {
+            println!("❌ Import cancelled");
+            return Ok(());
+        }
+    }
+
+    let mut storage = ConfiguredStorage::open(&config.storage).await?;
+    let backup = StateBundle::backup(&storage, Path::new(&config.storage.state_file)).await?;
+    println!("💾 Backed up the current state to {}", backup.display());
+    bundle.restore(&mut storage).await?;
+
+    if let Some(track_cache) = &bundle.track_cache {
+        track_cache
+            .save()
+            .map_err(|e| format!("Failed to save track cache: {e}"))?;
+    }
+    match (&bundle.session, include_session) {
+        (Some(session), true) => SessionManager::new(&session.username).save_session(session)?,
+        (Some(_), false) => {
+            println!(
+                "ℹ️  Skipped the bundled Last.fm session (use --include-session to restore it)"
+            )
+        }
+        (None, _) => {}
+    }
+
+    println!(
+        "📥 Imported state from {} (exported by scrobble-scrubber {} at {})",
+        path.display(),
+        bundle.exported_by,
+        bundle.exported_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    print_bundle_summary(&bundle);
+    println!(
+        "   Run `scrobble-scrubber state import {}` to go back",
+        backup.display()
+    );
+    Ok(())
+}
+
+fn print_bundle_summary(bundle: &StateBundle) {
+    println!(
+        "  {} rewrite rules, {} alias tables",
+        bundle.rewrite_rules_state.rewrite_rules.len(),
+        bundle.rewrite_rules_state.alias_rules.len()
+    );
+    println!(
+        "  {} pending edits, {} pending rules",
+        bundle.pending_edits_state.pending_edits.len(),
+        bundle.pending_rewrite_rules_state.pending_rules.len()
+    );
+    match bundle.timestamp_state.last_processed_timestamp {
+        Some(anchor) => println!(
+            "  Timestamp anchor: {}",
+            anchor.format("%Y-%m-%d %H:%M:%S UTC")
+        ),
+        None => println!("  No timestamp anchor"),
+    }
+    if let Some(track_cache) = &bundle.track_cache {
+        println!(
+            "  Track cache: {} recent tracks, {} artists",
+            track_cache.recent_tracks.len(),
+            track_cache.artist_tracks.len()
+        );
+    }
+}
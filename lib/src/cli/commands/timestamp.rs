@@ -6,7 +6,7 @@ use tokio::sync::Mutex;
 
 /// Set timestamp anchor back N tracks from current position
 pub async fn set_timestamp_anchor(
-    storage: &Arc<Mutex<crate::persistence::FileStorage>>,
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
     tracks_back: u32,
 ) -> Result<()> {
     use chrono::DateTime;
@@ -69,7 +69,7 @@ pub async fn set_timestamp_anchor(
 
 /// Set timestamp anchor to a specific timestamp
 pub async fn set_timestamp_anchor_to_timestamp(
-    storage: &Arc<Mutex<crate::persistence::FileStorage>>,
+    storage: &Arc<Mutex<crate::persistence::ConfiguredStorage>>,
     timestamp_str: &str,
 ) -> Result<()> {
     use chrono::DateTime;
@@ -1,14 +1,20 @@
 pub mod auth;
 pub mod commands;
 
+use crate::alias_rules::AliasMatching;
 #[cfg(feature = "openai")]
 use crate::config::OpenAIProviderConfig;
-use crate::config::{ScrobbleScrubberConfig, StorageConfig};
+use crate::config::{RuleFileFormat, ScrobbleScrubberConfig, StorageBackend, StorageConfig};
 use crate::event_logger::EventLogger;
-use crate::musicbrainz::CompilationToCanonicalProvider;
+use crate::http_provider::HttpScrubActionProvider;
+use crate::majority_album_provider::MajorityAlbumProvider;
+use crate::musicbrainz::{
+    CompilationToCanonicalProvider, MusicBrainzCache, MusicBrainzClient, MusicBrainzRateLimiter,
+};
 #[cfg(feature = "openai")]
 use crate::openai_provider::OpenAIScrubActionProvider;
-use crate::persistence::{FileStorage, StateStorage};
+use crate::persistence::{ConfiguredStorage, StateStorage};
+use crate::rewrite::{CompiledRuleSet, ReplacementMode, RuleField};
 use crate::scrub_action_provider::{OrScrubActionProvider, RewriteRulesScrubActionProvider};
 use crate::scrubber::ScrobbleScrubber;
 use crate::session_manager::SessionManager;
@@ -29,6 +35,10 @@ enum ProviderType {
     Openai,
     /// Compilation to Canonical provider (suggests earliest releases)
     CompilationToCanonical,
+    /// External HTTP/webhook suggestion provider
+    Http,
+    /// Majority album provider (moves scrobbles to the album most plays are on)
+    MajorityAlbum,
 }
 
 #[derive(Parser, Debug)]
@@ -317,6 +327,10 @@ struct AddRuleArgs {
     #[arg(long)]
     flags: Option<String>,
 
+    /// How much of each field to replace when a pattern matches
+    #[arg(long, value_enum, default_value_t = ReplacementMode::WholeString)]
+    mode: ReplacementMode,
+
     /// Require confirmation before applying this rule
     #[arg(long)]
     require_confirmation: bool,
@@ -324,6 +338,33 @@ struct AddRuleArgs {
     /// Require MusicBrainz confirmation of the rewritten metadata
     #[arg(long)]
     require_musicbrainz_confirmation: bool,
+
+    /// Example the rule must produce, as 'input -> expected' for the first rewritten field (repeatable)
+    #[arg(long = "example")]
+    examples: Vec<String>,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+struct AddAliasesArgs {
+    /// Alias table name (defaults to the file name)
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Field the aliases apply to
+    #[arg(long, value_enum)]
+    field: RuleField,
+
+    /// TSV (alias<TAB>canonical per line) or JSON ({"Canonical": ["alias", ...]}) file
+    #[arg(long)]
+    file: std::path::PathBuf,
+
+    /// How field values are compared with the aliases
+    #[arg(long, value_enum, default_value_t = AliasMatching::Exact)]
+    matching: AliasMatching,
+
+    /// Require confirmation before applying changes from this table
+    #[arg(long)]
+    require_confirmation: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -348,6 +389,48 @@ enum RulesCommands {
         #[arg(long)]
         all: bool,
     },
+    /// Check rewrite rules against their before → after examples
+    Test {
+        /// Test the built-in default rules instead of the active rules
+        #[arg(long)]
+        defaults: bool,
+    },
+    /// Check rewrite rules for invalid, shadowed, conflicting and non-idempotent rules
+    Lint {
+        /// Number of cached recent tracks to use as additional samples
+        #[arg(long, default_value = "1000")]
+        cached_tracks: usize,
+    },
+    /// Import an alias table mapping spellings of a field to canonical values
+    AddAliases(AddAliasesArgs),
+    /// Remove an alias table
+    RemoveAliases {
+        /// Alias table index to remove (1-based, as shown in rules show)
+        #[arg(short, long)]
+        index: Option<usize>,
+
+        /// Alias table name to remove (alternative to index)
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+    /// Write the active rules to a directory of rule files
+    ExportDir {
+        /// Directory to write to (defaults to the configured rules directory)
+        dir: Option<std::path::PathBuf>,
+
+        /// File format (defaults to the configured format)
+        #[arg(long, value_enum)]
+        format: Option<RuleFileFormat>,
+
+        /// Replace rule files already in the directory
+        #[arg(long)]
+        clean: bool,
+    },
+    /// Replace the active rules with the rules in a directory of rule files
+    ImportDir {
+        /// Directory to read from (defaults to the configured rules directory)
+        dir: Option<std::path::PathBuf>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -386,6 +469,12 @@ enum Commands {
     /// MusicBrainz operations
     #[command(subcommand, name = "musicbrainz")]
     MusicBrainz(MusicBrainzCommands),
+    /// Export or import the full scrubber state as a single file
+    #[command(subcommand)]
+    State(StateCommands),
+    /// Edit log operations
+    #[command(subcommand)]
+    Edits(EditsCommands),
     /// Clear saved session data (forces fresh login on next run)
     ClearSession,
 }
@@ -569,6 +658,19 @@ fn merge_args_into_config(
         Commands::MusicBrainz(_) => {
             // No specific configuration needed for MusicBrainz commands
         }
+        Commands::State(_) => {
+            // No specific configuration needed for state commands
+        }
+        Commands::Edits(EditsCommands::Undo(undo_args)) => {
+            if undo_args.dry_run {
+                config.scrubber.dry_run = true;
+            }
+            if undo_args.no_confirmation {
+                config.scrubber.require_confirmation = false;
+            } else if undo_args.require_confirmation {
+                config.scrubber.require_confirmation = true;
+            }
+        }
         Commands::ClearSession => {
             // No specific configuration needed for clearing session
         }
@@ -615,6 +717,7 @@ fn merge_args_into_config(
         config.providers.enable_musicbrainz = false;
         config.providers.enable_compilation_to_canonical = false;
         config.providers.enable_http = false;
+        config.providers.enable_majority_album = false;
 
         for provider in &args.providers {
             match provider {
@@ -627,6 +730,12 @@ fn merge_args_into_config(
                 ProviderType::CompilationToCanonical => {
                     config.providers.enable_compilation_to_canonical = true;
                 }
+                ProviderType::Http => {
+                    config.providers.enable_http = true;
+                }
+                ProviderType::MajorityAlbum => {
+                    config.providers.enable_majority_album = true;
+                }
             }
         }
     }
@@ -634,6 +743,32 @@ fn merge_args_into_config(
     config
 }
 
+/// The directory given to `rules export-dir`/`import-dir`, or the configured rules directory
+/// Stop rule commands that would change stored rules the rules directory replaces
+fn ensure_rules_editable(config: &ScrobbleScrubberConfig) -> Result<()> {
+    crate::rules_dir::ensure_rules_editable(config.storage.rules_dir.as_ref())
+        .map_err(|e| LastFmError::Io(std::io::Error::other(e.to_string())))
+}
+
+fn rules_dir_arg(
+    dir: &Option<std::path::PathBuf>,
+    config: &ScrobbleScrubberConfig,
+) -> Result<std::path::PathBuf> {
+    dir.clone()
+        .or_else(|| {
+            config
+                .storage
+                .rules_dir
+                .as_ref()
+                .map(|rules_dir| rules_dir.path.clone().into())
+        })
+        .ok_or_else(|| {
+            LastFmError::Io(std::io::Error::other(
+                "No directory given and no [storage.rules_dir] configured",
+            ))
+        })
+}
+
 pub async fn run() -> Result<()> {
     // Set default RUST_LOG if not already set
     if std::env::var("RUST_LOG").is_err() {
@@ -656,19 +791,53 @@ pub async fn run() -> Result<()> {
         config.scrubber.interval
     );
 
+    MusicBrainzCache::init_shared(&config);
+    MusicBrainzClient::init_server(&config);
+    MusicBrainzRateLimiter::init_shared(&config);
+
+    // State export/import works on local files only, so don't log in for it
+    if let Commands::State(state_cmd) = &args.command {
+        return state_cmd.clone().execute(&config).await.map_err(|e| {
+            log::error!("State command failed: {e}");
+            LastFmError::Io(std::io::Error::other(e.to_string()))
+        });
+    }
+
     // Create and login to LastFM client (using session if available)
     let client = auth::create_authenticated_client(&config).await?;
 
     // Create storage wrapped in Arc<Mutex<>>
-    log::info!("Using state file: {}", config.storage.state_file);
+    match config.storage.backend {
+        StorageBackend::Pickledb => {
+            log::info!("Using state file: {}", config.storage.state_file);
+        }
+        StorageBackend::Sqlite => log::info!(
+            "Using SQLite database: {}",
+            config.storage.sqlite_file_path().display()
+        ),
+    }
     let storage = Arc::new(Mutex::new(
-        FileStorage::new(&config.storage.state_file).map_err(|e| {
-            LastFmError::Io(std::io::Error::other(format!(
-                "Failed to create storage: {e}"
-            )))
-        })?,
+        ConfiguredStorage::open(&config.storage)
+            .await
+            .map_err(|e| {
+                LastFmError::Io(std::io::Error::other(format!(
+                    "Failed to create storage: {e}"
+                )))
+            })?,
     ));
 
+    // Replace the stored rules with the rules directory when it is the source of truth
+    if let Some(rules_dir) = &config.storage.rules_dir {
+        crate::rules_dir::sync_storage_from_dir(rules_dir, &mut *storage.lock().await)
+            .await
+            .map_err(|e| {
+                LastFmError::Io(std::io::Error::other(format!(
+                    "Failed to load rules directory {}: {e}",
+                    rules_dir.path
+                )))
+            })?;
+    }
+
     // Check if we should skip existing rewrite rules (for pattern analysis)
     let skip_existing_rules = matches!(
         &args.command,
@@ -696,9 +865,19 @@ pub async fn run() -> Result<()> {
     let mut action_provider = OrScrubActionProvider::new();
 
     if config.providers.enable_rewrite_rules && !skip_existing_rules {
-        let rewrite_provider = RewriteRulesScrubActionProvider::new(&rules_state);
+        let (compiled_rules, invalid_rules) =
+            CompiledRuleSet::compile_valid(rules_state.rewrite_rules.clone());
+        for invalid in &invalid_rules {
+            log::warn!("Skipping rewrite rule that failed to compile: {invalid}");
+        }
+        log::info!(
+            "Enabled rewrite rules provider with {} compiled rules and {} alias tables",
+            compiled_rules.len(),
+            rules_state.alias_rules.len()
+        );
+        let rewrite_provider = RewriteRulesScrubActionProvider::from_compiled(compiled_rules)
+            .with_alias_rules(&rules_state.alias_rules);
         action_provider = action_provider.add_provider(rewrite_provider);
-        log::info!("Enabled rewrite rules provider");
     } else if skip_existing_rules {
         log::info!("Skipping existing rewrite rules for pattern analysis");
     }
@@ -793,6 +972,7 @@ pub async fn run() -> Result<()> {
                     comp_config.confidence_threshold,
                 )
                 .with_enabled(comp_config.enabled)
+                .with_greatest_hits_policy(comp_config.greatest_hits_policy)
             } else {
                 CompilationToCanonicalProvider::new()
             };
@@ -801,6 +981,33 @@ pub async fn run() -> Result<()> {
         log::info!("Enabled Compilation to Canonical provider for suggesting earliest releases");
     }
 
+    // Add HTTP provider if enabled and configured
+    if config.providers.enable_http {
+        if let Some(http_config) = &config.providers.http {
+            match HttpScrubActionProvider::new(http_config) {
+                Ok(http_provider) => {
+                    action_provider = action_provider.add_provider(http_provider);
+                    log::info!(
+                        "Enabled HTTP provider with endpoint: {}",
+                        http_config.endpoint_url
+                    );
+                }
+                Err(e) => {
+                    log::warn!("Failed to create HTTP provider: {e}");
+                }
+            }
+        } else {
+            log::warn!("HTTP provider enabled but no [providers.http] configuration found");
+        }
+    }
+
+    // Add majority album provider if enabled
+    if config.providers.enable_majority_album {
+        let majority_config = config.providers.majority_album.clone().unwrap_or_default();
+        action_provider = action_provider.add_provider(MajorityAlbumProvider::new(majority_config));
+        log::info!("Enabled majority album provider for aligning scrobbles with play history");
+    }
+
     // Log active providers summary
     let mut active_providers = Vec::new();
     if config.providers.enable_rewrite_rules && !skip_existing_rules {
@@ -818,6 +1025,9 @@ pub async fn run() -> Result<()> {
     if config.providers.enable_http {
         active_providers.push("HTTP");
     }
+    if config.providers.enable_majority_album {
+        active_providers.push("MajorityAlbum");
+    }
 
     if active_providers.is_empty() {
         log::warn!("No scrub action providers are enabled");
@@ -825,6 +1035,11 @@ pub async fn run() -> Result<()> {
         log::info!("Active providers: {}", active_providers.join(", "));
     }
 
+    if config.providers.chain_providers {
+        action_provider = action_provider.chained();
+        log::info!("Chaining providers: each provider sees the previous providers' output");
+    }
+
     // Handle commands that don't need a scrubber instance first
     match &args.command {
         Commands::TrackCache(cache_cmd) => match cache_cmd {
@@ -855,10 +1070,12 @@ pub async fn run() -> Result<()> {
                 return Ok(());
             }
             RulesCommands::EnableDefaults => {
+                ensure_rules_editable(&config)?;
                 enable_default_rules(&storage).await?;
                 return Ok(());
             }
             RulesCommands::Add(args) => {
+                ensure_rules_editable(&config)?;
                 add_rewrite_rule(
                     &storage,
                     args.name.as_deref(),
@@ -871,26 +1088,65 @@ pub async fn run() -> Result<()> {
                     args.album_artist_find.as_deref(),
                     args.album_artist_replace.as_deref(),
                     args.flags.as_deref(),
+                    args.mode,
                     args.require_confirmation,
                     args.require_musicbrainz_confirmation,
+                    &args.examples,
                 )
                 .await?;
                 return Ok(());
             }
             RulesCommands::Remove { index, name, all } => {
+                ensure_rules_editable(&config)?;
                 remove_rewrite_rule(&storage, *index, name.as_deref(), *all).await?;
                 return Ok(());
             }
+            RulesCommands::Test { defaults } => {
+                test_rule_examples(&storage, *defaults).await?;
+                return Ok(());
+            }
+            RulesCommands::Lint { cached_tracks } => {
+                lint_active_rules(&storage, *cached_tracks).await?;
+                return Ok(());
+            }
+            RulesCommands::AddAliases(args) => {
+                ensure_rules_editable(&config)?;
+                add_alias_rule(
+                    &storage,
+                    args.name.as_deref(),
+                    args.field,
+                    &args.file,
+                    args.matching,
+                    args.require_confirmation,
+                )
+                .await?;
+                return Ok(());
+            }
+            RulesCommands::RemoveAliases { index, name } => {
+                ensure_rules_editable(&config)?;
+                remove_alias_rule(&storage, *index, name.as_deref()).await?;
+                return Ok(());
+            }
+            RulesCommands::ExportDir { dir, format, clean } => {
+                let rules_dir = config.storage.rules_dir.as_ref();
+                let format = format
+                    .or_else(|| rules_dir.map(|rules_dir| rules_dir.format))
+                    .unwrap_or_default();
+                export_rules_to_dir(&storage, &rules_dir_arg(dir, &config)?, format, *clean)
+                    .await?;
+                return Ok(());
+            }
+            RulesCommands::ImportDir { dir } => {
+                ensure_rules_editable(&config)?;
+                import_rules_from_dir(&storage, &rules_dir_arg(dir, &config)?).await?;
+                return Ok(());
+            }
         },
         Commands::Pending(pending_cmd) => {
-            let data_dir = std::path::PathBuf::from(&config.storage.state_file)
-                .parent()
-                .unwrap()
-                .to_path_buf();
             let pending_args = PendingArgs {
                 command: pending_cmd.clone(),
             };
-            if let Err(e) = handle_pending_command(pending_args, data_dir).await {
+            if let Err(e) = handle_pending_command(pending_args, &config.storage).await {
                 return Err(lastfm_edit::LastFmError::Io(std::io::Error::other(
                     format!("Pending command failed: {e}"),
                 )));
@@ -908,7 +1164,11 @@ pub async fn run() -> Result<()> {
             }
         },
         Commands::MusicBrainz(mb_cmd) => {
-            mb_cmd.clone().execute().await.map_err(|e| {
+            let result = mb_cmd.clone().execute(&config).await;
+            if let Err(e) = MusicBrainzCache::shared().flush() {
+                log::warn!("Failed to save MusicBrainz cache: {e}");
+            }
+            result.map_err(|e| {
                 log::error!("MusicBrainz command failed: {e}");
                 LastFmError::Io(std::io::Error::other(e.to_string()))
             })?;
@@ -924,8 +1184,11 @@ pub async fn run() -> Result<()> {
             println!("Next run will require username/password login");
             return Ok(());
         }
-        Commands::Scrubber(_) => {
-            // Continue to create scrubber for scrubber commands
+        Commands::State(_) => {
+            unreachable!("State commands are handled before logging in");
+        }
+        Commands::Scrubber(_) | Commands::Edits(_) => {
+            // Continue to create scrubber for scrubber and edit log commands
         }
     }
 
@@ -1044,16 +1307,24 @@ pub async fn run() -> Result<()> {
                 scrubber_guard.process_search_albums(query, *limit).await?;
             }
         },
+        Commands::Edits(EditsCommands::Undo(undo_args)) => {
+            undo_logged_edits(&mut *scrubber_guard, undo_args, &config).await?;
+        }
         Commands::TrackCache(_)
         | Commands::Rules(_)
         | Commands::Pending(_)
         | Commands::Timestamp(_)
         | Commands::MusicBrainz(_)
+        | Commands::State(_)
         | Commands::ClearSession => {
             // These cases are handled above
             unreachable!("Non-scrubber commands should have been handled earlier");
         }
     }
 
+    if let Err(e) = MusicBrainzCache::shared().flush() {
+        log::warn!("Failed to save MusicBrainz cache: {e}");
+    }
+
     Ok(())
 }
@@ -1,5 +1,7 @@
+use crate::musicbrainz::musicbrainz_provider::has_special_edition_marker;
 use config::{Config, ConfigError, Environment, File};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -15,6 +17,71 @@ impl Default for TrackProviderType {
     }
 }
 
+/// How the scrubber resolves providers suggesting different values for the same field
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Keep the value from the provider listed first in `provider_priority`
+    #[default]
+    ProviderPriority,
+    /// Keep the value from the most confident suggestion
+    ///
+    /// Only the MusicBrainz, majority album and HTTP providers report a confidence. Suggestions
+    /// without one rank below those with one, and ties fall back to `provider_priority`.
+    HighestConfidence,
+    /// Send the conflicting suggestions to pending review with every candidate value
+    PendingReview,
+}
+
+/// Confidence bands deciding how suggestions that report a confidence are handled
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ConfidenceThresholds {
+    /// Suggestions at least this confident are applied without confirmation
+    pub auto_apply: f32,
+    /// Suggestions at least this confident, but below `auto_apply`, go to pending review;
+    /// less confident suggestions are dropped
+    pub pending: f32,
+}
+
+impl Default for ConfidenceThresholds {
+    fn default() -> Self {
+        Self {
+            auto_apply: 0.95,
+            pending: 0.7,
+        }
+    }
+}
+
+/// What to do with a suggestion given its confidence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfidenceDecision {
+    AutoApply,
+    Pending,
+    Drop,
+}
+
+impl ConfidenceThresholds {
+    #[must_use]
+    pub fn decide(&self, confidence: f32) -> ConfidenceDecision {
+        if confidence >= self.auto_apply {
+            ConfidenceDecision::AutoApply
+        } else if confidence >= self.pending {
+            ConfidenceDecision::Pending
+        } else {
+            ConfidenceDecision::Drop
+        }
+    }
+
+    /// The stricter of two sets of thresholds
+    #[must_use]
+    pub fn strictest(self, other: Self) -> Self {
+        Self {
+            auto_apply: self.auto_apply.max(other.auto_apply),
+            pending: self.pending.max(other.pending),
+        }
+    }
+}
+
 /// Default system prompt for AI providers
 pub const DEFAULT_CLAUDE_SYSTEM_PROMPT: &str = "You are a music metadata cleaning assistant with function calling tools available. You work alongside automated rewrite rules and have two main responsibilities:
 
@@ -120,6 +187,9 @@ pub struct ScrobbleScrubberConfig {
     pub providers: ProvidersConfig,
     pub storage: StorageConfig,
     pub lastfm: LastFmConfig,
+    /// Settings shared by everything that talks to MusicBrainz
+    #[serde(default)]
+    pub musicbrainz: MusicBrainzConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -138,6 +208,21 @@ pub struct ScrubberConfig {
     pub track_provider: TrackProviderType,
     /// JSON logging configuration
     pub json_logging: JsonLoggingConfig,
+    /// How to resolve providers suggesting different values for the same field
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Provider names in order of precedence when resolving conflicts (e.g. `RewriteRules`,
+    /// `CompilationToCanonical`); unlisted providers rank after listed ones, in the order
+    /// they suggested their edits
+    #[serde(default)]
+    pub provider_priority: Vec<String>,
+    /// Confidence thresholds for suggestions that report a confidence; when unset, each
+    /// provider's own confirmation setting applies
+    #[serde(default)]
+    pub confidence_thresholds: Option<ConfidenceThresholds>,
+    /// Per-provider overrides of `confidence_thresholds`, keyed by provider name
+    #[serde(default)]
+    pub provider_confidence_thresholds: HashMap<String, ConfidenceThresholds>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -160,6 +245,16 @@ pub struct ProvidersConfig {
     pub musicbrainz: Option<MusicBrainzProviderConfig>,
     /// Compilation to Canonical provider configuration
     pub compilation_to_canonical: Option<CompilationToCanonicalConfig>,
+    /// Enable Majority Album provider
+    #[serde(default)]
+    pub enable_majority_album: bool,
+    /// Majority Album provider configuration
+    #[serde(default)]
+    pub majority_album: Option<MajorityAlbumConfig>,
+    /// Chain the providers: each provider sees the previous providers' output and their
+    /// edits are merged into one edit per track
+    #[serde(default)]
+    pub chain_providers: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -205,6 +300,32 @@ pub struct ReleaseFilterConfig {
     pub custom_exclusion_terms: Vec<String>,
 }
 
+impl ReleaseFilterConfig {
+    /// Check whether an album title (rather than a MusicBrainz release) is excluded
+    ///
+    /// Only the filters that can be judged from the title apply; country preferences are ignored.
+    #[must_use]
+    pub fn excludes_album_title(&self, title: &str) -> bool {
+        let lower = title.to_lowercase();
+        let contains_any = |terms: &[String]| {
+            terms
+                .iter()
+                .any(|term| lower.contains(&term.to_lowercase()))
+        };
+
+        let excluded_by_filter = self.filters.iter().any(|filter| match filter {
+            ReleaseFilterType::ExcludeDemo => lower.contains("demo"),
+            ReleaseFilterType::ExcludeSpecialEdition => has_special_edition_marker(title),
+            ReleaseFilterType::ExcludeByDisambiguation { terms } => contains_any(terms),
+            ReleaseFilterType::ExcludeByCountry { .. } | ReleaseFilterType::PreferNonJapanese => {
+                false
+            }
+        });
+
+        excluded_by_filter || contains_any(&self.custom_exclusion_terms)
+    }
+}
+
 impl Default for ReleaseFilterConfig {
     fn default() -> Self {
         Self {
@@ -225,8 +346,16 @@ pub struct MusicBrainzProviderConfig {
     pub confidence_threshold: f32,
     /// Maximum number of search results to examine
     pub max_results: usize,
-    /// Request delay in milliseconds to be respectful to MusicBrainz API
-    pub api_delay_ms: u64,
+    /// MusicBrainz server to query, e.g. a local mirror at `http://localhost:5000`
+    /// (defaults to <https://musicbrainz.org>)
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// User-Agent sent to MusicBrainz (defaults to `scrobble-scrubber/<version>`)
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Contact URL or email added to the User-Agent, as MusicBrainz asks of API clients
+    #[serde(default)]
+    pub contact: Option<String>,
     // NOTE: Release filters are now configured per-rewrite rule, not globally
     // Use RewriteRule.musicbrainz_release_filters for per-rule filtering
 }
@@ -237,6 +366,83 @@ pub struct CompilationToCanonicalConfig {
     pub confidence_threshold: f32,
     /// Enable the provider
     pub enabled: bool,
+    /// What to do with tracks on single-artist greatest hits and best-of albums
+    #[serde(default)]
+    pub greatest_hits_policy: GreatestHitsPolicy,
+}
+
+/// How tracks on single-artist greatest hits and best-of albums are remapped
+///
+/// Various-artists compilations are always remapped to the track's original release.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum GreatestHitsPolicy {
+    /// Leave tracks on the greatest hits album
+    Keep,
+    /// Move each track to the album it originally appeared on
+    #[default]
+    MapToOriginal,
+    /// Move a track only when its original release predates the compilation by at least `years`
+    MapIfOlder { years: u32 },
+}
+
+impl GreatestHitsPolicy {
+    /// Whether a track on a greatest hits album released in `compilation_year` may be moved
+    /// to its original release from `original_year`
+    ///
+    /// `MapIfOlder` keeps the track when either year is unknown.
+    #[must_use]
+    pub fn allows_remap(self, compilation_year: Option<i32>, original_year: Option<i32>) -> bool {
+        match self {
+            Self::Keep => false,
+            Self::MapToOriginal => true,
+            Self::MapIfOlder { years } => match (compilation_year, original_year) {
+                (Some(compilation), Some(original)) => {
+                    i64::from(compilation) - i64::from(original) >= i64::from(years)
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for GreatestHitsPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Keep => write!(f, "keep greatest hits albums"),
+            Self::MapToOriginal => write!(f, "map to the original album"),
+            Self::MapIfOlder { years } => {
+                write!(f, "map when the original is at least {years} years older")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MajorityAlbumConfig {
+    /// Share of a track's plays (0.0-1.0) an album needs before other scrobbles are moved to it
+    pub majority_threshold: f32,
+    /// Minimum number of plays of a track before suggesting its majority album
+    pub min_history: u64,
+    /// Fetch an artist's library tracks from Last.fm when they aren't in the track cache
+    pub fetch_missing_history: bool,
+    /// Require confirmation before moving scrobbles to the majority album
+    pub require_confirmation: bool,
+    /// Albums excluded by these filters are never suggested; compilations never are either
+    #[serde(default)]
+    pub release_filters: ReleaseFilterConfig,
+}
+
+impl Default for MajorityAlbumConfig {
+    fn default() -> Self {
+        Self {
+            majority_threshold: 0.75,
+            min_history: 5,
+            fetch_missing_history: true,
+            require_confirmation: true,
+            release_filters: ReleaseFilterConfig::default(),
+        }
+    }
 }
 
 impl Default for CompilationToCanonicalConfig {
@@ -244,14 +450,148 @@ impl Default for CompilationToCanonicalConfig {
         Self {
             confidence_threshold: 0.8,
             enabled: true,
+            greatest_hits_policy: GreatestHitsPolicy::default(),
         }
     }
 }
 
+/// Settings shared by all MusicBrainz lookups (providers, rule confirmation, CLI)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct MusicBrainzConfig {
+    /// On-disk cache of MusicBrainz responses
+    #[serde(default)]
+    pub cache: MusicBrainzCacheConfig,
+    /// Process-wide limit on MusicBrainz requests
+    #[serde(default)]
+    pub rate_limit: MusicBrainzRateLimitConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MusicBrainzCacheConfig {
+    /// Cache MusicBrainz responses on disk
+    pub enabled: bool,
+    /// Hours before a cached response is fetched again
+    pub ttl_hours: u64,
+    /// Maximum number of cached responses; the oldest are evicted first
+    pub max_entries: usize,
+    /// Cache file path (defaults to musicbrainz_cache.json next to the state file)
+    pub path: Option<String>,
+}
+
+impl Default for MusicBrainzCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_hours: 24 * 7,
+            max_entries: 20_000,
+            path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MusicBrainzRateLimitConfig {
+    /// Requests per second across all MusicBrainz lookups (MusicBrainz allows 1)
+    pub requests_per_second: f64,
+    /// Requests that may be sent back to back before the rate applies
+    pub burst: u32,
+    /// Retries of a request MusicBrainz rejected with 503 before giving up
+    pub max_retries: u32,
+    /// First backoff after a 503 without Retry-After, doubled on every retry
+    pub initial_backoff_ms: u64,
+    /// Longest backoff between retries
+    pub max_backoff_ms: u64,
+}
+
+impl Default for MusicBrainzRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 1.0,
+            burst: 1,
+            max_retries: 5,
+            initial_backoff_ms: 2000,
+            max_backoff_ms: 60_000,
+        }
+    }
+}
+
+/// Which database persistent state is kept in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// JSON documents in a PickleDB file at `state_file`
+    #[default]
+    Pickledb,
+    /// Tables in a SQLite database (requires the `sqlite` feature)
+    Sqlite,
+}
+
+/// Which copy of the rewrite rules is authoritative when a rules directory is configured
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RulesSource {
+    /// Rules live in the state storage; the directory is only written by `rules export-dir`
+    #[default]
+    Storage,
+    /// The directory replaces the stored rules on startup, and on change when watched
+    Directory,
+}
+
+/// File format of rules written to a rules directory
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum RuleFileFormat {
+    #[default]
+    Toml,
+    Json,
+}
+
+/// Rewrite rules kept as a directory of TOML/JSON files, e.g. for code review
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RulesDirConfig {
+    /// Directory holding one rule or rule pack per `.toml`/`.json` file
+    pub path: String,
+    /// Whether the state storage or the directory is the source of truth
+    #[serde(default)]
+    pub source: RulesSource,
+    /// Reload the directory while the scrubber runs (only when `source` is `directory`)
+    #[serde(default)]
+    pub watch: bool,
+    /// Seconds between checks of a watched directory
+    #[serde(default = "RulesDirConfig::default_watch_interval_secs")]
+    pub watch_interval_secs: u64,
+    /// Format of the files written by `rules export-dir`
+    #[serde(default)]
+    pub format: RuleFileFormat,
+}
+
+impl RulesDirConfig {
+    const fn default_watch_interval_secs() -> u64 {
+        10
+    }
+
+    /// Whether the running scrubber should reload the directory when it changes
+    #[must_use]
+    pub fn watches(&self) -> bool {
+        self.watch && self.source == RulesSource::Directory
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageConfig {
     /// Path to state file for persistence
     pub state_file: String,
+    /// Database the state is kept in
+    #[serde(default)]
+    pub backend: StorageBackend,
+    /// Path to the SQLite database (defaults to `state_file` with a `.sqlite` extension).
+    /// When it doesn't exist yet, an existing PickleDB `state_file` is migrated into it.
+    #[serde(default)]
+    pub sqlite_file: Option<String>,
+    /// Directory of rule files synced with the stored rules
+    #[serde(default)]
+    pub rules_dir: Option<RulesDirConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -282,10 +622,36 @@ impl Default for ScrubberConfig {
             auto_start: false,
             track_provider: TrackProviderType::Direct,
             json_logging: JsonLoggingConfig::default(),
+            conflict_policy: ConflictPolicy::default(),
+            provider_priority: Vec::new(),
+            confidence_thresholds: None,
+            provider_confidence_thresholds: HashMap::new(),
         }
     }
 }
 
+impl ScrubberConfig {
+    /// The confidence thresholds for a suggestion made by the given providers
+    ///
+    /// Merged suggestions use the strictest override among their contributing providers,
+    /// falling back to the global `confidence_thresholds`.
+    #[must_use]
+    pub fn confidence_thresholds_for<S: AsRef<str>>(
+        &self,
+        providers: &[S],
+    ) -> Option<ConfidenceThresholds> {
+        providers
+            .iter()
+            .filter_map(|provider| {
+                self.provider_confidence_thresholds
+                    .get(provider.as_ref())
+                    .copied()
+            })
+            .reduce(ConfidenceThresholds::strictest)
+            .or(self.confidence_thresholds)
+    }
+}
+
 impl JsonLoggingConfig {
     /// Get the default JSON log file path using XDG Base Directory specification
     /// Falls back to current directory if XDG data directory is not available
@@ -331,6 +697,9 @@ impl Default for ProvidersConfig {
             http: None,
             musicbrainz: None,
             compilation_to_canonical: None,
+            enable_majority_album: false,
+            majority_album: None,
+            chain_providers: false,
         }
     }
 }
@@ -340,11 +709,38 @@ impl Default for MusicBrainzProviderConfig {
         Self {
             confidence_threshold: 0.8, // 80% confidence required
             max_results: 5,            // Check top 5 results
-            api_delay_ms: 100,         // 100ms delay between requests
+            base_url: None,
+            user_agent: None,
+            contact: None,
         }
     }
 }
 
+impl MusicBrainzProviderConfig {
+    /// The configured server, unless it is the public musicbrainz.org
+    #[must_use]
+    pub fn mirror_url(&self) -> Option<&str> {
+        self.base_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/'))
+            .filter(|url| !url.is_empty() && !url.ends_with("musicbrainz.org"))
+    }
+
+    /// User-Agent header for MusicBrainz requests
+    #[must_use]
+    pub fn user_agent_header(&self) -> String {
+        let user_agent = self
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| format!("scrobble-scrubber/{}", env!("CARGO_PKG_VERSION")));
+        let contact = self
+            .contact
+            .as_deref()
+            .unwrap_or("https://github.com/colonelpanic8/scrobble-scrubber");
+        format!("{user_agent} ( {contact} )")
+    }
+}
+
 impl StorageConfig {
     /// Get the default state file path using XDG Base Directory specification
     /// Falls back to current directory if XDG data directory is not available
@@ -377,6 +773,15 @@ impl StorageConfig {
         }
     }
 
+    /// Path of the SQLite database used by the `sqlite` backend
+    #[must_use]
+    pub fn sqlite_file_path(&self) -> PathBuf {
+        match &self.sqlite_file {
+            Some(path) if !path.is_empty() => PathBuf::from(path),
+            _ => Path::new(&self.state_file).with_extension("sqlite"),
+        }
+    }
+
     /// Get the edit log file path based on the state file path
     /// Uses the same directory as the state file but with a cleaner name
     pub fn get_edit_log_path(state_file_path: &str) -> String {
@@ -393,6 +798,9 @@ impl Default for StorageConfig {
     fn default() -> Self {
         Self {
             state_file: Self::get_default_state_file_path(),
+            backend: StorageBackend::default(),
+            sqlite_file: None,
+            rules_dir: None,
         }
     }
 }
@@ -408,6 +816,7 @@ impl Default for ScrobbleScrubberConfig {
                 password: String::new(),
                 base_url: None,
             },
+            musicbrainz: MusicBrainzConfig::default(),
         }
     }
 }
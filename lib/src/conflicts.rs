@@ -0,0 +1,421 @@
+//! Detecting and resolving providers that suggest different values for the same field
+//!
+//! Without this, the scrubber would apply every provider's edit in turn and the last one
+//! would silently win. `resolve_conflicts` instead keeps one value per field according to
+//! the configured `ConflictPolicy`, or sets the suggestions aside for review.
+
+use crate::config::ConflictPolicy;
+use crate::rewrite::{create_no_op_edit, RuleField};
+use crate::scrub_action_provider::{ScrubActionSuggestion, SuggestionWithContext};
+use lastfm_edit::{ScrobbleEdit, Track};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// A value one provider suggested for a field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldCandidate {
+    pub provider_name: String,
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+/// Providers suggesting different values for the same field of a track
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldConflict {
+    pub field: RuleField,
+    /// The field's value before any edit
+    pub original: Option<String>,
+    /// Every suggested value, in the order the suggestions were made
+    pub candidates: Vec<FieldCandidate>,
+    /// The value that was kept, unless the conflict was sent to review
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<String>,
+}
+
+impl std::fmt::Display for FieldConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let candidates: Vec<String> = self
+            .candidates
+            .iter()
+            .map(|c| format!("'{}' ({})", c.value, c.provider_name))
+            .collect();
+        write!(f, "{}: {}", self.field, candidates.join(" vs "))?;
+        if let Some(resolved) = &self.resolved {
+            write!(f, ", kept '{resolved}'")?;
+        }
+        Ok(())
+    }
+}
+
+/// Conflicting edits merged into one edit for the user to review
+#[derive(Debug, Clone)]
+pub struct EscalatedEdit {
+    /// All changes of the conflicting suggestions, with the preferred candidate for each
+    /// conflicting field
+    pub edit: ScrobbleEdit,
+    pub conflicts: Vec<FieldConflict>,
+}
+
+/// The suggestions for a track after resolving conflicts
+#[derive(Debug, Clone, Default)]
+pub struct ConflictResolution {
+    /// Suggestions to apply as usual
+    pub suggestions: Vec<SuggestionWithContext>,
+    /// Conflicts that were found, with the kept value when they were resolved
+    pub conflicts: Vec<FieldConflict>,
+    /// Edit to send to pending review (only with `ConflictPolicy::PendingReview`)
+    pub escalated: Option<EscalatedEdit>,
+}
+
+/// The value an edit changes a field to, if it changes it
+fn changed_value<'a>(
+    field: RuleField,
+    edit: &'a ScrobbleEdit,
+    original: &ScrobbleEdit,
+) -> Option<&'a str> {
+    let value = field.edit_value(edit)?;
+    (Some(value) != field.edit_value(original)).then_some(value)
+}
+
+/// Reset a field of an edit to its original value
+fn reset_field(field: RuleField, edit: &mut ScrobbleEdit, original: &ScrobbleEdit) {
+    match field {
+        RuleField::TrackName => edit.track_name = original.track_name.clone(),
+        RuleField::ArtistName => edit.artist_name = original.artist_name.clone(),
+        RuleField::AlbumName => edit.album_name = original.album_name.clone(),
+        RuleField::AlbumArtistName => edit.album_artist_name = original.album_artist_name.clone(),
+    }
+}
+
+fn has_changes(edit: &ScrobbleEdit, original: &ScrobbleEdit) -> bool {
+    RuleField::ALL
+        .into_iter()
+        .any(|field| changed_value(field, edit, original).is_some())
+}
+
+/// Position of a provider in the priority list; unlisted providers rank last
+fn provider_rank(provider_priority: &[String], provider_name: &str) -> usize {
+    provider_priority
+        .iter()
+        .position(|name| name == provider_name)
+        .unwrap_or(provider_priority.len())
+}
+
+/// Find the fields that edit suggestions change to different values
+#[must_use]
+pub fn detect_conflicts(
+    track: &Track,
+    suggestions: &[SuggestionWithContext],
+) -> Vec<FieldConflict> {
+    let original = create_no_op_edit(track);
+    let mut conflicts = Vec::new();
+
+    for field in RuleField::ALL {
+        let candidates: Vec<FieldCandidate> = suggestions
+            .iter()
+            .filter_map(|suggestion| {
+                let ScrubActionSuggestion::Edit(edit) = &suggestion.suggestion else {
+                    return None;
+                };
+                Some(FieldCandidate {
+                    provider_name: suggestion.provider_name.clone(),
+                    value: changed_value(field, edit, &original)?.to_string(),
+                    confidence: suggestion.confidence,
+                })
+            })
+            .collect();
+
+        if candidates.iter().any(|c| c.value != candidates[0].value) {
+            conflicts.push(FieldConflict {
+                field,
+                original: field.edit_value(&original).map(str::to_string),
+                candidates,
+                resolved: None,
+            });
+        }
+    }
+
+    conflicts
+}
+
+/// The candidate a policy keeps; `PendingReview` prefers candidates by provider priority
+fn preferred_candidate<'a>(
+    candidates: &'a [FieldCandidate],
+    policy: ConflictPolicy,
+    provider_priority: &[String],
+) -> &'a FieldCandidate {
+    let by_priority = |a: &FieldCandidate, b: &FieldCandidate| {
+        provider_rank(provider_priority, &a.provider_name)
+            .cmp(&provider_rank(provider_priority, &b.provider_name))
+    };
+    let by_confidence = |a: &FieldCandidate, b: &FieldCandidate| match (a.confidence, b.confidence)
+    {
+        (Some(a), Some(b)) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+
+    // `min_by` keeps the first of equal candidates, so ties go to the earlier suggestion
+    candidates
+        .iter()
+        .min_by(|a, b| match policy {
+            ConflictPolicy::HighestConfidence => {
+                by_confidence(a, b).then_with(|| by_priority(a, b))
+            }
+            ConflictPolicy::ProviderPriority | ConflictPolicy::PendingReview => by_priority(a, b),
+        })
+        .expect("conflicts have at least two candidates")
+}
+
+/// Resolve conflicting field values across a track's suggestions
+///
+/// With `ProviderPriority` and `HighestConfidence`, the losing suggestions keep their other
+/// changes but leave the conflicting field alone, and are dropped when nothing is left.
+/// With `PendingReview`, every edit touching a conflicting field is merged into a single
+/// escalated edit instead. Rule proposals are never affected.
+#[must_use]
+pub fn resolve_conflicts(
+    track: &Track,
+    suggestions: Vec<SuggestionWithContext>,
+    policy: ConflictPolicy,
+    provider_priority: &[String],
+) -> ConflictResolution {
+    let mut conflicts = detect_conflicts(track, &suggestions);
+    if conflicts.is_empty() {
+        return ConflictResolution {
+            suggestions,
+            ..ConflictResolution::default()
+        };
+    }
+    let original = create_no_op_edit(track);
+
+    if policy == ConflictPolicy::PendingReview {
+        let (conflicting, kept): (Vec<_>, Vec<_>) =
+            suggestions.into_iter().partition(|suggestion| {
+                let ScrubActionSuggestion::Edit(edit) = &suggestion.suggestion else {
+                    return false;
+                };
+                conflicts
+                    .iter()
+                    .any(|conflict| changed_value(conflict.field, edit, &original).is_some())
+            });
+
+        // Merge the edits in priority order; the first change to a field wins
+        let mut conflicting: Vec<(usize, ScrobbleEdit)> = conflicting
+            .into_iter()
+            .filter_map(|suggestion| match suggestion.suggestion {
+                ScrubActionSuggestion::Edit(edit) => Some((
+                    provider_rank(provider_priority, &suggestion.provider_name),
+                    edit,
+                )),
+                _ => None,
+            })
+            .collect();
+        conflicting.sort_by_key(|(rank, _)| *rank);
+
+        let mut merged = original.clone();
+        for (_, edit) in &conflicting {
+            for field in RuleField::ALL {
+                if changed_value(field, &merged, &original).is_some() {
+                    continue;
+                }
+                if let Some(value) = changed_value(field, edit, &original) {
+                    field.set_edit_value(&mut merged, value.to_string());
+                }
+            }
+        }
+        for conflict in &conflicts {
+            let preferred = preferred_candidate(&conflict.candidates, policy, provider_priority);
+            conflict
+                .field
+                .set_edit_value(&mut merged, preferred.value.clone());
+        }
+
+        return ConflictResolution {
+            suggestions: kept,
+            escalated: Some(EscalatedEdit {
+                edit: merged,
+                conflicts: conflicts.clone(),
+            }),
+            conflicts,
+        };
+    }
+
+    for conflict in &mut conflicts {
+        let preferred = preferred_candidate(&conflict.candidates, policy, provider_priority);
+        conflict.resolved = Some(preferred.value.clone());
+    }
+
+    let suggestions = suggestions
+        .into_iter()
+        .filter_map(|mut suggestion| {
+            if let ScrubActionSuggestion::Edit(edit) = &mut suggestion.suggestion {
+                for conflict in &conflicts {
+                    let value = changed_value(conflict.field, edit, &original);
+                    if value.is_some() && value != conflict.resolved.as_deref() {
+                        reset_field(conflict.field, edit, &original);
+                    }
+                }
+                if !has_changes(edit, &original) {
+                    return None;
+                }
+            }
+            Some(suggestion)
+        })
+        .collect();
+
+    ConflictResolution {
+        suggestions,
+        conflicts,
+        escalated: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track() -> Track {
+        Track {
+            name: "Creep".to_string(),
+            artist: "Radiohead".to_string(),
+            album: Some("Greatest Hits".to_string()),
+            album_artist: None,
+            playcount: 1,
+            timestamp: Some(1_700_000_000),
+        }
+    }
+
+    fn album_edit(
+        provider_name: &str,
+        album: &str,
+        confidence: Option<f32>,
+    ) -> SuggestionWithContext {
+        let mut edit = create_no_op_edit(&track());
+        edit.album_name = Some(album.to_string());
+        let suggestion =
+            SuggestionWithContext::edit_with_confirmation(edit, false, provider_name.to_string());
+        match confidence {
+            Some(confidence) => suggestion.with_confidence(confidence),
+            None => suggestion,
+        }
+    }
+
+    fn album_of(suggestion: &SuggestionWithContext) -> Option<&str> {
+        match &suggestion.suggestion {
+            ScrubActionSuggestion::Edit(edit) => edit.album_name.as_deref(),
+            _ => None,
+        }
+    }
+
+    #[test_log::test]
+    fn should_not_report_agreeing_suggestions() {
+        let suggestions = vec![
+            album_edit("RewriteRules", "Pablo Honey", None),
+            album_edit("MusicBrainz", "Pablo Honey", None),
+        ];
+        assert!(detect_conflicts(&track(), &suggestions).is_empty());
+    }
+
+    #[test_log::test]
+    fn should_keep_value_by_provider_priority() {
+        let suggestions = vec![
+            album_edit("RewriteRules", "Pablo Honey", None),
+            album_edit("CompilationToCanonical", "Creep (Single)", Some(0.9)),
+        ];
+        let priority = vec!["CompilationToCanonical".to_string()];
+
+        let resolution = resolve_conflicts(
+            &track(),
+            suggestions.clone(),
+            ConflictPolicy::ProviderPriority,
+            &priority,
+        );
+        assert_eq!(resolution.suggestions.len(), 1);
+        assert_eq!(album_of(&resolution.suggestions[0]), Some("Creep (Single)"));
+        assert_eq!(
+            resolution.conflicts[0].resolved.as_deref(),
+            Some("Creep (Single)")
+        );
+
+        // Without a priority list, the earlier suggestion wins
+        let resolution =
+            resolve_conflicts(&track(), suggestions, ConflictPolicy::ProviderPriority, &[]);
+        assert_eq!(album_of(&resolution.suggestions[0]), Some("Pablo Honey"));
+    }
+
+    #[test_log::test]
+    fn should_keep_most_confident_value() {
+        let suggestions = vec![
+            album_edit("RewriteRules", "Pablo Honey", None),
+            album_edit(
+                "MusicBrainz",
+                "Pablo Honey (Collector's Edition)",
+                Some(0.7),
+            ),
+            album_edit("CompilationToCanonical", "Creep (Single)", Some(0.9)),
+        ];
+        let resolution = resolve_conflicts(
+            &track(),
+            suggestions,
+            ConflictPolicy::HighestConfidence,
+            &[],
+        );
+        assert_eq!(resolution.suggestions.len(), 1);
+        assert_eq!(
+            resolution.suggestions[0].provider_name,
+            "CompilationToCanonical"
+        );
+    }
+
+    #[test_log::test]
+    fn highest_confidence_should_fall_back_to_priority_without_confidences() {
+        let suggestions = vec![
+            album_edit("RewriteRules", "Pablo Honey", None),
+            album_edit("OpenAI", "Creep (Single)", None),
+        ];
+        let resolution = resolve_conflicts(
+            &track(),
+            suggestions,
+            ConflictPolicy::HighestConfidence,
+            &["OpenAI".to_string()],
+        );
+        assert_eq!(resolution.suggestions.len(), 1);
+        assert_eq!(resolution.suggestions[0].provider_name, "OpenAI");
+    }
+
+    #[test_log::test]
+    fn should_escalate_conflicts_with_every_candidate() {
+        let mut renamed = create_no_op_edit(&track());
+        renamed.track_name = Some("Creep (Acoustic)".to_string());
+        let suggestions = vec![
+            album_edit("RewriteRules", "Pablo Honey", None),
+            album_edit("MusicBrainz", "Creep (Single)", None),
+            SuggestionWithContext::edit_with_confirmation(renamed, false, "OpenAI".to_string()),
+        ];
+
+        let resolution = resolve_conflicts(
+            &track(),
+            suggestions,
+            ConflictPolicy::PendingReview,
+            &["MusicBrainz".to_string()],
+        );
+
+        // The unrelated edit is still applied as usual
+        assert_eq!(resolution.suggestions.len(), 1);
+        assert_eq!(resolution.suggestions[0].provider_name, "OpenAI");
+
+        let escalated = resolution.escalated.unwrap();
+        assert_eq!(escalated.edit.album_name.as_deref(), Some("Creep (Single)"));
+        assert_eq!(escalated.conflicts.len(), 1);
+        let values: Vec<&str> = escalated.conflicts[0]
+            .candidates
+            .iter()
+            .map(|c| c.value.as_str())
+            .collect();
+        assert_eq!(values, vec!["Pablo Honey", "Creep (Single)"]);
+        assert_eq!(escalated.conflicts[0].resolved, None);
+    }
+}
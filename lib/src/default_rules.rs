@@ -1,4 +1,8 @@
-use crate::rewrite::{RewriteRule, SdRule};
+use crate::rewrite::{
+    FieldExclusions, ReplacementMode, RewriteRule, RuleCondition, RuleField, SdRule,
+};
+use crate::rule_examples::RuleExample;
+use lastfm_edit::Track;
 use serde::{Deserialize, Serialize};
 
 /// Structures for default rule import
@@ -6,6 +10,8 @@ use serde::{Deserialize, Serialize};
 pub struct DefaultRulePattern {
     pub find: String,
     pub replace: String,
+    #[serde(default)]
+    pub mode: ReplacementMode,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -21,6 +27,12 @@ pub struct DefaultRule {
     /// Optional flag: require MusicBrainz confirmation of the rewritten metadata
     #[serde(default)]
     pub requires_musicbrainz_confirmation: bool,
+    /// Optional per-field exclusion patterns
+    #[serde(default)]
+    pub unless: FieldExclusions,
+    /// Optional extra conditions
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -31,34 +43,75 @@ pub struct DefaultRuleSet {
     pub rules: Vec<DefaultRule>,
 }
 
+impl DefaultRule {
+    /// The field the examples are written for (the first field the rule rewrites)
+    pub fn example_field(&self) -> Option<RuleField> {
+        [
+            (RuleField::TrackName, &self.track_name),
+            (RuleField::ArtistName, &self.artist_name),
+            (RuleField::AlbumName, &self.album_name),
+            (RuleField::AlbumArtistName, &self.album_artist_name),
+        ]
+        .into_iter()
+        .find_map(|(field, pattern)| pattern.as_ref().map(|_| field))
+    }
+
+    /// The `input → expected` examples as structured examples for the example field
+    pub fn rule_examples(&self) -> Vec<RuleExample> {
+        let Some(field) = self.example_field() else {
+            return Vec::new();
+        };
+        self.examples
+            .iter()
+            .filter_map(|example| RuleExample::parse(field, example))
+            .collect()
+    }
+
+    /// Example inputs as tracks, with placeholder values for the other fields
+    pub fn example_tracks(&self) -> Vec<Track> {
+        self.rule_examples()
+            .iter()
+            .map(RuleExample::input_track)
+            .collect()
+    }
+}
+
 /// Convert default rule to RewriteRule
 impl From<DefaultRule> for RewriteRule {
     fn from(default_rule: DefaultRule) -> Self {
+        let examples = default_rule.rule_examples();
         RewriteRule {
             name: Some(default_rule.name),
             track_name: default_rule.track_name.map(|p| SdRule {
                 find: p.find,
                 replace: p.replace,
                 flags: Some("i".to_string()), // Default to case-insensitive
+                mode: p.mode,
             }),
             artist_name: default_rule.artist_name.map(|p| SdRule {
                 find: p.find,
                 replace: p.replace,
                 flags: Some("i".to_string()),
+                mode: p.mode,
             }),
             album_name: default_rule.album_name.map(|p| SdRule {
                 find: p.find,
                 replace: p.replace,
                 flags: Some("i".to_string()),
+                mode: p.mode,
             }),
             album_artist_name: default_rule.album_artist_name.map(|p| SdRule {
                 find: p.find,
                 replace: p.replace,
                 flags: Some("i".to_string()),
+                mode: p.mode,
             }),
             requires_confirmation: default_rule.requires_confirmation,
             requires_musicbrainz_confirmation: default_rule.requires_musicbrainz_confirmation,
             musicbrainz_release_filters: None, // Default rules use default MusicBrainz filters
+            unless: default_rule.unless,
+            conditions: default_rule.conditions,
+            examples,
         }
     }
 }
@@ -80,3 +133,10 @@ pub fn load_all_default_rules() -> Result<Vec<DefaultRule>, Box<dyn std::error::
     let rule_set = load_default_rewrite_rules()?;
     Ok(rule_set.rules)
 }
+
+/// Example inputs of all default rules as tracks, useful as samples for linting rules
+pub fn default_rule_example_tracks() -> Vec<Track> {
+    load_all_default_rules()
+        .map(|rules| rules.iter().flat_map(DefaultRule::example_tracks).collect())
+        .unwrap_or_default()
+}
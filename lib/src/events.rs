@@ -77,6 +77,21 @@ pub struct LogEditInfo {
     pub new_album_artist_name: Option<String>,
 }
 
+/// Where an edit applied to Last.fm came from, recorded in the edit log
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditOrigin {
+    /// Id shared by the edits of one processing run
+    pub run_id: String,
+    /// Providers whose changes make up the edit
+    #[serde(default)]
+    pub providers: Vec<String>,
+    /// Names of the rewrite rules that produced the edit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
 /// Types of processing that can be performed by the scrubber
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ProcessingType {
@@ -186,6 +201,7 @@ pub struct ScrubberEvent {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum ScrubberEventType {
     /// Scrubber has started running
     Started(String),
@@ -242,6 +258,12 @@ pub enum ScrubberEventType {
         context: ProcessingContext,
         reason: String,
     },
+    /// An edit is about to be applied to Last.fm
+    EditApplying {
+        track: Track,
+        edit: LogEditInfo,
+        origin: EditOrigin,
+    },
     /// Client event forwarded from lastfm-edit client
     ClientEvent(ClientEvent),
     /// A pending edit was created requiring confirmation
@@ -270,6 +292,14 @@ pub enum ScrubberEventType {
         success: bool,
         result: ProcessingResult,
     },
+    /// MusicBrainz throttled a request; all MusicBrainz lookups wait before retrying
+    MusicBrainzRateLimited {
+        delay_seconds: u64,
+        attempt: u32,
+        message: String,
+    },
+    /// MusicBrainz accepted a request again after throttling
+    MusicBrainzRateLimitEnded,
 }
 
 impl ScrubberEvent {
@@ -479,6 +509,14 @@ impl ScrubberEvent {
         Self::new(ScrubberEventType::ClientEvent(client_event))
     }
 
+    pub fn edit_applying(track: &Track, edit: &LogEditInfo, origin: EditOrigin) -> Self {
+        Self::new(ScrubberEventType::EditApplying {
+            track: track.clone(),
+            edit: edit.clone(),
+            origin,
+        })
+    }
+
     pub fn pending_edit_created(
         pending_edit_id: String,
         track: &Track,
@@ -523,4 +561,16 @@ impl ScrubberEvent {
             result,
         })
     }
+
+    pub fn musicbrainz_rate_limited(delay_seconds: u64, attempt: u32, message: String) -> Self {
+        Self::new(ScrubberEventType::MusicBrainzRateLimited {
+            delay_seconds,
+            attempt,
+            message,
+        })
+    }
+
+    pub fn musicbrainz_rate_limit_ended() -> Self {
+        Self::new(ScrubberEventType::MusicBrainzRateLimitEnded)
+    }
 }
@@ -0,0 +1,366 @@
+//! HTTP/webhook scrub action provider.
+//!
+//! Lets external correction services written in any language take part in the
+//! scrubbing pipeline. For every batch of tracks the provider POSTs a JSON
+//! document to the configured endpoint and reads suggestions back.
+//!
+//! # Protocol
+//!
+//! Request body (`Content-Type: application/json`):
+//!
+//! ```json
+//! {
+//!   "tracks": [
+//!     { "name": "...", "artist": "...", "album": "...", "album_artist": null,
+//!       "playcount": 1, "timestamp": 1700000000 }
+//!   ],
+//!   "pending_edits": [ /* PendingEdit objects, may be empty */ ],
+//!   "pending_rules": [ /* PendingRewriteRule objects, may be empty */ ]
+//! }
+//! ```
+//!
+//! Response body:
+//!
+//! ```json
+//! {
+//!   "results": [
+//!     {
+//!       "track_index": 0,
+//!       "suggestions": [
+//!         { "suggestion": { "Edit": { /* ScrobbleEdit */ } },
+//!           "requires_confirmation": false,
+//!           "confidence": 0.97,
+//!           "explanation": "..." },
+//!         { "suggestion": { "ProposeRule": { "rule": { /* RewriteRule */ },
+//!                                            "motivation": "..." } } },
+//!         { "suggestion": "NoAction" }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `track_index` refers to the position of the track in the request's `tracks`
+//! array. `requires_confirmation` is optional and defaults to `true`, so remote
+//! suggestions are only applied automatically when the service opts in.
+//! `confidence` (0.0-1.0) and `explanation` are optional; a confidence lets the
+//! scrubber's confidence thresholds decide how the suggestion is handled.
+//! Tracks without suggestions can simply be left out of `results`.
+
+use crate::config::HttpProviderConfig;
+use crate::persistence::{PendingEdit, PendingRewriteRule};
+use crate::scrub_action_provider::{
+    ActionProviderError, ScrubActionProvider, ScrubActionSuggestion, SuggestionWithContext,
+};
+use async_trait::async_trait;
+use lastfm_edit::Track;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Request body sent to the HTTP endpoint
+#[derive(Debug, Serialize)]
+pub struct HttpProviderRequest<'a> {
+    pub tracks: &'a [Track],
+    pub pending_edits: &'a [PendingEdit],
+    pub pending_rules: &'a [PendingRewriteRule],
+}
+
+/// Response body expected from the HTTP endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpProviderResponse {
+    #[serde(default)]
+    pub results: Vec<HttpTrackSuggestions>,
+}
+
+/// Suggestions returned for a single track of the request batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpTrackSuggestions {
+    /// Index into the `tracks` array of the request
+    pub track_index: usize,
+    #[serde(default)]
+    pub suggestions: Vec<HttpSuggestion>,
+}
+
+/// A single suggestion returned by the HTTP endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpSuggestion {
+    pub suggestion: ScrubActionSuggestion,
+    /// Whether the suggestion needs user approval (defaults to true)
+    #[serde(default = "default_requires_confirmation")]
+    pub requires_confirmation: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+fn default_requires_confirmation() -> bool {
+    true
+}
+
+/// Action provider that delegates analysis to an external HTTP service
+pub struct HttpScrubActionProvider {
+    client: reqwest::Client,
+    endpoint_url: String,
+}
+
+impl HttpScrubActionProvider {
+    pub fn new(config: &HttpProviderConfig) -> Result<Self, ActionProviderError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .map_err(|e| ActionProviderError(format!("Failed to create HTTP client: {e}")))?;
+
+        Ok(Self {
+            client,
+            endpoint_url: config.endpoint_url.clone(),
+        })
+    }
+
+    #[must_use]
+    pub fn endpoint_url(&self) -> &str {
+        &self.endpoint_url
+    }
+
+    /// Convert a parsed response into per-track suggestions, dropping entries
+    /// that refer to tracks outside of the submitted batch
+    fn convert_response(
+        &self,
+        response: HttpProviderResponse,
+        track_count: usize,
+    ) -> Vec<(usize, Vec<SuggestionWithContext>)> {
+        let mut results: Vec<(usize, Vec<SuggestionWithContext>)> = Vec::new();
+
+        for entry in response.results {
+            if entry.track_index >= track_count {
+                log::warn!(
+                    "HTTP provider returned suggestions for track index {} but only {} tracks were sent, ignoring",
+                    entry.track_index,
+                    track_count
+                );
+                continue;
+            }
+
+            let suggestions: Vec<SuggestionWithContext> = entry
+                .suggestions
+                .into_iter()
+                .filter(|s| !matches!(s.suggestion, ScrubActionSuggestion::NoAction))
+                .map(|s| {
+                    let mut suggestion = SuggestionWithContext::new(
+                        s.suggestion,
+                        s.requires_confirmation,
+                        self.provider_name().to_string(),
+                    );
+                    suggestion.confidence = s.confidence.map(|c| c.clamp(0.0, 1.0));
+                    suggestion.explanation = s.explanation;
+                    suggestion
+                })
+                .collect();
+
+            if suggestions.is_empty() {
+                continue;
+            }
+
+            if let Some(existing) = results
+                .iter_mut()
+                .find(|(idx, _)| *idx == entry.track_index)
+            {
+                existing.1.extend(suggestions);
+            } else {
+                results.push((entry.track_index, suggestions));
+            }
+        }
+
+        results
+    }
+}
+
+#[async_trait]
+impl ScrubActionProvider for HttpScrubActionProvider {
+    type Error = ActionProviderError;
+
+    async fn analyze_tracks(
+        &self,
+        tracks: &[Track],
+        pending_edits: Option<&[PendingEdit]>,
+        pending_rules: Option<&[PendingRewriteRule]>,
+    ) -> Result<Vec<(usize, Vec<SuggestionWithContext>)>, Self::Error> {
+        if tracks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let request = HttpProviderRequest {
+            tracks,
+            pending_edits: pending_edits.unwrap_or(&[]),
+            pending_rules: pending_rules.unwrap_or(&[]),
+        };
+
+        log::debug!(
+            "Sending {} tracks to HTTP provider at {}",
+            tracks.len(),
+            self.endpoint_url
+        );
+
+        let response = self
+            .client
+            .post(&self.endpoint_url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| ActionProviderError(format!("HTTP provider request failed: {e}")))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ActionProviderError(format!(
+                "HTTP provider returned status {status}: {body}"
+            )));
+        }
+
+        let parsed: HttpProviderResponse = response.json().await.map_err(|e| {
+            ActionProviderError(format!("Failed to parse HTTP provider response: {e}"))
+        })?;
+
+        Ok(self.convert_response(parsed, tracks.len()))
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "HTTP"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewrite::{RewriteRule, SdRule};
+
+    fn test_provider() -> HttpScrubActionProvider {
+        HttpScrubActionProvider::new(&HttpProviderConfig {
+            endpoint_url: "http://localhost:1/suggest".to_string(),
+            timeout_seconds: 5,
+        })
+        .unwrap()
+    }
+
+    fn test_track() -> Track {
+        Track {
+            name: "Song (Remastered)".to_string(),
+            artist: "Artist".to_string(),
+            album: Some("Album".to_string()),
+            album_artist: None,
+            playcount: 1,
+            timestamp: Some(1_700_000_000),
+        }
+    }
+
+    #[test_log::test]
+    fn should_default_requires_confirmation_to_true() {
+        let json =
+            r#"{"results": [{"track_index": 0, "suggestions": [{"suggestion": "NoAction"}]}]}"#;
+        let response: HttpProviderResponse = serde_json::from_str(json).unwrap();
+
+        assert_eq!(response.results.len(), 1);
+        assert!(response.results[0].suggestions[0].requires_confirmation);
+        assert_eq!(response.results[0].suggestions[0].confidence, None);
+    }
+
+    #[test_log::test]
+    fn should_pass_through_confidence_and_explanation() {
+        let mut edit = crate::rewrite::create_no_op_edit(&test_track());
+        edit.track_name = Some("Song".to_string());
+        let response = HttpProviderResponse {
+            results: vec![HttpTrackSuggestions {
+                track_index: 0,
+                suggestions: vec![HttpSuggestion {
+                    suggestion: ScrubActionSuggestion::Edit(edit),
+                    requires_confirmation: true,
+                    confidence: Some(1.5),
+                    explanation: Some("Known remaster".to_string()),
+                }],
+            }],
+        };
+        let json = serde_json::to_string(&response).unwrap();
+        let parsed: HttpProviderResponse = serde_json::from_str(&json).unwrap();
+
+        let results = test_provider().convert_response(parsed, 1);
+
+        let suggestion = &results[0].1[0];
+        assert_eq!(suggestion.confidence, Some(1.0));
+        assert_eq!(suggestion.explanation.as_deref(), Some("Known remaster"));
+    }
+
+    #[test_log::test]
+    fn should_convert_edits_and_rules_and_drop_out_of_range_indices() {
+        let mut edit = crate::rewrite::create_no_op_edit(&test_track());
+        edit.track_name = Some("Song".to_string());
+        let rule = RewriteRule::new().with_track_name(SdRule::new(r"^(.*) \(Remastered\)$", "$1"));
+
+        let response = HttpProviderResponse {
+            results: vec![
+                HttpTrackSuggestions {
+                    track_index: 0,
+                    suggestions: vec![
+                        HttpSuggestion {
+                            suggestion: ScrubActionSuggestion::Edit(edit.clone()),
+                            requires_confirmation: false,
+                            confidence: None,
+                            explanation: None,
+                        },
+                        HttpSuggestion {
+                            suggestion: ScrubActionSuggestion::ProposeRule {
+                                rule: rule.clone(),
+                                motivation: "Strip remaster suffix".to_string(),
+                            },
+                            requires_confirmation: true,
+                            confidence: None,
+                            explanation: None,
+                        },
+                        HttpSuggestion {
+                            suggestion: ScrubActionSuggestion::NoAction,
+                            requires_confirmation: true,
+                            confidence: None,
+                            explanation: None,
+                        },
+                    ],
+                },
+                HttpTrackSuggestions {
+                    track_index: 5,
+                    suggestions: vec![HttpSuggestion {
+                        suggestion: ScrubActionSuggestion::Edit(edit.clone()),
+                        requires_confirmation: false,
+                        confidence: None,
+                        explanation: None,
+                    }],
+                },
+            ],
+        };
+
+        // Round-trip through JSON to exercise the wire format
+        let json = serde_json::to_string(&response).unwrap();
+        let parsed: HttpProviderResponse = serde_json::from_str(&json).unwrap();
+
+        let results = test_provider().convert_response(parsed, 1);
+
+        assert_eq!(results.len(), 1);
+        let (index, suggestions) = &results[0];
+        assert_eq!(*index, 0);
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].suggestion, ScrubActionSuggestion::Edit(edit));
+        assert!(!suggestions[0].requires_confirmation);
+        assert_eq!(suggestions[0].provider_name, "HTTP");
+        assert!(matches!(
+            suggestions[1].suggestion,
+            ScrubActionSuggestion::ProposeRule { .. }
+        ));
+        assert!(suggestions[1].requires_confirmation);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn should_skip_request_for_empty_batch() {
+        let results = test_provider()
+            .analyze_tracks(&[], None, None)
+            .await
+            .unwrap();
+        assert!(results.is_empty());
+    }
+}
@@ -7,7 +7,7 @@ use std::path::Path;
 use tokio::sync::broadcast;
 
 use crate::config::ScrubberConfig;
-use crate::events::{ScrubberEvent, ScrubberEventType};
+use crate::events::{EditOrigin, LogEditInfo, ScrubberEvent, ScrubberEventType};
 
 /// JSON log entry for scrobble edit attempts
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +17,9 @@ pub struct EditLogEntry {
     pub error_message: Option<String>,
     pub duration_ms: u64,
     pub edit: EditDetails,
+    /// The run, providers and rules behind the edit (not recorded by older versions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<EditOrigin>,
 }
 
 /// Detailed edit information extracted from ExactScrobbleEdit
@@ -43,6 +46,8 @@ pub struct JsonLogger {
     enabled: bool,
     receiver: broadcast::Receiver<ScrubberEvent>,
     config: ScrubberConfig,
+    /// Origin announced for the edit being applied, attached to its attempts
+    current_origin: Option<(LogEditInfo, EditOrigin)>,
 }
 
 impl JsonLogger {
@@ -57,6 +62,7 @@ impl JsonLogger {
             enabled,
             receiver,
             config,
+            current_origin: None,
         }
     }
 
@@ -70,11 +76,17 @@ impl JsonLogger {
     }
 
     /// Process a single ScrubberEvent and log if it contains ClientEvent::EditAttempted
-    fn process_event(&self, event: &ScrubberEvent) -> Result<(), Box<dyn std::error::Error>> {
+    fn process_event(&mut self, event: &ScrubberEvent) -> Result<(), Box<dyn std::error::Error>> {
         if !self.enabled {
             return Ok(());
         }
 
+        // The scrubber announces where an edit came from before the client attempts it
+        if let ScrubberEventType::EditApplying { edit, origin, .. } = &event.event_type {
+            self.current_origin = Some((edit.clone(), origin.clone()));
+            return Ok(());
+        }
+
         // Defensive check: Do not log during dry run mode
         if self.config.dry_run {
             log::trace!("JSON logger: Skipping log during dry run mode");
@@ -89,6 +101,17 @@ impl JsonLogger {
             duration_ms,
         }) = &event.event_type
         {
+            let origin = self
+                .current_origin
+                .as_ref()
+                .filter(|(announced, _)| {
+                    announced.original_artist_name.as_deref() == Some(&edit.artist_name_original)
+                        && announced
+                            .original_track_name
+                            .as_ref()
+                            .is_none_or(|track_name| track_name == &edit.track_name_original)
+                })
+                .map(|(_, origin)| origin.clone());
             let log_entry = EditLogEntry {
                 timestamp: event.timestamp,
                 success: *success,
@@ -110,6 +133,7 @@ impl JsonLogger {
                         album_artist_name: edit.album_artist_name.clone(),
                     },
                 },
+                origin,
             };
 
             self.write_log_entry(&log_entry)?;
@@ -1,17 +1,27 @@
+pub mod alias_rules;
 pub mod default_rules;
 pub mod edit;
 pub mod event_logger;
 pub mod events;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod http_provider;
 pub mod json_logger;
+pub mod majority_album_provider;
 pub mod rewrite;
 pub mod rewrite_processor;
+pub mod rewrite_template;
+pub mod rule_examples;
+pub mod rule_lint;
+pub mod rules_dir;
 pub mod scrub_action_provider;
 pub mod track_cache;
 pub mod track_provider;
+pub mod undo;
 
 #[cfg(feature = "cli")]
 pub mod cli;
 pub mod config;
+pub mod conflicts;
 pub mod musicbrainz;
 #[cfg(feature = "openai")]
 pub mod openai_provider;
@@ -0,0 +1,324 @@
+//! Majority album provider.
+//!
+//! Moves new scrobbles of a track to the album most of that track's plays are
+//! already filed under, e.g. a play scrobbled from "Now That's What I Call
+//! Music 47" when nearly every other play of the song sits on the original
+//! studio album. The play history comes from the artist data in the
+//! [`TrackCache`]; the scrubber loads missing artists from the Last.fm library
+//! before asking providers for suggestions (see
+//! [`MajorityAlbumConfig::fetch_missing_history`]).
+
+use crate::config::MajorityAlbumConfig;
+use crate::persistence::{PendingEdit, PendingRewriteRule};
+use crate::rewrite::{create_no_op_edit, RuleField};
+use crate::scrub_action_provider::{
+    ActionProviderError, ScrubActionProvider, SuggestionWithContext,
+};
+use crate::track_cache::TrackCache;
+use async_trait::async_trait;
+use lastfm_edit::Track;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Most tracks loaded per artist when fetching missing history
+pub const MAX_HISTORY_TRACKS: usize = 5000;
+
+/// Title fragments of multi-artist and best-of compilations
+const COMPILATION_TITLE_TERMS: &[&str] = &[
+    "now that's what i call",
+    "greatest hits",
+    "best of",
+    "the very best",
+    "hits of",
+    "anthology",
+    "the essential",
+    "compilation",
+    "various artists",
+];
+
+/// Check if an album title looks like a compilation
+#[must_use]
+pub fn is_compilation_title(title: &str) -> bool {
+    let lower = title.to_lowercase();
+    COMPILATION_TITLE_TERMS
+        .iter()
+        .any(|term| lower.contains(term))
+}
+
+/// The album most of a track's plays are filed under
+#[derive(Debug, Clone, PartialEq)]
+pub struct MajorityAlbum {
+    pub album: String,
+    /// Plays of the track on `album`
+    pub plays: u64,
+    /// Plays of the track across all albums
+    pub total_plays: u64,
+}
+
+impl MajorityAlbum {
+    /// Share of the track's plays on the majority album (0.0-1.0)
+    #[must_use]
+    pub fn share(&self) -> f32 {
+        if self.total_plays == 0 {
+            0.0
+        } else {
+            self.plays as f32 / self.total_plays as f32
+        }
+    }
+}
+
+/// Find the album to move `track` to, given the artist's play history
+///
+/// Returns `None` when the history is too short, no album holds the configured share of
+/// the plays, the track is already on the majority album, or the majority album is a
+/// compilation or excluded by the release filters.
+#[must_use]
+pub fn find_majority_album(
+    track: &Track,
+    history: &[Track],
+    config: &MajorityAlbumConfig,
+) -> Option<MajorityAlbum> {
+    let same_track = |other: &Track| {
+        other.name.trim().eq_ignore_ascii_case(track.name.trim())
+            && other
+                .artist
+                .trim()
+                .eq_ignore_ascii_case(track.artist.trim())
+    };
+
+    let mut plays_by_album: Vec<(&str, u64)> = Vec::new();
+    for entry in history.iter().filter(|entry| same_track(entry)) {
+        let Some(album) = entry.album.as_deref().filter(|album| !album.is_empty()) else {
+            continue;
+        };
+        match plays_by_album.iter_mut().find(|(name, _)| *name == album) {
+            Some((_, plays)) => *plays += entry.playcount as u64,
+            None => plays_by_album.push((album, entry.playcount as u64)),
+        }
+    }
+
+    let total_plays: u64 = plays_by_album.iter().map(|(_, plays)| plays).sum();
+    if total_plays < config.min_history {
+        return None;
+    }
+
+    // Ties go to the album seen first
+    let (album, plays) = plays_by_album.iter().copied().reduce(|best, candidate| {
+        if candidate.1 > best.1 {
+            candidate
+        } else {
+            best
+        }
+    })?;
+    let majority = MajorityAlbum {
+        album: album.to_string(),
+        plays,
+        total_plays,
+    };
+
+    if track.album.as_deref() == Some(album)
+        || majority.share() < config.majority_threshold
+        || is_compilation_title(album)
+        || config.release_filters.excludes_album_title(album)
+    {
+        return None;
+    }
+
+    Some(majority)
+}
+
+/// Provider that suggests moving scrobbles to the album most of the track's plays are on
+pub struct MajorityAlbumProvider {
+    config: MajorityAlbumConfig,
+    history: RwLock<HashMap<String, Vec<Track>>>,
+}
+
+impl MajorityAlbumProvider {
+    #[must_use]
+    pub fn new(config: MajorityAlbumConfig) -> Self {
+        Self {
+            config,
+            history: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Remember the play history of the artists in `tracks` that aren't known yet
+    ///
+    /// The track cache is read once for the whole batch. Artists missing from the cache
+    /// are looked up again with the next batch, since the scrubber may have loaded them in
+    /// the meantime, unless fetching is disabled.
+    fn load_history(&self, tracks: &[Track]) {
+        let Ok(mut history) = self.history.write() else {
+            return;
+        };
+        let mut missing: Vec<&str> = tracks
+            .iter()
+            .map(|track| track.artist.as_str())
+            .filter(|artist| !history.contains_key(*artist))
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        missing.sort_unstable();
+        missing.dedup();
+
+        let cache = TrackCache::load();
+        for artist in missing {
+            match cache.get_artist_tracks(artist) {
+                Some(tracks) => {
+                    history.insert(artist.to_string(), tracks.clone());
+                }
+                None if !self.config.fetch_missing_history => {
+                    history.insert(artist.to_string(), Vec::new());
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// The artist's play history, as remembered by `load_history`
+    fn artist_history(&self, artist: &str) -> Vec<Track> {
+        self.history
+            .read()
+            .ok()
+            .and_then(|history| history.get(artist).cloned())
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl ScrubActionProvider for MajorityAlbumProvider {
+    type Error = ActionProviderError;
+
+    async fn analyze_tracks(
+        &self,
+        tracks: &[Track],
+        _pending_edits: Option<&[PendingEdit]>,
+        _pending_rules: Option<&[PendingRewriteRule]>,
+    ) -> Result<Vec<(usize, Vec<SuggestionWithContext>)>, Self::Error> {
+        self.load_history(tracks);
+        let mut results = Vec::new();
+
+        for (index, track) in tracks.iter().enumerate() {
+            let history = self.artist_history(&track.artist);
+            let Some(majority) = find_majority_album(track, &history, &self.config) else {
+                continue;
+            };
+
+            log::debug!(
+                "'{}' by '{}' has {} of {} plays on '{}' (currently on '{}')",
+                track.name,
+                track.artist,
+                majority.plays,
+                majority.total_plays,
+                majority.album,
+                track.album.as_deref().unwrap_or("none")
+            );
+
+            let mut edit = create_no_op_edit(track);
+            RuleField::AlbumName.set_edit_value(&mut edit, majority.album.clone());
+            results.push((
+                index,
+                vec![SuggestionWithContext::edit_with_confirmation(
+                    edit,
+                    self.config.require_confirmation,
+                    self.provider_name().to_string(),
+                )
+                .with_confidence(majority.share())
+                .with_explanation(format!(
+                    "{} of {} plays of this track are on '{}'",
+                    majority.plays, majority.total_plays, majority.album
+                ))],
+            ));
+        }
+
+        Ok(results)
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "MajorityAlbum"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ReleaseFilterConfig;
+
+    fn play(name: &str, album: &str, playcount: u32) -> Track {
+        Track {
+            name: name.to_string(),
+            artist: "Radiohead".to_string(),
+            album: Some(album.to_string()),
+            album_artist: None,
+            playcount,
+            timestamp: None,
+        }
+    }
+
+    fn history() -> Vec<Track> {
+        vec![
+            play("Creep", "Pablo Honey", 18),
+            play("Creep", "Now That's What I Call Music 47", 1),
+            play("Creep", "Radiohead: The Best Of", 1),
+            play("Anyone Can Play Guitar", "Pablo Honey", 4),
+        ]
+    }
+
+    #[test_log::test]
+    fn should_suggest_album_holding_most_plays() {
+        let scrobble = play("Creep", "Now That's What I Call Music 47", 1);
+
+        let majority =
+            find_majority_album(&scrobble, &history(), &MajorityAlbumConfig::default()).unwrap();
+
+        assert_eq!(majority.album, "Pablo Honey");
+        assert_eq!((majority.plays, majority.total_plays), (18, 20));
+        assert!((majority.share() - 0.9).abs() < f32::EPSILON);
+    }
+
+    #[test_log::test]
+    fn should_require_history_and_majority() {
+        let config = MajorityAlbumConfig::default();
+
+        // Already on the majority album
+        let scrobble = play("Creep", "Pablo Honey", 1);
+        assert_eq!(find_majority_album(&scrobble, &history(), &config), None);
+
+        // Too few plays to judge
+        let scrobble = play("Anyone Can Play Guitar", "Hits", 1);
+        assert_eq!(find_majority_album(&scrobble, &history(), &config), None);
+
+        // No album holds enough of the plays
+        let split = vec![
+            play("Creep", "Pablo Honey", 6),
+            play("Creep", "Creep EP", 4),
+        ];
+        let scrobble = play("Creep", "Now That's What I Call Music 47", 1);
+        assert_eq!(find_majority_album(&scrobble, &split, &config), None);
+    }
+
+    #[test_log::test]
+    fn should_never_suggest_compilations_or_filtered_albums() {
+        let scrobble = play("Creep", "Pablo Honey", 1);
+
+        let best_of = vec![play("Creep", "The Best Of", 20)];
+        let config = MajorityAlbumConfig::default();
+        assert_eq!(find_majority_album(&scrobble, &best_of, &config), None);
+
+        let deluxe = vec![play("Creep", "Pablo Honey (Deluxe Edition)", 20)];
+        assert_eq!(find_majority_album(&scrobble, &deluxe, &config), None);
+
+        let config = MajorityAlbumConfig {
+            release_filters: ReleaseFilterConfig {
+                filters: Vec::new(),
+                ..ReleaseFilterConfig::default()
+            },
+            ..MajorityAlbumConfig::default()
+        };
+        assert_eq!(
+            find_majority_album(&scrobble, &deluxe, &config).map(|m| m.album),
+            Some("Pablo Honey (Deluxe Edition)".to_string())
+        );
+    }
+}
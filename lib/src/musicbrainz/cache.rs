@@ -0,0 +1,459 @@
+//! On-disk cache of MusicBrainz responses.
+//!
+//! Recording searches, release searches, releases and release groups are kept in a
+//! single JSON file shared by every MusicBrainz lookup, so long artist sweeps don't
+//! re-fetch yesterday's data at MusicBrainz's one request per second. Entries expire
+//! after the configured TTL and the oldest entries are evicted once the cache grows
+//! past its size cap.
+
+use crate::config::{MusicBrainzCacheConfig, ScrobbleScrubberConfig};
+use chrono::{DateTime, Utc};
+use musicbrainz_rs::entity::recording::Recording;
+use musicbrainz_rs::entity::release::Release;
+use musicbrainz_rs::entity::release_group::ReleaseGroup;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// New entries written to memory before the cache is saved to disk
+const SAVE_EVERY: usize = 25;
+
+static SHARED: OnceLock<MusicBrainzCache> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry<T> {
+    value: T,
+    fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheData {
+    #[serde(default)]
+    recording_searches: HashMap<String, CacheEntry<Vec<Recording>>>,
+    #[serde(default)]
+    release_searches: HashMap<String, CacheEntry<Vec<Release>>>,
+    #[serde(default)]
+    releases: HashMap<String, CacheEntry<Release>>,
+    #[serde(default)]
+    release_groups: HashMap<String, CacheEntry<Option<ReleaseGroup>>>,
+    #[serde(default)]
+    hits: u64,
+    #[serde(default)]
+    misses: u64,
+    #[serde(skip)]
+    unsaved: usize,
+}
+
+impl CacheData {
+    fn len(&self) -> usize {
+        self.recording_searches.len()
+            + self.release_searches.len()
+            + self.releases.len()
+            + self.release_groups.len()
+    }
+
+    fn fetch_times(&self) -> Vec<DateTime<Utc>> {
+        self.recording_searches
+            .values()
+            .map(|e| e.fetched_at)
+            .chain(self.release_searches.values().map(|e| e.fetched_at))
+            .chain(self.releases.values().map(|e| e.fetched_at))
+            .chain(self.release_groups.values().map(|e| e.fetched_at))
+            .collect()
+    }
+
+    /// Drop entries fetched before `cutoff`, returning how many were removed
+    fn remove_fetched_before(&mut self, cutoff: DateTime<Utc>) -> usize {
+        let before = self.len();
+        self.recording_searches
+            .retain(|_, e| e.fetched_at >= cutoff);
+        self.release_searches.retain(|_, e| e.fetched_at >= cutoff);
+        self.releases.retain(|_, e| e.fetched_at >= cutoff);
+        self.release_groups.retain(|_, e| e.fetched_at >= cutoff);
+        before - self.len()
+    }
+}
+
+/// Counts reported by `scrobble-scrubber musicbrainz cache stats`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MusicBrainzCacheStats {
+    pub recording_searches: usize,
+    pub release_searches: usize,
+    pub releases: usize,
+    pub release_groups: usize,
+    /// Entries older than the TTL, still on disk until purged or evicted
+    pub expired: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl MusicBrainzCacheStats {
+    pub fn total_entries(&self) -> usize {
+        self.recording_searches + self.release_searches + self.releases + self.release_groups
+    }
+
+    /// Share of lookups answered from the cache (0.0-1.0)
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Cache of MusicBrainz responses, persisted to a JSON file when it has a path
+pub struct MusicBrainzCache {
+    config: MusicBrainzCacheConfig,
+    path: Option<PathBuf>,
+    data: RwLock<CacheData>,
+}
+
+impl MusicBrainzCache {
+    /// Create a cache backed by `path`, loading any entries already saved there
+    pub fn new(config: MusicBrainzCacheConfig, path: Option<PathBuf>) -> Self {
+        let data = path
+            .as_deref()
+            .filter(|_| config.enabled)
+            .map(Self::load_data)
+            .unwrap_or_default();
+
+        Self {
+            config,
+            path,
+            data: RwLock::new(data),
+        }
+    }
+
+    /// Create a cache that only lives in memory
+    pub fn in_memory(config: MusicBrainzCacheConfig) -> Self {
+        Self::new(config, None)
+    }
+
+    /// The cache shared by all MusicBrainz lookups
+    ///
+    /// Until [`MusicBrainzCache::init_shared`] is called this is an in-memory cache with
+    /// default settings.
+    pub fn shared() -> &'static Self {
+        SHARED.get_or_init(|| Self::in_memory(MusicBrainzCacheConfig::default()))
+    }
+
+    /// Set up the shared cache from the configuration
+    ///
+    /// Returns `false` if the shared cache was already in use, in which case the
+    /// configuration is ignored.
+    pub fn init_shared(config: &ScrobbleScrubberConfig) -> bool {
+        let cache_config = config.musicbrainz.cache.clone();
+        let path = Self::cache_file_path(config);
+        let initialized = SHARED.set(Self::new(cache_config, path)).is_ok();
+        if !initialized {
+            log::debug!("MusicBrainz cache already initialized, keeping existing settings");
+        }
+        initialized
+    }
+
+    /// The cache file for a configuration: the configured path, or
+    /// `musicbrainz_cache.json` next to the state file
+    pub fn cache_file_path(config: &ScrobbleScrubberConfig) -> Option<PathBuf> {
+        if let Some(path) = &config.musicbrainz.cache.path {
+            return Some(PathBuf::from(path));
+        }
+
+        Path::new(&config.storage.state_file)
+            .parent()
+            .map(|dir| dir.join("musicbrainz_cache.json"))
+    }
+
+    /// Path of the cache file, if the cache is saved to disk
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    fn load_data(path: &Path) -> CacheData {
+        match fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str::<CacheData>(&content) {
+                Ok(data) => {
+                    log::info!(
+                        "Loaded {} cached MusicBrainz responses from {}",
+                        data.len(),
+                        path.display()
+                    );
+                    data
+                }
+                Err(e) => {
+                    log::warn!("Failed to parse MusicBrainz cache file: {e}, using empty cache");
+                    CacheData::default()
+                }
+            },
+            // File doesn't exist yet
+            Err(_) => CacheData::default(),
+        }
+    }
+
+    fn ttl(&self) -> chrono::Duration {
+        i64::try_from(self.config.ttl_hours)
+            .ok()
+            .and_then(chrono::Duration::try_hours)
+            .unwrap_or(chrono::Duration::MAX)
+    }
+
+    fn is_fresh(&self, fetched_at: DateTime<Utc>) -> bool {
+        Utc::now().signed_duration_since(fetched_at) < self.ttl()
+    }
+
+    fn get<T: Clone>(
+        &self,
+        select: fn(&CacheData) -> &HashMap<String, CacheEntry<T>>,
+        key: &str,
+    ) -> Option<T> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let mut data = self.data.write().ok()?;
+        let value = select(&data)
+            .get(key)
+            .filter(|entry| self.is_fresh(entry.fetched_at))
+            .map(|entry| entry.value.clone());
+        if value.is_some() {
+            data.hits += 1;
+            log::trace!("MusicBrainz cache hit for '{key}'");
+        } else {
+            data.misses += 1;
+        }
+        value
+    }
+
+    fn put<T>(
+        &self,
+        select: fn(&mut CacheData) -> &mut HashMap<String, CacheEntry<T>>,
+        key: &str,
+        value: T,
+    ) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let should_save = match self.data.write() {
+            Ok(mut data) => {
+                select(&mut data).insert(
+                    key.to_string(),
+                    CacheEntry {
+                        value,
+                        fetched_at: Utc::now(),
+                    },
+                );
+                self.evict_oldest(&mut data);
+                data.unsaved += 1;
+                data.unsaved >= SAVE_EVERY
+            }
+            Err(_) => false,
+        };
+
+        if should_save {
+            if let Err(e) = self.flush() {
+                log::warn!("Failed to save MusicBrainz cache: {e}");
+            }
+        }
+    }
+
+    /// Evict the oldest entries once the cache is over its size cap, down to 90% of the cap
+    fn evict_oldest(&self, data: &mut CacheData) {
+        if data.len() <= self.config.max_entries {
+            return;
+        }
+
+        let keep = self.config.max_entries * 9 / 10;
+        let mut fetch_times = data.fetch_times();
+        fetch_times.sort_unstable_by(|a, b| b.cmp(a));
+        // Keep the `keep` most recently fetched entries
+        let evicted = match keep.checked_sub(1).and_then(|i| fetch_times.get(i)) {
+            Some(cutoff) => data.remove_fetched_before(*cutoff),
+            None => data.remove_fetched_before(DateTime::<Utc>::MAX_UTC),
+        };
+        log::debug!("Evicted {evicted} MusicBrainz cache entries over the size cap");
+    }
+
+    pub fn get_recording_search(&self, query: &str) -> Option<Vec<Recording>> {
+        self.get(|data| &data.recording_searches, query)
+    }
+
+    pub fn put_recording_search(&self, query: &str, recordings: Vec<Recording>) {
+        self.put(|data| &mut data.recording_searches, query, recordings);
+    }
+
+    pub fn get_release_search(&self, query: &str) -> Option<Vec<Release>> {
+        self.get(|data| &data.release_searches, query)
+    }
+
+    pub fn put_release_search(&self, query: &str, releases: Vec<Release>) {
+        self.put(|data| &mut data.release_searches, query, releases);
+    }
+
+    /// Get a release fetched with the includes encoded in `key`
+    pub fn get_release(&self, key: &str) -> Option<Release> {
+        self.get(|data| &data.releases, key)
+    }
+
+    pub fn put_release(&self, key: &str, release: Release) {
+        self.put(|data| &mut data.releases, key, release);
+    }
+
+    /// Get the release group of a release; `Some(None)` means the release has none
+    pub fn get_release_group(&self, release_id: &str) -> Option<Option<ReleaseGroup>> {
+        self.get(|data| &data.release_groups, release_id)
+    }
+
+    pub fn put_release_group(&self, release_id: &str, release_group: Option<ReleaseGroup>) {
+        self.put(|data| &mut data.release_groups, release_id, release_group);
+    }
+
+    /// Entry counts and hit/miss totals
+    pub fn stats(&self) -> MusicBrainzCacheStats {
+        let Ok(data) = self.data.read() else {
+            return MusicBrainzCacheStats::default();
+        };
+
+        MusicBrainzCacheStats {
+            recording_searches: data.recording_searches.len(),
+            release_searches: data.release_searches.len(),
+            releases: data.releases.len(),
+            release_groups: data.release_groups.len(),
+            expired: data
+                .fetch_times()
+                .into_iter()
+                .filter(|fetched_at| !self.is_fresh(*fetched_at))
+                .count(),
+            hits: data.hits,
+            misses: data.misses,
+        }
+    }
+
+    /// Remove cached responses (only expired ones if `expired_only`) and save the cache,
+    /// returning how many entries were removed
+    pub fn purge(&self, expired_only: bool) -> Result<usize, Box<dyn std::error::Error>> {
+        let removed = {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|_| "MusicBrainz cache lock poisoned")?;
+            let removed = if expired_only {
+                match Utc::now().checked_sub_signed(self.ttl()) {
+                    Some(cutoff) => data.remove_fetched_before(cutoff),
+                    None => 0,
+                }
+            } else {
+                let removed = data.len();
+                *data = CacheData::default();
+                removed
+            };
+            data.unsaved += 1;
+            removed
+        };
+
+        self.flush()?;
+        Ok(removed)
+    }
+
+    /// Save the cache to disk if it has a path and unsaved changes
+    pub fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let content = {
+            let mut data = self
+                .data
+                .write()
+                .map_err(|_| "MusicBrainz cache lock poisoned")?;
+            if data.unsaved == 0 {
+                return Ok(());
+            }
+            data.unsaved = 0;
+            serde_json::to_string(&*data)?
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)?;
+        log::debug!("Saved MusicBrainz cache to {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_entries: usize) -> MusicBrainzCacheConfig {
+        MusicBrainzCacheConfig {
+            max_entries,
+            ..MusicBrainzCacheConfig::default()
+        }
+    }
+
+    #[test_log::test]
+    fn should_count_hits_and_misses() {
+        let cache = MusicBrainzCache::in_memory(config(100));
+
+        assert_eq!(cache.get_release_group("release-1"), None);
+        cache.put_release_group("release-1", None);
+        assert_eq!(cache.get_release_group("release-1"), Some(None));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+        assert_eq!(stats.release_groups, 1);
+    }
+
+    #[test_log::test]
+    fn should_treat_expired_entries_as_misses() {
+        let cache = MusicBrainzCache::in_memory(MusicBrainzCacheConfig {
+            ttl_hours: 0,
+            ..MusicBrainzCacheConfig::default()
+        });
+
+        cache.put_recording_search("recording:creep AND artist:radiohead", Vec::new());
+        assert_eq!(
+            cache.get_recording_search("recording:creep AND artist:radiohead"),
+            None
+        );
+        assert_eq!(cache.stats().expired, 1);
+        assert_eq!(cache.purge(true).unwrap(), 1);
+        assert_eq!(cache.stats().total_entries(), 0);
+    }
+
+    #[test_log::test]
+    fn should_evict_oldest_entries_over_the_size_cap() {
+        let cache = MusicBrainzCache::in_memory(config(10));
+
+        for i in 0..11 {
+            cache.put_release_group(&format!("release-{i}"), None);
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        assert_eq!(cache.stats().total_entries(), 9);
+        assert_eq!(cache.get_release_group("release-0"), None);
+        assert_eq!(cache.get_release_group("release-10"), Some(None));
+    }
+
+    #[test_log::test]
+    fn should_persist_entries_across_instances() {
+        let path = std::env::temp_dir().join(format!(
+            "scrobble-scrubber-mb-cache-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+
+        let cache = MusicBrainzCache::new(config(100), Some(path.clone()));
+        cache.put_release_group("release-1", None);
+        cache.flush().unwrap();
+
+        let reloaded = MusicBrainzCache::new(config(100), Some(path.clone()));
+        assert_eq!(reloaded.get_release_group("release-1"), Some(None));
+
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,9 +1,15 @@
-use crate::config::ReleaseFilterConfig;
+use super::cache::MusicBrainzCache;
+use super::musicbrainz_provider::has_reissue_marker;
+use super::rate_limiter::MusicBrainzRateLimiter;
+use crate::config::{MusicBrainzProviderConfig, ReleaseFilterConfig, ScrobbleScrubberConfig};
+use musicbrainz_rs::client::MusicBrainzClient as ApiClient;
 use musicbrainz_rs::entity::recording::{Recording, RecordingSearchQuery};
 use musicbrainz_rs::entity::release::Release;
+use musicbrainz_rs::entity::release_group::ReleaseGroup;
 use musicbrainz_rs::{Fetch, Search};
-use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::OnceLock;
+
+static API_CLIENT: OnceLock<ApiClient> = OnceLock::new();
 
 /// A match from MusicBrainz with confidence score
 #[derive(Debug, Clone)]
@@ -19,13 +25,6 @@ pub struct MusicBrainzClient {
     pub confidence_threshold: f32,
     pub max_results: usize,
     pub release_filters: ReleaseFilterConfig,
-    cache: RwLock<HashMap<String, CachedResult>>,
-}
-
-#[derive(Debug, Clone)]
-struct CachedResult {
-    recordings: Vec<Recording>,
-    timestamp: std::time::Instant,
 }
 
 impl MusicBrainzClient {
@@ -36,7 +35,6 @@ impl MusicBrainzClient {
             confidence_threshold,
             max_results: max_results.max(20), // Ensure we get enough results
             release_filters: ReleaseFilterConfig::default(),
-            cache: RwLock::new(HashMap::new()),
         }
     }
 
@@ -51,59 +49,136 @@ impl MusicBrainzClient {
             confidence_threshold,
             max_results,
             release_filters,
-            cache: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Point all MusicBrainz requests at the configured server and User-Agent
+    ///
+    /// Returns `false` if requests were already sent, in which case the configuration is
+    /// ignored. Until this is called requests go to musicbrainz.org.
+    pub fn init_server(config: &ScrobbleScrubberConfig) -> bool {
+        let server = config.providers.musicbrainz.clone().unwrap_or_default();
+        let initialized = API_CLIENT.set(Self::build_api_client(&server)).is_ok();
+        if !initialized {
+            log::debug!("MusicBrainz server already initialized, keeping existing settings");
+        }
+        initialized
+    }
+
+    /// The `musicbrainz_rs` client all requests are sent with
+    pub fn api_client() -> &'static ApiClient {
+        API_CLIENT.get_or_init(|| Self::build_api_client(&MusicBrainzProviderConfig::default()))
+    }
+
+    fn build_api_client(server: &MusicBrainzProviderConfig) -> ApiClient {
+        let mut client = ApiClient::default();
+        // Send each request once, so a 503 reaches the shared rate limiter's backoff instead
+        // of being retried here
+        client.max_retries = 1;
+        // Every request is paced by the shared rate limiter, which also knows about mirrors
+        client.drop_ratelimit();
+        if let Some(mirror) = server.mirror_url() {
+            log::info!("Using MusicBrainz mirror at {mirror}");
+            client.musicbrainz_domain = mirror.trim_end_matches("/ws/2").to_string();
+        }
+        if let Err(e) = client.set_user_agent(&server.user_agent_header()) {
+            log::warn!("Invalid MusicBrainz user agent, using the default: {e}");
+        }
+        client
+    }
+
     /// Search for recordings by artist and title
     pub async fn search_recording(
         &self,
         artist: &str,
         title: &str,
     ) -> Result<Vec<Recording>, Box<dyn std::error::Error + Send + Sync>> {
-        let cache_key = format!("{artist}:{title}");
-
-        // Check cache first (expires after 5 minutes)
-        if let Ok(cache) = self.cache.read() {
-            if let Some(cached) = cache.get(&cache_key) {
-                if cached.timestamp.elapsed().as_secs() < 300 {
-                    log::trace!("Using cached MusicBrainz search for '{cache_key}'");
-                    return Ok(cached.recordings.clone());
-                }
-            }
-        }
-
         let query = RecordingSearchQuery::query_builder()
             .recording(title)
             .and()
             .artist(artist)
             .build();
 
+        let recordings = Self::search_recordings(query).await?;
+        Ok(recordings.into_iter().take(self.max_results).collect())
+    }
+
+    /// Run a recording search query, using the shared MusicBrainz cache
+    pub async fn search_recordings(
+        query: String,
+    ) -> Result<Vec<Recording>, Box<dyn std::error::Error + Send + Sync>> {
+        let cache = MusicBrainzCache::shared();
+        if let Some(recordings) = cache.get_recording_search(&query) {
+            return Ok(recordings);
+        }
+
         log::debug!("Searching MusicBrainz for recording: {query}");
 
-        let search_results = Recording::search(query)
-            .execute()
+        let search_results = MusicBrainzRateLimiter::shared()
+            .execute("recording search", || {
+                let query = query.clone();
+                async move {
+                    Recording::search(query)
+                        .execute_with_client(Self::api_client())
+                        .await
+                }
+            })
             .await
             .map_err(|e| format!("MusicBrainz search failed: {e}"))?;
 
-        let recordings: Vec<Recording> = search_results
-            .entities
-            .into_iter()
-            .take(self.max_results)
-            .collect();
-
-        // Cache the results
-        if let Ok(mut cache) = self.cache.write() {
-            cache.insert(
-                cache_key,
-                CachedResult {
-                    recordings: recordings.clone(),
-                    timestamp: std::time::Instant::now(),
-                },
-            );
+        cache.put_recording_search(&query, search_results.entities.clone());
+        Ok(search_results.entities)
+    }
+
+    /// Run a release search query, using the shared MusicBrainz cache
+    pub async fn search_releases(
+        query: String,
+    ) -> Result<Vec<Release>, Box<dyn std::error::Error + Send + Sync>> {
+        let cache = MusicBrainzCache::shared();
+        if let Some(releases) = cache.get_release_search(&query) {
+            return Ok(releases);
         }
 
-        Ok(recordings)
+        log::debug!("Searching MusicBrainz for release: {query}");
+
+        let search_results = MusicBrainzRateLimiter::shared()
+            .execute("release search", || {
+                let query = query.clone();
+                async move {
+                    Release::search(query)
+                        .execute_with_client(Self::api_client())
+                        .await
+                }
+            })
+            .await
+            .map_err(|e| format!("Album search failed: {e}"))?;
+
+        cache.put_release_search(&query, search_results.entities.clone());
+        Ok(search_results.entities)
+    }
+
+    /// Fetch a release with its tracklist, using the shared MusicBrainz cache
+    pub async fn fetch_release_with_recordings(
+        release_id: &str,
+    ) -> Result<Release, Box<dyn std::error::Error + Send + Sync>> {
+        let cache = MusicBrainzCache::shared();
+        let key = format!("{release_id}+recordings");
+        if let Some(release) = cache.get_release(&key) {
+            return Ok(release);
+        }
+
+        let release = MusicBrainzRateLimiter::shared()
+            .execute("release lookup", move || async move {
+                Release::fetch()
+                    .id(release_id)
+                    .with_recordings()
+                    .execute_with_client(Self::api_client())
+                    .await
+            })
+            .await?;
+
+        cache.put_release(&key, release.clone());
+        Ok(release)
     }
 
     /// Fetch a release with its release group
@@ -111,12 +186,51 @@ impl MusicBrainzClient {
         &self,
         release_id: &str,
     ) -> Result<Release, Box<dyn std::error::Error + Send + Sync>> {
-        Release::fetch()
-            .id(release_id)
-            .with_release_groups()
-            .execute()
-            .await
-            .map_err(|e| e.into())
+        let cache = MusicBrainzCache::shared();
+        let key = format!("{release_id}+release-groups");
+        if let Some(release) = cache.get_release(&key) {
+            return Ok(release);
+        }
+
+        let release = MusicBrainzRateLimiter::shared()
+            .execute("release group lookup", move || async move {
+                Release::fetch()
+                    .id(release_id)
+                    .with_release_groups()
+                    .execute_with_client(Self::api_client())
+                    .await
+            })
+            .await?;
+
+        cache.put_release(&key, release.clone());
+        Ok(release)
+    }
+
+    /// Fetch the release group of a release, using the shared MusicBrainz cache
+    ///
+    /// Only the release group is cached, so this is cheaper to keep around than
+    /// [`MusicBrainzClient::fetch_release_with_group`] when the release itself isn't needed.
+    pub async fn fetch_release_group(
+        &self,
+        release_id: &str,
+    ) -> Result<Option<ReleaseGroup>, Box<dyn std::error::Error + Send + Sync>> {
+        let cache = MusicBrainzCache::shared();
+        if let Some(release_group) = cache.get_release_group(release_id) {
+            return Ok(release_group);
+        }
+
+        let release = MusicBrainzRateLimiter::shared()
+            .execute("release group lookup", move || async move {
+                Release::fetch()
+                    .id(release_id)
+                    .with_release_groups()
+                    .execute_with_client(Self::api_client())
+                    .await
+            })
+            .await?;
+
+        cache.put_release_group(release_id, release.release_group.clone());
+        Ok(release.release_group)
     }
 
     /// Compare release dates for sorting (earliest first)
@@ -177,15 +291,8 @@ impl MusicBrainzClient {
     pub fn is_special_edition(release: &Release) -> bool {
         release
             .disambiguation
-            .as_ref()
-            .map(|d| {
-                let lower = d.to_lowercase();
-                lower.contains("deluxe")
-                    || lower.contains("remaster")
-                    || lower.contains("expanded")
-                    || lower.contains("anniversary")
-            })
-            .unwrap_or(false)
+            .as_deref()
+            .is_some_and(has_reissue_marker)
     }
 
     /// Find albums by artist and album name
@@ -256,3 +363,25 @@ impl MusicBrainzClient {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn api_client_should_leave_503_retries_to_the_shared_limiter() {
+        let client = MusicBrainzClient::build_api_client(&MusicBrainzProviderConfig::default());
+        assert_eq!(client.max_retries, 1);
+    }
+
+    #[test_log::test]
+    fn api_client_should_leave_pacing_to_the_shared_limiter() {
+        let mirror = MusicBrainzProviderConfig {
+            base_url: Some("http://localhost:5000".to_string()),
+            ..MusicBrainzProviderConfig::default()
+        };
+        let client = MusicBrainzClient::build_api_client(&mirror);
+        assert_eq!(client.musicbrainz_domain, "http://localhost:5000");
+        assert!(client.rate_limit.is_none());
+    }
+}
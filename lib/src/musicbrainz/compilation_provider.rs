@@ -1,4 +1,6 @@
 use super::client::MusicBrainzClient;
+use super::musicbrainz_provider::has_reissue_marker;
+use crate::config::GreatestHitsPolicy;
 use crate::persistence::{PendingEdit, PendingRewriteRule};
 use crate::scrub_action_provider::{
     ActionProviderError, ScrubActionProvider, SuggestionWithContext,
@@ -33,10 +35,23 @@ pub struct RankedRelease {
     pub secondary_types: Vec<ReleaseGroupSecondaryType>,
     pub is_compilation: bool,
     pub is_various_artists: bool,
+    /// Single-artist compilation (greatest hits, best-of)
+    #[serde(default)]
+    pub is_greatest_hits: bool,
     pub rank: usize,
     pub rank_reason: String,
 }
 
+impl RankedRelease {
+    /// Release year, if the release has a date
+    pub fn year(&self) -> Option<i32> {
+        self.date
+            .as_ref()
+            .and_then(|d| d.get(0..4))
+            .and_then(|year_str| year_str.parse::<i32>().ok())
+    }
+}
+
 /// Check if there's a significant date gap that should override type priority
 /// Returns Some(ordering) if a date gap preference should apply, None otherwise
 fn check_date_gap_preference(
@@ -138,23 +153,25 @@ pub fn is_compilation_release(release: &Release, release_group: Option<&ReleaseG
     }
 }
 
-/// Check if a release title indicates a special edition (deluxe, remaster, etc.)
-fn is_special_edition(title: &str) -> bool {
-    let lower = title.to_lowercase();
-    lower.contains("deluxe")
-        || lower.contains("remaster")
-        || lower.contains("special")
-        || lower.contains("anniversary")
-        || lower.contains("expanded")
-        || lower.contains("collector")
-        || lower.contains("limited")
-        || lower.contains("super")
-        || lower.contains("bonus")
+/// Check if a release is a single-artist compilation such as a greatest hits or best-of album
+pub fn is_greatest_hits_release(release: &Release, release_group: Option<&ReleaseGroup>) -> bool {
+    if MusicBrainzClient::is_various_artists_release(release) {
+        return false;
+    }
+
+    release_group
+        .map(|rg| {
+            rg.secondary_types
+                .iter()
+                .any(|st| matches!(st, ReleaseGroupSecondaryType::Compilation))
+        })
+        .unwrap_or(false)
 }
 
 /// Default release comparison function
 /// Prefers: Official > None > Promotion > Bootleg > PseudoRelease
-/// Then: Non-compilations over compilations, then regular editions over special editions,
+/// Then: Non-compilations over compilations (single-artist greatest hits over other
+/// compilations), then regular editions over special editions,
 /// then Studio Albums > EPs > Singles > Broadcast > Unknown
 /// For same quality and status, prefers earlier releases
 pub fn default_release_comparer(
@@ -176,12 +193,23 @@ pub fn default_release_comparer(
             match (a_is_compilation, b_is_compilation) {
                 (true, false) => return Ordering::Greater, // b is better (non-compilation)
                 (false, true) => return Ordering::Less,    // a is better (non-compilation)
-                _ => {} // Both are compilations or both are non-compilations, continue comparing
+                (true, true) => {
+                    // A greatest hits album at least keeps the track with its artist
+                    match (
+                        is_greatest_hits_release(a, a_group),
+                        is_greatest_hits_release(b, b_group),
+                    ) {
+                        (true, false) => return Ordering::Less,
+                        (false, true) => return Ordering::Greater,
+                        _ => {}
+                    }
+                }
+                _ => {} // Both are non-compilations, continue comparing
             }
 
             // Check if either is a special edition (prefer regular editions)
-            let a_is_special = is_special_edition(&a.title);
-            let b_is_special = is_special_edition(&b.title);
+            let a_is_special = has_reissue_marker(&a.title);
+            let b_is_special = has_reissue_marker(&b.title);
 
             match (a_is_special, b_is_special) {
                 (true, false) => return Ordering::Greater, // b is better (regular edition)
@@ -236,6 +264,7 @@ pub struct CompilationToCanonicalProvider {
     confidence_threshold: f32,
     release_comparer: ReleaseComparer,
     official_releases_only: bool,
+    greatest_hits_policy: GreatestHitsPolicy,
 }
 
 impl CompilationToCanonicalProvider {
@@ -248,6 +277,7 @@ impl CompilationToCanonicalProvider {
             confidence_threshold: 0.8,
             release_comparer: default_release_comparer,
             official_releases_only: true,
+            greatest_hits_policy: GreatestHitsPolicy::default(),
         }
     }
 
@@ -260,6 +290,7 @@ impl CompilationToCanonicalProvider {
             confidence_threshold,
             release_comparer: default_release_comparer,
             official_releases_only: true,
+            greatest_hits_policy: GreatestHitsPolicy::default(),
         }
     }
 
@@ -272,6 +303,7 @@ impl CompilationToCanonicalProvider {
             confidence_threshold: 0.8,
             release_comparer: comparer,
             official_releases_only: true,
+            greatest_hits_policy: GreatestHitsPolicy::default(),
         }
     }
 
@@ -289,6 +321,18 @@ impl CompilationToCanonicalProvider {
         self
     }
 
+    /// Set how tracks on single-artist greatest hits albums are remapped
+    #[must_use]
+    pub const fn with_greatest_hits_policy(mut self, policy: GreatestHitsPolicy) -> Self {
+        self.greatest_hits_policy = policy;
+        self
+    }
+
+    /// The configured greatest hits policy
+    pub const fn greatest_hits_policy(&self) -> GreatestHitsPolicy {
+        self.greatest_hits_policy
+    }
+
     /// Rank all releases for a recording and return detailed information
     pub async fn rank_releases_for_recording(
         &self,
@@ -562,9 +606,8 @@ impl CompilationToCanonicalProvider {
         if let Some(cached) = cache.get(&release.id) {
             cached.clone()
         } else if release.id.len() == 36 {
-            match self.client.fetch_release_with_group(&release.id).await {
-                Ok(full_release) => {
-                    let rg = full_release.release_group;
+            match self.client.fetch_release_group(&release.id).await {
+                Ok(rg) => {
                     cache.insert(release.id.clone(), rg.clone());
                     rg
                 }
@@ -589,9 +632,15 @@ impl CompilationToCanonicalProvider {
         for (rank, (release, release_group)) in releases_with_groups.iter().enumerate() {
             let is_compilation = is_compilation_release(release, release_group.as_ref());
             let is_va = MusicBrainzClient::is_various_artists_release(release);
+            let is_greatest_hits = is_greatest_hits_release(release, release_group.as_ref());
 
-            let rank_reason =
-                self.determine_rank_reason(rank, is_compilation, is_va, release_group);
+            let rank_reason = self.determine_rank_reason(
+                rank,
+                is_compilation,
+                is_va,
+                is_greatest_hits,
+                release_group,
+            );
             let artist_name = self.extract_artist_name(release);
 
             ranked_releases.push(RankedRelease {
@@ -611,6 +660,7 @@ impl CompilationToCanonicalProvider {
                     .unwrap_or_default(),
                 is_compilation,
                 is_various_artists: is_va,
+                is_greatest_hits,
                 rank: rank + 1,
                 rank_reason,
             });
@@ -625,10 +675,13 @@ impl CompilationToCanonicalProvider {
         rank: usize,
         is_compilation: bool,
         is_va: bool,
+        is_greatest_hits: bool,
         release_group: &Option<ReleaseGroup>,
     ) -> String {
         if rank == 0 {
-            if is_compilation {
+            if is_greatest_hits {
+                "Best available (greatest hits)".to_string()
+            } else if is_compilation {
                 "Best available (compilation)".to_string()
             } else if let Some(rg) = release_group {
                 match rg.primary_type.as_ref() {
@@ -640,6 +693,8 @@ impl CompilationToCanonicalProvider {
             } else {
                 "Best available release".to_string()
             }
+        } else if is_greatest_hits {
+            "Greatest hits (deprioritized)".to_string()
         } else if is_compilation {
             "Compilation (deprioritized)".to_string()
         } else if is_va {
@@ -743,15 +798,41 @@ impl CompilationToCanonicalProvider {
         false
     }
 
-    /// Check if a release is an acceptable replacement for a compilation
-    #[allow(dead_code)]
-    fn is_acceptable_replacement(
+    /// Check if a release is an acceptable replacement for the track's current release
+    ///
+    /// Anything that's not a compilation is acceptable, except that tracks on a greatest hits
+    /// album are only moved when the greatest hits policy allows it.
+    pub fn is_acceptable_replacement(
         &self,
-        release: &Release,
-        release_group: Option<&ReleaseGroup>,
+        candidate: &RankedRelease,
+        current: Option<&RankedRelease>,
     ) -> bool {
-        // Anything that's not a compilation is acceptable
-        !is_compilation_release(release, release_group)
+        if candidate.is_compilation || candidate.is_various_artists {
+            return false;
+        }
+
+        match current {
+            Some(current) if current.is_greatest_hits => self
+                .greatest_hits_policy
+                .allows_remap(current.year(), candidate.year()),
+            _ => true,
+        }
+    }
+
+    /// Pick the release to move a track on `current_album` to from its ranked releases
+    pub fn select_replacement<'a>(
+        &self,
+        ranked_releases: &'a [RankedRelease],
+        current_album: &str,
+    ) -> Option<&'a RankedRelease> {
+        let current = ranked_releases
+            .iter()
+            .find(|r| r.title.eq_ignore_ascii_case(current_album));
+
+        ranked_releases.iter().find(|release| {
+            !release.title.eq_ignore_ascii_case(current_album)
+                && self.is_acceptable_replacement(release, current)
+        })
     }
 
     /// Find the canonical (non-compilation) release for a recording
@@ -797,6 +878,13 @@ impl CompilationToCanonicalProvider {
                     return Ok(None);
                 }
 
+                if found_release.is_greatest_hits {
+                    log::debug!(
+                        "Album '{current_album}' is a greatest hits album, applying policy: {}",
+                        self.greatest_hits_policy
+                    );
+                }
+
                 log::debug!(
                     "Album '{current_album}' confirmed as compilation/non-album. Looking for studio album replacement for '{title}'."
                 );
@@ -820,19 +908,8 @@ impl CompilationToCanonicalProvider {
             }
         }
 
-        // Find the best non-compilation release
-        for release in &ranked_releases {
-            // Skip if it's the current album
-            if release.title.eq_ignore_ascii_case(current_album) {
-                continue;
-            }
-
-            // Skip compilations and various artists
-            if release.is_compilation || release.is_various_artists {
-                continue;
-            }
-
-            // Found a good replacement
+        // Find the best acceptable release
+        if let Some(release) = self.select_replacement(&ranked_releases, current_album) {
             let title_ref = &release.title;
             let rank = release.rank;
             let reason = &release.rank_reason;
@@ -913,7 +990,10 @@ impl ScrubActionProvider for CompilationToCanonicalProvider {
                         edit,
                         true, // Always require confirmation for album corrections
                         self.provider_name().to_string(),
-                    );
+                    )
+                    .with_explanation(format!(
+                        "'{canonical_album}' is the original release of this recording"
+                    ));
 
                     results.push((index, vec![suggestion]));
                 }
@@ -940,9 +1020,6 @@ impl ScrubActionProvider for CompilationToCanonicalProvider {
                     );
                 }
             }
-
-            // Add a small delay to be respectful to MusicBrainz API
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
 
         log::debug!(
@@ -954,3 +1031,78 @@ impl ScrubActionProvider for CompilationToCanonicalProvider {
         Ok(results)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(title: &str, date: &str, is_greatest_hits: bool) -> RankedRelease {
+        RankedRelease {
+            title: title.to_string(),
+            artist: "Queen".to_string(),
+            release_id: String::new(),
+            date: Some(date.to_string()),
+            country: None,
+            disambiguation: None,
+            status: None,
+            primary_type: Some(ReleaseGroupPrimaryType::Album),
+            secondary_types: Vec::new(),
+            is_compilation: is_greatest_hits,
+            is_various_artists: false,
+            is_greatest_hits,
+            rank: 0,
+            rank_reason: String::new(),
+        }
+    }
+
+    fn releases() -> Vec<RankedRelease> {
+        vec![
+            ranked("A Night at the Opera", "1975-11-21", false),
+            ranked("Greatest Hits", "1981-10-26", true),
+        ]
+    }
+
+    #[test_log::test]
+    fn greatest_hits_policy_should_gate_replacements() {
+        let releases = releases();
+
+        let provider = CompilationToCanonicalProvider::new();
+        assert_eq!(
+            provider
+                .select_replacement(&releases, "Greatest Hits")
+                .map(|r| r.title.as_str()),
+            Some("A Night at the Opera")
+        );
+
+        let provider = CompilationToCanonicalProvider::new()
+            .with_greatest_hits_policy(GreatestHitsPolicy::Keep);
+        assert!(provider
+            .select_replacement(&releases, "Greatest Hits")
+            .is_none());
+
+        // The hit is only six years older than the compilation
+        let provider = CompilationToCanonicalProvider::new()
+            .with_greatest_hits_policy(GreatestHitsPolicy::MapIfOlder { years: 10 });
+        assert!(provider
+            .select_replacement(&releases, "Greatest Hits")
+            .is_none());
+        let provider = CompilationToCanonicalProvider::new()
+            .with_greatest_hits_policy(GreatestHitsPolicy::MapIfOlder { years: 5 });
+        assert!(provider
+            .select_replacement(&releases, "Greatest Hits")
+            .is_some());
+    }
+
+    #[test_log::test]
+    fn greatest_hits_policy_should_not_affect_other_compilations() {
+        let mut releases = releases();
+        releases[1].is_greatest_hits = false;
+        releases[1].is_various_artists = true;
+
+        let provider = CompilationToCanonicalProvider::new()
+            .with_greatest_hits_policy(GreatestHitsPolicy::Keep);
+        assert!(provider
+            .select_replacement(&releases, "Greatest Hits")
+            .is_some());
+    }
+}
@@ -1,9 +1,13 @@
+pub mod cache;
 pub mod client;
 pub mod compilation_provider;
 pub mod musicbrainz_provider;
+pub mod rate_limiter;
 
+pub use cache::{MusicBrainzCache, MusicBrainzCacheStats};
 pub use client::{MusicBrainzClient, MusicBrainzMatch};
 pub use compilation_provider::{
     default_release_comparer, CompilationToCanonicalProvider, RankedRelease, ReleaseComparer,
 };
 pub use musicbrainz_provider::MusicBrainzScrubActionProvider;
+pub use rate_limiter::MusicBrainzRateLimiter;
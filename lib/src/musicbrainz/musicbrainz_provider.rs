@@ -7,8 +7,8 @@ use musicbrainz_rs::entity::recording::RecordingSearchQuery;
 use std::collections::HashMap;
 use std::sync::RwLock;
 
+use super::client::MusicBrainzClient;
 use musicbrainz_rs::entity::recording::Recording;
-use musicbrainz_rs::Search;
 
 /// MusicBrainz-based scrub action provider that suggests corrections using the MusicBrainz database
 pub struct MusicBrainzScrubActionProvider {
@@ -28,24 +28,61 @@ pub struct MusicBrainzMatch {
     pub release_id: Option<String>,
 }
 
+/// Terms that mark a special (deluxe, expanded, ...) edition of a release
+pub const SPECIAL_EDITION_TERMS: &[&str] = &[
+    "deluxe",
+    "legacy",
+    "expanded",
+    "anniversary",
+    "special",
+    "bonus",
+    "collector",
+    "limited",
+];
+
+/// Check if text (an album title, disambiguation or album suffix) names a special edition
+#[must_use]
+pub fn has_special_edition_marker(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    SPECIAL_EDITION_TERMS
+        .iter()
+        .any(|term| lower.contains(term))
+}
+
+/// Check if text names a special edition or a remaster, i.e. not the original release
+#[must_use]
+pub fn has_reissue_marker(text: &str) -> bool {
+    has_special_edition_marker(text) || text.to_lowercase().contains("remaster")
+}
+
 impl MusicBrainzScrubActionProvider {
     /// Check if a release has special edition markers in its disambiguation
     pub fn is_special_edition(release: &musicbrainz_rs::entity::release::Release) -> bool {
         release
             .disambiguation
-            .as_ref()
-            .map(|d| {
-                let d_lower = d.to_lowercase();
-                d_lower.contains("deluxe")
-                    || d_lower.contains("legacy")
-                    || d_lower.contains("expanded")
-                    || d_lower.contains("anniversary")
-                    || d_lower.contains("special")
-                    || d_lower.contains("bonus")
-            })
+            .as_deref()
+            .map(has_special_edition_marker)
             .unwrap_or(false)
     }
 
+    /// Check if renaming `original_album` to `new_album` only strips a special edition suffix
+    ///
+    /// E.g. "XO (Deluxe Edition)" → "XO". Such a rename is only correct for tracks that
+    /// are also on the standard edition.
+    pub fn is_edition_suffix_removal(original_album: &str, new_album: &str) -> bool {
+        let original = original_album.trim();
+        let new = new_album.trim();
+        if new.is_empty() || original.len() <= new.len() {
+            return false;
+        }
+        match original.get(..new.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(new) => {
+                has_special_edition_marker(&original[new.len()..])
+            }
+            _ => false,
+        }
+    }
+
     /// Check if a release is a demo in its disambiguation
     pub fn is_demo(release: &musicbrainz_rs::entity::release::Release) -> bool {
         release
@@ -470,8 +507,7 @@ impl MusicBrainzScrubActionProvider {
         Vec<musicbrainz_rs::entity::release::Release>,
         Box<dyn std::error::Error + Send + Sync>,
     > {
-        use musicbrainz_rs::entity::release::{Release, ReleaseSearchQuery};
-        use musicbrainz_rs::Search;
+        use musicbrainz_rs::entity::release::ReleaseSearchQuery;
 
         let query = if let Some(album) = album_filter {
             ReleaseSearchQuery::query_builder()
@@ -485,13 +521,8 @@ impl MusicBrainzScrubActionProvider {
 
         log::debug!("Searching for albums with query: {query}");
 
-        let search_results = Release::search(query)
-            .execute()
-            .await
-            .map_err(|e| format!("Album search failed: {e}"))?;
-
         // Sort releases by date
-        let mut releases = search_results.entities;
+        let mut releases = MusicBrainzClient::search_releases(query).await?;
         releases.sort_by(Self::compare_release_dates);
 
         Ok(releases)
@@ -509,7 +540,7 @@ impl MusicBrainzScrubActionProvider {
         // Create a temporary provider with the specified filters for verification
         let verification_provider = Self::with_filters(0.8, 20, release_filters.clone());
         verification_provider
-            ._verify_track_exists_on_canonical_release(artist, title, album)
+            ._verify_track_exists_on_canonical_release(artist, title, album, false)
             .await
     }
 
@@ -523,7 +554,22 @@ impl MusicBrainzScrubActionProvider {
         title: &str,
         album: Option<&str>,
     ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        self._verify_track_exists_on_canonical_release(artist, title, album)
+        self._verify_track_exists_on_canonical_release(artist, title, album, false)
+            .await
+    }
+
+    /// Verify that a track is on the standard (non-deluxe) edition of an album
+    /// This is used before stripping an edition suffix like "(Deluxe Edition)" from an album name:
+    /// bonus tracks that only exist on special editions are not confirmed. Unlike
+    /// `verify_track_exists_on_canonical_release`, this never falls back to a special edition
+    /// when the album has no standard edition in MusicBrainz.
+    pub async fn verify_track_on_standard_edition(
+        &self,
+        artist: &str,
+        title: &str,
+        standard_album: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        self._verify_track_exists_on_canonical_release(artist, title, Some(standard_album), true)
             .await
     }
 
@@ -533,10 +579,9 @@ impl MusicBrainzScrubActionProvider {
         artist: &str,
         title: &str,
         album: Option<&str>,
+        standard_edition_only: bool,
     ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        use musicbrainz_rs::entity::release::Release;
         use musicbrainz_rs::entity::release::ReleaseSearchQuery;
-        use musicbrainz_rs::Search;
 
         log::debug!(
             "MB verify: Checking if track exists on canonical release - '{}' by '{}' [{}]",
@@ -555,21 +600,18 @@ impl MusicBrainzScrubActionProvider {
 
             log::debug!("Searching for album releases with query: {album_query}");
 
-            let album_search = Release::search(album_query)
-                .execute()
-                .await
-                .map_err(|e| format!("Album search failed: {e}"))?;
+            let album_search = MusicBrainzClient::search_releases(album_query).await?;
 
-            if album_search.entities.is_empty() {
+            if album_search.is_empty() {
                 log::debug!("No album releases found for '{desired_album}' by '{artist}'");
                 return Ok(false);
             }
 
             // Filter to only releases that match the album title exactly
             let releases: Vec<_> = album_search
-                .entities
                 .into_iter()
                 .filter(|r| r.title.eq_ignore_ascii_case(desired_album))
+                .filter(|r| !standard_edition_only || !Self::is_special_edition(r))
                 .collect();
 
             if releases.is_empty() {
@@ -604,12 +646,8 @@ impl MusicBrainzScrubActionProvider {
 
             // Now check if the track exists on this specific canonical release
             // We need to fetch the full release with its recordings
-            use musicbrainz_rs::Fetch;
-            let full_release = Release::fetch()
-                .id(&canonical_release.id)
-                .with_recordings()
-                .execute()
-                .await?;
+            let full_release =
+                MusicBrainzClient::fetch_release_with_recordings(&canonical_release.id).await?;
 
             // Check if any track on this release matches our track
             if let Some(media) = full_release.media {
@@ -683,20 +721,17 @@ impl MusicBrainzScrubActionProvider {
         log::debug!("MusicBrainz query string: {query_string}");
 
         // Perform the search
-        let search_results = Recording::search(query_string)
-            .execute()
-            .await
-            .map_err(|e| format!("MusicBrainz search failed: {e}"))?;
+        let recordings = MusicBrainzClient::search_recordings(query_string).await?;
 
         log::debug!(
             "Found {} MusicBrainz results (showing up to {})",
-            search_results.entities.len(),
+            recordings.len(),
             self.max_results
         );
 
         let mut results = Vec::new();
 
-        for recording in search_results.entities.iter().take(self.max_results) {
+        for recording in recordings.iter().take(self.max_results) {
             if let Some(artist_credit) = &recording.artist_credit {
                 let mb_artist = artist_credit
                     .first()
@@ -916,11 +951,19 @@ impl MusicBrainzScrubActionProvider {
         );
 
         // MusicBrainz suggestions typically don't require confirmation since they're based on authoritative data
-        Some(SuggestionWithContext::edit_with_confirmation(
-            edit,
-            false, // MusicBrainz corrections are generally high-confidence
-            "MusicBrainz".to_string(),
-        ))
+        Some(
+            SuggestionWithContext::edit_with_confirmation(
+                edit,
+                false, // MusicBrainz corrections are generally high-confidence
+                "MusicBrainz".to_string(),
+            )
+            .with_confidence(mb_match.confidence)
+            .with_explanation(format!(
+                "MusicBrainz recording {} ({})",
+                mb_match.mbid,
+                correction_details.join(", ")
+            )),
+        )
     }
 }
 
@@ -960,9 +1003,6 @@ impl ScrubActionProvider for MusicBrainzScrubActionProvider {
                     results.push((index, vec![suggestion]));
                 }
             }
-
-            // Add a small delay to be respectful to MusicBrainz API
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
 
         log::trace!(
@@ -994,6 +1034,35 @@ mod tests {
         assert!(similarity < 1.0 && similarity > 0.0);
     }
 
+    #[test_log::test]
+    fn should_detect_edition_suffix_removal() {
+        type Provider = MusicBrainzScrubActionProvider;
+
+        assert!(Provider::is_edition_suffix_removal(
+            "XO (Deluxe Edition)",
+            "XO"
+        ));
+        assert!(Provider::is_edition_suffix_removal(
+            "Skelethon [Deluxe Version]",
+            "Skelethon"
+        ));
+        assert!(Provider::is_edition_suffix_removal(
+            "Grace (Legacy Edition)",
+            "grace"
+        ));
+
+        // Not an edition suffix, or not a plain suffix removal
+        assert!(!Provider::is_edition_suffix_removal(
+            "Paranoid (Remaster)",
+            "Paranoid"
+        ));
+        assert!(!Provider::is_edition_suffix_removal(
+            "XO (Deluxe Edition)",
+            "Either/Or"
+        ));
+        assert!(!Provider::is_edition_suffix_removal("XO", "XO"));
+    }
+
     #[test_log::test(tokio::test)]
     async fn should_implement_provider_interface_correctly() {
         let provider = MusicBrainzScrubActionProvider::default();
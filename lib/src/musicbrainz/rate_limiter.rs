@@ -0,0 +1,411 @@
+//! Process-wide MusicBrainz rate limiter.
+//!
+//! MusicBrainz allows one request per second per client. The MusicBrainz provider, the
+//! compilation provider, rule confirmation and the CLI all send their requests through
+//! [`MusicBrainzRateLimiter::shared`], a token bucket that paces them together. When
+//! MusicBrainz still answers 503, every caller backs off (honouring Retry-After when the
+//! error carries it) and the throttle is broadcast as a [`ScrubberEvent`].
+
+use crate::config::{MusicBrainzRateLimitConfig, ScrobbleScrubberConfig};
+use crate::events::ScrubberEvent;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+static SHARED: OnceLock<MusicBrainzRateLimiter> = OnceLock::new();
+
+/// Requests per second allowed against a local MusicBrainz mirror
+pub const MIRROR_REQUESTS_PER_SECOND: f64 = 50.0;
+
+/// Token bucket refilled at a fixed rate
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket holding `capacity` tokens, refilled at `per_second` tokens per second
+    #[must_use]
+    pub fn new(capacity: u32, per_second: f64, now: Instant) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        Self {
+            capacity,
+            per_second: per_second.max(f64::MIN_POSITIVE),
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Take a token, or return how long until one is available
+    pub fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+/// Check if a MusicBrainz error means the request was throttled
+#[must_use]
+pub fn is_throttled_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("503") || lower.contains("service unavailable") || lower.contains("rate limit")
+}
+
+/// Read the Retry-After seconds from a MusicBrainz error message, if it has one
+#[must_use]
+pub fn retry_after_from_error(message: &str) -> Option<Duration> {
+    let lower = message.to_lowercase();
+    let rest = &lower[lower.find("retry-after")? + "retry-after".len()..];
+    let seconds: String = rest
+        .trim_start_matches(|c: char| c == ':' || c == '=' || c.is_whitespace())
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    seconds.parse().ok().map(Duration::from_secs)
+}
+
+/// Errors [`MusicBrainzRateLimiter::execute`] can tell apart as MusicBrainz throttling
+pub trait ThrottleError: std::fmt::Display {
+    /// Whether the request was rejected because too many were sent
+    fn is_throttled(&self) -> bool;
+
+    /// How long MusicBrainz asked to wait, if it said
+    fn retry_after(&self) -> Option<Duration> {
+        retry_after_from_error(&self.to_string())
+    }
+}
+
+impl ThrottleError for musicbrainz_rs::Error {
+    /// `musicbrainz_rs` reports a 503 it stopped retrying as `MaxRetriesExceeded`, after
+    /// already waiting out the Retry-After
+    fn is_throttled(&self) -> bool {
+        matches!(self, Self::MaxRetriesExceeded)
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl ThrottleError for String {
+    fn is_throttled(&self) -> bool {
+        is_throttled_error(self)
+    }
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    bucket: TokenBucket,
+    /// No requests are sent before this while backing off from a 503
+    backoff_until: Option<Instant>,
+}
+
+/// Rate limiter shared by all MusicBrainz requests
+pub struct MusicBrainzRateLimiter {
+    config: MusicBrainzRateLimitConfig,
+    state: Mutex<LimiterState>,
+    event_sender: broadcast::Sender<ScrubberEvent>,
+}
+
+impl MusicBrainzRateLimiter {
+    #[must_use]
+    pub fn new(config: MusicBrainzRateLimitConfig) -> Self {
+        let (event_sender, _) = broadcast::channel(100);
+        let bucket = TokenBucket::new(config.burst, config.requests_per_second, Instant::now());
+        Self {
+            config,
+            state: Mutex::new(LimiterState {
+                bucket,
+                backoff_until: None,
+            }),
+            event_sender,
+        }
+    }
+
+    /// The limiter shared by all MusicBrainz lookups
+    ///
+    /// Until [`MusicBrainzRateLimiter::init_shared`] is called this uses the default
+    /// one request per second.
+    pub fn shared() -> &'static Self {
+        SHARED.get_or_init(|| Self::new(MusicBrainzRateLimitConfig::default()))
+    }
+
+    /// Set up the shared limiter from the configuration, relaxed when a MusicBrainz mirror
+    /// is configured
+    ///
+    /// Returns `false` if the shared limiter was already in use, in which case the
+    /// configuration is ignored.
+    pub fn init_shared(config: &ScrobbleScrubberConfig) -> bool {
+        let initialized = SHARED.set(Self::new(Self::rate_limit_for(config))).is_ok();
+        if !initialized {
+            log::debug!("MusicBrainz rate limiter already initialized, keeping existing settings");
+        }
+        initialized
+    }
+
+    /// The configured rate limit, relaxed when a MusicBrainz mirror is configured
+    #[must_use]
+    pub fn rate_limit_for(config: &ScrobbleScrubberConfig) -> MusicBrainzRateLimitConfig {
+        let mut rate_limit = config.musicbrainz.rate_limit.clone();
+        let uses_mirror = config
+            .providers
+            .musicbrainz
+            .as_ref()
+            .and_then(|server| server.mirror_url())
+            .is_some();
+        if uses_mirror {
+            // A local mirror isn't bound by the public server's one request per second
+            rate_limit.requests_per_second = rate_limit
+                .requests_per_second
+                .max(MIRROR_REQUESTS_PER_SECOND);
+        }
+        rate_limit
+    }
+
+    /// Subscribe to rate limit events
+    pub fn subscribe(&self) -> broadcast::Receiver<ScrubberEvent> {
+        self.event_sender.subscribe()
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let Ok(mut state) = self.state.lock() else {
+                    return;
+                };
+                let now = Instant::now();
+                match state.backoff_until {
+                    Some(until) if until > now => Err(until - now),
+                    _ => {
+                        state.backoff_until = None;
+                        state.bucket.try_take(now)
+                    }
+                }
+            };
+
+            match wait {
+                Ok(()) => return,
+                Err(delay) => tokio::time::sleep(delay).await,
+            }
+        }
+    }
+
+    /// Backoff before retry number `attempt` (starting at 0)
+    #[must_use]
+    pub fn backoff_delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let max = Duration::from_millis(self.config.max_backoff_ms);
+        retry_after
+            .unwrap_or_else(|| {
+                Duration::from_millis(self.config.initial_backoff_ms)
+                    .saturating_mul(2u32.saturating_pow(attempt))
+            })
+            .min(max)
+    }
+
+    /// Pause all MusicBrainz requests for `delay`
+    fn back_off(&self, delay: Duration) {
+        if let Ok(mut state) = self.state.lock() {
+            let until = Instant::now() + delay;
+            if !matches!(state.backoff_until, Some(current) if current >= until) {
+                state.backoff_until = Some(until);
+            }
+        }
+    }
+
+    /// Send a MusicBrainz request once the rate limit allows, retrying with exponential
+    /// backoff while MusicBrainz answers 503
+    pub async fn execute<T, E, F, Fut>(&self, description: &str, mut request: F) -> Result<T, E>
+    where
+        E: ThrottleError,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 0;
+        loop {
+            self.acquire().await;
+
+            match request().await {
+                Err(e) if attempt < self.config.max_retries && e.is_throttled() => {
+                    let message = e.to_string();
+                    let delay = self.backoff_delay(attempt, e.retry_after());
+                    attempt += 1;
+                    log::warn!(
+                        "MusicBrainz throttled {description} (attempt {attempt}), backing off for {}s: {message}",
+                        delay.as_secs()
+                    );
+                    self.back_off(delay);
+                    let _ = self
+                        .event_sender
+                        .send(ScrubberEvent::musicbrainz_rate_limited(
+                            delay.as_secs(),
+                            attempt,
+                            message,
+                        ));
+                }
+                result => {
+                    if attempt > 0 {
+                        log::info!("MusicBrainz accepted {description} after {attempt} retries");
+                        let _ = self
+                            .event_sender
+                            .send(ScrubberEvent::musicbrainz_rate_limit_ended());
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ScrubberEventType;
+
+    #[test_log::test]
+    fn bucket_should_pace_requests_after_burst() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, 1.0, start);
+
+        assert_eq!(bucket.try_take(start), Ok(()));
+        assert_eq!(bucket.try_take(start), Ok(()));
+        let wait = bucket.try_take(start).unwrap_err();
+        assert!(wait > Duration::from_millis(990) && wait <= Duration::from_secs(1));
+
+        assert_eq!(bucket.try_take(start + Duration::from_secs(1)), Ok(()));
+        assert!(bucket.try_take(start + Duration::from_secs(1)).is_err());
+    }
+
+    #[test_log::test]
+    fn should_recognize_throttled_requests() {
+        assert!(is_throttled_error(
+            "MusicBrainz search failed: HTTP status server error (503 Service Unavailable)"
+        ));
+        assert!(!is_throttled_error(
+            "MusicBrainz search failed: 404 Not Found"
+        ));
+
+        assert_eq!(
+            retry_after_from_error("503 Service Unavailable, Retry-After: 7"),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(retry_after_from_error("503 Service Unavailable"), None);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn mirror_should_not_be_throttled_to_one_request_per_second() {
+        let mut config = ScrobbleScrubberConfig::default();
+        config.providers.musicbrainz = Some(crate::config::MusicBrainzProviderConfig {
+            base_url: Some("http://localhost:5000".to_string()),
+            ..Default::default()
+        });
+        let limiter = MusicBrainzRateLimiter::new(MusicBrainzRateLimiter::rate_limit_for(&config));
+
+        let start = Instant::now();
+        for _ in 0..5 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let public = MusicBrainzRateLimiter::rate_limit_for(&ScrobbleScrubberConfig::default());
+        assert_eq!(public.requests_per_second, 1.0);
+    }
+
+    #[test_log::test]
+    fn backoff_should_double_up_to_the_cap() {
+        let limiter = MusicBrainzRateLimiter::new(MusicBrainzRateLimitConfig {
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 5000,
+            ..MusicBrainzRateLimitConfig::default()
+        });
+
+        assert_eq!(limiter.backoff_delay(0, None), Duration::from_secs(1));
+        assert_eq!(limiter.backoff_delay(2, None), Duration::from_secs(4));
+        assert_eq!(limiter.backoff_delay(3, None), Duration::from_secs(5));
+        assert_eq!(
+            limiter.backoff_delay(0, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn execute_should_retry_throttled_requests() {
+        let limiter = MusicBrainzRateLimiter::new(MusicBrainzRateLimitConfig {
+            requests_per_second: 1000.0,
+            initial_backoff_ms: 1,
+            ..MusicBrainzRateLimitConfig::default()
+        });
+        let mut events = limiter.subscribe();
+
+        let mut calls = 0;
+        let result: Result<u32, String> = limiter
+            .execute("test request", || {
+                calls += 1;
+                let outcome = if calls < 3 {
+                    Err("503 Service Unavailable".to_string())
+                } else {
+                    Ok(calls)
+                };
+                async move { outcome }
+            })
+            .await;
+
+        assert_eq!(result, Ok(3));
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event.event_type);
+        }
+        assert_eq!(received.len(), 3);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn execute_should_back_off_when_musicbrainz_rs_gives_up_on_a_503() {
+        let limiter = MusicBrainzRateLimiter::new(MusicBrainzRateLimitConfig {
+            requests_per_second: 1000.0,
+            initial_backoff_ms: 1,
+            ..MusicBrainzRateLimitConfig::default()
+        });
+        let mut events = limiter.subscribe();
+
+        let mut calls = 0;
+        let result = limiter
+            .execute("test request", || {
+                calls += 1;
+                let outcome = if calls < 2 {
+                    Err(musicbrainz_rs::Error::MaxRetriesExceeded)
+                } else {
+                    Ok(calls)
+                };
+                async move { outcome }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 2);
+        assert!(matches!(
+            events.try_recv().unwrap().event_type,
+            ScrubberEventType::MusicBrainzRateLimited { .. }
+        ));
+        assert!(matches!(
+            events.try_recv().unwrap().event_type,
+            ScrubberEventType::MusicBrainzRateLimitEnded
+        ));
+
+        let not_found = musicbrainz_rs::Error::NotFound("query".to_string());
+        assert!(!not_found.is_throttled());
+    }
+}
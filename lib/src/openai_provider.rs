@@ -14,6 +14,7 @@ use tokio::sync::Mutex;
 use crate::rewrite::RewriteRule;
 
 use crate::config::DEFAULT_CLAUDE_SYSTEM_PROMPT;
+use crate::persistence::{EditDecision, PendingEdit};
 use crate::scrub_action_provider::{
     ActionProviderError, ScrubActionProvider, ScrubActionSuggestion, SuggestionWithContext,
 };
@@ -392,6 +393,11 @@ impl OpenAIScrubActionProvider {
 
         let existing_rules = self.format_existing_rules();
 
+        // Remembered rejections arrive alongside the pending edits
+        let (rejected_edits, pending_edits): (Vec<_>, Vec<_>) = pending_edits
+            .iter()
+            .partition(|edit| edit.decision == Some(EditDecision::Rejected));
+
         // Format pending edits information
         let pending_edits_info = if pending_edits.is_empty() {
             "PENDING EDITS: None".to_string()
@@ -409,6 +415,17 @@ impl OpenAIScrubActionProvider {
             format!("PENDING EDITS (already suggested, avoid duplicates):\n{edits_list}")
         };
 
+        let rejected_edits_info = if rejected_edits.is_empty() {
+            "REJECTED EDITS: None".to_string()
+        } else {
+            let edits_list = rejected_edits
+                .iter()
+                .map(|edit| format!("- {}", describe_edit_mapping(edit)))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("REJECTED EDITS (the user declined these changes, never suggest them again):\n{edits_list}")
+        };
+
         // Format pending rewrite rules information
         let pending_rules_info = if pending_rules.is_empty() {
             "PENDING REWRITE RULES: None".to_string()
@@ -450,7 +467,7 @@ impl OpenAIScrubActionProvider {
             .join("\n");
 
         let user_message = format!(
-            "Analyze these Last.fm scrobbles and provide suggestions for each track that needs improvement.\n\nIMPORTANT: Check the pending items below to avoid suggesting duplicates.\n\n{tracks_info}\n\n{existing_rules}\n\n{pending_edits_info}\n\n{pending_rules_info}"
+            "Analyze these Last.fm scrobbles and provide suggestions for each track that needs improvement.\n\nIMPORTANT: Check the pending and rejected items below to avoid suggesting duplicates.\n\n{tracks_info}\n\n{existing_rules}\n\n{pending_edits_info}\n\n{rejected_edits_info}\n\n{pending_rules_info}"
         );
 
         self.make_openai_request(&user_message, tracks).await
@@ -839,3 +856,22 @@ impl OpenAIScrubActionProvider {
         Ok(results)
     }
 }
+
+/// Describe the change a pending edit makes, e.g. `"Song" by "Artist" → track: "Song (Live)"`
+fn describe_edit_mapping(edit: &PendingEdit) -> String {
+    let changes: Vec<String> = [
+        ("track", &edit.new_track_name),
+        ("artist", &edit.new_artist_name),
+        ("album", &edit.new_album_name),
+        ("album artist", &edit.new_album_artist_name),
+    ]
+    .into_iter()
+    .filter_map(|(field, value)| Some(format!("{field}: \"{}\"", value.as_deref()?)))
+    .collect();
+    format!(
+        "\"{}\" by \"{}\" → {}",
+        edit.original_track_name,
+        edit.original_artist_name,
+        changes.join(", ")
+    )
+}
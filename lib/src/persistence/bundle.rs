@@ -0,0 +1,209 @@
+//! Single-file export of a scrubber setup.
+//!
+//! A [`StateBundle`] holds everything kept in [`StateStorage`] (rules, pending edits and
+//! rules, settings, the timestamp anchor, rule statistics and remembered decisions), plus
+//! the track cache and the Last.fm session when asked for. Bundles record the state schema
+//! version, so a bundle from an older version is migrated on import and one from a newer
+//! version is refused.
+
+use chrono::{DateTime, Utc};
+use lastfm_edit::LastFmEditSession;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use super::migrations::{self, SchemaError, StateDocument, CURRENT_SCHEMA_VERSION};
+use super::{
+    EditDecisionsState, PendingEditsState, PendingRewriteRulesState, RewriteRulesState,
+    RuleStatsState, SettingsState, StateStorage, TimestampState,
+};
+use crate::track_cache::TrackCache;
+
+/// Version of the bundle layout written by this build
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Bundle fields holding `StateStorage` states, which are upgraded by the schema migrations
+const STATE_KEYS: &[&str] = &[
+    "timestamp_state",
+    "rewrite_rules_state",
+    "pending_edits_state",
+    "pending_rewrite_rules_state",
+    "settings_state",
+    "rule_stats_state",
+    "edit_decisions_state",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateBundle {
+    pub format_version: u32,
+    /// State schema version the states were written with
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// The scrobble-scrubber version that wrote the bundle
+    pub exported_by: String,
+    pub timestamp_state: TimestampState,
+    pub rewrite_rules_state: RewriteRulesState,
+    pub pending_edits_state: PendingEditsState,
+    pub pending_rewrite_rules_state: PendingRewriteRulesState,
+    pub settings_state: SettingsState,
+    #[serde(default)]
+    pub rule_stats_state: RuleStatsState,
+    #[serde(default)]
+    pub edit_decisions_state: EditDecisionsState,
+    /// Only included when asked for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_cache: Option<TrackCache>,
+    /// Last.fm login session; only included when asked for, since it grants account access
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<LastFmEditSession>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    #[error("Invalid bundle: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+    #[error(
+        "Bundle format version {found} is newer than this build supports ({supported}); \
+         upgrade scrobble-scrubber to import it"
+    )]
+    UnsupportedFormat { found: u32, supported: u32 },
+    #[error("Failed to read the current state: {0}")]
+    Storage(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl StateBundle {
+    /// Bundle everything in `storage`
+    pub async fn export<S: StateStorage>(storage: &S) -> Result<Self, S::Error> {
+        Ok(Self {
+            format_version: BUNDLE_FORMAT_VERSION,
+            schema_version: CURRENT_SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            exported_by: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp_state: storage.load_timestamp_state().await?,
+            rewrite_rules_state: storage.load_rewrite_rules_state().await?,
+            pending_edits_state: storage.load_pending_edits_state().await?,
+            pending_rewrite_rules_state: storage.load_pending_rewrite_rules_state().await?,
+            settings_state: storage.load_settings_state().await?,
+            rule_stats_state: storage.load_rule_stats_state().await?,
+            edit_decisions_state: storage.load_edit_decisions_state().await?,
+            track_cache: None,
+            session: None,
+        })
+    }
+
+    #[must_use]
+    pub fn with_track_cache(mut self, track_cache: TrackCache) -> Self {
+        self.track_cache = Some(track_cache);
+        self
+    }
+
+    #[must_use]
+    pub fn with_session(mut self, session: LastFmEditSession) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Export the current contents of `storage` next to `state_file`, returning the backup path
+    ///
+    /// Call this before [`Self::restore`], which keeps no copy of the state it replaces.
+    pub async fn backup<S: StateStorage>(
+        storage: &S,
+        state_file: &Path,
+    ) -> Result<PathBuf, BundleError> {
+        let bundle = Self::export(storage)
+            .await
+            .map_err(|e| BundleError::Storage(Box::new(e)))?;
+        let mut backup = state_file.as_os_str().to_owned();
+        backup.push(format!(
+            ".before-import-{}.json",
+            bundle.exported_at.format("%Y%m%d-%H%M%S")
+        ));
+        let backup = PathBuf::from(backup);
+        bundle.write_to(&backup)?;
+        log::info!("Backed up current state to {}", backup.display());
+        Ok(backup)
+    }
+
+    /// Replace everything in `storage` with the bundled state
+    ///
+    /// The track cache and session are left to the caller, and nothing is backed up first
+    /// (see [`Self::backup`]).
+    pub async fn restore<S: StateStorage>(&self, storage: &mut S) -> Result<(), S::Error> {
+        storage.save_timestamp_state(&self.timestamp_state).await?;
+        storage
+            .save_rewrite_rules_state(&self.rewrite_rules_state)
+            .await?;
+        storage
+            .save_pending_edits_state(&self.pending_edits_state)
+            .await?;
+        storage
+            .save_pending_rewrite_rules_state(&self.pending_rewrite_rules_state)
+            .await?;
+        storage.save_settings_state(&self.settings_state).await?;
+        storage
+            .save_rule_stats_state(&self.rule_stats_state)
+            .await?;
+        storage
+            .save_edit_decisions_state(&self.edit_decisions_state)
+            .await
+    }
+
+    pub fn to_json(&self) -> Result<String, BundleError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a bundle, migrating states written with an older schema version
+    pub fn from_json(content: &str) -> Result<Self, BundleError> {
+        let mut value: Value = serde_json::from_str(content)?;
+        let version_of = |value: &Value, key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_u64)
+                .and_then(|version| u32::try_from(version).ok())
+        };
+
+        if let Some(found) = version_of(&value, "format_version") {
+            if found > BUNDLE_FORMAT_VERSION {
+                return Err(BundleError::UnsupportedFormat {
+                    found,
+                    supported: BUNDLE_FORMAT_VERSION,
+                });
+            }
+        }
+
+        if let Some(schema_version) = version_of(&value, "schema_version") {
+            if schema_version != CURRENT_SCHEMA_VERSION {
+                if let Value::Object(fields) = &mut value {
+                    let mut document: StateDocument = STATE_KEYS
+                        .iter()
+                        .filter_map(|key| Some((key.to_string(), fields.remove(*key)?)))
+                        .collect();
+                    migrations::migrate(&mut document, schema_version)?;
+                    fields.extend(document);
+                    fields.insert(
+                        "schema_version".to_string(),
+                        Value::from(CURRENT_SCHEMA_VERSION),
+                    );
+                }
+            }
+        }
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<(), BundleError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self, BundleError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+
+use super::file_storage::FileStorageError;
+#[cfg(feature = "sqlite")]
+use super::sqlite_storage::SqliteStorageError;
+use super::{
+    EditDecision, EditDecisionsState, FileStorage, PendingEdit, PendingEditsState,
+    PendingRewriteRulesState, RewriteRulesState, RuleStatsState, SettingsState, StateStorage,
+    TimestampState,
+};
+use crate::config::{StorageBackend, StorageConfig};
+
+/// The storage backend selected by `StorageConfig::backend`
+pub enum ConfiguredStorage {
+    File(FileStorage),
+    #[cfg(feature = "sqlite")]
+    Sqlite(super::SqliteStorage),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfiguredStorageError {
+    #[error(transparent)]
+    File(#[from] FileStorageError),
+    #[cfg(feature = "sqlite")]
+    #[error(transparent)]
+    Sqlite(#[from] SqliteStorageError),
+    #[error("The {0:?} storage backend is not available in this build")]
+    Unsupported(StorageBackend),
+}
+
+impl ConfiguredStorage {
+    /// Open the storage backend selected by `config`
+    ///
+    /// The SQLite backend migrates an existing PickleDB `state_file` the first time it is
+    /// opened.
+    pub async fn open(config: &StorageConfig) -> Result<Self, ConfiguredStorageError> {
+        match config.backend {
+            StorageBackend::Pickledb => Ok(Self::File(FileStorage::new(&config.state_file)?)),
+            #[cfg(feature = "sqlite")]
+            StorageBackend::Sqlite => Ok(Self::Sqlite(
+                super::SqliteStorage::new_migrating_from(
+                    config.sqlite_file_path(),
+                    &config.state_file,
+                )
+                .await?,
+            )),
+            #[cfg(not(feature = "sqlite"))]
+            StorageBackend::Sqlite => Err(ConfiguredStorageError::Unsupported(config.backend)),
+        }
+    }
+}
+
+/// Forward a call to whichever backend is in use
+macro_rules! dispatch {
+    ($self:ident, $storage:ident => $call:expr) => {
+        match $self {
+            ConfiguredStorage::File($storage) => Ok($call.await?),
+            #[cfg(feature = "sqlite")]
+            ConfiguredStorage::Sqlite($storage) => Ok($call.await?),
+        }
+    };
+}
+
+#[async_trait]
+impl StateStorage for ConfiguredStorage {
+    type Error = ConfiguredStorageError;
+
+    async fn save_timestamp_state(&mut self, state: &TimestampState) -> Result<(), Self::Error> {
+        dispatch!(self, storage => storage.save_timestamp_state(state))
+    }
+
+    async fn load_timestamp_state(&self) -> Result<TimestampState, Self::Error> {
+        dispatch!(self, storage => storage.load_timestamp_state())
+    }
+
+    async fn save_rewrite_rules_state(
+        &mut self,
+        state: &RewriteRulesState,
+    ) -> Result<(), Self::Error> {
+        dispatch!(self, storage => storage.save_rewrite_rules_state(state))
+    }
+
+    async fn load_rewrite_rules_state(&self) -> Result<RewriteRulesState, Self::Error> {
+        dispatch!(self, storage => storage.load_rewrite_rules_state())
+    }
+
+    async fn save_pending_edits_state(
+        &mut self,
+        state: &PendingEditsState,
+    ) -> Result<(), Self::Error> {
+        dispatch!(self, storage => storage.save_pending_edits_state(state))
+    }
+
+    async fn load_pending_edits_state(&self) -> Result<PendingEditsState, Self::Error> {
+        dispatch!(self, storage => storage.load_pending_edits_state())
+    }
+
+    async fn save_pending_rewrite_rules_state(
+        &mut self,
+        state: &PendingRewriteRulesState,
+    ) -> Result<(), Self::Error> {
+        dispatch!(self, storage => storage.save_pending_rewrite_rules_state(state))
+    }
+
+    async fn load_pending_rewrite_rules_state(
+        &self,
+    ) -> Result<PendingRewriteRulesState, Self::Error> {
+        dispatch!(self, storage => storage.load_pending_rewrite_rules_state())
+    }
+
+    async fn save_settings_state(&mut self, state: &SettingsState) -> Result<(), Self::Error> {
+        dispatch!(self, storage => storage.save_settings_state(state))
+    }
+
+    async fn load_settings_state(&self) -> Result<SettingsState, Self::Error> {
+        dispatch!(self, storage => storage.load_settings_state())
+    }
+
+    async fn save_rule_stats_state(&mut self, state: &RuleStatsState) -> Result<(), Self::Error> {
+        dispatch!(self, storage => storage.save_rule_stats_state(state))
+    }
+
+    async fn load_rule_stats_state(&self) -> Result<RuleStatsState, Self::Error> {
+        dispatch!(self, storage => storage.load_rule_stats_state())
+    }
+
+    async fn save_edit_decisions_state(
+        &mut self,
+        state: &EditDecisionsState,
+    ) -> Result<(), Self::Error> {
+        dispatch!(self, storage => storage.save_edit_decisions_state(state))
+    }
+
+    async fn load_edit_decisions_state(&self) -> Result<EditDecisionsState, Self::Error> {
+        dispatch!(self, storage => storage.load_edit_decisions_state())
+    }
+
+    async fn record_edit_decision(
+        &mut self,
+        edit: &PendingEdit,
+        decision: EditDecision,
+    ) -> Result<(), Self::Error> {
+        dispatch!(self, storage => storage.record_edit_decision(edit, decision))
+    }
+
+    async fn load_pending_edit(&self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        dispatch!(self, storage => storage.load_pending_edit(id))
+    }
+
+    async fn load_pending_edits_for_artist(
+        &self,
+        artist: &str,
+    ) -> Result<Vec<PendingEdit>, Self::Error> {
+        dispatch!(self, storage => storage.load_pending_edits_for_artist(artist))
+    }
+
+    async fn add_pending_edit(&mut self, edit: &PendingEdit) -> Result<(), Self::Error> {
+        dispatch!(self, storage => storage.add_pending_edit(edit))
+    }
+
+    async fn remove_pending_edit(&mut self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        dispatch!(self, storage => storage.remove_pending_edit(id))
+    }
+
+    async fn approve_pending_edit(&mut self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        dispatch!(self, storage => storage.approve_pending_edit(id))
+    }
+
+    async fn reject_pending_edit(&mut self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        dispatch!(self, storage => storage.reject_pending_edit(id))
+    }
+}
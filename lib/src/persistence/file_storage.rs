@@ -2,9 +2,13 @@ use async_trait::async_trait;
 use pickledb::{PickleDb, PickleDbDumpPolicy, SerializationMethod};
 use std::path::Path;
 
+use super::migrations::{
+    self, Migration, SchemaError, StateDocument, CURRENT_SCHEMA_VERSION, SCHEMA_VERSION_KEY,
+    UNVERSIONED_SCHEMA_VERSION,
+};
 use super::{
-    PendingEditsState, PendingRewriteRulesState, RewriteRulesState, SettingsState, StateStorage,
-    TimestampState,
+    EditDecisionsState, PendingEditsState, PendingRewriteRulesState, RewriteRulesState,
+    RuleStatsState, SettingsState, StateStorage, TimestampState,
 };
 use crate::rewrite::load_comprehensive_default_rules;
 
@@ -22,6 +26,8 @@ pub enum FileStorageError {
     SerializationError(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    SchemaError(#[from] SchemaError),
 }
 
 impl FileStorage {
@@ -58,16 +64,71 @@ impl FileStorage {
             } else {
                 log::info!("Initialized new database with comprehensive default rewrite rules");
             }
+            storage.set_schema_version(CURRENT_SCHEMA_VERSION)?;
+        } else {
+            storage.upgrade_schema(path_ref, migrations::MIGRATIONS)?;
         }
 
         Ok(storage)
     }
 
+    /// Schema version the state was written with
+    pub fn schema_version(&self) -> u32 {
+        self.db
+            .get(SCHEMA_VERSION_KEY)
+            .unwrap_or(UNVERSIONED_SCHEMA_VERSION)
+    }
+
+    fn set_schema_version(&mut self, version: u32) -> Result<(), FileStorageError> {
+        self.db
+            .set(SCHEMA_VERSION_KEY, &version)
+            .map_err(|e| FileStorageError::SerializationError(e.to_string()))?;
+        self.db
+            .dump()
+            .map_err(|e| FileStorageError::DatabaseError(e.to_string()))
+    }
+
+    /// Refuse state from a newer version, and migrate state from an older one after backing
+    /// up the file
+    fn upgrade_schema(
+        &mut self,
+        path: &Path,
+        migrations: &[Migration],
+    ) -> Result<(), FileStorageError> {
+        let version = self.schema_version();
+        migrations::check_version(version)?;
+        if version == CURRENT_SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        migrations::backup_state_file(path, version)?;
+
+        let mut document: StateDocument = self
+            .db
+            .get_all()
+            .into_iter()
+            .filter(|key| key != SCHEMA_VERSION_KEY)
+            .filter_map(|key| {
+                let value = self.db.get::<serde_json::Value>(&key)?;
+                Some((key, value))
+            })
+            .collect();
+        migrations::migrate_with(&mut document, version, migrations)?;
+
+        for (key, value) in &document {
+            self.db
+                .set(key, value)
+                .map_err(|e| FileStorageError::SerializationError(e.to_string()))?;
+        }
+        self.set_schema_version(CURRENT_SCHEMA_VERSION)
+    }
+
     /// Initialize default rewrite rules for a new database
     fn initialize_default_rules(&mut self) -> Result<(), FileStorageError> {
         let default_rules = load_comprehensive_default_rules();
         let rules_state = RewriteRulesState {
             rewrite_rules: default_rules,
+            alias_rules: Vec::new(),
         };
 
         self.db
@@ -173,9 +234,99 @@ impl StateStorage for FileStorage {
     async fn load_settings_state(&self) -> Result<SettingsState, Self::Error> {
         Ok(self.db.get("settings_state").unwrap_or_default())
     }
+
+    async fn save_rule_stats_state(&mut self, state: &RuleStatsState) -> Result<(), Self::Error> {
+        self.db
+            .set("rule_stats_state", state)
+            .map_err(|e| FileStorageError::SerializationError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn load_rule_stats_state(&self) -> Result<RuleStatsState, Self::Error> {
+        Ok(self.db.get("rule_stats_state").unwrap_or_default())
+    }
+
+    async fn save_edit_decisions_state(
+        &mut self,
+        state: &EditDecisionsState,
+    ) -> Result<(), Self::Error> {
+        self.db
+            .set("edit_decisions_state", state)
+            .map_err(|e| FileStorageError::SerializationError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn load_edit_decisions_state(&self) -> Result<EditDecisionsState, Self::Error> {
+        Ok(self.db.get("edit_decisions_state").unwrap_or_default())
+    }
 }
 
 // PickleDb is not Send + Sync by default, but since we're using it in a controlled manner
 // with proper synchronization via Arc<Mutex<FileStorage>>, we can safely implement Send + Sync
 unsafe impl Send for FileStorage {}
 unsafe impl Sync for FileStorage {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn set_anchor(document: &mut StateDocument) {
+        document.insert(
+            "timestamp_state".to_string(),
+            json!({ "last_processed_timestamp": "2023-11-14T22:13:20Z" }),
+        );
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn older_state_should_be_backed_up_and_migrated() {
+        let dir = std::env::temp_dir().join(format!("scrobble-scrubber-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("state.db");
+        let mut storage = FileStorage::new(&path).unwrap();
+        storage.set_schema_version(0).unwrap();
+        drop(storage);
+
+        // No migration from version 0 is registered, so the real registry refuses it after
+        // backing the file up
+        let backup = dir.join("state.db.v0.bak");
+        assert!(matches!(
+            FileStorage::new(&path),
+            Err(FileStorageError::SchemaError(
+                SchemaError::MissingMigration(0)
+            ))
+        ));
+        assert!(backup.exists());
+        std::fs::remove_file(&backup).unwrap();
+
+        let mut storage = FileStorage {
+            db: PickleDb::load(
+                &path,
+                PickleDbDumpPolicy::AutoDump,
+                SerializationMethod::Json,
+            )
+            .unwrap(),
+        };
+        storage
+            .upgrade_schema(
+                &path,
+                &[Migration {
+                    from_version: 0,
+                    description: "Set the timestamp anchor",
+                    apply: set_anchor,
+                }],
+            )
+            .unwrap();
+
+        assert!(backup.exists());
+        assert_eq!(storage.schema_version(), CURRENT_SCHEMA_VERSION);
+        assert_eq!(
+            storage
+                .load_timestamp_state()
+                .await
+                .unwrap()
+                .last_processed_timestamp,
+            chrono::DateTime::from_timestamp(1_700_000_000, 0)
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,8 +2,8 @@ use async_trait::async_trait;
 use std::sync::{Arc, RwLock};
 
 use super::{
-    PendingEditsState, PendingRewriteRulesState, RewriteRulesState, SettingsState, StateStorage,
-    TimestampState,
+    EditDecisionsState, PendingEditsState, PendingRewriteRulesState, RewriteRulesState,
+    RuleStatsState, SettingsState, StateStorage, TimestampState,
 };
 
 /// In-memory storage implementation - perfect for WASM and testing
@@ -14,6 +14,8 @@ pub struct MemoryStorage {
     pending_edits_state: Arc<RwLock<PendingEditsState>>,
     pending_rules_state: Arc<RwLock<PendingRewriteRulesState>>,
     settings_state: Arc<RwLock<SettingsState>>,
+    rule_stats_state: Arc<RwLock<RuleStatsState>>,
+    edit_decisions_state: Arc<RwLock<EditDecisionsState>>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -30,6 +32,8 @@ impl MemoryStorage {
             pending_edits_state: Arc::new(RwLock::new(PendingEditsState::default())),
             pending_rules_state: Arc::new(RwLock::new(PendingRewriteRulesState::default())),
             settings_state: Arc::new(RwLock::new(SettingsState::default())),
+            rule_stats_state: Arc::new(RwLock::new(RuleStatsState::default())),
+            edit_decisions_state: Arc::new(RwLock::new(EditDecisionsState::default())),
         }
     }
 
@@ -140,4 +144,39 @@ impl StateStorage for MemoryStorage {
             .map_err(|e| MemoryStorageError::LockError(e.to_string()))?
             .clone())
     }
+
+    async fn save_rule_stats_state(&mut self, state: &RuleStatsState) -> Result<(), Self::Error> {
+        *self
+            .rule_stats_state
+            .write()
+            .map_err(|e| MemoryStorageError::LockError(e.to_string()))? = state.clone();
+        Ok(())
+    }
+
+    async fn load_rule_stats_state(&self) -> Result<RuleStatsState, Self::Error> {
+        Ok(self
+            .rule_stats_state
+            .read()
+            .map_err(|e| MemoryStorageError::LockError(e.to_string()))?
+            .clone())
+    }
+
+    async fn save_edit_decisions_state(
+        &mut self,
+        state: &EditDecisionsState,
+    ) -> Result<(), Self::Error> {
+        *self
+            .edit_decisions_state
+            .write()
+            .map_err(|e| MemoryStorageError::LockError(e.to_string()))? = state.clone();
+        Ok(())
+    }
+
+    async fn load_edit_decisions_state(&self) -> Result<EditDecisionsState, Self::Error> {
+        Ok(self
+            .edit_decisions_state
+            .read()
+            .map_err(|e| MemoryStorageError::LockError(e.to_string()))?
+            .clone())
+    }
 }
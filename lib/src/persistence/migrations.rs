@@ -0,0 +1,184 @@
+//! Versioned state schema.
+//!
+//! Every storage backend records the schema version its state was written with. State written
+//! by an older version is upgraded one step at a time by the migrations registered in
+//! [`MIGRATIONS`], after a backup of the state file is taken. State written by a newer version
+//! is refused, since this build can't know what changed.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Schema version of the state written by this build
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Schema version of state files written before the version was recorded
+pub const UNVERSIONED_SCHEMA_VERSION: u32 = 1;
+
+/// Key the schema version is stored under
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Raw state as stored, keyed by state name (e.g. `rewrite_rules_state`)
+pub type StateDocument = BTreeMap<String, Value>;
+
+/// A single step upgrading state from `from_version` to `from_version + 1`
+pub struct Migration {
+    pub from_version: u32,
+    pub description: &'static str,
+    pub apply: fn(&mut StateDocument),
+}
+
+/// All migrations, in order
+///
+/// Empty until the stored layout changes in a way `#[serde(default)]` fields can't absorb;
+/// such a change bumps [`CURRENT_SCHEMA_VERSION`] and registers the step from the old version.
+pub static MIGRATIONS: &[Migration] = &[];
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    #[error(
+        "State was written by a newer version of scrobble-scrubber (schema version {found}, \
+         this build supports up to {supported}); upgrade scrobble-scrubber to open it"
+    )]
+    TooNew { found: u32, supported: u32 },
+    #[error("No migration registered from schema version {0}")]
+    MissingMigration(u32),
+}
+
+/// Fail if state with schema version `found` can't be opened by this build
+pub fn check_version(found: u32) -> Result<(), SchemaError> {
+    if found > CURRENT_SCHEMA_VERSION {
+        return Err(SchemaError::TooNew {
+            found,
+            supported: CURRENT_SCHEMA_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// Upgrade `document` from schema version `from` to [`CURRENT_SCHEMA_VERSION`]
+///
+/// Returns the descriptions of the migrations that were applied.
+pub fn migrate(document: &mut StateDocument, from: u32) -> Result<Vec<&'static str>, SchemaError> {
+    migrate_with(document, from, MIGRATIONS)
+}
+
+/// [`migrate`] with the steps in `migrations` instead of [`MIGRATIONS`]
+pub fn migrate_with(
+    document: &mut StateDocument,
+    from: u32,
+    migrations: &[Migration],
+) -> Result<Vec<&'static str>, SchemaError> {
+    check_version(from)?;
+
+    let mut applied = Vec::new();
+    for version in from..CURRENT_SCHEMA_VERSION {
+        let migration = migrations
+            .iter()
+            .find(|migration| migration.from_version == version)
+            .ok_or(SchemaError::MissingMigration(version))?;
+        log::info!(
+            "Migrating state from schema version {version} to {}: {}",
+            version + 1,
+            migration.description
+        );
+        (migration.apply)(document);
+        applied.push(migration.description);
+    }
+    Ok(applied)
+}
+
+/// Copy the state file at `path` next to itself before migrating it, returning the backup path
+pub fn backup_state_file(path: &Path, version: u32) -> std::io::Result<PathBuf> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{version}.bak"));
+    let backup = PathBuf::from(backup);
+    std::fs::copy(path, &backup)?;
+    log::info!("Backed up state file to {}", backup.display());
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn add_alias_rules(document: &mut StateDocument) {
+        document.insert("alias_rules".to_string(), json!([]));
+    }
+
+    /// A step from the version before the oldest one this build knows
+    const TEST_MIGRATIONS: &[Migration] = &[Migration {
+        from_version: UNVERSIONED_SCHEMA_VERSION - 1,
+        description: "Add alias rules",
+        apply: add_alias_rules,
+    }];
+
+    #[test_log::test]
+    fn should_apply_each_migration_up_to_the_current_version() {
+        let mut document = StateDocument::new();
+
+        let applied = migrate_with(
+            &mut document,
+            UNVERSIONED_SCHEMA_VERSION - 1,
+            TEST_MIGRATIONS,
+        )
+        .unwrap();
+
+        assert_eq!(applied, vec!["Add alias rules"]);
+        assert_eq!(document["alias_rules"], json!([]));
+    }
+
+    #[test_log::test]
+    fn should_refuse_versions_without_a_migration() {
+        let mut document = StateDocument::new();
+        assert!(matches!(
+            migrate(&mut document, UNVERSIONED_SCHEMA_VERSION - 1),
+            Err(SchemaError::MissingMigration(0))
+        ));
+        assert!(document.is_empty());
+    }
+
+    #[test_log::test]
+    fn backup_should_copy_the_state_file_next_to_it() {
+        let dir = std::env::temp_dir().join(format!("scrobble-scrubber-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.db");
+        std::fs::write(&path, "{}").unwrap();
+
+        let backup = backup_state_file(&path, 0).unwrap();
+
+        assert_eq!(backup, dir.join("state.db.v0.bak"));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "{}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test_log::test]
+    fn unversioned_state_should_need_no_migration() {
+        let mut document = StateDocument::new();
+        document.insert(
+            "rewrite_rules_state".to_string(),
+            json!({ "rewrite_rules": [] }),
+        );
+
+        let applied = migrate(&mut document, UNVERSIONED_SCHEMA_VERSION).unwrap();
+
+        assert!(applied.is_empty());
+        assert_eq!(
+            document["rewrite_rules_state"],
+            json!({ "rewrite_rules": [] })
+        );
+    }
+
+    #[test_log::test]
+    fn should_refuse_state_from_newer_version() {
+        let mut document = StateDocument::new();
+        assert!(matches!(
+            migrate(&mut document, CURRENT_SCHEMA_VERSION + 1),
+            Err(SchemaError::TooNew { .. })
+        ));
+        assert!(migrate(&mut document, CURRENT_SCHEMA_VERSION)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -3,7 +3,11 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 // use uuid::Uuid;
 
+use crate::alias_rules::AliasRule;
+use crate::conflicts::FieldConflict;
 use crate::rewrite::RewriteRule;
+use crate::scrub_action_provider::{RuleActivity, RuleOutcome};
+use std::collections::BTreeMap;
 
 /// Preview of rule transformation showing changes
 #[derive(Debug, Clone)]
@@ -27,6 +31,9 @@ pub struct TimestampState {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RewriteRulesState {
     pub rewrite_rules: Vec<RewriteRule>,
+    /// Alias tables, applied after the rewrite rules
+    #[serde(default)]
+    pub alias_rules: Vec<AliasRule>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +48,21 @@ pub struct PendingEdit {
     pub new_album_name: Option<String>,
     pub new_album_artist_name: Option<String>,
     pub timestamp: Option<u64>,
+    /// Fields the providers disagreed on, with every suggested value
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<FieldConflict>,
+    /// Confidence the suggesting provider reported (0.0-1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Why the provider suggested the edit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    /// The user's earlier decision, set on remembered edits passed to providers as context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<EditDecision>,
+    /// The logged change this edit undoes, remembered as rejected once the edit is approved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverts: Option<EditMapping>,
 }
 
 impl PendingEdit {
@@ -70,9 +92,60 @@ impl PendingEdit {
             new_album_name,
             new_album_artist_name,
             timestamp,
+            conflicts: Vec::new(),
+            confidence: None,
+            explanation: None,
+            decision: None,
+            reverts: None,
         }
     }
 
+    /// Build a pending edit for `track`, keeping only the fields `edit` changes
+    #[must_use]
+    pub fn from_edit(track: &lastfm_edit::Track, edit: &lastfm_edit::ScrobbleEdit) -> Self {
+        let changed = |new: Option<&String>, original: Option<&String>| {
+            if new == original {
+                None
+            } else {
+                new.cloned()
+            }
+        };
+
+        Self::new(
+            track.name.clone(),
+            track.artist.clone(),
+            edit.album_name_original.clone(),
+            edit.album_artist_name_original.clone(),
+            changed(edit.track_name.as_ref(), edit.track_name_original.as_ref()),
+            changed(Some(&edit.artist_name), Some(&edit.artist_name_original)),
+            changed(edit.album_name.as_ref(), edit.album_name_original.as_ref()),
+            changed(
+                edit.album_artist_name.as_ref(),
+                edit.album_artist_name_original.as_ref(),
+            ),
+            track.timestamp,
+        )
+    }
+
+    #[must_use]
+    pub fn with_conflicts(mut self, conflicts: Vec<FieldConflict>) -> Self {
+        self.conflicts = conflicts;
+        self
+    }
+
+    #[must_use]
+    pub fn with_confidence(mut self, confidence: Option<f32>, explanation: Option<String>) -> Self {
+        self.confidence = confidence;
+        self.explanation = explanation;
+        self
+    }
+
+    #[must_use]
+    pub fn with_reverts(mut self, reverts: Option<EditMapping>) -> Self {
+        self.reverts = reverts;
+        self
+    }
+
     /// Convert this PendingEdit to a ScrobbleEdit for applying to Last.fm
     pub fn to_scrobble_edit(&self) -> lastfm_edit::ScrobbleEdit {
         lastfm_edit::ScrobbleEdit {
@@ -167,7 +240,7 @@ impl PendingRewriteRule {
             playcount: 1,                // placeholder
             timestamp: Some(1640995200), // placeholder timestamp
             album: self.example_album_name.clone(),
-            album_artist: None, // placeholder
+            album_artist: self.example_album_artist_name.clone(),
         };
 
         let original_track_name = self.example_track_name.clone();
@@ -175,10 +248,11 @@ impl PendingRewriteRule {
         let original_album_name = self.example_album_name.clone();
         let original_album_artist_name = self.example_album_artist_name.clone();
 
-        // Apply the rule to see the transformation
-        if self.rule.matches(&track)? {
+        // Apply the rule to see the transformation (exclusions and conditions included)
+        let rule = self.rule.compile()?;
+        if rule.matches(&track) {
             let mut edit = crate::rewrite::create_no_op_edit(&track);
-            let changed = self.rule.apply(&mut edit)?;
+            let changed = rule.apply(&mut edit);
 
             if changed {
                 Ok(RuleTransformationPreview {
@@ -264,6 +338,177 @@ pub struct SettingsState {
     pub require_confirmation_for_new_rules: bool,
 }
 
+/// Hit statistics for a single rewrite rule
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    /// Tracks the rule matched
+    pub matched: u64,
+    /// Edits the rule contributed changes to
+    pub produced: u64,
+    /// Edits the rule contributed to that were applied to Last.fm directly
+    pub applied: u64,
+    /// Changes rejected by MusicBrainz confirmation
+    pub rejected_by_musicbrainz: u64,
+    /// When the rule last matched a track
+    pub last_matched: Option<DateTime<Utc>>,
+    /// The track the rule last matched, as "Artist - Track"
+    pub last_matched_track: Option<String>,
+    /// When the rule last contributed to an edit
+    pub last_fired: Option<DateTime<Utc>>,
+}
+
+/// Per-rule hit statistics, keyed by `RewriteRule::stats_key`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RuleStatsState {
+    pub rules: BTreeMap<String, RuleStats>,
+}
+
+impl RuleStatsState {
+    /// Statistics for a rule, if it has matched anything yet
+    #[must_use]
+    pub fn for_rule(&self, rule: &RewriteRule) -> Option<&RuleStats> {
+        self.rules.get(&rule.stats_key())
+    }
+
+    /// Record a rule matching `track`; `applied` tells whether the edit the rule
+    /// contributed to was applied to Last.fm
+    pub fn record(
+        &mut self,
+        activity: &RuleActivity,
+        track: &lastfm_edit::Track,
+        applied: bool,
+        now: DateTime<Utc>,
+    ) {
+        let stats = self.rules.entry(activity.rule_key.clone()).or_default();
+        stats.matched += 1;
+        stats.last_matched = Some(now);
+        stats.last_matched_track = Some(format!("{} - {}", track.artist, track.name));
+
+        match activity.outcome {
+            RuleOutcome::Unchanged => {}
+            RuleOutcome::Produced => {
+                stats.produced += 1;
+                stats.last_fired = Some(now);
+                if applied {
+                    stats.applied += 1;
+                }
+            }
+            RuleOutcome::RejectedByMusicBrainz => stats.rejected_by_musicbrainz += 1,
+        }
+    }
+}
+
+/// The user's verdict on a suggested edit
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EditDecision {
+    Approved,
+    Rejected,
+}
+
+/// A change from a track's original metadata to new values, independent of the scrobble
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EditMapping {
+    pub original_artist_name: String,
+    pub original_album_name: Option<String>,
+    pub original_track_name: String,
+    pub new_track_name: Option<String>,
+    pub new_artist_name: Option<String>,
+    pub new_album_name: Option<String>,
+    pub new_album_artist_name: Option<String>,
+}
+
+impl EditMapping {
+    /// This mapping as a pending edit without a scrobble
+    #[must_use]
+    pub fn to_pending_edit(&self) -> PendingEdit {
+        PendingEdit::new(
+            self.original_track_name.clone(),
+            self.original_artist_name.clone(),
+            self.original_album_name.clone(),
+            None,
+            self.new_track_name.clone(),
+            self.new_artist_name.clone(),
+            self.new_album_name.clone(),
+            self.new_album_artist_name.clone(),
+            None,
+        )
+    }
+}
+
+impl From<&PendingEdit> for EditMapping {
+    fn from(edit: &PendingEdit) -> Self {
+        Self {
+            original_artist_name: edit.original_artist_name.clone(),
+            original_album_name: edit.original_album_name.clone(),
+            original_track_name: edit.original_track_name.clone(),
+            new_track_name: edit.new_track_name.clone(),
+            new_artist_name: edit.new_artist_name.clone(),
+            new_album_name: edit.new_album_name.clone(),
+            new_album_artist_name: edit.new_album_artist_name.clone(),
+        }
+    }
+}
+
+/// A remembered decision on an edit mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RememberedEdit {
+    #[serde(flatten)]
+    pub mapping: EditMapping,
+    pub decision: EditDecision,
+    pub decided_at: DateTime<Utc>,
+}
+
+impl RememberedEdit {
+    /// This decision as a pending edit, for passing to providers as context
+    #[must_use]
+    pub fn to_pending_edit(&self) -> PendingEdit {
+        let mut edit = self.mapping.to_pending_edit();
+        edit.decision = Some(self.decision);
+        edit
+    }
+}
+
+/// Approved and rejected edits, so the same change is never suggested for review twice
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EditDecisionsState {
+    pub decisions: Vec<RememberedEdit>,
+}
+
+impl EditDecisionsState {
+    /// Remember a decision on `edit`, replacing any earlier decision on the same mapping
+    pub fn record(&mut self, edit: &PendingEdit, decision: EditDecision, now: DateTime<Utc>) {
+        let mapping = EditMapping::from(edit);
+        self.decisions
+            .retain(|remembered| remembered.mapping != mapping);
+        self.decisions.push(RememberedEdit {
+            mapping,
+            decision,
+            decided_at: now,
+        });
+    }
+
+    /// The decision previously made on the same mapping as `edit`
+    #[must_use]
+    pub fn decision_for(&self, edit: &PendingEdit) -> Option<EditDecision> {
+        let mapping = EditMapping::from(edit);
+        self.decisions
+            .iter()
+            .find(|remembered| remembered.mapping == mapping)
+            .map(|remembered| remembered.decision)
+    }
+
+    /// Rejected edits as pending edits, for passing to providers as context
+    #[must_use]
+    pub fn rejected_as_pending_edits(&self) -> Vec<PendingEdit> {
+        self.decisions
+            .iter()
+            .filter(|remembered| remembered.decision == EditDecision::Rejected)
+            .map(RememberedEdit::to_pending_edit)
+            .collect()
+    }
+}
+
 /// Main persistence trait
 #[async_trait]
 pub trait StateStorage: Send + Sync {
@@ -294,13 +539,111 @@ pub trait StateStorage: Send + Sync {
 
     async fn save_settings_state(&mut self, state: &SettingsState) -> Result<(), Self::Error>;
     async fn load_settings_state(&self) -> Result<SettingsState, Self::Error>;
+
+    async fn save_rule_stats_state(&mut self, state: &RuleStatsState) -> Result<(), Self::Error>;
+    async fn load_rule_stats_state(&self) -> Result<RuleStatsState, Self::Error>;
+
+    async fn save_edit_decisions_state(
+        &mut self,
+        state: &EditDecisionsState,
+    ) -> Result<(), Self::Error>;
+    async fn load_edit_decisions_state(&self) -> Result<EditDecisionsState, Self::Error>;
+
+    /// Remember the user's decision on a pending edit
+    async fn record_edit_decision(
+        &mut self,
+        edit: &PendingEdit,
+        decision: EditDecision,
+    ) -> Result<(), Self::Error> {
+        let mut state = self.load_edit_decisions_state().await?;
+        state.record(edit, decision, Utc::now());
+        self.save_edit_decisions_state(&state).await
+    }
+
+    /// The pending edit with `id`, if it is still pending
+    async fn load_pending_edit(&self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        Ok(self
+            .load_pending_edits_state()
+            .await?
+            .pending_edits
+            .into_iter()
+            .find(|edit| edit.id == id))
+    }
+
+    /// Pending edits of scrobbles by `artist`
+    async fn load_pending_edits_for_artist(
+        &self,
+        artist: &str,
+    ) -> Result<Vec<PendingEdit>, Self::Error> {
+        let mut state = self.load_pending_edits_state().await?;
+        state
+            .pending_edits
+            .retain(|edit| edit.original_artist_name == artist);
+        Ok(state.pending_edits)
+    }
+
+    /// Add an edit to the end of the pending edits
+    async fn add_pending_edit(&mut self, edit: &PendingEdit) -> Result<(), Self::Error> {
+        let mut state = self.load_pending_edits_state().await?;
+        state.pending_edits.push(edit.clone());
+        self.save_pending_edits_state(&state).await
+    }
+
+    /// Remove the pending edit with `id`, returning it if it was pending
+    async fn remove_pending_edit(&mut self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        let mut state = self.load_pending_edits_state().await?;
+        let Some(index) = state.pending_edits.iter().position(|edit| edit.id == id) else {
+            return Ok(None);
+        };
+        let edit = state.pending_edits.remove(index);
+        self.save_pending_edits_state(&state).await?;
+        Ok(Some(edit))
+    }
+
+    /// Remove the pending edit with `id` and remember it as approved, and the change it
+    /// undoes as rejected
+    async fn approve_pending_edit(&mut self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        let edit = self.remove_pending_edit(id).await?;
+        if let Some(edit) = &edit {
+            self.record_edit_decision(edit, EditDecision::Approved)
+                .await?;
+            if let Some(reverted) = &edit.reverts {
+                self.record_edit_decision(&reverted.to_pending_edit(), EditDecision::Rejected)
+                    .await?;
+            }
+        }
+        Ok(edit)
+    }
+
+    /// Remove the pending edit with `id` and remember it as rejected
+    async fn reject_pending_edit(&mut self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        let edit = self.remove_pending_edit(id).await?;
+        if let Some(edit) = &edit {
+            self.record_edit_decision(edit, EditDecision::Rejected)
+                .await?;
+        }
+        Ok(edit)
+    }
 }
 
+pub mod bundle;
+pub mod migrations;
+
 // Re-export implementations
 #[cfg(feature = "pickledb")]
 mod file_storage;
 #[cfg(feature = "pickledb")]
 pub use file_storage::FileStorage;
 
+#[cfg(feature = "sqlite")]
+mod sqlite_storage;
+#[cfg(feature = "sqlite")]
+pub use sqlite_storage::{SqliteStorage, SqliteStorageError};
+
+#[cfg(feature = "pickledb")]
+mod configured_storage;
+#[cfg(feature = "pickledb")]
+pub use configured_storage::{ConfiguredStorage, ConfiguredStorageError};
+
 mod memory_storage;
 pub use memory_storage::MemoryStorage;
@@ -0,0 +1,1263 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use super::migrations::{self, SchemaError, CURRENT_SCHEMA_VERSION, SCHEMA_VERSION_KEY};
+use super::{
+    EditDecision, EditDecisionsState, EditMapping, PendingEdit, PendingEditsState,
+    PendingRewriteRule, PendingRewriteRulesState, RememberedEdit, RewriteRulesState, RuleStats,
+    RuleStatsState, SettingsState, StateStorage, TimestampState,
+};
+use crate::rewrite::load_comprehensive_default_rules;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS timestamp_anchor (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    last_processed_timestamp TEXT
+);
+CREATE TABLE IF NOT EXISTS rewrite_rules (
+    position INTEGER PRIMARY KEY,
+    name TEXT,
+    rule TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS alias_rules (
+    position INTEGER PRIMARY KEY,
+    name TEXT,
+    rule TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS pending_edits (
+    position INTEGER PRIMARY KEY,
+    id TEXT NOT NULL,
+    original_track_name TEXT NOT NULL,
+    original_artist_name TEXT NOT NULL,
+    original_album_name TEXT,
+    original_album_artist_name TEXT,
+    new_track_name TEXT,
+    new_artist_name TEXT,
+    new_album_name TEXT,
+    new_album_artist_name TEXT,
+    timestamp INTEGER,
+    confidence REAL,
+    explanation TEXT,
+    conflicts TEXT NOT NULL,
+    decision TEXT,
+    reverts TEXT
+);
+CREATE INDEX IF NOT EXISTS pending_edits_by_id ON pending_edits (id);
+CREATE INDEX IF NOT EXISTS pending_edits_by_artist ON pending_edits (original_artist_name);
+CREATE INDEX IF NOT EXISTS pending_edits_by_timestamp ON pending_edits (timestamp);
+CREATE TABLE IF NOT EXISTS pending_rules (
+    position INTEGER PRIMARY KEY,
+    id TEXT NOT NULL,
+    rule TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    example_track_name TEXT NOT NULL,
+    example_artist_name TEXT NOT NULL,
+    example_album_name TEXT,
+    example_album_artist_name TEXT
+);
+CREATE TABLE IF NOT EXISTS settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    require_confirmation INTEGER NOT NULL,
+    require_confirmation_for_edits INTEGER NOT NULL,
+    require_confirmation_for_new_rules INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS rule_stats (
+    rule_key TEXT PRIMARY KEY,
+    matched INTEGER NOT NULL,
+    produced INTEGER NOT NULL,
+    applied INTEGER NOT NULL,
+    rejected_by_musicbrainz INTEGER NOT NULL,
+    last_matched TEXT,
+    last_matched_track TEXT,
+    last_fired TEXT
+);
+CREATE TABLE IF NOT EXISTS edit_decisions (
+    position INTEGER PRIMARY KEY,
+    original_artist_name TEXT NOT NULL,
+    original_album_name TEXT,
+    original_track_name TEXT NOT NULL,
+    new_track_name TEXT,
+    new_artist_name TEXT,
+    new_album_name TEXT,
+    new_album_artist_name TEXT,
+    decision TEXT NOT NULL,
+    decided_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS edit_decisions_by_track
+    ON edit_decisions (original_artist_name, original_track_name);
+";
+
+/// SQLite-based storage implementation with a table per kind of state
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[allow(clippy::enum_variant_names)]
+pub enum SqliteStorageError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+    #[error("Serialization error: {0}")]
+    SerializationError(#[from] serde_json::Error),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Migration error: {0}")]
+    MigrationError(String),
+    #[error(transparent)]
+    SchemaError(#[from] SchemaError),
+}
+
+impl SqliteStorage {
+    /// Open the database at `path`, creating it with the default rewrite rules if it
+    /// doesn't exist
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, SqliteStorageError> {
+        let path_ref = path.as_ref();
+        let is_new_database = !path_ref.exists();
+
+        let storage = Self::open(path_ref)?;
+        if is_new_database {
+            storage.initialize_default_rules()?;
+            log::info!("Initialized new database with comprehensive default rewrite rules");
+        }
+        Ok(storage)
+    }
+
+    /// Open the database at `path`, migrating the PickleDB state file at `pickledb_path`
+    /// into it if the database doesn't exist yet
+    ///
+    /// The migration only runs once: the PickleDB file is left untouched and ignored as
+    /// soon as the SQLite database exists.
+    #[cfg(feature = "pickledb")]
+    pub async fn new_migrating_from<P: AsRef<Path>, Q: AsRef<Path>>(
+        path: P,
+        pickledb_path: Q,
+    ) -> Result<Self, SqliteStorageError> {
+        let path_ref = path.as_ref();
+        let pickledb_path = pickledb_path.as_ref();
+        if path_ref.exists() || !pickledb_path.exists() {
+            return Self::new(path_ref);
+        }
+
+        log::info!(
+            "Migrating state from {} to {}",
+            pickledb_path.display(),
+            path_ref.display()
+        );
+        let source = super::FileStorage::new(pickledb_path)
+            .map_err(|e| SqliteStorageError::MigrationError(e.to_string()))?;
+        let mut storage = Self::open(path_ref)?;
+        if let Err(e) = storage.copy_from(&source).await {
+            // Don't leave a half-migrated database behind, or the migration would never rerun
+            drop(storage);
+            let _ = std::fs::remove_file(path_ref);
+            return Err(e);
+        }
+        storage.set_meta("migrated_from", &pickledb_path.to_string_lossy())?;
+        Ok(storage)
+    }
+
+    /// Open an in-memory database, mainly for tests
+    pub fn in_memory() -> Result<Self, SqliteStorageError> {
+        let conn = Connection::open_in_memory()?;
+        create_tables(&conn)?;
+        let storage = Self {
+            conn: Mutex::new(conn),
+        };
+        storage.upgrade_schema(None)?;
+        Ok(storage)
+    }
+
+    fn open(path: &Path) -> Result<Self, SqliteStorageError> {
+        // Create parent directory if it doesn't exist
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(path)?;
+        // Refuse a newer database before creating tables it may have renamed or dropped
+        if let Some(version) = read_schema_version(&conn)? {
+            migrations::check_version(version)?;
+        }
+        create_tables(&conn)?;
+        let storage = Self {
+            conn: Mutex::new(conn),
+        };
+        storage.upgrade_schema(Some(path))?;
+        Ok(storage)
+    }
+
+    /// Schema version the database was written with, if it has been recorded
+    pub fn schema_version(&self) -> Result<Option<u32>, SqliteStorageError> {
+        read_schema_version(&self.conn())
+    }
+
+    /// Refuse a database from a newer version, and back up one from an older version before
+    /// recording the current version
+    ///
+    /// Tables are created with `IF NOT EXISTS` and rules are decoded with the current types,
+    /// so older databases need no changes beyond new tables and [`ADDED_COLUMNS`].
+    fn upgrade_schema(&self, path: Option<&Path>) -> Result<(), SqliteStorageError> {
+        if let Some(version) = self.schema_version()? {
+            migrations::check_version(version)?;
+            if version == CURRENT_SCHEMA_VERSION {
+                return Ok(());
+            }
+            if let Some(path) = path {
+                migrations::backup_state_file(path, version)?;
+            }
+        }
+        self.set_meta(SCHEMA_VERSION_KEY, &CURRENT_SCHEMA_VERSION.to_string())
+    }
+
+    #[cfg(feature = "pickledb")]
+    async fn copy_from(&mut self, source: &super::FileStorage) -> Result<(), SqliteStorageError> {
+        let migration_error = |e: super::file_storage::FileStorageError| {
+            SqliteStorageError::MigrationError(e.to_string())
+        };
+
+        let timestamp = source
+            .load_timestamp_state()
+            .await
+            .map_err(migration_error)?;
+        self.save_timestamp_state(&timestamp).await?;
+        let rules = source
+            .load_rewrite_rules_state()
+            .await
+            .map_err(migration_error)?;
+        self.save_rewrite_rules_state(&rules).await?;
+        let pending_edits = source
+            .load_pending_edits_state()
+            .await
+            .map_err(migration_error)?;
+        self.save_pending_edits_state(&pending_edits).await?;
+        let pending_rules = source
+            .load_pending_rewrite_rules_state()
+            .await
+            .map_err(migration_error)?;
+        self.save_pending_rewrite_rules_state(&pending_rules)
+            .await?;
+        let settings = source
+            .load_settings_state()
+            .await
+            .map_err(migration_error)?;
+        self.save_settings_state(&settings).await?;
+        let rule_stats = source
+            .load_rule_stats_state()
+            .await
+            .map_err(migration_error)?;
+        self.save_rule_stats_state(&rule_stats).await?;
+        let decisions = source
+            .load_edit_decisions_state()
+            .await
+            .map_err(migration_error)?;
+        self.save_edit_decisions_state(&decisions).await?;
+
+        log::info!(
+            "Migrated {} rewrite rules, {} pending edits and {} pending rules",
+            rules.rewrite_rules.len(),
+            pending_edits.pending_edits.len(),
+            pending_rules.pending_rules.len()
+        );
+        Ok(())
+    }
+
+    /// Initialize default rewrite rules for a new database
+    fn initialize_default_rules(&self) -> Result<(), SqliteStorageError> {
+        let rules_state = RewriteRulesState {
+            rewrite_rules: load_comprehensive_default_rules(),
+            alias_rules: Vec::new(),
+        };
+        self.write_rules(&rules_state)
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // A panic while holding the lock can't leave a transaction half-applied, so the
+        // connection is still usable
+        self.conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<(), SqliteStorageError> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    fn write_rules(&self, state: &RewriteRulesState) -> Result<(), SqliteStorageError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM rewrite_rules", [])?;
+        tx.execute("DELETE FROM alias_rules", [])?;
+        {
+            let mut insert =
+                tx.prepare("INSERT INTO rewrite_rules (position, name, rule) VALUES (?1, ?2, ?3)")?;
+            for (position, rule) in state.rewrite_rules.iter().enumerate() {
+                insert.execute(params![
+                    position as i64,
+                    rule.name,
+                    serde_json::to_string(rule)?
+                ])?;
+            }
+            let mut insert =
+                tx.prepare("INSERT INTO alias_rules (position, name, rule) VALUES (?1, ?2, ?3)")?;
+            for (position, rule) in state.alias_rules.iter().enumerate() {
+                insert.execute(params![
+                    position as i64,
+                    rule.name,
+                    serde_json::to_string(rule)?
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove a pending edit and remember the decision on it in one transaction
+    fn decide_pending_edit(
+        &self,
+        id: &str,
+        decision: EditDecision,
+    ) -> Result<Option<PendingEdit>, SqliteStorageError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let edit = take_pending_edit(&tx, id)?;
+        if let Some(edit) = &edit {
+            let now = Utc::now();
+            write_edit_decision(&tx, edit, decision, now)?;
+            if let (EditDecision::Approved, Some(reverted)) = (decision, &edit.reverts) {
+                write_edit_decision(
+                    &tx,
+                    &reverted.to_pending_edit(),
+                    EditDecision::Rejected,
+                    now,
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(edit)
+    }
+}
+
+fn to_timestamp(time: Option<DateTime<Utc>>) -> Option<String> {
+    time.map(|time| time.to_rfc3339())
+}
+
+fn from_timestamp(value: Option<String>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn decision_name(decision: EditDecision) -> &'static str {
+    match decision {
+        EditDecision::Approved => "approved",
+        EditDecision::Rejected => "rejected",
+    }
+}
+
+fn parse_decision(value: &str) -> Option<EditDecision> {
+    match value {
+        "approved" => Some(EditDecision::Approved),
+        "rejected" => Some(EditDecision::Rejected),
+        _ => None,
+    }
+}
+
+fn json_column<T: serde::de::DeserializeOwned>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    let value: String = row.get(index)?;
+    serde_json::from_str(&value).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+const PENDING_EDIT_COLUMNS: &str = "id, original_track_name, original_artist_name,
+    original_album_name, original_album_artist_name, new_track_name, new_artist_name,
+    new_album_name, new_album_artist_name, timestamp, confidence, explanation, conflicts,
+    decision, reverts";
+
+/// Columns added to existing tables after they were first created, as (table, column, type)
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[("pending_edits", "reverts", "TEXT")];
+
+/// Create missing tables, and add columns that older databases' tables lack
+fn create_tables(conn: &Connection) -> Result<(), SqliteStorageError> {
+    conn.execute_batch(SCHEMA)?;
+    for (table, column, column_type) in ADDED_COLUMNS {
+        let exists: bool = conn.query_row(
+            &format!("SELECT EXISTS (SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1)"),
+            params![column],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {table} ADD COLUMN {column} {column_type}"
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Schema version recorded in `conn`, or `None` for a new database or one that predates it
+fn read_schema_version(conn: &Connection) -> Result<Option<u32>, SqliteStorageError> {
+    let has_meta: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'meta')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_meta {
+        return Ok(None);
+    }
+    let version: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = ?1",
+            params![SCHEMA_VERSION_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(version.and_then(|version| version.parse().ok()))
+}
+
+fn read_pending_edit(row: &Row) -> rusqlite::Result<PendingEdit> {
+    Ok(PendingEdit {
+        id: row.get(0)?,
+        original_track_name: row.get(1)?,
+        original_artist_name: row.get(2)?,
+        original_album_name: row.get(3)?,
+        original_album_artist_name: row.get(4)?,
+        new_track_name: row.get(5)?,
+        new_artist_name: row.get(6)?,
+        new_album_name: row.get(7)?,
+        new_album_artist_name: row.get(8)?,
+        timestamp: row
+            .get::<_, Option<i64>>(9)?
+            .map(|timestamp| timestamp as u64),
+        confidence: row
+            .get::<_, Option<f64>>(10)?
+            .map(|confidence| confidence as f32),
+        explanation: row.get(11)?,
+        conflicts: json_column(row, 12)?,
+        decision: row
+            .get::<_, Option<String>>(13)?
+            .as_deref()
+            .and_then(parse_decision),
+        reverts: row
+            .get::<_, Option<String>>(14)?
+            .map(|reverts| serde_json::from_str(&reverts))
+            .transpose()
+            .map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    14,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?,
+    })
+}
+
+fn insert_pending_edit(
+    conn: &Connection,
+    position: i64,
+    edit: &PendingEdit,
+) -> Result<(), SqliteStorageError> {
+    conn.prepare_cached(
+        "INSERT INTO pending_edits (
+            position, id, original_track_name, original_artist_name, original_album_name,
+            original_album_artist_name, new_track_name, new_artist_name, new_album_name,
+            new_album_artist_name, timestamp, confidence, explanation, conflicts, decision,
+            reverts
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+    )?
+    .execute(params![
+        position,
+        edit.id,
+        edit.original_track_name,
+        edit.original_artist_name,
+        edit.original_album_name,
+        edit.original_album_artist_name,
+        edit.new_track_name,
+        edit.new_artist_name,
+        edit.new_album_name,
+        edit.new_album_artist_name,
+        edit.timestamp.map(|timestamp| timestamp as i64),
+        edit.confidence.map(f64::from),
+        edit.explanation,
+        serde_json::to_string(&edit.conflicts)?,
+        edit.decision.map(decision_name),
+        edit.reverts
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?,
+    ])?;
+    Ok(())
+}
+
+/// Delete the pending edit with `id`, returning it if it was there
+fn take_pending_edit(
+    conn: &Connection,
+    id: &str,
+) -> Result<Option<PendingEdit>, SqliteStorageError> {
+    let edit = conn
+        .query_row(
+            &format!("SELECT {PENDING_EDIT_COLUMNS} FROM pending_edits WHERE id = ?1"),
+            params![id],
+            read_pending_edit,
+        )
+        .optional()?;
+    conn.execute("DELETE FROM pending_edits WHERE id = ?1", params![id])?;
+    Ok(edit)
+}
+
+/// Replace the decision remembered for `edit`'s mapping, like `EditDecisionsState::record`
+fn write_edit_decision(
+    conn: &Connection,
+    edit: &PendingEdit,
+    decision: EditDecision,
+    now: DateTime<Utc>,
+) -> Result<(), SqliteStorageError> {
+    let mapping = EditMapping::from(edit);
+    conn.execute(
+        "DELETE FROM edit_decisions
+        WHERE original_artist_name = ?1 AND original_album_name IS ?2
+            AND original_track_name = ?3 AND new_track_name IS ?4 AND new_artist_name IS ?5
+            AND new_album_name IS ?6 AND new_album_artist_name IS ?7",
+        params![
+            mapping.original_artist_name,
+            mapping.original_album_name,
+            mapping.original_track_name,
+            mapping.new_track_name,
+            mapping.new_artist_name,
+            mapping.new_album_name,
+            mapping.new_album_artist_name,
+        ],
+    )?;
+    conn.execute(
+        "INSERT INTO edit_decisions (
+            position, original_artist_name, original_album_name, original_track_name,
+            new_track_name, new_artist_name, new_album_name, new_album_artist_name,
+            decision, decided_at
+        ) VALUES (
+            (SELECT COALESCE(MAX(position) + 1, 0) FROM edit_decisions),
+            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
+        )",
+        params![
+            mapping.original_artist_name,
+            mapping.original_album_name,
+            mapping.original_track_name,
+            mapping.new_track_name,
+            mapping.new_artist_name,
+            mapping.new_album_name,
+            mapping.new_album_artist_name,
+            decision_name(decision),
+            now.to_rfc3339(),
+        ],
+    )?;
+    Ok(())
+}
+
+#[async_trait]
+impl StateStorage for SqliteStorage {
+    type Error = SqliteStorageError;
+
+    async fn save_timestamp_state(&mut self, state: &TimestampState) -> Result<(), Self::Error> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO timestamp_anchor (id, last_processed_timestamp) VALUES (1, ?1)",
+            params![to_timestamp(state.last_processed_timestamp)],
+        )?;
+        Ok(())
+    }
+
+    async fn load_timestamp_state(&self) -> Result<TimestampState, Self::Error> {
+        let last_processed_timestamp = self
+            .conn()
+            .query_row(
+                "SELECT last_processed_timestamp FROM timestamp_anchor WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(TimestampState {
+            last_processed_timestamp: from_timestamp(last_processed_timestamp),
+        })
+    }
+
+    async fn save_rewrite_rules_state(
+        &mut self,
+        state: &RewriteRulesState,
+    ) -> Result<(), Self::Error> {
+        self.write_rules(state)
+    }
+
+    async fn load_rewrite_rules_state(&self) -> Result<RewriteRulesState, Self::Error> {
+        let conn = self.conn();
+        let rewrite_rules = conn
+            .prepare("SELECT rule FROM rewrite_rules ORDER BY position")?
+            .query_map([], |row| json_column(row, 0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let alias_rules = conn
+            .prepare("SELECT rule FROM alias_rules ORDER BY position")?
+            .query_map([], |row| json_column(row, 0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(RewriteRulesState {
+            rewrite_rules,
+            alias_rules,
+        })
+    }
+
+    async fn save_pending_edits_state(
+        &mut self,
+        state: &PendingEditsState,
+    ) -> Result<(), Self::Error> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM pending_edits", [])?;
+        for (position, edit) in state.pending_edits.iter().enumerate() {
+            insert_pending_edit(&tx, position as i64, edit)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn load_pending_edits_state(&self) -> Result<PendingEditsState, Self::Error> {
+        let conn = self.conn();
+        let pending_edits = conn
+            .prepare(&format!(
+                "SELECT {PENDING_EDIT_COLUMNS} FROM pending_edits ORDER BY position"
+            ))?
+            .query_map([], read_pending_edit)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PendingEditsState { pending_edits })
+    }
+
+    async fn save_pending_rewrite_rules_state(
+        &mut self,
+        state: &PendingRewriteRulesState,
+    ) -> Result<(), Self::Error> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM pending_rules", [])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO pending_rules (
+                    position, id, rule, reason, example_track_name, example_artist_name,
+                    example_album_name, example_album_artist_name
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for (position, pending) in state.pending_rules.iter().enumerate() {
+                insert.execute(params![
+                    position as i64,
+                    pending.id,
+                    serde_json::to_string(&pending.rule)?,
+                    pending.reason,
+                    pending.example_track_name,
+                    pending.example_artist_name,
+                    pending.example_album_name,
+                    pending.example_album_artist_name,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn load_pending_rewrite_rules_state(
+        &self,
+    ) -> Result<PendingRewriteRulesState, Self::Error> {
+        let conn = self.conn();
+        let pending_rules = conn
+            .prepare(
+                "SELECT id, rule, reason, example_track_name, example_artist_name,
+                    example_album_name, example_album_artist_name
+                FROM pending_rules ORDER BY position",
+            )?
+            .query_map([], |row| {
+                Ok(PendingRewriteRule {
+                    id: row.get(0)?,
+                    rule: json_column(row, 1)?,
+                    reason: row.get(2)?,
+                    example_track_name: row.get(3)?,
+                    example_artist_name: row.get(4)?,
+                    example_album_name: row.get(5)?,
+                    example_album_artist_name: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PendingRewriteRulesState { pending_rules })
+    }
+
+    async fn save_settings_state(&mut self, state: &SettingsState) -> Result<(), Self::Error> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO settings (
+                id, require_confirmation, require_confirmation_for_edits,
+                require_confirmation_for_new_rules
+            ) VALUES (1, ?1, ?2, ?3)",
+            params![
+                state.require_confirmation,
+                state.require_confirmation_for_edits,
+                state.require_confirmation_for_new_rules,
+            ],
+        )?;
+        Ok(())
+    }
+
+    async fn load_settings_state(&self) -> Result<SettingsState, Self::Error> {
+        let settings = self
+            .conn()
+            .query_row(
+                "SELECT require_confirmation, require_confirmation_for_edits,
+                    require_confirmation_for_new_rules
+                FROM settings WHERE id = 1",
+                [],
+                |row| {
+                    Ok(SettingsState {
+                        require_confirmation: row.get(0)?,
+                        require_confirmation_for_edits: row.get(1)?,
+                        require_confirmation_for_new_rules: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(settings.unwrap_or_default())
+    }
+
+    async fn save_rule_stats_state(&mut self, state: &RuleStatsState) -> Result<(), Self::Error> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM rule_stats", [])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO rule_stats (
+                    rule_key, matched, produced, applied, rejected_by_musicbrainz,
+                    last_matched, last_matched_track, last_fired
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for (rule_key, stats) in &state.rules {
+                insert.execute(params![
+                    rule_key,
+                    stats.matched as i64,
+                    stats.produced as i64,
+                    stats.applied as i64,
+                    stats.rejected_by_musicbrainz as i64,
+                    to_timestamp(stats.last_matched),
+                    stats.last_matched_track,
+                    to_timestamp(stats.last_fired),
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn load_rule_stats_state(&self) -> Result<RuleStatsState, Self::Error> {
+        let conn = self.conn();
+        let rules = conn
+            .prepare(
+                "SELECT rule_key, matched, produced, applied, rejected_by_musicbrainz,
+                    last_matched, last_matched_track, last_fired
+                FROM rule_stats",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    RuleStats {
+                        matched: row.get::<_, i64>(1)? as u64,
+                        produced: row.get::<_, i64>(2)? as u64,
+                        applied: row.get::<_, i64>(3)? as u64,
+                        rejected_by_musicbrainz: row.get::<_, i64>(4)? as u64,
+                        last_matched: from_timestamp(row.get(5)?),
+                        last_matched_track: row.get(6)?,
+                        last_fired: from_timestamp(row.get(7)?),
+                    },
+                ))
+            })?
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+        Ok(RuleStatsState { rules })
+    }
+
+    async fn save_edit_decisions_state(
+        &mut self,
+        state: &EditDecisionsState,
+    ) -> Result<(), Self::Error> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM edit_decisions", [])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO edit_decisions (
+                    position, original_artist_name, original_album_name, original_track_name,
+                    new_track_name, new_artist_name, new_album_name, new_album_artist_name,
+                    decision, decided_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for (position, remembered) in state.decisions.iter().enumerate() {
+                let mapping = &remembered.mapping;
+                insert.execute(params![
+                    position as i64,
+                    mapping.original_artist_name,
+                    mapping.original_album_name,
+                    mapping.original_track_name,
+                    mapping.new_track_name,
+                    mapping.new_artist_name,
+                    mapping.new_album_name,
+                    mapping.new_album_artist_name,
+                    decision_name(remembered.decision),
+                    remembered.decided_at.to_rfc3339(),
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn load_edit_decisions_state(&self) -> Result<EditDecisionsState, Self::Error> {
+        let conn = self.conn();
+        let rows = conn
+            .prepare(
+                "SELECT original_artist_name, original_album_name, original_track_name,
+                    new_track_name, new_artist_name, new_album_name, new_album_artist_name,
+                    decision, decided_at
+                FROM edit_decisions ORDER BY position",
+            )?
+            .query_map([], |row| {
+                let mapping = EditMapping {
+                    original_artist_name: row.get(0)?,
+                    original_album_name: row.get(1)?,
+                    original_track_name: row.get(2)?,
+                    new_track_name: row.get(3)?,
+                    new_artist_name: row.get(4)?,
+                    new_album_name: row.get(5)?,
+                    new_album_artist_name: row.get(6)?,
+                };
+                let decision: String = row.get(7)?;
+                let decided_at: Option<String> = row.get(8)?;
+                Ok((mapping, decision, decided_at))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let decisions = rows
+            .into_iter()
+            .filter_map(|(mapping, decision, decided_at)| {
+                Some(RememberedEdit {
+                    mapping,
+                    decision: parse_decision(&decision)?,
+                    decided_at: from_timestamp(decided_at)?,
+                })
+            })
+            .collect();
+        Ok(EditDecisionsState { decisions })
+    }
+
+    async fn record_edit_decision(
+        &mut self,
+        edit: &PendingEdit,
+        decision: EditDecision,
+    ) -> Result<(), Self::Error> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        write_edit_decision(&tx, edit, decision, Utc::now())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn load_pending_edit(&self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        Ok(self
+            .conn()
+            .query_row(
+                &format!("SELECT {PENDING_EDIT_COLUMNS} FROM pending_edits WHERE id = ?1"),
+                params![id],
+                read_pending_edit,
+            )
+            .optional()?)
+    }
+
+    async fn load_pending_edits_for_artist(
+        &self,
+        artist: &str,
+    ) -> Result<Vec<PendingEdit>, Self::Error> {
+        let conn = self.conn();
+        let pending_edits = conn
+            .prepare(&format!(
+                "SELECT {PENDING_EDIT_COLUMNS} FROM pending_edits
+                WHERE original_artist_name = ?1 ORDER BY position"
+            ))?
+            .query_map(params![artist], read_pending_edit)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(pending_edits)
+    }
+
+    async fn add_pending_edit(&mut self, edit: &PendingEdit) -> Result<(), Self::Error> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let position: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM pending_edits",
+            [],
+            |row| row.get(0),
+        )?;
+        insert_pending_edit(&tx, position, edit)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn remove_pending_edit(&mut self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let edit = take_pending_edit(&tx, id)?;
+        tx.commit()?;
+        Ok(edit)
+    }
+
+    async fn approve_pending_edit(&mut self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        self.decide_pending_edit(id, EditDecision::Approved)
+    }
+
+    async fn reject_pending_edit(&mut self, id: &str) -> Result<Option<PendingEdit>, Self::Error> {
+        self.decide_pending_edit(id, EditDecision::Rejected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewrite::{RewriteRule, SdRule};
+
+    fn sample_edit() -> PendingEdit {
+        let mut edit = PendingEdit::new(
+            "Song (Remastered)".to_string(),
+            "Artist".to_string(),
+            Some("Album".to_string()),
+            None,
+            Some("Song".to_string()),
+            None,
+            None,
+            None,
+            Some(1_700_000_000),
+        );
+        edit.confidence = Some(0.75);
+        edit.explanation = Some("Strip remaster suffix".to_string());
+        edit
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn should_round_trip_every_state() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        storage
+            .save_timestamp_state(&TimestampState {
+                last_processed_timestamp: Some(now),
+            })
+            .await
+            .unwrap();
+
+        let rule = RewriteRule::new()
+            .with_track_name(SdRule::new(r"(.*) \(Remastered\)", "$1"))
+            .with_name("Strip remaster");
+        storage
+            .save_rewrite_rules_state(&RewriteRulesState {
+                rewrite_rules: vec![rule.clone()],
+                alias_rules: Vec::new(),
+            })
+            .await
+            .unwrap();
+
+        let edit = sample_edit();
+        storage
+            .save_pending_edits_state(&PendingEditsState {
+                pending_edits: vec![edit.clone()],
+            })
+            .await
+            .unwrap();
+        storage
+            .save_pending_rewrite_rules_state(&PendingRewriteRulesState {
+                pending_rules: vec![PendingRewriteRule::new(
+                    rule.clone(),
+                    "Seen on many tracks".to_string(),
+                    "Song (Remastered)".to_string(),
+                    "Artist".to_string(),
+                )],
+            })
+            .await
+            .unwrap();
+        storage
+            .save_settings_state(&SettingsState {
+                require_confirmation: true,
+                require_confirmation_for_edits: false,
+                require_confirmation_for_new_rules: true,
+            })
+            .await
+            .unwrap();
+        storage
+            .record_edit_decision(&edit, EditDecision::Rejected)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            storage
+                .load_timestamp_state()
+                .await
+                .unwrap()
+                .last_processed_timestamp,
+            Some(now)
+        );
+        let rules = storage.load_rewrite_rules_state().await.unwrap();
+        assert_eq!(rules.rewrite_rules, vec![rule]);
+
+        let pending = storage.load_pending_edits_state().await.unwrap();
+        assert_eq!(pending.pending_edits.len(), 1);
+        assert_eq!(pending.pending_edits[0].id, edit.id);
+        assert_eq!(pending.pending_edits[0].timestamp, edit.timestamp);
+        assert_eq!(pending.pending_edits[0].confidence, edit.confidence);
+        assert_eq!(pending.pending_edits[0].explanation, edit.explanation);
+
+        let pending_rules = storage.load_pending_rewrite_rules_state().await.unwrap();
+        assert_eq!(pending_rules.pending_rules[0].reason, "Seen on many tracks");
+
+        let settings = storage.load_settings_state().await.unwrap();
+        assert!(settings.require_confirmation && settings.require_confirmation_for_new_rules);
+        assert!(!settings.require_confirmation_for_edits);
+
+        let decisions = storage.load_edit_decisions_state().await.unwrap();
+        assert_eq!(decisions.decision_for(&edit), Some(EditDecision::Rejected));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn empty_database_should_load_defaults() {
+        let storage = SqliteStorage::in_memory().unwrap();
+
+        assert!(storage
+            .load_timestamp_state()
+            .await
+            .unwrap()
+            .last_processed_timestamp
+            .is_none());
+        assert!(storage
+            .load_pending_edits_state()
+            .await
+            .unwrap()
+            .pending_edits
+            .is_empty());
+        assert!(
+            !storage
+                .load_settings_state()
+                .await
+                .unwrap()
+                .require_confirmation
+        );
+    }
+
+    #[test_log::test]
+    fn newer_database_should_be_refused_untouched() {
+        let dir =
+            std::env::temp_dir().join(format!("scrobble-scrubber-sqlite-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.sqlite");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)",
+            params![SCHEMA_VERSION_KEY, (CURRENT_SCHEMA_VERSION + 1).to_string()],
+        )
+        .unwrap();
+        drop(conn);
+
+        let result = SqliteStorage::new(&path);
+
+        assert!(matches!(
+            result,
+            Err(SqliteStorageError::SchemaError(SchemaError::TooNew { .. }))
+        ));
+        let tables: i64 = Connection::open(&path)
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(tables, 1);
+    }
+
+    #[cfg(feature = "pickledb")]
+    #[test_log::test(tokio::test)]
+    async fn should_migrate_pickledb_state_once() {
+        let dir =
+            std::env::temp_dir().join(format!("scrobble-scrubber-sqlite-{}", uuid::Uuid::new_v4()));
+        let pickledb_path = dir.join("state.db");
+        let sqlite_path = dir.join("state.sqlite");
+
+        let mut source = crate::persistence::FileStorage::new(&pickledb_path).unwrap();
+        let edit = sample_edit();
+        source
+            .save_pending_edits_state(&PendingEditsState {
+                pending_edits: vec![edit.clone()],
+            })
+            .await
+            .unwrap();
+        let rule_count = source
+            .load_rewrite_rules_state()
+            .await
+            .unwrap()
+            .rewrite_rules
+            .len();
+        drop(source);
+
+        let mut storage = SqliteStorage::new_migrating_from(&sqlite_path, &pickledb_path)
+            .await
+            .unwrap();
+        let pending = storage.load_pending_edits_state().await.unwrap();
+        assert_eq!(pending.pending_edits[0].id, edit.id);
+        assert_eq!(
+            storage
+                .load_rewrite_rules_state()
+                .await
+                .unwrap()
+                .rewrite_rules
+                .len(),
+            rule_count
+        );
+
+        // Changes made after the migration aren't overwritten by a second one
+        storage
+            .save_pending_edits_state(&PendingEditsState::default())
+            .await
+            .unwrap();
+        drop(storage);
+        let reopened = SqliteStorage::new_migrating_from(&sqlite_path, &pickledb_path)
+            .await
+            .unwrap();
+        assert!(reopened
+            .load_pending_edits_state()
+            .await
+            .unwrap()
+            .pending_edits
+            .is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn should_update_pending_edits_one_at_a_time() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let first = sample_edit();
+        let mut second = sample_edit();
+        second.original_artist_name = "Other Artist".to_string();
+        let third = sample_edit();
+        for edit in [&first, &second, &third] {
+            storage.add_pending_edit(edit).await.unwrap();
+        }
+
+        let by_artist = storage
+            .load_pending_edits_for_artist("Artist")
+            .await
+            .unwrap();
+        assert_eq!(
+            by_artist.iter().map(|edit| &edit.id).collect::<Vec<_>>(),
+            vec![&first.id, &third.id]
+        );
+        assert_eq!(
+            storage
+                .load_pending_edit(&second.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .confidence,
+            Some(0.75)
+        );
+
+        // Rejecting then approving the same change keeps only the later decision
+        assert!(storage
+            .reject_pending_edit(&first.id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(storage
+            .approve_pending_edit(&third.id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(storage
+            .approve_pending_edit(&third.id)
+            .await
+            .unwrap()
+            .is_none());
+        let decisions = storage.load_edit_decisions_state().await.unwrap();
+        assert_eq!(decisions.decisions.len(), 1);
+        assert_eq!(decisions.decision_for(&first), Some(EditDecision::Approved));
+
+        let removed = storage.remove_pending_edit(&second.id).await.unwrap();
+        assert_eq!(removed.map(|edit| edit.id), Some(second.id));
+        assert!(storage
+            .load_pending_edits_state()
+            .await
+            .unwrap()
+            .pending_edits
+            .is_empty());
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn approving_an_undo_should_reject_the_change_it_reverts() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let reverted = sample_edit();
+        let undo = PendingEdit::new(
+            "Renamed".to_string(),
+            "Artist".to_string(),
+            None,
+            None,
+            Some("Track".to_string()),
+            None,
+            None,
+            None,
+            Some(1_700_000_000),
+        )
+        .with_reverts(Some(EditMapping::from(&reverted)));
+        storage.add_pending_edit(&undo).await.unwrap();
+
+        let approved = storage.approve_pending_edit(&undo.id).await.unwrap();
+
+        assert_eq!(approved.unwrap().reverts, undo.reverts);
+        let decisions = storage.load_edit_decisions_state().await.unwrap();
+        assert_eq!(decisions.decision_for(&undo), Some(EditDecision::Approved));
+        assert_eq!(
+            decisions.decision_for(&reverted),
+            Some(EditDecision::Rejected)
+        );
+    }
+
+    #[test_log::test]
+    fn older_pending_edits_table_should_gain_added_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        // The pending edits table as it was before `reverts` was added
+        conn.execute_batch(&SCHEMA.replace(
+            "    decision TEXT,\n    reverts TEXT\n",
+            "    decision TEXT\n",
+        ))
+        .unwrap();
+        assert!(conn.prepare("SELECT reverts FROM pending_edits").is_err());
+
+        create_tables(&conn).unwrap();
+        create_tables(&conn).unwrap();
+
+        let columns = conn
+            .prepare("SELECT name FROM pragma_table_info('pending_edits')")
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(columns.contains(&"reverts".to_string()));
+    }
+}
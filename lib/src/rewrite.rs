@@ -1,4 +1,6 @@
 use crate::config::ReleaseFilterConfig;
+use crate::rewrite_template::{CaptureRef, ReplacementTemplate, TemplateError};
+use crate::rule_examples::RuleExample;
 use lastfm_edit::{ScrobbleEdit, Track};
 use serde::{Deserialize, Serialize};
 
@@ -69,18 +71,55 @@ pub fn apply_all_rules(
     Ok(any_changes)
 }
 
-/// A single find-and-replace transformation with whole-string replacement behavior
+/// How much of the input an `SdRule` replaces when its pattern matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "cli", value(rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum ReplacementMode {
+    /// Replace the entire input string with the expanded replacement (default)
+    #[default]
+    WholeString,
+    /// Replace only the first match, keeping the rest of the input
+    FirstMatch,
+    /// Replace every non-overlapping match, keeping the rest of the input
+    AllMatches,
+}
+
+impl ReplacementMode {
+    /// Whether this is the default whole-string mode (used to keep serialized rules unchanged)
+    #[must_use]
+    pub fn is_whole_string(&self) -> bool {
+        *self == Self::WholeString
+    }
+}
+
+impl std::fmt::Display for ReplacementMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WholeString => write!(f, "whole_string"),
+            Self::FirstMatch => write!(f, "first_match"),
+            Self::AllMatches => write!(f, "all_matches"),
+        }
+    }
+}
+
+/// A single find-and-replace transformation
 ///
 /// ## Behavior
 ///
-/// When a pattern matches anywhere in the input string, the **entire input string**
-/// is replaced with the replacement text (not just the matched portion).
+/// By default ([`ReplacementMode::WholeString`]), when a pattern matches anywhere in the
+/// input string, the **entire input string** is replaced with the replacement text (not
+/// just the matched portion).
+///
+/// With [`ReplacementMode::FirstMatch`] or [`ReplacementMode::AllMatches`] only the
+/// matched portions are replaced, so substring cleanups don't need anchored patterns.
 ///
 /// ## Pattern Matching
 ///
 /// - Uses regular expressions for pattern matching
 /// - Pattern can match anywhere in the input string
-/// - If pattern matches, entire string is replaced
+/// - In whole-string mode, if the pattern matches, the entire string is replaced
 ///
 /// ## Replacement Syntax
 ///
@@ -96,6 +135,16 @@ pub fn apply_all_rules(
 /// - `${name}` - Named capture group
 /// - Example: `(?P<artist>.+)` can be referenced as `${artist}`
 ///
+/// ### Cross-Field Captures
+/// - `${track.name}`, `${album.1}` - Group captured by another field's pattern of the same
+///   `RewriteRule` (scopes: `track`, `artist`, `album`, `album_artist`)
+///
+/// ### Filters
+/// - `${1:upper}`, `${artist:title}` - Transform a captured value before inserting it
+/// - Filters can be chained: `${2:trim:lower}`
+/// - Available: `upper`, `lower`, `title`, `trim`, `nfc`, `strip_diacritics`
+/// - Unknown filters are reported when the rule is compiled
+///
 /// ### Literal Characters and Escaping
 /// - `\$` - Literal dollar sign (escaped)
 /// - `$$` - Literal dollar sign (alternative syntax)
@@ -122,6 +171,15 @@ pub fn apply_all_rules(
 ///     "${song} by ${artist}"
 /// );
 /// assert_eq!(rule.apply("Queen - Bohemian Rhapsody").unwrap(), "Bohemian Rhapsody by Queen");
+///
+/// // Filters: normalize the case of a captured value
+/// let rule = SdRule::new(r"^(.+) - (.+)$", "${2:title}");
+/// assert_eq!(rule.apply("queen - BOHEMIAN RHAPSODY").unwrap(), "Bohemian Rhapsody");
+///
+/// // Substring replacement: only the matched text is replaced
+/// use scrobble_scrubber::rewrite::ReplacementMode;
+/// let rule = SdRule::new(r" ft\. ", " feat. ").with_mode(ReplacementMode::AllMatches);
+/// assert_eq!(rule.apply("Artist ft. Someone").unwrap(), "Artist feat. Someone");
 /// ```
 ///
 /// ## Regex Flags
@@ -139,6 +197,9 @@ pub struct SdRule {
     pub replace: String,
     /// Regex flags (e.g., "i" for case insensitive)
     pub flags: Option<String>,
+    /// How much of the input is replaced when the pattern matches
+    #[serde(default, skip_serializing_if = "ReplacementMode::is_whole_string")]
+    pub mode: ReplacementMode,
 }
 
 impl SdRule {
@@ -149,6 +210,7 @@ impl SdRule {
             find: find.to_string(),
             replace: replace.to_string(),
             flags: None,
+            mode: ReplacementMode::WholeString,
         }
     }
 
@@ -159,119 +221,489 @@ impl SdRule {
         self
     }
 
+    /// Set the replacement mode
+    #[must_use]
+    pub const fn with_mode(mut self, mode: ReplacementMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Compile this rule's pattern so it can be applied repeatedly without rebuilding the regex
+    ///
+    /// Cross-field references such as `${track.feat}` are only available when the rule is
+    /// compiled as part of a [`RewriteRule`]; here they are kept as literal text.
+    pub fn compile(&self) -> Result<CompiledSdRule, RewriteError> {
+        CompiledSdRule::new(self.clone(), self.build_regex()?, |_| false)
+    }
+
+    fn build_regex(&self) -> Result<regex::Regex, regex::Error> {
+        RegexOptions::from_flags(self.flags.as_deref()).build(&self.find)
+    }
+
     /// Apply this rule to a string, returning the result
-    /// If the pattern matches anywhere in the input, the entire string is replaced
+    /// In whole-string mode, if the pattern matches anywhere in the input, the entire string is replaced
     pub fn apply(&self, input: &str) -> Result<String, RewriteError> {
-        // Always use regex mode - if pattern matches anywhere, replace entire string
-        // But allow capture group substitution from the original input
-        let mut regex_builder = regex::RegexBuilder::new(&self.find);
-        regex_builder.multi_line(true);
+        Ok(self.compile()?.apply(input))
+    }
 
-        // Apply flags if present
-        if let Some(flags) = &self.flags {
+    /// Check if this rule's pattern matches the input string (regardless of whether it would modify it)
+    pub fn matches(&self, input: &str) -> Result<bool, RewriteError> {
+        Ok(self.compile()?.matches(input))
+    }
+}
+
+/// Regex options resolved from an `SdRule` flag string
+#[derive(Debug, Clone, Copy)]
+struct RegexOptions {
+    case_insensitive: bool,
+    multi_line: bool,
+    dot_matches_new_line: bool,
+}
+
+impl RegexOptions {
+    fn from_flags(flags: Option<&str>) -> Self {
+        let mut options = Self {
+            case_insensitive: false,
+            multi_line: true,
+            dot_matches_new_line: false,
+        };
+
+        if let Some(flags) = flags {
             for c in flags.chars() {
                 match c {
-                    'c' => {
-                        regex_builder.case_insensitive(false);
-                    }
-                    'i' => {
-                        regex_builder.case_insensitive(true);
-                    }
+                    'c' => options.case_insensitive = false,
+                    'i' => options.case_insensitive = true,
                     'm' => {}
-                    'e' => {
-                        regex_builder.multi_line(false);
-                    }
+                    'e' => options.multi_line = false,
                     's' => {
                         if !flags.contains('m') {
-                            regex_builder.multi_line(false);
+                            options.multi_line = false;
                         }
-                        regex_builder.dot_matches_new_line(true);
+                        options.dot_matches_new_line = true;
                     }
                     _ => {}
                 }
             }
         }
 
-        let regex = regex_builder.build().map_err(RewriteError::RegexError)?;
+        options
+    }
+
+    fn build(self, pattern: &str) -> Result<regex::Regex, regex::Error> {
+        regex::RegexBuilder::new(pattern)
+            .case_insensitive(self.case_insensitive)
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_matches_new_line)
+            .build()
+    }
+
+    /// Wrap a pattern in an inline flag group so it behaves the same inside a `RegexSet`
+    fn wrap_pattern(self, pattern: &str) -> String {
+        let mut enabled = String::new();
+        let mut disabled = String::new();
+        for (flag, on) in [
+            ('i', self.case_insensitive),
+            ('m', self.multi_line),
+            ('s', self.dot_matches_new_line),
+        ] {
+            if on {
+                enabled.push(flag);
+            } else {
+                disabled.push(flag);
+            }
+        }
+        let disabled = if disabled.is_empty() {
+            String::new()
+        } else {
+            format!("-{disabled}")
+        };
+        format!("(?{enabled}{disabled}:{pattern})")
+    }
+}
+
+/// An `SdRule` with its regex compiled ahead of time
+#[derive(Debug, Clone)]
+pub struct CompiledSdRule {
+    rule: SdRule,
+    regex: regex::Regex,
+    template: ReplacementTemplate,
+}
 
-        if let Some(captures) = regex.captures(input) {
-            // Pattern matches - replace entire string, expanding capture groups
-            let mut result = self.replace.clone();
+impl CompiledSdRule {
+    /// Parse the replacement template; `is_external_name` accepts references to groups
+    /// captured outside of this rule's pattern
+    fn new(
+        rule: SdRule,
+        regex: regex::Regex,
+        is_external_name: impl Fn(&str) -> bool,
+    ) -> Result<Self, RewriteError> {
+        let template = ReplacementTemplate::parse(&rule.replace, regex.captures_len(), |name| {
+            has_capture_group(&regex, name) || is_external_name(name)
+        })?;
 
-            // Handle escaped characters first (convert to placeholders)
-            let escaped_dollar_placeholder = "\u{E000}ESCAPED_DOLLAR\u{E000}"; // Use private use area
-            let escaped_lbrace_placeholder = "\u{E000}ESCAPED_LBRACE\u{E000}";
-            let escaped_rbrace_placeholder = "\u{E000}ESCAPED_RBRACE\u{E000}";
-            let escaped_backslash_placeholder = "\u{E000}ESCAPED_BACKSLASH\u{E000}";
+        Ok(Self {
+            rule,
+            regex,
+            template,
+        })
+    }
 
-            // Handle backslashes first to prevent double-processing
-            result = result.replace(r"\\", escaped_backslash_placeholder);
-            result = result.replace(r"\$", escaped_dollar_placeholder);
-            result = result.replace("$$", escaped_dollar_placeholder);
-            result = result.replace(r"\{", escaped_lbrace_placeholder);
-            result = result.replace(r"\}", escaped_rbrace_placeholder);
+    /// The rule this was compiled from
+    #[must_use]
+    pub const fn rule(&self) -> &SdRule {
+        &self.rule
+    }
 
-            // Replace numbered capture group references ($0, $1, $2, etc.)
-            for i in 0..captures.len() {
-                let placeholder = format!("${i}");
-                if let Some(capture) = captures.get(i) {
-                    result = result.replace(&placeholder, capture.as_str());
-                }
-            }
+    /// The compiled regex
+    #[must_use]
+    pub const fn regex(&self) -> &regex::Regex {
+        &self.regex
+    }
+
+    /// Check if the pattern matches the input string
+    #[must_use]
+    pub fn matches(&self, input: &str) -> bool {
+        self.regex.is_match(input)
+    }
 
-            // Replace named capture group references (${name})
-            for name in regex.capture_names().flatten() {
-                let placeholder = format!("${{{name}}}");
-                if let Some(capture) = captures.name(name) {
-                    result = result.replace(&placeholder, capture.as_str());
+    /// Apply the rule to a string, returning the result
+    ///
+    /// How much of the input is replaced depends on the rule's [`ReplacementMode`].
+    #[must_use]
+    pub fn apply(&self, input: &str) -> String {
+        self.apply_with(input, |_| None)
+    }
+
+    /// Apply the rule, resolving references to groups captured outside of this rule's
+    /// pattern (such as `${track.feat}`) with `external`
+    #[must_use]
+    pub fn apply_with<'e>(
+        &self,
+        input: &str,
+        external: impl Fn(&str) -> Option<&'e str>,
+    ) -> String {
+        match self.rule.mode {
+            ReplacementMode::WholeString => {
+                // If pattern matches anywhere, replace entire string
+                // But allow capture group substitution from the original input
+                match self.regex.captures(input) {
+                    Some(captures) => self.expand(&captures, &external),
+                    // Pattern doesn't match - return input unchanged
+                    None => input.to_string(),
                 }
             }
+            ReplacementMode::FirstMatch => self
+                .regex
+                .replacen(input, 1, |captures: &regex::Captures| {
+                    self.expand(captures, &external)
+                })
+                .into_owned(),
+            ReplacementMode::AllMatches => self
+                .regex
+                .replace_all(input, |captures: &regex::Captures| {
+                    self.expand(captures, &external)
+                })
+                .into_owned(),
+        }
+    }
 
-            // Restore escaped characters
-            result = result.replace(escaped_dollar_placeholder, "$");
-            result = result.replace(escaped_lbrace_placeholder, "{");
-            result = result.replace(escaped_rbrace_placeholder, "}");
-            result = result.replace(escaped_backslash_placeholder, "\\");
+    /// Expand the replacement template using the given captures
+    fn expand<'e>(
+        &self,
+        captures: &regex::Captures,
+        external: &impl Fn(&str) -> Option<&'e str>,
+    ) -> String {
+        self.template.expand(|reference| match reference {
+            CaptureRef::Index(index) => captures.get(*index).map(|m| m.as_str()),
+            CaptureRef::Name(name) => captures
+                .name(name)
+                .map(|m| m.as_str())
+                .or_else(|| external(name.as_str())),
+        })
+    }
+}
 
-            Ok(result)
-        } else {
-            // Pattern doesn't match - return input unchanged
-            Ok(input.to_string())
+/// Check if a regex has a capture group with the given name or index
+fn has_capture_group(regex: &regex::Regex, group: &str) -> bool {
+    match group.parse::<usize>() {
+        Ok(index) => index < regex.captures_len(),
+        Err(_) => regex.capture_names().flatten().any(|name| name == group),
+    }
+}
+
+/// Look up a cross-field reference (`track.feat`, `album.1`) in the captures of each field
+fn cross_field_capture<'h>(
+    captures: &[(RuleField, Option<regex::Captures<'h>>)],
+    reference: &str,
+) -> Option<&'h str> {
+    let (scope, group) = reference.split_once('.')?;
+    let field = RuleField::from_scope(scope)?;
+    let (_, field_captures) = captures.iter().find(|(f, _)| *f == field)?;
+    let field_captures = field_captures.as_ref()?;
+    let capture = match group.parse::<usize>() {
+        Ok(index) => field_captures.get(index),
+        Err(_) => field_captures.name(group),
+    }?;
+    Some(capture.as_str())
+}
+
+/// A metadata field of a scrobble
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum RuleField {
+    TrackName,
+    ArtistName,
+    AlbumName,
+    AlbumArtistName,
+}
+
+impl RuleField {
+    /// All fields, in the order rules evaluate them
+    pub const ALL: [Self; 4] = [
+        Self::TrackName,
+        Self::ArtistName,
+        Self::AlbumName,
+        Self::AlbumArtistName,
+    ];
+
+    /// The field name as used in rule JSON
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::TrackName => "track_name",
+            Self::ArtistName => "artist_name",
+            Self::AlbumName => "album_name",
+            Self::AlbumArtistName => "album_artist_name",
         }
     }
 
-    /// Check if this rule's pattern matches the input string (regardless of whether it would modify it)
-    pub fn matches(&self, input: &str) -> Result<bool, RewriteError> {
-        let mut regex_builder = regex::RegexBuilder::new(&self.find);
-        regex_builder.multi_line(true);
+    /// Parse the scope of a cross-field reference (`track` in `${track.feat}`)
+    ///
+    /// Both the short form and the full field name are accepted.
+    #[must_use]
+    pub fn from_scope(scope: &str) -> Option<Self> {
+        match scope {
+            "track" | "track_name" => Some(Self::TrackName),
+            "artist" | "artist_name" => Some(Self::ArtistName),
+            "album" | "album_name" => Some(Self::AlbumName),
+            "album_artist" | "album_artist_name" => Some(Self::AlbumArtistName),
+            _ => None,
+        }
+    }
+
+    /// Value of this field for a track (missing album and album artist are treated as empty)
+    #[must_use]
+    pub fn track_value(self, track: &Track) -> &str {
+        match self {
+            Self::TrackName => &track.name,
+            Self::ArtistName => &track.artist,
+            Self::AlbumName => track.album.as_deref().unwrap_or(""),
+            Self::AlbumArtistName => track.album_artist.as_deref().unwrap_or(""),
+        }
+    }
 
-        // Apply flags if present
+    /// Value of this field for an edit
+    #[must_use]
+    pub fn edit_value(self, edit: &ScrobbleEdit) -> Option<&str> {
+        match self {
+            Self::TrackName => edit.track_name.as_deref(),
+            Self::ArtistName => Some(&edit.artist_name),
+            Self::AlbumName => edit.album_name.as_deref(),
+            Self::AlbumArtistName => edit.album_artist_name.as_deref(),
+        }
+    }
+
+    /// Set this field of an edit
+    pub fn set_edit_value(self, edit: &mut ScrobbleEdit, value: String) {
+        match self {
+            Self::TrackName => edit.track_name = Some(value),
+            Self::ArtistName => edit.artist_name = value,
+            Self::AlbumName => edit.album_name = Some(value),
+            Self::AlbumArtistName => edit.album_artist_name = Some(value),
+        }
+    }
+
+    /// Set this field of a track
+    pub fn set_track_value(self, track: &mut Track, value: String) {
+        match self {
+            Self::TrackName => track.name = value,
+            Self::ArtistName => track.artist = value,
+            Self::AlbumName => track.album = Some(value),
+            Self::AlbumArtistName => track.album_artist = Some(value),
+        }
+    }
+}
+
+impl std::fmt::Display for RuleField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Per-field exclusion patterns for a `RewriteRule`
+///
+/// A rule doesn't match when any field's value matches its `unless` pattern. Use inline
+/// flags such as `(?i)` for case insensitive exclusions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldExclusions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artist_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album_artist_name: Option<String>,
+}
+
+impl FieldExclusions {
+    /// No exclusions
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            track_name: None,
+            artist_name: None,
+            album_name: None,
+            album_artist_name: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.track_name.is_none()
+            && self.artist_name.is_none()
+            && self.album_name.is_none()
+            && self.album_artist_name.is_none()
+    }
+
+    /// The exclusion pattern for a field
+    #[must_use]
+    pub fn get(&self, field: RuleField) -> Option<&str> {
+        match field {
+            RuleField::TrackName => self.track_name.as_deref(),
+            RuleField::ArtistName => self.artist_name.as_deref(),
+            RuleField::AlbumName => self.album_name.as_deref(),
+            RuleField::AlbumArtistName => self.album_artist_name.as_deref(),
+        }
+    }
+
+    /// Set the exclusion pattern for a field
+    pub fn set(&mut self, field: RuleField, pattern: Option<String>) {
+        match field {
+            RuleField::TrackName => self.track_name = pattern,
+            RuleField::ArtistName => self.artist_name = pattern,
+            RuleField::AlbumName => self.album_name = pattern,
+            RuleField::AlbumArtistName => self.album_artist_name = pattern,
+        }
+    }
+}
+
+/// An extra condition that must hold for a `RewriteRule` to match
+///
+/// Conditions can check any field, including fields the rule doesn't rewrite. A negated
+/// condition holds when the pattern does *not* match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleCondition {
+    /// The field to check
+    pub field: RuleField,
+    /// Regex pattern checked against the field value
+    pub pattern: String,
+    /// Regex flags, same as `SdRule::flags`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flags: Option<String>,
+    /// Require the pattern to not match instead
+    #[serde(default)]
+    pub negate: bool,
+}
+
+impl RuleCondition {
+    /// Condition that holds when `field` matches `pattern`
+    #[must_use]
+    pub fn matching(field: RuleField, pattern: &str) -> Self {
+        Self {
+            field,
+            pattern: pattern.to_string(),
+            flags: None,
+            negate: false,
+        }
+    }
+
+    /// Condition that holds when `field` does not match `pattern`
+    #[must_use]
+    pub fn not_matching(field: RuleField, pattern: &str) -> Self {
+        Self {
+            negate: true,
+            ..Self::matching(field, pattern)
+        }
+    }
+
+    /// Add regex flags
+    #[must_use]
+    pub fn with_flags(mut self, flags: &str) -> Self {
+        self.flags = Some(flags.to_string());
+        self
+    }
+}
+
+impl std::fmt::Display for RuleCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = if self.negate {
+            "does not match"
+        } else {
+            "matches"
+        };
+        write!(f, "{} {verb} '{}'", self.field, self.pattern)?;
         if let Some(flags) = &self.flags {
-            for c in flags.chars() {
-                match c {
-                    'c' => {
-                        regex_builder.case_insensitive(false);
-                    }
-                    'i' => {
-                        regex_builder.case_insensitive(true);
-                    }
-                    'm' => {}
-                    'e' => {
-                        regex_builder.multi_line(false);
-                    }
-                    's' => {
-                        if !flags.contains('m') {
-                            regex_builder.multi_line(false);
-                        }
-                        regex_builder.dot_matches_new_line(true);
-                    }
-                    _ => {}
-                }
-            }
+            write!(f, " (flags: {flags})")?;
         }
+        Ok(())
+    }
+}
+
+/// A rule condition or exclusion with its regex compiled
+#[derive(Debug, Clone)]
+struct CompiledCondition {
+    field: RuleField,
+    regex: regex::Regex,
+    negate: bool,
+}
+
+impl CompiledCondition {
+    fn compile(
+        field: RuleField,
+        pattern: &str,
+        flags: Option<&str>,
+        negate: bool,
+    ) -> Result<Self, RewriteError> {
+        Ok(Self {
+            field,
+            regex: RegexOptions::from_flags(flags).build(pattern)?,
+            negate,
+        })
+    }
+
+    /// Whether the condition holds for a field value. A missing value can't match any
+    /// pattern, so only negated conditions hold for it.
+    fn holds(&self, value: Option<&str>) -> bool {
+        value.map_or(self.negate, |value| {
+            self.regex.is_match(value) != self.negate
+        })
+    }
 
-        let regex = regex_builder.build().map_err(RewriteError::RegexError)?;
-        Ok(regex.is_match(input))
+    /// Describe why the condition failed for the given value
+    fn describe_failure(&self, value: Option<&str>) -> String {
+        let field = self.field;
+        let pattern = self.regex.as_str();
+        match (value, self.negate) {
+            (Some(value), true) => {
+                format!("{field}('{value}' matches excluded pattern '{pattern}')")
+            }
+            (Some(value), false) => {
+                format!("{field}('{value}' ≠ condition pattern '{pattern}')")
+            }
+            (None, _) => format!("{field}(None ≠ condition pattern '{pattern}')"),
+        }
     }
 }
 
@@ -298,6 +730,15 @@ pub struct RewriteRule {
     /// If None, uses default MusicBrainz provider filters. These filters are NEVER applied to search operations.
     #[serde(default)]
     pub musicbrainz_release_filters: Option<ReleaseFilterConfig>,
+    /// Patterns that prevent the rule from matching when the corresponding field matches
+    #[serde(default, skip_serializing_if = "FieldExclusions::is_empty")]
+    pub unless: FieldExclusions,
+    /// Extra conditions that must all hold for the rule to match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<RuleCondition>,
+    /// Before → after examples, checked by `rules test`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<RuleExample>,
 }
 
 impl RewriteRule {
@@ -313,6 +754,9 @@ impl RewriteRule {
             requires_confirmation: false,
             requires_musicbrainz_confirmation: false,
             musicbrainz_release_filters: None,
+            unless: FieldExclusions::new(),
+            conditions: Vec::new(),
+            examples: Vec::new(),
         }
     }
 
@@ -376,6 +820,188 @@ impl RewriteRule {
         self
     }
 
+    /// The transformation for a field
+    #[must_use]
+    pub const fn field_rule(&self, field: RuleField) -> Option<&SdRule> {
+        match field {
+            RuleField::TrackName => self.track_name.as_ref(),
+            RuleField::ArtistName => self.artist_name.as_ref(),
+            RuleField::AlbumName => self.album_name.as_ref(),
+            RuleField::AlbumArtistName => self.album_artist_name.as_ref(),
+        }
+    }
+
+    /// Don't match when `field` matches `pattern`
+    #[must_use]
+    pub fn with_unless(mut self, field: RuleField, pattern: &str) -> Self {
+        self.unless.set(field, Some(pattern.to_string()));
+        self
+    }
+
+    /// Add a condition that must hold for the rule to match
+    #[must_use]
+    pub fn with_condition(mut self, condition: RuleCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Add a before → after example
+    #[must_use]
+    pub fn with_example(mut self, example: RuleExample) -> Self {
+        self.examples.push(example);
+        self
+    }
+
+    /// Identifies the rule by its find/replace patterns, used to detect duplicate rules
+    #[must_use]
+    pub fn signature(&self) -> String {
+        format!(
+            "track:{:?}|artist:{:?}|album:{:?}|album_artist:{:?}",
+            self.track_name.as_ref().map(|r| (&r.find, &r.replace)),
+            self.artist_name.as_ref().map(|r| (&r.find, &r.replace)),
+            self.album_name.as_ref().map(|r| (&r.find, &r.replace)),
+            self.album_artist_name
+                .as_ref()
+                .map(|r| (&r.find, &r.replace))
+        )
+    }
+
+    /// Identifies everything deciding what the rule matches and produces, used to key
+    /// per-rule statistics so a changed rule starts counting afresh
+    #[must_use]
+    pub fn stats_key(&self) -> String {
+        serde_json::json!({
+            "track_name": self.track_name,
+            "artist_name": self.artist_name,
+            "album_name": self.album_name,
+            "album_artist_name": self.album_artist_name,
+            "unless": self.unless,
+            "conditions": self.conditions,
+        })
+        .to_string()
+    }
+
+    /// Compile all of this rule's patterns so it can be evaluated repeatedly without
+    /// rebuilding regexes
+    pub fn compile(&self) -> Result<CompiledRewriteRule, RewriteError> {
+        let exclusions = RuleField::ALL.into_iter().filter_map(|field| {
+            self.unless
+                .get(field)
+                .map(|pattern| CompiledCondition::compile(field, pattern, None, true))
+        });
+        let conditions = self.conditions.iter().map(|condition| {
+            CompiledCondition::compile(
+                condition.field,
+                &condition.pattern,
+                condition.flags.as_deref(),
+                condition.negate,
+            )
+        });
+
+        // Build every field's regex first so replacements can refer to groups captured by
+        // the other fields' patterns
+        let mut regexes = Vec::new();
+        for field in RuleField::ALL {
+            if let Some(sd_rule) = self.field_rule(field) {
+                regexes.push((field, sd_rule, sd_rule.build_regex()?));
+            }
+        }
+        let is_cross_field_group = |name: &str| {
+            name.split_once('.').is_some_and(|(scope, group)| {
+                RuleField::from_scope(scope).is_some_and(|field| {
+                    regexes
+                        .iter()
+                        .any(|(f, _, regex)| *f == field && has_capture_group(regex, group))
+                })
+            })
+        };
+
+        let mut fields = Vec::with_capacity(regexes.len());
+        for (field, sd_rule, regex) in &regexes {
+            let compiled =
+                CompiledSdRule::new((*sd_rule).clone(), regex.clone(), is_cross_field_group)?;
+            fields.push((*field, compiled));
+        }
+        let mut take = |field: RuleField| {
+            fields
+                .iter()
+                .position(|(f, _)| *f == field)
+                .map(|index| fields.swap_remove(index).1)
+        };
+
+        Ok(CompiledRewriteRule {
+            conditions: exclusions
+                .chain(conditions)
+                .collect::<Result<Vec<_>, _>>()?,
+            rule: self.clone(),
+            track_name: take(RuleField::TrackName),
+            album_name: take(RuleField::AlbumName),
+            artist_name: take(RuleField::ArtistName),
+            album_artist_name: take(RuleField::AlbumArtistName),
+        })
+    }
+
+    /// Check if this rule's patterns match the given track (regardless of whether it would modify it)
+    ///
+    /// See [`CompiledRewriteRule::matches`] for the matching semantics. Prefer compiling the
+    /// rule (or a [`CompiledRuleSet`]) when checking many tracks.
+    pub fn matches(&self, track: &Track) -> Result<bool, RewriteError> {
+        Ok(self.compile()?.matches(track))
+    }
+
+    /// Check if this rule's patterns match the given ScrobbleEdit with semantic None handling
+    ///
+    /// See [`CompiledRewriteRule::matches_scrobble_edit`] for the matching semantics.
+    pub fn matches_scrobble_edit(&self, edit: &ScrobbleEdit) -> Result<bool, RewriteError> {
+        Ok(self.compile()?.matches_scrobble_edit(edit))
+    }
+
+    /// Apply this rule to an existing `ScrobbleEdit`, modifying it in place
+    /// Returns true if any changes were made
+    ///
+    /// IMPORTANT: This method assumes the rule has already been checked to match the ScrobbleEdit.
+    /// Rules should be filtered using matches_scrobble_edit() before calling apply().
+    pub fn apply(&self, edit: &mut ScrobbleEdit) -> Result<bool, RewriteError> {
+        Ok(self.compile()?.apply(edit))
+    }
+}
+
+impl Default for RewriteRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A `RewriteRule` with all of its patterns compiled ahead of time
+#[derive(Debug, Clone)]
+pub struct CompiledRewriteRule {
+    rule: RewriteRule,
+    track_name: Option<CompiledSdRule>,
+    album_name: Option<CompiledSdRule>,
+    artist_name: Option<CompiledSdRule>,
+    album_artist_name: Option<CompiledSdRule>,
+    /// `unless` exclusions followed by the rule's conditions
+    conditions: Vec<CompiledCondition>,
+}
+
+impl CompiledRewriteRule {
+    /// The rule this was compiled from
+    #[must_use]
+    pub const fn rule(&self) -> &RewriteRule {
+        &self.rule
+    }
+
+    /// The compiled transformation for a field
+    #[must_use]
+    pub const fn field_rule(&self, field: RuleField) -> Option<&CompiledSdRule> {
+        match field {
+            RuleField::TrackName => self.track_name.as_ref(),
+            RuleField::ArtistName => self.artist_name.as_ref(),
+            RuleField::AlbumName => self.album_name.as_ref(),
+            RuleField::AlbumArtistName => self.album_artist_name.as_ref(),
+        }
+    }
+
     /// Check if this rule's patterns match the given track (regardless of whether it would modify it)
     ///
     /// A rule matches when:
@@ -383,8 +1009,10 @@ impl RewriteRule {
     /// - All Some fields must match their respective track fields (pattern matching only)
     /// - A rule with all None fields is treated as always matching (acts as a catch-all)
     /// - If any Some field's pattern doesn't match, the rule doesn't match
-    pub fn matches(&self, track: &Track) -> Result<bool, RewriteError> {
-        let rule_name = self.name.as_deref().unwrap_or("unnamed");
+    /// - The rule doesn't match if any field matches its `unless` pattern or any condition fails
+    #[must_use]
+    pub fn matches(&self, track: &Track) -> bool {
+        let rule_name = self.rule.name.as_deref().unwrap_or("unnamed");
         let mut matched_fields = Vec::new();
         let mut failed_fields = Vec::new();
         let mut checked_fields = Vec::new();
@@ -392,12 +1020,12 @@ impl RewriteRule {
         // Check track name pattern if present
         if let Some(rule) = &self.track_name {
             checked_fields.push("track_name");
-            if rule.matches(&track.name)? {
+            if rule.matches(&track.name) {
                 let track_name = &track.name;
                 matched_fields.push(format!("track_name('{track_name}')"));
             } else {
                 let track_name = &track.name;
-                let pattern = &rule.find;
+                let pattern = &rule.rule().find;
                 failed_fields.push(format!("track_name('{track_name}' ≠ pattern '{pattern}')"));
             }
         }
@@ -405,12 +1033,12 @@ impl RewriteRule {
         // Check artist name pattern if present
         if let Some(rule) = &self.artist_name {
             checked_fields.push("artist_name");
-            if rule.matches(&track.artist)? {
+            if rule.matches(&track.artist) {
                 let artist_name = &track.artist;
                 matched_fields.push(format!("artist_name('{artist_name}')"));
             } else {
                 let artist_name = &track.artist;
-                let pattern = &rule.find;
+                let pattern = &rule.rule().find;
                 failed_fields.push(format!(
                     "artist_name('{artist_name}' ≠ pattern '{pattern}')"
                 ));
@@ -421,12 +1049,12 @@ impl RewriteRule {
         if let Some(rule) = &self.album_name {
             checked_fields.push("album_name");
             let album_name = track.album.as_deref().unwrap_or("");
-            if rule.matches(album_name)? {
+            if rule.matches(album_name) {
                 matched_fields.push(format!("album_name('{album_name}')"));
             } else {
                 failed_fields.push(format!(
                     "album_name('{album_name}' ≠ pattern '{}')",
-                    rule.find
+                    rule.rule().find
                 ));
             }
         }
@@ -434,14 +1062,25 @@ impl RewriteRule {
         // Check album artist name pattern if present (always empty for Track)
         if let Some(rule) = &self.album_artist_name {
             checked_fields.push("album_artist_name");
-            if rule.matches("")? {
+            if rule.matches("") {
                 matched_fields.push("album_artist_name('')".to_string());
             } else {
-                let pattern = &rule.find;
+                let pattern = &rule.rule().find;
                 failed_fields.push(format!("album_artist_name('' ≠ pattern '{pattern}')"));
             }
         }
 
+        // Check exclusions and conditions
+        if !self.conditions.is_empty() {
+            checked_fields.push("conditions");
+        }
+        for condition in &self.conditions {
+            let value = condition.field.track_value(track);
+            if !condition.holds(Some(value)) {
+                failed_fields.push(condition.describe_failure(Some(value)));
+            }
+        }
+
         let rule_matches = failed_fields.is_empty();
 
         // Log comprehensive summary
@@ -472,30 +1111,30 @@ impl RewriteRule {
             );
         }
 
-        Ok(rule_matches)
+        rule_matches
     }
 
     /// Helper function to check if a single field matches between rule and ScrobbleEdit
     /// with semantic None handling
     fn check_field_match(
         field_name: &str,
-        rule: &SdRule,
+        rule: &CompiledSdRule,
         edit_field: Option<&str>,
         matched_fields: &mut Vec<String>,
         failed_fields: &mut Vec<String>,
-    ) -> Result<(), RewriteError> {
+    ) {
         if let Some(field_value) = edit_field {
-            if rule.matches(field_value)? {
+            if rule.matches(field_value) {
                 matched_fields.push(format!("{field_name}('{field_value}')"));
             } else {
-                let pattern = &rule.find;
+                let pattern = &rule.rule().find;
                 failed_fields.push(format!(
                     "{field_name}('{field_value}' ≠ pattern '{pattern}')"
                 ));
             }
         } else {
             // ScrobbleEdit field is None - check for special .* pattern that matches anything including None
-            let pattern = &rule.find;
+            let pattern = &rule.rule().find;
             if pattern == ".*" {
                 // Special case: .* pattern matches None (conceptually "match anything, including nothing")
                 matched_fields.push(format!("{field_name}(None - matched by .* pattern)"));
@@ -504,7 +1143,6 @@ impl RewriteRule {
                 failed_fields.push(format!("{field_name}(None ≠ pattern '{pattern}' - cannot match pattern against None value)"));
             }
         }
-        Ok(())
     }
 
     /// Check if this rule's patterns match the given ScrobbleEdit with semantic None handling
@@ -518,8 +1156,11 @@ impl RewriteRule {
     ///   - If rule field is Some and ScrobbleEdit field is None: **NO MATCH** (constraint exists but no value to match)
     ///   - If rule field is Some and ScrobbleEdit field is Some: **Check pattern match**
     /// - If any Some field's pattern doesn't match, the rule doesn't match
-    pub fn matches_scrobble_edit(&self, edit: &ScrobbleEdit) -> Result<bool, RewriteError> {
-        let rule_name = self.name.as_deref().unwrap_or("unnamed");
+    /// - Exclusions and conditions are checked the same way: an `unless` pattern or a
+    ///   condition can't match a None field, so only negated checks pass for it
+    #[must_use]
+    pub fn matches_scrobble_edit(&self, edit: &ScrobbleEdit) -> bool {
+        let rule_name = self.rule.name.as_deref().unwrap_or("unnamed");
         let mut matched_fields = Vec::new();
         let mut failed_fields = Vec::new();
         let mut checked_fields = Vec::new();
@@ -533,7 +1174,7 @@ impl RewriteRule {
                 edit.track_name.as_deref(),
                 &mut matched_fields,
                 &mut failed_fields,
-            )?;
+            );
         }
 
         // Check artist name pattern if present
@@ -546,7 +1187,7 @@ impl RewriteRule {
                 Some(&edit.artist_name),
                 &mut matched_fields,
                 &mut failed_fields,
-            )?;
+            );
         }
 
         // Check album name pattern if present
@@ -558,7 +1199,7 @@ impl RewriteRule {
                 edit.album_name.as_deref(),
                 &mut matched_fields,
                 &mut failed_fields,
-            )?;
+            );
         }
 
         // Check album artist name pattern if present
@@ -570,7 +1211,18 @@ impl RewriteRule {
                 edit.album_artist_name.as_deref(),
                 &mut matched_fields,
                 &mut failed_fields,
-            )?;
+            );
+        }
+
+        // Check exclusions and conditions
+        if !self.conditions.is_empty() {
+            checked_fields.push("conditions");
+        }
+        for condition in &self.conditions {
+            let value = condition.field.edit_value(edit);
+            if !condition.holds(value) {
+                failed_fields.push(condition.describe_failure(value));
+            }
         }
 
         let rule_matches = failed_fields.is_empty();
@@ -593,7 +1245,7 @@ impl RewriteRule {
             log::debug!("Rule '{rule_name}' does not match ScrobbleEdit | Matched: [{matched}] | Failed: [{failed}]");
         }
 
-        Ok(rule_matches)
+        rule_matches
     }
 
     /// Apply this rule to an existing `ScrobbleEdit`, modifying it in place
@@ -601,59 +1253,297 @@ impl RewriteRule {
     ///
     /// IMPORTANT: This method assumes the rule has already been checked to match the ScrobbleEdit.
     /// Rules should be filtered using matches_scrobble_edit() before calling apply().
-    pub fn apply(&self, edit: &mut ScrobbleEdit) -> Result<bool, RewriteError> {
-        let mut has_changes = false;
+    ///
+    /// All field patterns are evaluated against the values as they were before this rule ran,
+    /// so replacements can use groups captured from other fields (`${track.feat}`).
+    pub fn apply(&self, edit: &mut ScrobbleEdit) -> bool {
+        // Snapshot the fields this rule rewrites; fields without a value are left alone
+        let inputs: Vec<(RuleField, &CompiledSdRule, String)> = RuleField::ALL
+            .into_iter()
+            .filter_map(|field| {
+                let rule = self.field_rule(field)?;
+                let value = field.edit_value(edit)?.to_string();
+                Some((field, rule, value))
+            })
+            .collect();
 
-        // Apply track name transformation if present
-        if let Some(rule) = &self.track_name {
-            if let Some(current_value) = &edit.track_name {
-                let new_value = rule.apply(current_value)?;
-                if new_value != *current_value {
-                    edit.track_name = Some(new_value);
-                    has_changes = true;
-                }
-            }
-        }
+        // Evaluate every pattern before writing anything
+        let captures: Vec<(RuleField, Option<regex::Captures>)> = inputs
+            .iter()
+            .map(|(field, rule, value)| (*field, rule.regex().captures(value)))
+            .collect();
 
-        // Apply artist name transformation if present
-        if let Some(rule) = &self.artist_name {
-            let current_value = &edit.artist_name;
-            let new_value = rule.apply(current_value)?;
+        let new_values: Vec<String> = inputs
+            .iter()
+            .map(|(_, rule, value)| {
+                rule.apply_with(value, |reference: &str| {
+                    cross_field_capture(&captures, reference)
+                })
+            })
+            .collect();
+
+        let mut has_changes = false;
+        for ((field, _, current_value), new_value) in inputs.iter().zip(new_values) {
             if new_value != *current_value {
-                edit.artist_name = new_value;
+                field.set_edit_value(edit, new_value);
                 has_changes = true;
             }
         }
 
-        // Apply album name transformation if present
-        if let Some(rule) = &self.album_name {
-            if let Some(current_value) = &edit.album_name {
-                let new_value = rule.apply(current_value)?;
-                if new_value != *current_value {
-                    edit.album_name = Some(new_value);
-                    has_changes = true;
-                }
+        has_changes
+    }
+}
+
+/// A rule that failed to compile when building a [`CompiledRuleSet`]
+#[derive(Debug)]
+pub struct InvalidRule {
+    /// Position of the rule in the original list
+    pub index: usize,
+    /// Name of the rule, if it has one
+    pub name: Option<String>,
+    /// The compilation error
+    pub error: RewriteError,
+}
+
+impl std::fmt::Display for InvalidRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{} '{}': {}",
+            self.index + 1,
+            self.name.as_deref().unwrap_or("Unnamed"),
+            self.error
+        )
+    }
+}
+
+/// `RegexSet` over one field's patterns, used to rule out most rules with a single scan
+#[derive(Debug, Clone)]
+struct FieldPrefilter {
+    set: regex::RegexSet,
+    /// Rule index for each pattern in `set`
+    rule_indices: Vec<usize>,
+}
+
+impl FieldPrefilter {
+    fn build(
+        rules: &[CompiledRewriteRule],
+        field: fn(&CompiledRewriteRule) -> Option<&CompiledSdRule>,
+    ) -> Result<Option<Self>, regex::Error> {
+        let mut patterns = Vec::new();
+        let mut rule_indices = Vec::new();
+        for (index, rule) in rules.iter().enumerate() {
+            if let Some(sd_rule) = field(rule) {
+                let options = RegexOptions::from_flags(sd_rule.rule().flags.as_deref());
+                patterns.push(options.wrap_pattern(&sd_rule.rule().find));
+                rule_indices.push(index);
             }
         }
 
-        // Apply album artist name transformation if present
-        if let Some(rule) = &self.album_artist_name {
-            if let Some(current_value) = &edit.album_artist_name {
-                let new_value = rule.apply(current_value)?;
-                if new_value != *current_value {
-                    edit.album_artist_name = Some(new_value);
-                    has_changes = true;
-                }
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            set: regex::RegexSet::new(patterns)?,
+            rule_indices,
+        }))
+    }
+
+    /// Clear the mask entry of every rule whose pattern for this field doesn't match `value`
+    fn exclude_non_matching(&self, value: &str, mask: &mut [bool]) {
+        let matched = self.set.matches(value);
+        for (pattern_index, rule_index) in self.rule_indices.iter().enumerate() {
+            if !matched.matched(pattern_index) {
+                mask[*rule_index] = false;
             }
         }
+    }
+}
 
-        Ok(has_changes)
+#[derive(Debug, Clone)]
+struct RulePrefilter {
+    track_name: Option<FieldPrefilter>,
+    artist_name: Option<FieldPrefilter>,
+    album_name: Option<FieldPrefilter>,
+    album_artist_name: Option<FieldPrefilter>,
+}
+
+impl RulePrefilter {
+    fn build(rules: &[CompiledRewriteRule]) -> Result<Self, regex::Error> {
+        Ok(Self {
+            track_name: FieldPrefilter::build(rules, |r| r.track_name.as_ref())?,
+            artist_name: FieldPrefilter::build(rules, |r| r.artist_name.as_ref())?,
+            album_name: FieldPrefilter::build(rules, |r| r.album_name.as_ref())?,
+            album_artist_name: FieldPrefilter::build(rules, |r| r.album_artist_name.as_ref())?,
+        })
+    }
+
+    /// Narrow `mask` using the given field values. A `None` value leaves the
+    /// decision to the exact per-rule check.
+    fn apply(&self, values: [Option<&str>; 4], mask: &mut [bool]) {
+        let prefilters = [
+            &self.track_name,
+            &self.artist_name,
+            &self.album_name,
+            &self.album_artist_name,
+        ];
+        for (prefilter, value) in prefilters.into_iter().zip(values) {
+            if let (Some(prefilter), Some(value)) = (prefilter, value) {
+                prefilter.exclude_non_matching(value, mask);
+            }
+        }
     }
 }
 
-impl Default for RewriteRule {
-    fn default() -> Self {
-        Self::new()
+/// A list of rewrite rules compiled once and reused for every track
+///
+/// Rules keep their original order. In addition to the compiled per-rule regexes, a
+/// `RegexSet` per field is used to skip rules that cannot match before running the
+/// individual patterns.
+#[derive(Debug, Clone, Default)]
+pub struct CompiledRuleSet {
+    rules: Vec<CompiledRewriteRule>,
+    prefilter: Option<RulePrefilter>,
+}
+
+impl CompiledRuleSet {
+    /// Compile all rules, failing on the first invalid one
+    pub fn new(rules: Vec<RewriteRule>) -> Result<Self, RewriteError> {
+        let compiled = rules
+            .iter()
+            .map(RewriteRule::compile)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_compiled(compiled))
+    }
+
+    /// Compile all valid rules, returning the rules that failed to compile alongside the set
+    #[must_use]
+    pub fn compile_valid(rules: Vec<RewriteRule>) -> (Self, Vec<InvalidRule>) {
+        let mut compiled = Vec::with_capacity(rules.len());
+        let mut invalid = Vec::new();
+
+        for (index, rule) in rules.into_iter().enumerate() {
+            match rule.compile() {
+                Ok(compiled_rule) => compiled.push(compiled_rule),
+                Err(error) => invalid.push(InvalidRule {
+                    index,
+                    name: rule.name,
+                    error,
+                }),
+            }
+        }
+
+        (Self::from_compiled(compiled), invalid)
+    }
+
+    fn from_compiled(rules: Vec<CompiledRewriteRule>) -> Self {
+        let prefilter = match RulePrefilter::build(&rules) {
+            Ok(prefilter) => Some(prefilter),
+            Err(e) => {
+                log::debug!("Could not build rule prefilter, checking rules individually: {e}");
+                None
+            }
+        };
+        Self { rules, prefilter }
+    }
+
+    /// The compiled rules, in their original order
+    #[must_use]
+    pub fn rules(&self) -> &[CompiledRewriteRule] {
+        &self.rules
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Mask of rules that may match the track; `false` entries can be skipped
+    #[must_use]
+    pub fn prefilter_track(&self, track: &Track) -> Vec<bool> {
+        let mut mask = vec![true; self.rules.len()];
+        if let Some(prefilter) = &self.prefilter {
+            prefilter.apply(
+                [
+                    Some(&track.name),
+                    Some(&track.artist),
+                    Some(track.album.as_deref().unwrap_or("")),
+                    Some(""),
+                ],
+                &mut mask,
+            );
+        }
+        mask
+    }
+
+    /// Mask of rules that may match the edit; `false` entries can be skipped
+    #[must_use]
+    pub fn prefilter_edit(&self, edit: &ScrobbleEdit) -> Vec<bool> {
+        let mut mask = vec![true; self.rules.len()];
+        if let Some(prefilter) = &self.prefilter {
+            prefilter.apply(
+                [
+                    edit.track_name.as_deref(),
+                    Some(&edit.artist_name),
+                    edit.album_name.as_deref(),
+                    edit.album_artist_name.as_deref(),
+                ],
+                &mut mask,
+            );
+        }
+        mask
+    }
+
+    /// Indices of the rules whose patterns match the given track
+    #[must_use]
+    pub fn matching_rules(&self, track: &Track) -> Vec<usize> {
+        self.prefilter_track(track)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, candidate)| *candidate && self.rules[*index].matches(track))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Check if any rule's patterns match the given track
+    #[must_use]
+    pub fn any_rule_matches(&self, track: &Track) -> bool {
+        self.prefilter_track(track)
+            .into_iter()
+            .enumerate()
+            .any(|(index, candidate)| candidate && self.rules[index].matches(track))
+    }
+
+    /// Apply all rules in order to a `ScrobbleEdit`, returning true if any changes were made
+    /// Each rule only applies if it matches the edit as transformed by the preceding rules
+    pub fn apply_all(&self, edit: &mut ScrobbleEdit) -> bool {
+        let mut any_changes = false;
+        let mut mask = self.prefilter_edit(edit);
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !mask[index] || !rule.matches_scrobble_edit(edit) {
+                continue;
+            }
+
+            if rule.apply(edit) {
+                any_changes = true;
+                log::debug!(
+                    "Applied rewrite rule '{}' to track '{}' by '{}'",
+                    rule.rule().name.as_deref().unwrap_or("unnamed rule"),
+                    edit.track_name_original.as_deref().unwrap_or("unknown"),
+                    &edit.artist_name_original
+                );
+                mask = self.prefilter_edit(edit);
+            }
+        }
+
+        any_changes
     }
 }
 
@@ -666,6 +1556,8 @@ pub enum RewriteError {
     InvalidReplaceCapture(String),
     #[error("Invalid UTF-8 in result: {0}")]
     InvalidUtf8(#[from] std::string::FromUtf8Error),
+    #[error("Invalid replacement template: {0}")]
+    InvalidTemplate(#[from] TemplateError),
 }
 
 /// Load comprehensive default rewrite rules from the embedded JSON files
@@ -696,6 +1588,8 @@ pub fn load_comprehensive_default_rules() -> Vec<RewriteRule> {
 }
 
 /// Default rewrite rules for common cleanup tasks
+///
+/// These rules only rewrite the matched portion of each field (all-matches mode).
 #[must_use]
 pub fn default_rules() -> Vec<RewriteRule> {
     vec![
@@ -704,24 +1598,27 @@ pub fn default_rules() -> Vec<RewriteRule> {
             .with_track_name(SdRule::new(
                 r" - \d{4} [Rr]emaster| - [Rr]emaster \d{4}| - [Rr]emaster| \(\d{4} [Rr]emaster\)| \([Rr]emaster \d{4}\)| \([Rr]emaster\)",
                 ""
-            )),
+            ).with_mode(ReplacementMode::AllMatches)),
 
         // Normalize featuring formats in artist names
         RewriteRule::new()
-            .with_artist_name(SdRule::new(r" [Ff]t\. | [Ff]eaturing ", " feat. ")),
+            .with_artist_name(
+                SdRule::new(r" [Ff]t\. | [Ff]eaturing ", " feat. ")
+                    .with_mode(ReplacementMode::AllMatches),
+            ),
 
         // Clean up extra whitespace in track names
         RewriteRule::new()
-            .with_track_name(SdRule::new(r"\s+", " "))
-            .with_artist_name(SdRule::new(r"\s+", " ")),
+            .with_track_name(SdRule::new(r"\s+", " ").with_mode(ReplacementMode::AllMatches))
+            .with_artist_name(SdRule::new(r"\s+", " ").with_mode(ReplacementMode::AllMatches)),
 
         // Remove leading/trailing whitespace
         RewriteRule::new()
-            .with_track_name(SdRule::new(r"^\s+|\s+$", ""))
-            .with_artist_name(SdRule::new(r"^\s+|\s+$", "")),
+            .with_track_name(SdRule::new(r"^\s+|\s+$", "").with_mode(ReplacementMode::AllMatches))
+            .with_artist_name(SdRule::new(r"^\s+|\s+$", "").with_mode(ReplacementMode::AllMatches)),
 
         // Remove explicit content warnings
         RewriteRule::new()
-            .with_track_name(SdRule::new(r" \(Explicit\)$| - Explicit$", "")),
+            .with_track_name(SdRule::new(r" \(Explicit\)$| - Explicit$", "").with_mode(ReplacementMode::AllMatches)),
     ]
 }
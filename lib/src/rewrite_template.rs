@@ -0,0 +1,481 @@
+//! Replacement templates used by `SdRule`
+//!
+//! Templates are parsed once when a rule is compiled and then expanded for every match.
+//!
+//! ## Syntax
+//!
+//! - `$n` - numbered capture group `0`-`9` (`$0` is the entire match). Only one digit is
+//!   read, so `$12` is group 1 followed by `2`. References to groups that don't exist are
+//!   kept as literal text
+//! - `${name}` - named capture group. References to groups that don't exist are kept as
+//!   literal text
+//! - `$n` and `${name}` references to groups that didn't participate in the match are kept
+//!   as literal text too; every other reference expands to an empty string
+//! - `${scope.group}` - group captured by another pattern, such as `${track.feat}`. Which
+//!   scopes exist is decided by the caller (`RewriteRule` uses its field names)
+//! - `${ref:filter}` / `${ref:filter1:filter2}` - capture group (named or numbered)
+//!   transformed by one or more filters, applied left to right
+//! - `\$`, `$$`, `\{`, `\}`, `\\` - literal `$`, `{`, `}` and `\`
+//!
+//! ## Filters
+//!
+//! - `upper` / `lower` - change case
+//! - `title` - capitalize the first letter of every word and lowercase the rest
+//! - `trim` - remove leading and trailing whitespace
+//! - `nfc` - Unicode NFC normalization
+//! - `strip_diacritics` - remove accents and other combining marks (`Beyoncé` → `Beyonce`)
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Reference to a capture group inside a template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureRef {
+    /// Numbered group (`$1`, `${1:upper}`)
+    Index(usize),
+    /// Named group (`${artist}`)
+    Name(String),
+}
+
+impl std::fmt::Display for CaptureRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{index}"),
+            Self::Name(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// A text transformation applied to a captured value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateFilter {
+    Upper,
+    Lower,
+    Title,
+    Trim,
+    Nfc,
+    StripDiacritics,
+}
+
+impl TemplateFilter {
+    /// All available filters
+    pub const ALL: [Self; 6] = [
+        Self::Upper,
+        Self::Lower,
+        Self::Title,
+        Self::Trim,
+        Self::Nfc,
+        Self::StripDiacritics,
+    ];
+
+    /// The name used in templates
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Upper => "upper",
+            Self::Lower => "lower",
+            Self::Title => "title",
+            Self::Trim => "trim",
+            Self::Nfc => "nfc",
+            Self::StripDiacritics => "strip_diacritics",
+        }
+    }
+
+    /// Look up a filter by its template name
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|filter| filter.name() == name)
+    }
+
+    /// Apply this filter to a value
+    #[must_use]
+    pub fn apply(self, input: &str) -> String {
+        match self {
+            Self::Upper => input.to_uppercase(),
+            Self::Lower => input.to_lowercase(),
+            Self::Title => title_case(input),
+            Self::Trim => input.trim().to_string(),
+            Self::Nfc => input.nfc().collect(),
+            Self::StripDiacritics => input
+                .nfd()
+                .filter(|c| !is_combining_mark(*c))
+                .nfc()
+                .collect(),
+        }
+    }
+}
+
+/// Capitalize the first letter of every word, lowercasing the rest
+///
+/// Apostrophes don't start a new word, so "don't" becomes "Don't" rather than "Don'T".
+fn title_case(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut at_word_start = true;
+
+    for c in input.chars() {
+        if c.is_alphanumeric() {
+            if at_word_start {
+                result.extend(c.to_uppercase());
+            } else {
+                result.extend(c.to_lowercase());
+            }
+            at_word_start = false;
+        } else {
+            result.push(c);
+            at_word_start = !matches!(c, '\'' | '’');
+        }
+    }
+
+    result
+}
+
+/// Errors found while parsing a replacement template
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TemplateError {
+    #[error("unknown filter '{filter}' in '{placeholder}' (available: upper, lower, title, trim, nfc, strip_diacritics)")]
+    UnknownFilter { filter: String, placeholder: String },
+    #[error("unknown capture group '{reference}' in '{placeholder}'")]
+    UnknownCapture {
+        reference: String,
+        placeholder: String,
+    },
+    #[error("unterminated placeholder '{0}'")]
+    Unterminated(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Capture {
+        reference: CaptureRef,
+        filters: Vec<TemplateFilter>,
+        /// Text written in place of a group that didn't participate in the match, if not empty
+        unmatched: Option<String>,
+    },
+}
+
+/// A parsed replacement template
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplacementTemplate {
+    segments: Vec<Segment>,
+}
+
+impl ReplacementTemplate {
+    /// Parse a template for a pattern with `group_count` numbered groups (including group 0)
+    ///
+    /// `is_known_name` decides which `${name}` references resolve to capture groups.
+    pub fn parse(
+        template: &str,
+        group_count: usize,
+        is_known_name: impl Fn(&str) -> bool,
+    ) -> Result<Self, TemplateError> {
+        let chars: Vec<char> = template.chars().collect();
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+
+            // Escaped characters
+            if c == '\\' && matches!(next, Some('\\' | '$' | '{' | '}')) {
+                literal.extend(next);
+                i += 2;
+                continue;
+            }
+
+            if c != '$' {
+                literal.push(c);
+                i += 1;
+                continue;
+            }
+
+            match next {
+                Some('$') => {
+                    literal.push('$');
+                    i += 2;
+                }
+                Some(d) if d.is_ascii_digit() => {
+                    let index = d.to_digit(10).unwrap_or_default() as usize;
+                    if index < group_count {
+                        Self::push_capture(
+                            &mut segments,
+                            &mut literal,
+                            CaptureRef::Index(index),
+                            Vec::new(),
+                            Some(format!("${d}")),
+                        );
+                    } else {
+                        literal.push('$');
+                        literal.push(d);
+                    }
+                    i += 2;
+                }
+                Some('{') => {
+                    let start = i + 2;
+                    let end = (start..chars.len())
+                        .find(|&j| !Self::is_reference_char(chars[j]))
+                        .unwrap_or(chars.len());
+                    let reference: String = chars[start..end].iter().collect();
+
+                    match chars.get(end) {
+                        Some('}') if !reference.is_empty() => {
+                            // Unfiltered references only resolve to named groups; anything
+                            // else is kept as literal text
+                            let is_named_group = !reference.chars().all(|c| c.is_ascii_digit())
+                                && is_known_name(&reference);
+                            if is_named_group {
+                                // Groups of other fields (`${track.feat}`) expand to nothing
+                                let unmatched =
+                                    (!reference.contains('.')).then(|| format!("${{{reference}}}"));
+                                Self::push_capture(
+                                    &mut segments,
+                                    &mut literal,
+                                    CaptureRef::Name(reference),
+                                    Vec::new(),
+                                    unmatched,
+                                );
+                            } else {
+                                literal.push_str("${");
+                                literal.push_str(&reference);
+                                literal.push('}');
+                            }
+                            i = end + 1;
+                        }
+                        Some(':') if !reference.is_empty() => {
+                            let close =
+                                (end..chars.len())
+                                    .find(|&j| chars[j] == '}')
+                                    .ok_or_else(|| {
+                                        TemplateError::Unterminated(chars[i..].iter().collect())
+                                    })?;
+                            let placeholder: String = chars[i..=close].iter().collect();
+                            let filter_spec: String = chars[end + 1..close].iter().collect();
+
+                            let capture_ref =
+                                Self::resolve_reference(&reference, group_count, &is_known_name)
+                                    .ok_or_else(|| TemplateError::UnknownCapture {
+                                        reference: reference.clone(),
+                                        placeholder: placeholder.clone(),
+                                    })?;
+
+                            let filters = filter_spec
+                                .split(':')
+                                .map(|name| {
+                                    TemplateFilter::from_name(name.trim()).ok_or_else(|| {
+                                        TemplateError::UnknownFilter {
+                                            filter: name.trim().to_string(),
+                                            placeholder: placeholder.clone(),
+                                        }
+                                    })
+                                })
+                                .collect::<Result<Vec<_>, _>>()?;
+
+                            Self::push_capture(
+                                &mut segments,
+                                &mut literal,
+                                capture_ref,
+                                filters,
+                                None,
+                            );
+                            i = close + 1;
+                        }
+                        _ => {
+                            // Not a placeholder - keep "${" and continue parsing after it
+                            literal.push_str("${");
+                            i += 2;
+                        }
+                    }
+                }
+                _ => {
+                    literal.push('$');
+                    i += 1;
+                }
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    fn is_reference_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || c == '.'
+    }
+
+    fn resolve_reference(
+        reference: &str,
+        group_count: usize,
+        is_known_name: &impl Fn(&str) -> bool,
+    ) -> Option<CaptureRef> {
+        if reference.chars().all(|c| c.is_ascii_digit()) {
+            reference
+                .parse::<usize>()
+                .ok()
+                .filter(|index| *index < group_count)
+                .map(CaptureRef::Index)
+        } else if is_known_name(reference) {
+            Some(CaptureRef::Name(reference.to_string()))
+        } else {
+            None
+        }
+    }
+
+    fn push_capture(
+        segments: &mut Vec<Segment>,
+        literal: &mut String,
+        reference: CaptureRef,
+        filters: Vec<TemplateFilter>,
+        unmatched: Option<String>,
+    ) {
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(std::mem::take(literal)));
+        }
+        segments.push(Segment::Capture {
+            reference,
+            filters,
+            unmatched,
+        });
+    }
+
+    /// All capture references used by this template
+    pub fn references(&self) -> impl Iterator<Item = &CaptureRef> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Capture { reference, .. } => Some(reference),
+            Segment::Literal(_) => None,
+        })
+    }
+
+    /// Expand the template, looking up captured values with `lookup`
+    ///
+    /// Plain `$n` and `${name}` references to groups that didn't participate in the match are
+    /// kept as written, as they were before templates were parsed; other references expand
+    /// to an empty string.
+    #[must_use]
+    pub fn expand<'h>(&self, lookup: impl Fn(&CaptureRef) -> Option<&'h str>) -> String {
+        let mut result = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => result.push_str(text),
+                Segment::Capture {
+                    reference,
+                    filters,
+                    unmatched,
+                } => {
+                    let Some(value) = lookup(reference) else {
+                        result.push_str(unmatched.as_deref().unwrap_or_default());
+                        continue;
+                    };
+                    if filters.is_empty() {
+                        result.push_str(value);
+                    } else {
+                        let filtered = filters
+                            .iter()
+                            .fold(value.to_string(), |value, filter| filter.apply(&value));
+                        result.push_str(&filtered);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(template: &str) -> Result<ReplacementTemplate, TemplateError> {
+        ReplacementTemplate::parse(template, 3, |name| name == "artist")
+    }
+
+    fn expand(template: &str) -> String {
+        parse(template)
+            .unwrap()
+            .expand(|reference| match reference {
+                CaptureRef::Index(0) => Some("  josé gonzález - héroes "),
+                CaptureRef::Index(1) => Some("  josé gonzález "),
+                CaptureRef::Index(2) => Some("héroes "),
+                CaptureRef::Name(name) if name == "artist" => Some("THE ROLLING STONES"),
+                _ => None,
+            })
+    }
+
+    #[test_log::test]
+    fn should_apply_filters() {
+        assert_eq!(expand("${1:trim:upper}"), "JOSÉ GONZÁLEZ");
+        assert_eq!(expand("${artist:title}"), "The Rolling Stones");
+        assert_eq!(expand("${artist:lower}"), "the rolling stones");
+        assert_eq!(expand("${2:trim}!"), "héroes!");
+        assert_eq!(expand("${1:trim:strip_diacritics}"), "jose gonzalez");
+        assert_eq!(expand("${2:nfc}"), "héroes ".nfc().collect::<String>());
+    }
+
+    #[test_log::test]
+    fn should_title_case_words_with_apostrophes() {
+        assert_eq!(
+            TemplateFilter::Title.apply("DON'T STOP me-now"),
+            "Don't Stop Me-Now"
+        );
+    }
+
+    #[test_log::test]
+    fn should_report_unknown_filters_and_groups() {
+        assert_eq!(
+            parse("${1:shout}"),
+            Err(TemplateError::UnknownFilter {
+                filter: "shout".to_string(),
+                placeholder: "${1:shout}".to_string(),
+            })
+        );
+        assert!(matches!(
+            parse("${missing:upper}"),
+            Err(TemplateError::UnknownCapture { .. })
+        ));
+        assert!(matches!(
+            parse("${7:upper}"),
+            Err(TemplateError::UnknownCapture { .. })
+        ));
+        assert!(matches!(
+            parse("${1:upper"),
+            Err(TemplateError::Unterminated(_))
+        ));
+    }
+
+    #[test_log::test]
+    fn should_keep_unresolved_references_literal() {
+        assert_eq!(expand("${1} ${missing} ${} $9"), "${1} ${missing} ${} $9");
+        assert_eq!(expand("$12"), "  josé gonzález 2");
+        assert_eq!(expand(r"\$$$\{\}\\"), r"$${}\");
+    }
+
+    #[test_log::test]
+    fn should_keep_plain_references_to_unmatched_groups_literal() {
+        let template =
+            ReplacementTemplate::parse("$1|$2|${artist}|${2:upper}|${track.feat}", 3, |name| {
+                name == "artist" || name == "track.feat"
+            })
+            .unwrap();
+        let expanded = template.expand(|reference| match reference {
+            CaptureRef::Index(1) => Some("Queen"),
+            _ => None,
+        });
+        assert_eq!(expanded, "Queen|$2|${artist}||");
+    }
+
+    #[test_log::test]
+    fn should_read_a_single_digit_after_the_dollar_sign() {
+        let template = ReplacementTemplate::parse("$12", 13, |_| false).unwrap();
+        let expanded = template.expand(|reference| match reference {
+            CaptureRef::Index(1) => Some("one"),
+            CaptureRef::Index(12) => Some("twelve"),
+            _ => None,
+        });
+        assert_eq!(expanded, "one2");
+    }
+}
@@ -0,0 +1,296 @@
+//! Before → after examples for rewrite rules and a runner that checks them
+//!
+//! Examples document what a rule is meant to do and double as regression tests: whenever
+//! a rule is edited, `check_rule_examples` (or `rules test` on the command line) reports
+//! every example the rule no longer produces.
+
+use crate::rewrite::{create_no_op_edit, RewriteRule, RuleField};
+use lastfm_edit::Track;
+use serde::{Deserialize, Serialize};
+
+/// Values for some of a scrobble's fields
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldValues {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artist_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album_artist_name: Option<String>,
+}
+
+impl FieldValues {
+    /// No values
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            track_name: None,
+            artist_name: None,
+            album_name: None,
+            album_artist_name: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.track_name.is_none()
+            && self.artist_name.is_none()
+            && self.album_name.is_none()
+            && self.album_artist_name.is_none()
+    }
+
+    /// Set the value of a field
+    #[must_use]
+    pub fn with(mut self, field: RuleField, value: &str) -> Self {
+        self.set(field, Some(value.to_string()));
+        self
+    }
+
+    /// The value of a field
+    #[must_use]
+    pub fn get(&self, field: RuleField) -> Option<&str> {
+        match field {
+            RuleField::TrackName => self.track_name.as_deref(),
+            RuleField::ArtistName => self.artist_name.as_deref(),
+            RuleField::AlbumName => self.album_name.as_deref(),
+            RuleField::AlbumArtistName => self.album_artist_name.as_deref(),
+        }
+    }
+
+    /// Set or clear the value of a field
+    pub fn set(&mut self, field: RuleField, value: Option<String>) {
+        match field {
+            RuleField::TrackName => self.track_name = value,
+            RuleField::ArtistName => self.artist_name = value,
+            RuleField::AlbumName => self.album_name = value,
+            RuleField::AlbumArtistName => self.album_artist_name = value,
+        }
+    }
+}
+
+/// A before → after example for a `RewriteRule`
+///
+/// Fields missing from `input` get placeholder values (`"Track"`, `"Artist"`, no album).
+/// Fields missing from `expected` must be left unchanged, so an example with an empty
+/// `expected` checks that the rule leaves the input alone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleExample {
+    /// Field values before the rule runs
+    pub input: FieldValues,
+    /// Field values the rule should produce
+    #[serde(default, skip_serializing_if = "FieldValues::is_empty")]
+    pub expected: FieldValues,
+}
+
+impl RuleExample {
+    /// Example rewriting a single field from `input` to `expected`
+    #[must_use]
+    pub fn new(field: RuleField, input: &str, expected: &str) -> Self {
+        Self {
+            input: FieldValues::new().with(field, input),
+            expected: FieldValues::new().with(field, expected),
+        }
+    }
+
+    /// Example that the rule must leave unchanged
+    #[must_use]
+    pub fn unchanged(field: RuleField, input: &str) -> Self {
+        Self {
+            input: FieldValues::new().with(field, input),
+            expected: FieldValues::new(),
+        }
+    }
+
+    /// Parse a single-field example written as `input → expected` (or `input -> expected`)
+    #[must_use]
+    pub fn parse(field: RuleField, text: &str) -> Option<Self> {
+        let (input, expected) = text.split_once('→').or_else(|| text.split_once("->"))?;
+        Some(Self::new(field, input.trim(), expected.trim()))
+    }
+
+    /// The example input as a track
+    #[must_use]
+    pub fn input_track(&self) -> Track {
+        Track {
+            name: self
+                .input
+                .get(RuleField::TrackName)
+                .unwrap_or("Track")
+                .to_string(),
+            artist: self
+                .input
+                .get(RuleField::ArtistName)
+                .unwrap_or("Artist")
+                .to_string(),
+            album: self.input.album_name.clone(),
+            album_artist: self.input.album_artist_name.clone(),
+            playcount: 1,
+            timestamp: None,
+        }
+    }
+}
+
+impl std::fmt::Display for RuleExample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let describe = |values: &FieldValues| {
+            RuleField::ALL
+                .into_iter()
+                .filter_map(|field| Some(format!("{field}: '{}'", values.get(field)?)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if self.expected.is_empty() {
+            write!(f, "{} (unchanged)", describe(&self.input))
+        } else {
+            write!(
+                f,
+                "{} → {}",
+                describe(&self.input),
+                describe(&self.expected)
+            )
+        }
+    }
+}
+
+/// An example a rule failed to produce
+///
+/// Indices are positions in the checked rule list and the rule's `examples` (0-based);
+/// `Display` shows them 1-based like `rules show`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExampleFailure {
+    /// The rule doesn't compile, so none of its examples can pass
+    InvalidRule { rule_index: usize, error: String },
+    /// A field doesn't have the expected value after applying the rule
+    Mismatch {
+        rule_index: usize,
+        example_index: usize,
+        field: RuleField,
+        input: Option<String>,
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+}
+
+impl ExampleFailure {
+    /// The rule the failure belongs to
+    #[must_use]
+    pub const fn rule_index(&self) -> usize {
+        match self {
+            Self::InvalidRule { rule_index, .. } | Self::Mismatch { rule_index, .. } => *rule_index,
+        }
+    }
+}
+
+impl std::fmt::Display for ExampleFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = |value: &Option<String>| {
+            value
+                .as_ref()
+                .map_or_else(|| "None".to_string(), |v| format!("'{v}'"))
+        };
+        match self {
+            Self::InvalidRule { rule_index, error } => {
+                write!(f, "rule #{} is invalid: {error}", rule_index + 1)
+            }
+            Self::Mismatch {
+                rule_index,
+                example_index,
+                field,
+                input,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "rule #{} example #{}: {field} {} should become {} but became {}",
+                rule_index + 1,
+                example_index + 1,
+                value(input),
+                value(expected),
+                value(actual)
+            ),
+        }
+    }
+}
+
+/// Run every rule against its examples, returning the examples that fail
+///
+/// Each example is checked the way the scrubber applies the rule: the rule only rewrites
+/// the input when it matches, including its exclusions and conditions.
+#[must_use]
+pub fn check_rule_examples(rules: &[RewriteRule]) -> Vec<ExampleFailure> {
+    let mut failures = Vec::new();
+
+    for (rule_index, rule) in rules.iter().enumerate() {
+        if rule.examples.is_empty() {
+            continue;
+        }
+        let compiled = match rule.compile() {
+            Ok(compiled) => compiled,
+            Err(e) => {
+                failures.push(ExampleFailure::InvalidRule {
+                    rule_index,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        for (example_index, example) in rule.examples.iter().enumerate() {
+            let before = create_no_op_edit(&example.input_track());
+            let mut after = before.clone();
+            if compiled.matches_scrobble_edit(&after) {
+                compiled.apply(&mut after);
+            }
+
+            for field in RuleField::ALL {
+                let expected = example
+                    .expected
+                    .get(field)
+                    .or_else(|| field.edit_value(&before));
+                let actual = field.edit_value(&after);
+                if actual != expected {
+                    failures.push(ExampleFailure::Mismatch {
+                        rule_index,
+                        example_index,
+                        field,
+                        input: field.edit_value(&before).map(str::to_string),
+                        expected: expected.map(str::to_string),
+                        actual: actual.map(str::to_string),
+                    });
+                }
+            }
+        }
+    }
+
+    failures
+}
+
+/// Total number of examples in a rule list
+#[must_use]
+pub fn example_count(rules: &[RewriteRule]) -> usize {
+    rules.iter().map(|rule| rule.examples.len()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn should_parse_single_field_examples() {
+        assert_eq!(
+            RuleExample::parse(RuleField::AlbumName, "Abbey Road (Remastered) → Abbey Road"),
+            Some(RuleExample::new(
+                RuleField::AlbumName,
+                "Abbey Road (Remastered)",
+                "Abbey Road"
+            ))
+        );
+        assert_eq!(
+            RuleExample::parse(RuleField::TrackName, "Song -> Song"),
+            Some(RuleExample::new(RuleField::TrackName, "Song", "Song"))
+        );
+        assert_eq!(RuleExample::parse(RuleField::TrackName, "no arrow"), None);
+    }
+}
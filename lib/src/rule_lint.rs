@@ -0,0 +1,487 @@
+//! Checks for problems in a list of rewrite rules
+//!
+//! Besides static checks (rules that don't compile, ignored flags), rules are run against
+//! sample tracks to find rules that are never reached, rules that undo each other and
+//! rules that keep changing their own output.
+
+use crate::rewrite::{create_no_op_edit, CompiledRewriteRule, RewriteRule, RuleField};
+use crate::rule_examples::RuleExample;
+use lastfm_edit::{ScrobbleEdit, Track};
+use std::collections::{BTreeSet, HashSet};
+
+/// Regex flags understood by `SdRule` and `RuleCondition`
+pub const KNOWN_FLAGS: &str = "imsce";
+
+/// Number of passes over the whole rule list before it is reported as not converging
+const MAX_PASSES: usize = 10;
+
+/// How serious a lint is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    Warning,
+    Error,
+}
+
+/// A problem found in a rule list
+///
+/// Rule indices are positions in the linted list (0-based); `Display` shows them 1-based
+/// like `rules show`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleLint {
+    /// The rule doesn't compile and is skipped by the scrubber
+    InvalidRule { index: usize, error: String },
+    /// The rule uses flags that are silently ignored
+    UnknownFlags {
+        index: usize,
+        location: String,
+        flags: String,
+    },
+    /// Applying the rule to its own output changes it again
+    NotIdempotent {
+        index: usize,
+        input: String,
+        first: String,
+        second: String,
+    },
+    /// Every sample the rule would change is rewritten by earlier rules first
+    Shadowed {
+        index: usize,
+        by: Vec<usize>,
+        samples: usize,
+    },
+    /// A later rule undoes the change of an earlier one
+    Oscillating {
+        first: usize,
+        second: usize,
+        input: String,
+        output: String,
+    },
+    /// Applying the whole list repeatedly never settles
+    DoesNotConverge { input: String, passes: usize },
+}
+
+impl RuleLint {
+    #[must_use]
+    pub const fn severity(&self) -> LintSeverity {
+        match self {
+            Self::InvalidRule { .. } => LintSeverity::Error,
+            _ => LintSeverity::Warning,
+        }
+    }
+
+    /// The rules involved in this lint
+    #[must_use]
+    pub fn rule_indices(&self) -> Vec<usize> {
+        match self {
+            Self::InvalidRule { index, .. }
+            | Self::UnknownFlags { index, .. }
+            | Self::NotIdempotent { index, .. } => vec![*index],
+            Self::Shadowed { index, by, .. } => {
+                std::iter::once(*index).chain(by.iter().copied()).collect()
+            }
+            Self::Oscillating { first, second, .. } => vec![*first, *second],
+            Self::DoesNotConverge { .. } => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for RuleLint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidRule { index, error } => {
+                write!(f, "rule #{} is invalid: {error}", index + 1)
+            }
+            Self::UnknownFlags {
+                index,
+                location,
+                flags,
+            } => write!(
+                f,
+                "rule #{} {location} has unknown flags '{flags}' (known: {KNOWN_FLAGS})",
+                index + 1
+            ),
+            Self::NotIdempotent {
+                index,
+                input,
+                first,
+                second,
+            } => write!(
+                f,
+                "rule #{} is not idempotent: {input} → {first} → {second}",
+                index + 1
+            ),
+            Self::Shadowed { index, by, samples } => {
+                let by = by
+                    .iter()
+                    .map(|i| format!("#{}", i + 1))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "rule #{} never fires: on all {samples} matching samples, rule {by} rewrites first",
+                    index + 1
+                )
+            }
+            Self::Oscillating {
+                first,
+                second,
+                input,
+                output,
+            } => write!(
+                f,
+                "rules #{} and #{} undo each other: {input} → {output} → {input}",
+                first + 1,
+                second + 1
+            ),
+            Self::DoesNotConverge { input, passes } => write!(
+                f,
+                "rules keep changing {input} after {passes} passes over the rule list"
+            ),
+        }
+    }
+}
+
+/// Lint a list of rules, using `samples` and the rules' example inputs to exercise them
+///
+/// Each rule is also checked against its own output. Lints are returned grouped by kind,
+/// in rule order.
+#[must_use]
+pub fn lint_rules(rules: &[RewriteRule], samples: &[Track]) -> Vec<RuleLint> {
+    let mut lints = Vec::new();
+
+    let mut valid = Vec::new();
+    for (index, rule) in rules.iter().enumerate() {
+        match rule.compile() {
+            Ok(compiled) => valid.push((index, compiled)),
+            Err(e) => lints.push(RuleLint::InvalidRule {
+                index,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    for (index, rule) in rules.iter().enumerate() {
+        lints.extend(unknown_flags(index, rule));
+    }
+
+    let example_inputs: Vec<Track> = rules
+        .iter()
+        .flat_map(|rule| &rule.examples)
+        .map(RuleExample::input_track)
+        .collect();
+    let samples: Vec<ScrobbleEdit> = samples
+        .iter()
+        .chain(&example_inputs)
+        .map(create_no_op_edit)
+        .collect();
+
+    lints.extend(check_idempotence(&valid, &samples));
+    lints.extend(check_shadowing(&valid, &samples));
+    lints.extend(check_oscillation(&valid, &samples));
+    lints.extend(check_convergence(&valid, &samples));
+
+    lints
+}
+
+fn unknown_flags(index: usize, rule: &RewriteRule) -> Vec<RuleLint> {
+    let unknown = |flags: Option<&str>| -> Option<String> {
+        let unknown: String = flags?
+            .chars()
+            .filter(|c| !KNOWN_FLAGS.contains(*c))
+            .collect();
+        (!unknown.is_empty()).then_some(unknown)
+    };
+
+    let field_flags = RuleField::ALL.into_iter().filter_map(|field| {
+        let sd_rule = rule.field_rule(field)?;
+        Some((field.to_string(), unknown(sd_rule.flags.as_deref())?))
+    });
+    let condition_flags = rule
+        .conditions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, condition)| {
+            Some((
+                format!("condition {}", i + 1),
+                unknown(condition.flags.as_deref())?,
+            ))
+        });
+
+    field_flags
+        .chain(condition_flags)
+        .map(|(location, flags)| RuleLint::UnknownFlags {
+            index,
+            location,
+            flags,
+        })
+        .collect()
+}
+
+/// Apply a rule the way the scrubber does, returning the new edit if it changed anything
+fn apply_if_matches(rule: &CompiledRewriteRule, edit: &ScrobbleEdit) -> Option<ScrobbleEdit> {
+    if !rule.matches_scrobble_edit(edit) {
+        return None;
+    }
+    let mut result = edit.clone();
+    rule.apply(&mut result).then_some(result)
+}
+
+fn same_values(a: &ScrobbleEdit, b: &ScrobbleEdit) -> bool {
+    RuleField::ALL
+        .into_iter()
+        .all(|field| field.edit_value(a) == field.edit_value(b))
+}
+
+fn describe(edit: &ScrobbleEdit) -> String {
+    let mut description = format!(
+        "'{}' by '{}'",
+        edit.track_name.as_deref().unwrap_or(""),
+        edit.artist_name
+    );
+    if let Some(album) = &edit.album_name {
+        description.push_str(&format!(" on '{album}'"));
+    }
+    description
+}
+
+fn check_idempotence(
+    rules: &[(usize, CompiledRewriteRule)],
+    samples: &[ScrobbleEdit],
+) -> Vec<RuleLint> {
+    let mut lints = Vec::new();
+    for (index, rule) in rules {
+        for sample in samples {
+            let Some(first) = apply_if_matches(rule, sample) else {
+                continue;
+            };
+            if let Some(second) = apply_if_matches(rule, &first) {
+                lints.push(RuleLint::NotIdempotent {
+                    index: *index,
+                    input: describe(sample),
+                    first: describe(&first),
+                    second: describe(&second),
+                });
+                break;
+            }
+        }
+    }
+    lints
+}
+
+fn check_shadowing(
+    rules: &[(usize, CompiledRewriteRule)],
+    samples: &[ScrobbleEdit],
+) -> Vec<RuleLint> {
+    let mut lints = Vec::new();
+
+    for (position, (index, rule)) in rules.iter().enumerate() {
+        let mut matching_samples = 0;
+        let mut fires = false;
+        let mut shadowed_by = BTreeSet::new();
+
+        for sample in samples {
+            // Only samples this rule would change on its own are relevant
+            if apply_if_matches(rule, sample).is_none() {
+                continue;
+            }
+            matching_samples += 1;
+
+            // Run the earlier rules in order and see whether this one still applies
+            let mut edit = sample.clone();
+            let mut blocked_by = None;
+            for (earlier_index, earlier_rule) in &rules[..position] {
+                if let Some(next) = apply_if_matches(earlier_rule, &edit) {
+                    edit = next;
+                    if apply_if_matches(rule, &edit).is_none() {
+                        blocked_by = Some(*earlier_index);
+                        break;
+                    }
+                }
+            }
+
+            match blocked_by {
+                Some(earlier_index) => {
+                    shadowed_by.insert(earlier_index);
+                }
+                None => {
+                    fires = true;
+                    break;
+                }
+            }
+        }
+
+        if matching_samples > 0 && !fires {
+            lints.push(RuleLint::Shadowed {
+                index: *index,
+                by: shadowed_by.into_iter().collect(),
+                samples: matching_samples,
+            });
+        }
+    }
+
+    lints
+}
+
+fn check_oscillation(
+    rules: &[(usize, CompiledRewriteRule)],
+    samples: &[ScrobbleEdit],
+) -> Vec<RuleLint> {
+    let mut lints = Vec::new();
+    let mut reported = HashSet::new();
+
+    for (first_index, first_rule) in rules {
+        for sample in samples {
+            let Some(first) = apply_if_matches(first_rule, sample) else {
+                continue;
+            };
+            for (second_index, second_rule) in rules {
+                let pair = (
+                    (*first_index).min(*second_index),
+                    (*first_index).max(*second_index),
+                );
+                if second_index == first_index || reported.contains(&pair) {
+                    continue;
+                }
+                let Some(second) = apply_if_matches(second_rule, &first) else {
+                    continue;
+                };
+                if same_values(&second, sample) {
+                    reported.insert(pair);
+                    lints.push(RuleLint::Oscillating {
+                        first: *first_index,
+                        second: *second_index,
+                        input: describe(sample),
+                        output: describe(&first),
+                    });
+                }
+            }
+        }
+    }
+
+    lints
+}
+
+fn check_convergence(
+    rules: &[(usize, CompiledRewriteRule)],
+    samples: &[ScrobbleEdit],
+) -> Option<RuleLint> {
+    for sample in samples {
+        let mut edit = sample.clone();
+        for pass in 1..=MAX_PASSES {
+            let mut changed = false;
+            for (_, rule) in rules {
+                if let Some(next) = apply_if_matches(rule, &edit) {
+                    edit = next;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+            if pass == MAX_PASSES {
+                // One example is enough; the other lints point at the rules involved
+                return Some(RuleLint::DoesNotConverge {
+                    input: describe(sample),
+                    passes: MAX_PASSES,
+                });
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewrite::{ReplacementMode, SdRule};
+
+    fn track(name: &str) -> Track {
+        Track {
+            name: name.to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            album_artist: None,
+            playcount: 1,
+            timestamp: None,
+        }
+    }
+
+    fn track_rule(find: &str, replace: &str) -> RewriteRule {
+        RewriteRule::new().with_track_name(SdRule::new(find, replace))
+    }
+
+    #[test_log::test]
+    fn should_report_invalid_rules_and_unknown_flags() {
+        let rules = vec![
+            track_rule("(unclosed", "$1"),
+            RewriteRule::new().with_track_name(SdRule::new("a", "b").with_flags("ix")),
+        ];
+        let lints = lint_rules(&rules, &[]);
+
+        assert!(matches!(lints[0], RuleLint::InvalidRule { index: 0, .. }));
+        assert_eq!(
+            lints[1],
+            RuleLint::UnknownFlags {
+                index: 1,
+                location: "track_name".to_string(),
+                flags: "x".to_string(),
+            }
+        );
+        assert_eq!(lints[0].severity(), LintSeverity::Error);
+    }
+
+    #[test_log::test]
+    fn should_report_rules_that_are_not_idempotent() {
+        let rules = vec![track_rule("^(.+)$", "$1 (Live)")];
+        let lints = lint_rules(&rules, &[track("Song")]);
+
+        assert!(lints
+            .iter()
+            .any(|lint| matches!(lint, RuleLint::NotIdempotent { index: 0, .. })));
+        assert!(lints
+            .iter()
+            .any(|lint| matches!(lint, RuleLint::DoesNotConverge { .. })));
+    }
+
+    #[test_log::test]
+    fn should_report_shadowed_rules() {
+        let rules = vec![
+            track_rule(r"^(.+) - .*Remaster.*$", "$1"),
+            track_rule(r"^(.+) - \d{4} Remaster$", "$1"),
+            track_rule(r"^(.+) \(Live\)$", "$1"),
+        ];
+        let samples = [track("Song - 2011 Remaster"), track("Song (Live)")];
+        let lints = lint_rules(&rules, &samples);
+
+        assert_eq!(
+            lints,
+            vec![RuleLint::Shadowed {
+                index: 1,
+                by: vec![0],
+                samples: 1,
+            }]
+        );
+    }
+
+    #[test_log::test]
+    fn should_report_rules_that_undo_each_other() {
+        let rules = vec![
+            RewriteRule::new().with_track_name(
+                SdRule::new(" feat. ", " ft. ").with_mode(ReplacementMode::AllMatches),
+            ),
+            RewriteRule::new().with_track_name(
+                SdRule::new(" ft. ", " feat. ").with_mode(ReplacementMode::AllMatches),
+            ),
+        ];
+        let lints = lint_rules(&rules, &[track("Song feat. Someone")]);
+
+        assert!(lints.iter().any(|lint| matches!(
+            lint,
+            RuleLint::Oscillating {
+                first: 0,
+                second: 1,
+                ..
+            }
+        )));
+    }
+}
@@ -0,0 +1,360 @@
+//! Rewrite rules kept as a directory of TOML/JSON files.
+//!
+//! Each `.toml` or `.json` file in the directory holds either a single [`RewriteRule`] or a
+//! [`RulePack`] with several rules and alias tables. Files are loaded in file name order, so
+//! a zero-padded numeric prefix (as written by [`export_rules_dir`]) keeps the rule order
+//! stable. Hidden files and files with other extensions are ignored, so the directory can
+//! live in a git repository next to a README.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::alias_rules::AliasRule;
+use crate::config::{RuleFileFormat, RulesDirConfig, RulesSource};
+use crate::persistence::{RewriteRulesState, StateStorage};
+use crate::rewrite::RewriteRule;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RulesDirError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid rule file {path}: {message}")]
+    InvalidFile { path: PathBuf, message: String },
+    #[error("Failed to serialize rules: {0}")]
+    Serialization(String),
+    #[error("{0} already contains rule files (use --clean to replace them)")]
+    NotEmpty(PathBuf),
+    #[error("Rules are loaded from the rules directory {0}, edit the rule files there instead")]
+    DirectorySource(PathBuf),
+}
+
+/// Several rules and alias tables kept in one file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RulePack {
+    /// Optional name for the pack, for the reader's benefit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RewriteRule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alias_rules: Vec<AliasRule>,
+}
+
+impl RuleFileFormat {
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Toml => "toml",
+            Self::Json => "json",
+        }
+    }
+
+    fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The rule files in `dir`, in load order
+pub fn rule_files(dir: &Path) -> Result<Vec<PathBuf>, RulesDirError> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+        if !hidden && path.is_file() && RuleFileFormat::of(&path).is_some() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Parse a rule file holding either a single rule or a rule pack
+pub fn parse_rule_file(path: &Path) -> Result<RulePack, RulesDirError> {
+    let invalid = |message: String| RulesDirError::InvalidFile {
+        path: path.to_path_buf(),
+        message,
+    };
+    let content = std::fs::read_to_string(path)?;
+    let value: Value = match RuleFileFormat::of(path) {
+        Some(RuleFileFormat::Toml) => {
+            toml::from_str(&content).map_err(|e| invalid(e.to_string()))?
+        }
+        Some(RuleFileFormat::Json) => {
+            serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?
+        }
+        None => return Err(invalid("expected a .toml or .json file".to_string())),
+    };
+
+    let is_pack = value.get("rules").is_some() || value.get("alias_rules").is_some();
+    if is_pack {
+        serde_json::from_value(value).map_err(|e| invalid(e.to_string()))
+    } else {
+        let rule: RewriteRule =
+            serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+        Ok(RulePack {
+            name: None,
+            rules: vec![rule],
+            alias_rules: Vec::new(),
+        })
+    }
+}
+
+/// Load every rule file in `dir`
+pub fn load_rules_dir(dir: &Path) -> Result<RewriteRulesState, RulesDirError> {
+    let mut state = RewriteRulesState::default();
+    for path in rule_files(dir)? {
+        let pack = parse_rule_file(&path)?;
+        state.rewrite_rules.extend(pack.rules);
+        state.alias_rules.extend(pack.alias_rules);
+    }
+    log::debug!(
+        "Loaded {} rewrite rules and {} alias tables from {}",
+        state.rewrite_rules.len(),
+        state.alias_rules.len(),
+        dir.display()
+    );
+    Ok(state)
+}
+
+/// Write `state` to `dir`, one file per rewrite rule and per alias table
+///
+/// Fails if `dir` already holds rule files, unless `clean` is set, in which case they are
+/// removed first. Returns the written files.
+pub fn export_rules_dir(
+    dir: &Path,
+    state: &RewriteRulesState,
+    format: RuleFileFormat,
+    clean: bool,
+) -> Result<Vec<PathBuf>, RulesDirError> {
+    std::fs::create_dir_all(dir)?;
+    let existing = rule_files(dir)?;
+    if !existing.is_empty() {
+        if !clean {
+            return Err(RulesDirError::NotEmpty(dir.to_path_buf()));
+        }
+        for path in existing {
+            std::fs::remove_file(path)?;
+        }
+    }
+
+    let mut written = Vec::new();
+    let mut write = |stem: String, content: String| -> Result<(), RulesDirError> {
+        let path = dir.join(format!("{stem}.{}", format.extension()));
+        std::fs::write(&path, content)?;
+        written.push(path);
+        Ok(())
+    };
+
+    // Files load in name order, so every prefix needs the same number of digits
+    let file_count = state.rewrite_rules.len() + state.alias_rules.len();
+    let width = file_count.to_string().len().max(3);
+    for (index, rule) in state.rewrite_rules.iter().enumerate() {
+        let stem = format!(
+            "{:0width$}-{}",
+            index + 1,
+            slug(rule.name.as_deref(), "rule")
+        );
+        write(stem, serialize(rule, format)?)?;
+    }
+    for (index, alias_rule) in state.alias_rules.iter().enumerate() {
+        let stem = format!(
+            "{:0width$}-aliases-{}",
+            state.rewrite_rules.len() + index + 1,
+            slug(alias_rule.name.as_deref(), "table")
+        );
+        let pack = RulePack {
+            name: alias_rule.name.clone(),
+            rules: Vec::new(),
+            alias_rules: vec![alias_rule.clone()],
+        };
+        write(stem, serialize(&pack, format)?)?;
+    }
+    Ok(written)
+}
+
+fn serialize<T: Serialize>(value: &T, format: RuleFileFormat) -> Result<String, RulesDirError> {
+    match format {
+        RuleFileFormat::Toml => {
+            toml::to_string_pretty(value).map_err(|e| RulesDirError::Serialization(e.to_string()))
+        }
+        RuleFileFormat::Json => serde_json::to_string_pretty(value)
+            .map(|json| json + "\n")
+            .map_err(|e| RulesDirError::Serialization(e.to_string())),
+    }
+}
+
+/// File name friendly version of a rule name
+fn slug(name: Option<&str>, fallback: &str) -> String {
+    let slug = name
+        .unwrap_or_default()
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        fallback.to_string()
+    } else {
+        slug.chars().take(48).collect()
+    }
+}
+
+/// Names, sizes and modification times of the rule files, used to notice changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RulesDirFingerprint(Vec<(PathBuf, u64, Option<SystemTime>)>);
+
+impl RulesDirFingerprint {
+    pub fn of(dir: &Path) -> Result<Self, RulesDirError> {
+        rule_files(dir)?
+            .into_iter()
+            .map(|path| {
+                let metadata = std::fs::metadata(&path)?;
+                Ok((path, metadata.len(), metadata.modified().ok()))
+            })
+            .collect::<Result<Vec<_>, RulesDirError>>()
+            .map(Self)
+    }
+}
+
+/// Refuse changes to the stored rules when the rules directory is the source of truth
+///
+/// The directory replaces the stored rules on every start and reload, so a rule added or
+/// removed only in the storage would be lost.
+pub fn ensure_rules_editable(config: Option<&RulesDirConfig>) -> Result<(), RulesDirError> {
+    match config {
+        Some(config) if config.source == RulesSource::Directory => {
+            Err(RulesDirError::DirectorySource(PathBuf::from(&config.path)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Replace the stored rules with the rules directory, if it is the source of truth
+///
+/// Returns the loaded rules, or `None` when the storage is the source of truth.
+pub async fn sync_storage_from_dir<S: StateStorage>(
+    config: &RulesDirConfig,
+    storage: &mut S,
+) -> Result<Option<RewriteRulesState>, Box<dyn std::error::Error + Send + Sync>> {
+    if config.source != RulesSource::Directory {
+        return Ok(None);
+    }
+    let state = load_rules_dir(Path::new(&config.path))?;
+    storage.save_rewrite_rules_state(&state).await?;
+    log::info!(
+        "Loaded {} rewrite rules and {} alias tables from {}",
+        state.rewrite_rules.len(),
+        state.alias_rules.len(),
+        config.path
+    );
+    Ok(Some(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewrite::{RuleField, SdRule};
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("scrobble-scrubber-rules-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn sample_state() -> RewriteRulesState {
+        RewriteRulesState {
+            rewrite_rules: vec![
+                RewriteRule::new()
+                    .with_track_name(SdRule::new(r"(.*) - \d{4} Remaster", "$1"))
+                    .with_name("Strip remaster"),
+                RewriteRule::new().with_artist_name(SdRule::new("^The Beatles$", "Beatles")),
+            ],
+            alias_rules: vec![AliasRule::new(RuleField::ArtistName)
+                .with_name("Artist spellings")
+                .with_alias("Sigur Rós", "Sigur Ros")],
+        }
+    }
+
+    #[test_log::test]
+    fn should_round_trip_rules_in_both_formats() {
+        for format in [RuleFileFormat::Toml, RuleFileFormat::Json] {
+            let dir = temp_dir();
+            let state = sample_state();
+
+            let written = export_rules_dir(&dir, &state, format, false).unwrap();
+            let loaded = load_rules_dir(&dir).unwrap();
+            std::fs::remove_dir_all(&dir).unwrap();
+
+            assert_eq!(written.len(), 3);
+            assert!(written[0].ends_with(format!("001-strip-remaster.{}", format.extension())));
+            assert_eq!(loaded.rewrite_rules, state.rewrite_rules);
+            assert_eq!(loaded.alias_rules, state.alias_rules);
+        }
+    }
+
+    #[test_log::test]
+    fn should_load_packs_and_skip_other_files() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("10-pack.toml"),
+            r#"
+name = "Remasters"
+
+[[rules]]
+name = "Strip remaster"
+requires_confirmation = false
+track_name = { find = '(.*) - Remastered', replace = "$1" }
+
+[[rules]]
+requires_confirmation = true
+album_name = { find = '(.*) \(Deluxe\)', replace = "$1" }
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("20-single.json"),
+            r#"{"requires_confirmation": false, "artist_name": {"find": "^Beatles$", "replace": "The Beatles"}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("README.md"), "# Rules").unwrap();
+        std::fs::write(dir.join(".draft.toml"), "not a rule").unwrap();
+
+        let loaded = load_rules_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.rewrite_rules.len(), 3);
+        assert_eq!(
+            loaded.rewrite_rules[0].name.as_deref(),
+            Some("Strip remaster")
+        );
+        assert!(loaded.rewrite_rules[1].requires_confirmation);
+        assert!(loaded.rewrite_rules[2].artist_name.is_some());
+    }
+
+    #[test_log::test]
+    fn export_should_refuse_existing_rule_files_unless_cleaning() {
+        let dir = temp_dir();
+        let state = sample_state();
+        export_rules_dir(&dir, &state, RuleFileFormat::Toml, false).unwrap();
+
+        let result = export_rules_dir(&dir, &state, RuleFileFormat::Json, false);
+        assert!(matches!(result, Err(RulesDirError::NotEmpty(_))));
+
+        export_rules_dir(&dir, &state, RuleFileFormat::Json, true).unwrap();
+        let files = rule_files(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(files.len(), 3);
+        assert!(files
+            .iter()
+            .all(|path| RuleFileFormat::of(path) == Some(RuleFileFormat::Json)));
+    }
+}
@@ -1,9 +1,11 @@
-use crate::persistence::{PendingEdit, PendingRewriteRule, RewriteRulesState};
-use crate::rewrite::{RewriteError, RewriteRule};
+use crate::alias_rules::{AliasRule, CompiledAliasRule};
+use crate::persistence::{EditMapping, PendingEdit, PendingRewriteRule, RewriteRulesState};
+use crate::rewrite::{create_no_op_edit, CompiledRuleSet, RewriteError, RewriteRule, RuleField};
 use async_trait::async_trait;
 use lastfm_edit::{ScrobbleEdit, Track};
 use std::error::Error;
 use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
 
 /// Generic error type for action providers
 #[derive(Debug)]
@@ -37,6 +39,7 @@ impl From<&str> for ActionProviderError {
 
 /// Represents a suggested action from an external source (LLM, API, etc.)
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum ScrubActionSuggestion {
     /// Suggest an immediate scrobble edit
     Edit(ScrobbleEdit),
@@ -49,12 +52,90 @@ pub enum ScrubActionSuggestion {
     NoAction,
 }
 
+/// The provider that set each field of a merged edit
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EditProvenance {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artist_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album_artist_name: Option<String>,
+}
+
+impl EditProvenance {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.track_name.is_none()
+            && self.artist_name.is_none()
+            && self.album_name.is_none()
+            && self.album_artist_name.is_none()
+    }
+
+    /// The provider that set a field, if any
+    #[must_use]
+    pub fn get(&self, field: RuleField) -> Option<&str> {
+        match field {
+            RuleField::TrackName => self.track_name.as_deref(),
+            RuleField::ArtistName => self.artist_name.as_deref(),
+            RuleField::AlbumName => self.album_name.as_deref(),
+            RuleField::AlbumArtistName => self.album_artist_name.as_deref(),
+        }
+    }
+
+    /// Record the provider that set a field
+    pub fn set(&mut self, field: RuleField, provider_name: &str) {
+        let provider_name = Some(provider_name.to_string());
+        match field {
+            RuleField::TrackName => self.track_name = provider_name,
+            RuleField::ArtistName => self.artist_name = provider_name,
+            RuleField::AlbumName => self.album_name = provider_name,
+            RuleField::AlbumArtistName => self.album_artist_name = provider_name,
+        }
+    }
+
+    /// The providers that set any field, without duplicates, in field order
+    #[must_use]
+    pub fn providers(&self) -> Vec<&str> {
+        let mut providers = Vec::new();
+        for provider in RuleField::ALL
+            .into_iter()
+            .filter_map(|field| self.get(field))
+        {
+            if !providers.contains(&provider) {
+                providers.push(provider);
+            }
+        }
+        providers
+    }
+}
+
+impl fmt::Display for EditProvenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields: Vec<String> = RuleField::ALL
+            .into_iter()
+            .filter_map(|field| Some(format!("{field} by {}", self.get(field)?)))
+            .collect();
+        f.write_str(&fields.join(", "))
+    }
+}
+
 /// Context wrapper for suggestions that includes confirmation requirements
 #[derive(Debug, Clone)]
 pub struct SuggestionWithContext {
     pub suggestion: ScrubActionSuggestion,
     pub requires_confirmation: bool,
     pub provider_name: String,
+    /// Which provider set each field, for edits merged from several providers
+    pub provenance: EditProvenance,
+    /// How sure the provider is of the suggestion (0.0-1.0), when it reports one
+    pub confidence: Option<f32>,
+    /// Why the provider made the suggestion, shown alongside it for review
+    pub explanation: Option<String>,
+    /// The logged change an undo suggestion reverts, kept on the pending edit it may become
+    pub reverts: Option<EditMapping>,
 }
 
 impl SuggestionWithContext {
@@ -67,6 +148,61 @@ impl SuggestionWithContext {
             suggestion,
             requires_confirmation,
             provider_name,
+            provenance: EditProvenance::default(),
+            confidence: None,
+            explanation: None,
+            reverts: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    #[must_use]
+    pub fn with_explanation(mut self, explanation: impl Into<String>) -> Self {
+        self.explanation = Some(explanation.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_reverts(mut self, reverts: EditMapping) -> Self {
+        self.reverts = Some(reverts);
+        self
+    }
+
+    /// A short description of the confidence and explanation, for logs and events
+    #[must_use]
+    pub fn confidence_summary(&self) -> Option<String> {
+        match (self.confidence, self.explanation.as_deref()) {
+            (Some(confidence), Some(explanation)) => {
+                Some(format!("confidence {confidence:.2}: {explanation}"))
+            }
+            (Some(confidence), None) => Some(format!("confidence {confidence:.2}")),
+            (None, Some(explanation)) => Some(explanation.to_string()),
+            (None, None) => None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_provenance(mut self, provenance: EditProvenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// The providers whose changes make up this suggestion
+    #[must_use]
+    pub fn contributing_providers(&self) -> Vec<String> {
+        if self.provenance.is_empty() {
+            vec![self.provider_name.clone()]
+        } else {
+            self.provenance
+                .providers()
+                .into_iter()
+                .map(str::to_string)
+                .collect()
         }
     }
 
@@ -100,6 +236,31 @@ impl SuggestionWithContext {
     }
 }
 
+/// What happened when a rewrite rule matched a track
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleOutcome {
+    /// The rule matched but didn't change anything
+    Unchanged,
+    /// The rule's changes became part of the suggested edit
+    Produced,
+    /// The rule's changes were rejected by MusicBrainz confirmation
+    RejectedByMusicBrainz,
+}
+
+/// A rewrite rule matching one of the analyzed tracks, used for per-rule statistics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleActivity {
+    /// `RewriteRule::stats_key` of the rule
+    pub rule_key: String,
+    /// Name of the rule, if it has one
+    pub rule_name: Option<String>,
+    /// Index of the track in the analyzed slice
+    pub track_index: usize,
+    pub outcome: RuleOutcome,
+    /// Provider whose suggestion contains the rule's changes
+    pub provider_name: String,
+}
+
 /// Trait for external providers that can suggest scrobble actions
 #[async_trait]
 pub trait ScrubActionProvider: Send + Sync {
@@ -120,44 +281,140 @@ pub trait ScrubActionProvider: Send + Sync {
 
     /// Get a human-readable name for this provider
     fn provider_name(&self) -> &str;
+
+    /// Take the rule activity recorded by the most recent `analyze_tracks` call
+    ///
+    /// Providers that don't apply rewrite rules record nothing.
+    fn take_rule_activity(&self) -> Vec<RuleActivity> {
+        Vec::new()
+    }
+
+    /// Replace the rewrite rules used by later `analyze_tracks` calls
+    ///
+    /// Providers that don't apply rewrite rules ignore this.
+    fn reload_rules(&self, _rules_state: &RewriteRulesState) {}
 }
 
 /// Rewrite rules-based action provider
 pub struct RewriteRulesScrubActionProvider {
-    rules: Vec<RewriteRule>,
+    rules: RwLock<Arc<LoadedRules>>,
+    activity: Mutex<Vec<RuleActivity>>,
+}
+
+/// The compiled rules a provider applies, swapped out as a whole on reload
+#[derive(Clone)]
+struct LoadedRules {
+    rules: CompiledRuleSet,
+    alias_rules: Vec<CompiledAliasRule>,
+}
+
+impl LoadedRules {
+    fn compile(rules_state: &RewriteRulesState) -> Self {
+        let (rules, invalid_rules) =
+            CompiledRuleSet::compile_valid(rules_state.rewrite_rules.clone());
+        for invalid in &invalid_rules {
+            log::warn!("Skipping rewrite rule that failed to compile: {invalid}");
+        }
+        Self {
+            rules,
+            alias_rules: compile_alias_rules(&rules_state.alias_rules),
+        }
+    }
+}
+
+/// Compile alias tables, skipping (and logging) any that fail to compile
+fn compile_alias_rules(alias_rules: &[AliasRule]) -> Vec<CompiledAliasRule> {
+    alias_rules
+        .iter()
+        .filter_map(|alias_rule| match alias_rule.compile() {
+            Ok(compiled) => Some(compiled),
+            Err(e) => {
+                log::warn!(
+                    "Skipping alias rule '{}' that failed to compile: {e}",
+                    alias_rule.name.as_deref().unwrap_or("Unnamed")
+                );
+                None
+            }
+        })
+        .collect()
 }
 
 impl RewriteRulesScrubActionProvider {
+    /// Create a provider from stored rules, skipping (and logging) any that fail to compile
     #[must_use]
     pub fn new(rules_state: &RewriteRulesState) -> Self {
+        Self::from_loaded(LoadedRules::compile(rules_state))
+    }
+
+    /// Create a provider from a list of rules, skipping (and logging) any that fail to compile
+    #[must_use]
+    pub fn from_rules(rules: Vec<RewriteRule>) -> Self {
+        Self::new(&RewriteRulesState {
+            rewrite_rules: rules,
+            alias_rules: Vec::new(),
+        })
+    }
+
+    /// Create a provider from an already compiled rule set
+    #[must_use]
+    pub fn from_compiled(rules: CompiledRuleSet) -> Self {
+        Self::from_loaded(LoadedRules {
+            rules,
+            alias_rules: Vec::new(),
+        })
+    }
+
+    fn from_loaded(loaded: LoadedRules) -> Self {
         Self {
-            rules: rules_state.rewrite_rules.clone(),
+            rules: RwLock::new(Arc::new(loaded)),
+            activity: Mutex::new(Vec::new()),
         }
     }
 
+    /// Add alias tables, applied after the rewrite rules. Tables that fail to compile are
+    /// skipped and logged.
     #[must_use]
-    pub const fn from_rules(rules: Vec<RewriteRule>) -> Self {
-        Self { rules }
+    pub fn with_alias_rules(mut self, alias_rules: &[AliasRule]) -> Self {
+        let loaded = self.rules.get_mut().unwrap_or_else(PoisonError::into_inner);
+        Arc::make_mut(loaded)
+            .alias_rules
+            .extend(compile_alias_rules(alias_rules));
+        self
+    }
+
+    /// The rules in use, kept alive across awaits even if they are reloaded meanwhile
+    fn loaded(&self) -> Arc<LoadedRules> {
+        self.rules
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
     }
 
     // Apply rules sequentially to a track, gating on per-rule MusicBrainz confirmation when requested.
-    // Returns Some((final_edit, requires_confirmation)) if any changes applied, otherwise None.
+    // Returns Some((final_edit, requires_confirmation, applied_rule_names)) if any changes applied,
+    // otherwise None.
     async fn apply_rules_sequentially(
         &self,
+        loaded: &LoadedRules,
         track: &Track,
-    ) -> Result<Option<(ScrobbleEdit, bool)>, ActionProviderError> {
+        track_index: usize,
+    ) -> Result<Option<(ScrobbleEdit, bool, Vec<String>)>, ActionProviderError> {
         let mut edit = crate::rewrite::create_no_op_edit(track);
         let mut any_changes = false;
         let mut requires_confirmation_applied = false;
+        let mut applied_rules = Vec::new();
+        let mut candidates = loaded.rules.prefilter_edit(&edit);
 
-        for rule in &self.rules {
-            if !rule.matches_scrobble_edit(&edit)? {
+        for (index, compiled_rule) in loaded.rules.rules().iter().enumerate() {
+            if !candidates[index] || !compiled_rule.matches_scrobble_edit(&edit) {
                 continue;
             }
 
+            let rule = compiled_rule.rule();
             let mut candidate = edit.clone();
-            let changed = rule.apply(&mut candidate)?;
+            let changed = compiled_rule.apply(&mut candidate);
             if !changed {
+                self.record_activity(rule, track_index, RuleOutcome::Unchanged);
                 continue;
             }
 
@@ -176,23 +433,54 @@ impl RewriteRulesScrubActionProvider {
                         track.name,
                         track.album.as_deref().unwrap_or("none")
                     );
+                    self.record_activity(rule, track_index, RuleOutcome::RejectedByMusicBrainz);
                     continue; // Skip this rule only
                 }
             }
 
             // Accept candidate
+            self.record_activity(rule, track_index, RuleOutcome::Produced);
             edit = candidate;
             any_changes = true;
             requires_confirmation_applied |= rule.requires_confirmation;
+            applied_rules.push(rule.name.clone().unwrap_or_else(|| "Unnamed".to_string()));
+            candidates = loaded.rules.prefilter_edit(&edit);
+        }
+
+        // Canonicalize the rewritten values with the alias tables
+        for alias_rule in &loaded.alias_rules {
+            if alias_rule.apply(&mut edit) {
+                any_changes = true;
+                requires_confirmation_applied |= alias_rule.rule().requires_confirmation;
+                applied_rules.push(
+                    alias_rule
+                        .rule()
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| "Unnamed alias".to_string()),
+                );
+            }
         }
 
         if any_changes {
-            Ok(Some((edit, requires_confirmation_applied)))
+            Ok(Some((edit, requires_confirmation_applied, applied_rules)))
         } else {
             Ok(None)
         }
     }
 
+    fn record_activity(&self, rule: &RewriteRule, track_index: usize, outcome: RuleOutcome) {
+        if let Ok(mut activity) = self.activity.lock() {
+            activity.push(RuleActivity {
+                rule_key: rule.stats_key(),
+                rule_name: rule.name.clone(),
+                track_index,
+                outcome,
+                provider_name: self.provider_name().to_string(),
+            });
+        }
+    }
+
     // Verify that the candidate edit corresponds to a real MB match using rule-specific filters
     async fn verify_with_musicbrainz_using_rule_filters(
         candidate: &ScrobbleEdit,
@@ -206,6 +494,33 @@ impl RewriteRulesScrubActionProvider {
             .unwrap_or_else(|| track.name.clone());
         let album = candidate.album_name.clone();
 
+        // Stripping an edition suffix ("XO (Deluxe Edition)" → "XO") is only right for tracks
+        // on the standard edition; bonus tracks keep the special edition's album name
+        if let (Some(original_album), Some(new_album)) = (track.album.as_deref(), album.as_deref())
+        {
+            if crate::musicbrainz::MusicBrainzScrubActionProvider::is_edition_suffix_removal(
+                original_album,
+                new_album,
+            ) {
+                let provider = match release_filters {
+                    Some(filters) => {
+                        crate::musicbrainz::MusicBrainzScrubActionProvider::with_filters(
+                            0.8,
+                            20,
+                            filters.clone(),
+                        )
+                    }
+                    None => crate::musicbrainz::MusicBrainzScrubActionProvider::new(0.8, 20),
+                };
+                return provider
+                    .verify_track_on_standard_edition(&artist, &title, new_album)
+                    .await
+                    .map_err(|e| {
+                        ActionProviderError(format!("MusicBrainz verification failed: {e}"))
+                    });
+            }
+        }
+
         // Use rule-specific filters if provided, otherwise use default MusicBrainz provider behavior
         if let Some(filters) = release_filters {
             // Use static method with custom filters for this specific verification
@@ -239,13 +554,19 @@ impl ScrubActionProvider for RewriteRulesScrubActionProvider {
         _pending_rules: Option<&[crate::persistence::PendingRewriteRule]>,
     ) -> Result<Vec<(usize, Vec<SuggestionWithContext>)>, Self::Error> {
         let mut results = Vec::new();
+        if let Ok(mut activity) = self.activity.lock() {
+            activity.clear();
+        }
+        let loaded = self.loaded();
 
         for (index, track) in tracks.iter().enumerate() {
             log::trace!("RewriteRulesScrubActionProvider analyzing track {index}: '{track_name}' by '{track_artist}' against {rules_count} rules",
-                   track_name = track.name, track_artist = track.artist, rules_count = self.rules.len());
+                   track_name = track.name, track_artist = track.artist, rules_count = loaded.rules.len());
 
             // Early continue if no rules apply
-            if !crate::rewrite::any_rules_apply(&self.rules, track)? {
+            if !loaded.rules.any_rule_matches(track)
+                && !loaded.alias_rules.iter().any(|rule| rule.matches(track))
+            {
                 log::trace!(
                     "RewriteRulesScrubActionProvider track {index}: no rules apply, skipping"
                 );
@@ -253,8 +574,10 @@ impl ScrubActionProvider for RewriteRulesScrubActionProvider {
             }
 
             // Apply rules with per-rule MB gating
-            if let Some((final_edit, requires_confirmation)) =
-                self.apply_rules_sequentially(track).await?
+            // Rules are deterministic, so they report no confidence and their own
+            // confirmation settings always apply
+            if let Some((final_edit, requires_confirmation, applied_rules)) =
+                self.apply_rules_sequentially(&loaded, track, index).await?
             {
                 results.push((
                     index,
@@ -262,7 +585,8 @@ impl ScrubActionProvider for RewriteRulesScrubActionProvider {
                         final_edit,
                         requires_confirmation,
                         self.provider_name().to_string(),
-                    )],
+                    )
+                    .with_explanation(format!("Rewrite rules: {}", applied_rules.join(", ")))],
                 ));
             }
         }
@@ -273,6 +597,18 @@ impl ScrubActionProvider for RewriteRulesScrubActionProvider {
     fn provider_name(&self) -> &'static str {
         "RewriteRules"
     }
+
+    fn take_rule_activity(&self) -> Vec<RuleActivity> {
+        self.activity
+            .lock()
+            .map(|mut activity| std::mem::take(&mut *activity))
+            .unwrap_or_default()
+    }
+
+    fn reload_rules(&self, rules_state: &RewriteRulesState) {
+        let loaded = Arc::new(LoadedRules::compile(rules_state));
+        *self.rules.write().unwrap_or_else(PoisonError::into_inner) = loaded;
+    }
 }
 
 /// Combines multiple providers, trying each one in order until one returns a non-NoAction result
@@ -322,6 +658,12 @@ impl OrScrubActionProvider {
         }
         or_provider
     }
+
+    /// Run the providers as a chain instead, each seeing the previous providers' output
+    #[must_use]
+    pub fn chained(self) -> Self {
+        Self::new().add_provider(ChainScrubActionProvider::from(self))
+    }
 }
 
 // Adapter to convert different error types to our unified error type
@@ -352,6 +694,14 @@ where
     fn provider_name(&self) -> &str {
         self.inner.provider_name()
     }
+
+    fn take_rule_activity(&self) -> Vec<RuleActivity> {
+        self.inner.take_rule_activity()
+    }
+
+    fn reload_rules(&self, rules_state: &RewriteRulesState) {
+        self.inner.reload_rules(rules_state);
+    }
 }
 
 #[async_trait]
@@ -407,4 +757,207 @@ impl ScrubActionProvider for OrScrubActionProvider {
     fn provider_name(&self) -> &'static str {
         "OrProvider"
     }
+
+    fn take_rule_activity(&self) -> Vec<RuleActivity> {
+        self.providers
+            .iter()
+            .flat_map(|provider| provider.take_rule_activity())
+            .collect()
+    }
+
+    fn reload_rules(&self, rules_state: &RewriteRulesState) {
+        for provider in &self.providers {
+            provider.reload_rules(rules_state);
+        }
+    }
+}
+
+/// Runs providers in order, threading the accumulated edit through each of them
+///
+/// Every provider analyzes the tracks as rewritten by the providers before it, so e.g. a
+/// MusicBrainz lookup sees the track name with its remaster suffix already removed. The
+/// edits are merged into a single edit per track whose `provenance` records which provider
+/// set each field; later providers win when they change a field again. Rule proposals are
+/// passed through unchanged.
+pub struct ChainScrubActionProvider {
+    providers: Vec<Box<dyn ScrubActionProvider<Error = ActionProviderError>>>,
+    provider_names: Vec<String>,
+}
+
+impl Default for ChainScrubActionProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainScrubActionProvider {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            providers: Vec::new(),
+            provider_names: Vec::new(),
+        }
+    }
+
+    pub fn add_provider<P>(mut self, provider: P) -> Self
+    where
+        P: ScrubActionProvider + 'static,
+        P::Error: Into<ActionProviderError>,
+    {
+        self.provider_names
+            .push(provider.provider_name().to_string());
+        self.providers
+            .push(Box::new(ErrorAdapter { inner: provider }));
+        self
+    }
+}
+
+impl From<OrScrubActionProvider> for ChainScrubActionProvider {
+    fn from(or_provider: OrScrubActionProvider) -> Self {
+        Self {
+            providers: or_provider.providers,
+            provider_names: or_provider.provider_names,
+        }
+    }
+}
+
+/// The accumulated result of a chain for one track
+#[derive(Default)]
+struct ChainedTrack {
+    provenance: EditProvenance,
+    requires_confirmation: bool,
+    /// The lowest confidence reported by a contributing provider
+    confidence: Option<f32>,
+    explanations: Vec<String>,
+    proposals: Vec<SuggestionWithContext>,
+}
+
+#[async_trait]
+impl ScrubActionProvider for ChainScrubActionProvider {
+    type Error = ActionProviderError;
+
+    async fn analyze_tracks(
+        &self,
+        tracks: &[Track],
+        pending_edits: Option<&[PendingEdit]>,
+        pending_rules: Option<&[PendingRewriteRule]>,
+    ) -> Result<Vec<(usize, Vec<SuggestionWithContext>)>, Self::Error> {
+        let mut current_tracks = tracks.to_vec();
+        let mut chained: Vec<ChainedTrack> =
+            tracks.iter().map(|_| ChainedTrack::default()).collect();
+
+        for (provider, provider_name) in self.providers.iter().zip(&self.provider_names) {
+            let provider_results = match provider
+                .analyze_tracks(&current_tracks, pending_edits, pending_rules)
+                .await
+            {
+                Ok(provider_results) => provider_results,
+                Err(e) => {
+                    log::warn!("Error from provider '{provider_name}', skipping it in chain: {e}");
+                    continue;
+                }
+            };
+
+            for (track_idx, suggestions) in provider_results {
+                let Some(current) = current_tracks.get_mut(track_idx) else {
+                    continue;
+                };
+                let state = &mut chained[track_idx];
+
+                for suggestion in suggestions {
+                    if matches!(
+                        suggestion.suggestion,
+                        ScrubActionSuggestion::ProposeRule { .. }
+                    ) {
+                        state.proposals.push(suggestion);
+                        continue;
+                    }
+                    let ScrubActionSuggestion::Edit(edit) = &suggestion.suggestion else {
+                        continue;
+                    };
+
+                    // Only fields this provider actually changed are taken from its edit
+                    let before = create_no_op_edit(current);
+                    let mut changed = false;
+                    for field in RuleField::ALL {
+                        let Some(value) = field.edit_value(edit) else {
+                            continue;
+                        };
+                        if Some(value) != field.edit_value(&before) {
+                            field.set_track_value(current, value.to_string());
+                            state.provenance.set(field, provider_name);
+                            changed = true;
+                        }
+                    }
+                    state.requires_confirmation |= changed && suggestion.requires_confirmation;
+                    if changed {
+                        if let Some(confidence) = suggestion.confidence {
+                            state.confidence = Some(
+                                state
+                                    .confidence
+                                    .map_or(confidence, |current| current.min(confidence)),
+                            );
+                        }
+                        if let Some(explanation) = &suggestion.explanation {
+                            state.explanations.push(explanation.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut results = Vec::new();
+        for (track_idx, (track, state)) in tracks.iter().zip(chained).enumerate() {
+            let mut suggestions = Vec::new();
+            if !state.provenance.is_empty() {
+                let mut edit = create_no_op_edit(track);
+                for field in RuleField::ALL {
+                    if state.provenance.get(field).is_some() {
+                        let value = field.track_value(&current_tracks[track_idx]).to_string();
+                        field.set_edit_value(&mut edit, value);
+                    }
+                }
+                log::debug!(
+                    "Chained edit for '{} - {}': {}",
+                    track.artist,
+                    track.name,
+                    state.provenance
+                );
+                let mut suggestion = SuggestionWithContext::edit_with_confirmation(
+                    edit,
+                    state.requires_confirmation,
+                    self.provider_name().to_string(),
+                )
+                .with_provenance(state.provenance);
+                suggestion.confidence = state.confidence;
+                if !state.explanations.is_empty() {
+                    suggestion = suggestion.with_explanation(state.explanations.join("; "));
+                }
+                suggestions.push(suggestion);
+            }
+            suggestions.extend(state.proposals);
+            if !suggestions.is_empty() {
+                results.push((track_idx, suggestions));
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "ChainProvider"
+    }
+
+    fn take_rule_activity(&self) -> Vec<RuleActivity> {
+        self.providers
+            .iter()
+            .flat_map(|provider| provider.take_rule_activity())
+            .collect()
+    }
+
+    fn reload_rules(&self, rules_state: &RewriteRulesState) {
+        for provider in &self.providers {
+            provider.reload_rules(rules_state);
+        }
+    }
 }
@@ -2,18 +2,27 @@ use chrono::{DateTime, Utc};
 use lastfm_edit::{LastFmEditClient, Result, ScrobbleEdit};
 use uuid::Uuid;
 
-use crate::config::ScrobbleScrubberConfig;
+use crate::config::{ConfidenceDecision, RulesDirConfig, ScrobbleScrubberConfig};
+use crate::conflicts::{resolve_conflicts, FieldConflict};
 use crate::edit::{apply_edit_to_lastfm, dry_run_edit};
 use crate::events::ScrubberEvent;
-use crate::events::{LogEditInfo, ProcessingContext, ProcessingType};
-use crate::persistence::{PendingEdit, PendingRewriteRule, StateStorage, TimestampState};
+use crate::events::{EditOrigin, LogEditInfo, ProcessingContext, ProcessingType};
+use crate::musicbrainz::MusicBrainzRateLimiter;
+use crate::persistence::{
+    EditDecision, EditMapping, PendingEdit, PendingRewriteRule, RuleStatsState, StateStorage,
+    TimestampState,
+};
+use crate::rules_dir::{ensure_rules_editable, load_rules_dir, RulesDirFingerprint};
 use crate::scrub_action_provider::{
-    ScrubActionProvider, ScrubActionSuggestion, SuggestionWithContext,
+    RuleActivity, RuleOutcome, ScrubActionProvider, ScrubActionSuggestion, SuggestionWithContext,
 };
 use crate::track_provider::{CachedTrackProvider, DirectTrackProvider, TrackProvider};
+use crate::undo::{UndoEdit, UNDO_PROVIDER_NAME};
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{broadcast, Mutex, Notify, RwLock};
+use tokio::task::AbortHandle;
 
 pub struct ScrobbleScrubber<S: StateStorage, P: ScrubActionProvider> {
     client: Box<dyn LastFmEditClient + Send + Sync>,
@@ -25,6 +34,11 @@ pub struct ScrobbleScrubber<S: StateStorage, P: ScrubActionProvider> {
     event_sender: broadcast::Sender<ScrubberEvent>,
     trigger_immediate: Arc<Notify>,
     track_provider: TrackProvider,
+    /// Rule files last seen in the watched rules directory
+    rules_dir_fingerprint: Option<RulesDirFingerprint>,
+    /// Task forwarding events from the process-wide MusicBrainz rate limiter, which outlives
+    /// the scrubber, so it is stopped on drop
+    musicbrainz_forwarding: Option<AbortHandle>,
 }
 
 impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
@@ -62,8 +76,11 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
             event_sender,
             trigger_immediate: Arc::new(Notify::new()),
             track_provider,
+            rules_dir_fingerprint: None,
+            musicbrainz_forwarding: None,
         };
         scrubber.setup_client_event_forwarding();
+        scrubber.setup_musicbrainz_event_forwarding();
         scrubber
     }
 
@@ -112,6 +129,19 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         });
     }
 
+    /// Forward MusicBrainz rate limit events to scrubber subscribers
+    fn setup_musicbrainz_event_forwarding(&mut self) {
+        let mut rate_limit_receiver = MusicBrainzRateLimiter::shared().subscribe();
+        let event_sender = self.event_sender.clone();
+
+        let forwarding = tokio::spawn(async move {
+            while let Ok(event) = rate_limit_receiver.recv().await {
+                let _ = event_sender.send(event);
+            }
+        });
+        self.musicbrainz_forwarding = Some(forwarding.abort_handle());
+    }
+
     /// Trigger immediate processing, bypassing the normal wait interval
     pub fn trigger_immediate_processing(&self) {
         self.trigger_immediate.notify_one();
@@ -242,6 +272,19 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         ));
         interval.tick().await; // Skip the first immediate tick
 
+        let watched_rules_dir = self
+            .config
+            .storage
+            .rules_dir
+            .clone()
+            .filter(RulesDirConfig::watches);
+        // Only polled when a rules directory is watched
+        let mut rules_dir_interval = tokio::time::interval(Duration::from_secs(
+            watched_rules_dir
+                .as_ref()
+                .map_or(1, |rules_dir| rules_dir.watch_interval_secs.max(1)),
+        ));
+
         loop {
             tokio::select! {
                 // Regular interval tick
@@ -263,6 +306,13 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
                     }
                 }
 
+                // Rules directory check
+                _ = rules_dir_interval.tick(), if watched_rules_dir.is_some() => {
+                    if let Some(rules_dir) = &watched_rules_dir {
+                        self.reload_rules_dir_if_changed(rules_dir).await;
+                    }
+                }
+
                 // Stop signal received
                 _ = self.should_stop.notified() => {
                     log::info!("Scrubber stop requested, exiting main loop");
@@ -275,6 +325,55 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         Ok(())
     }
 
+    /// Reload the rewrite rules if the files in the watched rules directory changed
+    ///
+    /// The first check only records the files, since the rules were loaded on startup. Rules
+    /// that fail to load are reported and the current rules are kept.
+    async fn reload_rules_dir_if_changed(&mut self, rules_dir: &RulesDirConfig) {
+        let dir = Path::new(&rules_dir.path);
+        let fingerprint = match RulesDirFingerprint::of(dir) {
+            Ok(fingerprint) => fingerprint,
+            Err(e) => {
+                log::warn!("Failed to check rules directory {}: {e}", rules_dir.path);
+                return;
+            }
+        };
+        let previous = self.rules_dir_fingerprint.replace(fingerprint);
+        if previous.is_none() || previous == self.rules_dir_fingerprint {
+            return;
+        }
+
+        let rules_state = match load_rules_dir(dir) {
+            Ok(rules_state) => rules_state,
+            Err(e) => {
+                log::warn!("Keeping the current rewrite rules: {e}");
+                self.emit_event(ScrubberEvent::error_from_string(format!(
+                    "Failed to reload rules directory: {e}"
+                )));
+                return;
+            }
+        };
+        if let Err(e) = self
+            .storage
+            .lock()
+            .await
+            .save_rewrite_rules_state(&rules_state)
+            .await
+        {
+            log::warn!("Failed to save reloaded rewrite rules: {e}");
+        }
+        self.action_provider.reload_rules(&rules_state);
+
+        let message = format!(
+            "Reloaded {} rewrite rules and {} alias tables from {}",
+            rules_state.rewrite_rules.len(),
+            rules_state.alias_rules.len(),
+            rules_dir.path
+        );
+        log::info!("{message}");
+        self.emit_event(ScrubberEvent::info(message));
+    }
+
     /// Run a single processing cycle with proper state management
     pub async fn run_processing_cycle(&mut self) -> Result<()> {
         *self.is_running.write().await = true;
@@ -291,6 +390,10 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
 
         let result = self.check_and_process_tracks_inner().await;
 
+        if let Err(e) = crate::musicbrainz::MusicBrainzCache::shared().flush() {
+            log::warn!("Failed to save MusicBrainz cache: {e}");
+        }
+
         if let Err(ref e) = result {
             log::warn!("Error during track processing: {e}");
             self.emit_event(ScrubberEvent::error_from_string(format!(
@@ -579,13 +682,18 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         tracks: &[lastfm_edit::Track],
         processing_type: ProcessingType,
     ) -> Result<()> {
-        // Emit batch started event for progress UI
-        if !tracks.is_empty() {
-            self.emit_event(ScrubberEvent::processing_batch_started(
-                tracks.to_vec(),
-                processing_type,
-            ));
+        if tracks.is_empty() {
+            return Ok(());
         }
+        // Emit batch started event for progress UI
+        self.emit_event(ScrubberEvent::processing_batch_started(
+            tracks.to_vec(),
+            processing_type,
+        ));
+        let run_id = Uuid::new_v4().to_string();
+        // Rule statistics are updated in memory and saved once the run ends
+        let mut rule_stats = self.load_rule_stats().await;
+        let mut result = Ok(());
 
         for (track_index, track) in tracks.iter().enumerate() {
             log::debug!(
@@ -597,19 +705,36 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
             );
 
             // Process this track individually without timestamp updates
-            self.process_single_track_with_context(
-                track,
-                track_index,
-                tracks.len(),
-                processing_type,
-            )
-            .await?;
+            result = self
+                .process_single_track_with_context(
+                    track,
+                    track_index,
+                    tracks.len(),
+                    processing_type,
+                    &run_id,
+                    rule_stats.as_mut(),
+                )
+                .await;
+            if result.is_err() {
+                break;
+            }
 
             // Yield control to allow other async tasks (like UI updates) to run
             tokio::task::yield_now().await;
         }
 
-        Ok(())
+        if let Some(rule_stats) = &rule_stats {
+            if let Err(e) = self
+                .storage
+                .lock()
+                .await
+                .save_rule_stats_state(rule_stats)
+                .await
+            {
+                log::warn!("Failed to save rule statistics: {e}");
+            }
+        }
+        result
     }
 
     /// Process a single track with its suggestions and artist processing context
@@ -624,6 +749,8 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         track_index: usize,
         total_tracks: usize,
         processing_type: ProcessingType,
+        run_id: &str,
+        rule_stats: Option<&mut RuleStatsState>,
     ) -> Result<()> {
         // Emit track processing started event for progress UI
         self.emit_event(ScrubberEvent::track_processing_started(
@@ -640,7 +767,7 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         // Analyze this single track
         let track_slice = std::slice::from_ref(track);
         let track_suggestions = self.analyze_tracks(track_slice).await;
-        let run_id = Uuid::new_v4().to_string();
+        let rule_activity = self.action_provider.take_rule_activity();
 
         // Find suggestions for this track (should be at index 0 since we only passed one track)
         let empty_suggestions = vec![];
@@ -655,8 +782,19 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         log::debug!("Processed track: {track}");
 
         // Apply suggestions using the helper method
-        self.apply_suggestions_to_track(track, suggestions, run_id, track_index, processing_type)
+        let applied_providers = self
+            .apply_suggestions_to_track(
+                track,
+                suggestions,
+                &rule_activity,
+                run_id,
+                track_index,
+                processing_type,
+            )
             .await?;
+        if let Some(rule_stats) = rule_stats {
+            Self::record_rule_stats(rule_stats, track, &rule_activity, &applied_providers);
+        }
 
         // Always process suggestions and log the result
         let mut summary_parts = Vec::new();
@@ -817,19 +955,88 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         Ok(())
     }
 
+    /// The stored per-rule statistics, or `None` when they can't be loaded (so a run doesn't
+    /// overwrite them)
+    async fn load_rule_stats(&self) -> Option<RuleStatsState> {
+        match self.storage.lock().await.load_rule_stats_state().await {
+            Ok(stats) => Some(stats),
+            Err(e) => {
+                log::warn!("Failed to load rule statistics: {e}");
+                None
+            }
+        }
+    }
+
+    /// Update the per-rule statistics with the rules that matched a track
+    fn record_rule_stats(
+        stats: &mut RuleStatsState,
+        track: &lastfm_edit::Track,
+        rule_activity: &[RuleActivity],
+        applied_providers: &[String],
+    ) {
+        let now = Utc::now();
+        for activity in rule_activity.iter().filter(|a| a.track_index == 0) {
+            let applied = applied_providers.contains(&activity.provider_name);
+            stats.record(activity, track, applied, now);
+        }
+    }
+
     /// Apply suggestions to a track with proper context and event emission
+    ///
+    /// Returns the names of the providers whose edits were applied to Last.fm.
     async fn apply_suggestions_to_track(
         &mut self,
         track: &lastfm_edit::Track,
         suggestions: &[SuggestionWithContext],
-        run_id: String,
+        rule_activity: &[RuleActivity],
+        run_id: &str,
         track_index: usize,
         processing_type: ProcessingType,
-    ) -> Result<()> {
+    ) -> Result<Vec<String>> {
+        let mut applied_providers = Vec::new();
         if suggestions.is_empty() {
-            return Ok(());
+            return Ok(applied_providers);
         }
 
+        let make_context = || ProcessingContext {
+            run_id: run_id.to_string(),
+            batch_id: None, // No batch processing anymore
+            track_index: Some(track_index),
+            batch_size: Some(1), // Always 1 since we process individually
+            is_artist_processing: processing_type == ProcessingType::Artist,
+        };
+
+        let resolution = resolve_conflicts(
+            track,
+            suggestions.to_vec(),
+            self.config.scrubber.conflict_policy,
+            &self.config.scrubber.provider_priority,
+        );
+        for conflict in &resolution.conflicts {
+            let message = format!(
+                "Providers disagree on '{} - {}': {conflict}",
+                track.artist, track.name
+            );
+            log::info!("{message}");
+            self.emit_event(ScrubberEvent::info(message));
+        }
+        if let Some(escalated) = resolution.escalated {
+            self.create_pending_edit(
+                track,
+                &escalated.edit,
+                None,
+                escalated.conflicts,
+                Some(make_context()),
+            )
+            .await?;
+            self.emit_event(ScrubberEvent::track_skipped(
+                track,
+                make_context(),
+                "Providers disagree - created as pending".to_string(),
+            ));
+        }
+        let suggestions = &resolution.suggestions;
+
         log::trace!(
             "Applying {} suggestions to track: {} - {}",
             suggestions.len(),
@@ -847,21 +1054,26 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
                 suggestion
             );
 
-            let suggestion_context = ProcessingContext {
-                run_id: run_id.clone(),
-                batch_id: None, // No batch processing anymore
-                track_index: Some(track_index),
-                batch_size: Some(1), // Always 1 since we process individually
-                is_artist_processing: processing_type == ProcessingType::Artist,
-            };
-            self.apply_suggestion_with_context(track, suggestion, Some(suggestion_context))
+            let rules = Self::produced_rules(rule_activity, suggestion);
+            let applied = self
+                .apply_suggestion_with_context(track, suggestion, Some(make_context()), &rules)
                 .await?;
+            if applied {
+                applied_providers.extend(suggestion.contributing_providers());
+            }
 
             // Emit rule applied event based on suggestion type
             let description = match &suggestion.suggestion {
                 crate::scrub_action_provider::ScrubActionSuggestion::Edit(edit) => {
                     log::trace!("Applied edit: {edit:?}");
-                    format!("Applied edit from {}", suggestion.provider_name)
+                    let mut description = format!("Applied edit from {}", suggestion.provider_name);
+                    if !suggestion.provenance.is_empty() {
+                        description.push_str(&format!(" ({})", suggestion.provenance));
+                    }
+                    if let Some(summary) = suggestion.confidence_summary() {
+                        description.push_str(&format!(" [{summary}]"));
+                    }
+                    description
                 }
                 crate::scrub_action_provider::ScrubActionSuggestion::ProposeRule {
                     rule,
@@ -885,7 +1097,29 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
             ));
         }
 
-        Ok(())
+        Ok(applied_providers)
+    }
+
+    /// Names of the rewrite rules whose changes make up `suggestion`
+    fn produced_rules(
+        rule_activity: &[RuleActivity],
+        suggestion: &SuggestionWithContext,
+    ) -> Vec<String> {
+        let providers = suggestion.contributing_providers();
+        rule_activity
+            .iter()
+            .filter(|activity| {
+                activity.track_index == 0
+                    && activity.outcome == RuleOutcome::Produced
+                    && providers.contains(&activity.provider_name)
+            })
+            .map(|activity| {
+                activity
+                    .rule_name
+                    .clone()
+                    .unwrap_or_else(|| activity.rule_key.clone())
+            })
+            .collect()
     }
 
     /// Process all tracks for a specific artist
@@ -1138,6 +1372,63 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         Ok(())
     }
 
+    /// Apply the inverse edits of an undo plan, returning how many were applied to Last.fm
+    ///
+    /// Undo edits go through the same path as provider suggestions, so dry run mode only
+    /// logs them and confirmation settings turn them into pending edits. They share one
+    /// run id in the edit log, so an undo can itself be undone. Each reverted change is
+    /// remembered as rejected, so later runs don't make it again; for undo edits that go to
+    /// pending review, this happens when they are approved.
+    pub async fn undo_edits(&mut self, plan: &[UndoEdit]) -> Result<usize> {
+        let run_id = Uuid::new_v4().to_string();
+        let mut applied_count = 0;
+
+        for (index, undo) in plan.iter().enumerate() {
+            let track = undo.track();
+            let suggestion = SuggestionWithContext::edit_with_confirmation(
+                undo.edit.clone(),
+                false,
+                UNDO_PROVIDER_NAME.to_string(),
+            )
+            .with_explanation(format!(
+                "Undo of edit made at {}",
+                undo.entry.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+            ))
+            .with_reverts(EditMapping::from(&undo.reverted_edit()));
+            let context = ProcessingContext {
+                run_id: run_id.clone(),
+                batch_id: None,
+                track_index: Some(index),
+                batch_size: Some(plan.len()),
+                is_artist_processing: false,
+            };
+
+            if self
+                .apply_suggestion_with_context(&track, &suggestion, Some(context), &[])
+                .await?
+            {
+                applied_count += 1;
+                self.storage
+                    .lock()
+                    .await
+                    .record_edit_decision(&undo.reverted_edit(), EditDecision::Rejected)
+                    .await
+                    .map_err(|e| {
+                        lastfm_edit::LastFmError::Io(std::io::Error::other(format!(
+                            "Failed to record edit decision: {e}"
+                        )))
+                    })?;
+            }
+            tokio::task::yield_now().await;
+        }
+
+        log::info!(
+            "Undo complete: applied {applied_count} of {} edits (run {run_id})",
+            plan.len()
+        );
+        Ok(applied_count)
+    }
+
     /// Set the processing timestamp anchor directly
     /// This allows manual control of where the scrubber starts processing from
     pub async fn set_timestamp(&mut self, timestamp: DateTime<Utc>) -> Result<()> {
@@ -1211,16 +1502,24 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         &self,
         tracks: &[lastfm_edit::Track],
     ) -> Vec<(usize, Vec<SuggestionWithContext>)> {
+        self.load_missing_artist_history(tracks).await;
+
         // Load pending items to provide context for action providers
+        // Rejected edits are passed along with the pending ones so providers don't suggest them again
         let (pending_edits_result, pending_rules_result) = tokio::join!(
             async {
-                self.storage
-                    .lock()
-                    .await
+                let storage = self.storage.lock().await;
+                let mut pending_edits = storage
                     .load_pending_edits_state()
                     .await
                     .map(|state| state.pending_edits)
-                    .map_err(|e| format!("Failed to load pending edits: {e}"))
+                    .map_err(|e| format!("Failed to load pending edits: {e}"))?;
+                let decisions = storage
+                    .load_edit_decisions_state()
+                    .await
+                    .map_err(|e| format!("Failed to load edit decisions: {e}"))?;
+                pending_edits.extend(decisions.rejected_as_pending_edits());
+                Ok::<_, String>(pending_edits)
             },
             async {
                 self.storage
@@ -1346,14 +1645,64 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         }
     }
 
+    /// Load the library history of artists in `tracks` that aren't in the track cache yet
+    ///
+    /// The majority album provider only reads the cache, so the history is fetched here
+    /// before the providers run.
+    async fn load_missing_artist_history(&self, tracks: &[lastfm_edit::Track]) {
+        if !self.config.providers.enable_majority_album {
+            return;
+        }
+        let majority_config = self
+            .config
+            .providers
+            .majority_album
+            .clone()
+            .unwrap_or_default();
+        if !majority_config.fetch_missing_history {
+            return;
+        }
+
+        let mut cache = crate::track_cache::TrackCache::load();
+        let mut artists: Vec<&str> = tracks
+            .iter()
+            .map(|track| track.artist.as_str())
+            .filter(|artist| cache.get_artist_tracks(artist).is_none())
+            .collect();
+        artists.sort_unstable();
+        artists.dedup();
+
+        let mut fetched = false;
+        for artist in artists {
+            match cache
+                .fetch_artist_tracks(
+                    self.client.as_ref(),
+                    artist,
+                    crate::majority_album_provider::MAX_HISTORY_TRACKS,
+                )
+                .await
+            {
+                Ok(_) => fetched = true,
+                Err(e) => log::warn!("Failed to load history for '{artist}': {e}"),
+            }
+        }
+
+        if fetched {
+            if let Err(e) = cache.save() {
+                log::warn!("Failed to save track cache: {e}");
+            }
+        }
+    }
+
     #[allow(dead_code)]
     async fn apply_suggestion(
         &mut self,
         track: &lastfm_edit::Track,
         suggestion: &SuggestionWithContext,
     ) -> Result<()> {
-        self.apply_suggestion_with_context(track, suggestion, None)
+        self.apply_suggestion_with_context(track, suggestion, None, &[])
             .await
+            .map(|_| ())
     }
 
     /// Check if a ScrobbleEdit contains any actual changes
@@ -1377,12 +1726,17 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         false
     }
 
+    /// Apply a suggestion, returning whether an edit was applied to Last.fm
+    ///
+    /// `rules` names the rewrite rules behind the suggestion, for the edit log.
     async fn apply_suggestion_with_context(
         &mut self,
         track: &lastfm_edit::Track,
         suggestion: &SuggestionWithContext,
         context: Option<ProcessingContext>,
-    ) -> Result<()> {
+        rules: &[String],
+    ) -> Result<bool> {
+        let mut applied = false;
         // Load settings to check global confirmation requirement
         let settings_state = self
             .storage
@@ -1401,19 +1755,100 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
                 // Clone edit (edit_all is now always true by default)
                 let edit = edit.clone();
 
+                // The user's earlier decision on the same change overrides every other setting
+                let remembered = self.remembered_decision(track, &edit).await?;
+                match remembered {
+                    Some(EditDecision::Rejected) => {
+                        log::info!(
+                            "Skipping edit from {} for '{}' by '{}': the same change was rejected before",
+                            suggestion.provider_name,
+                            track.name,
+                            track.artist
+                        );
+                        let default_context = ProcessingContext {
+                            run_id: "rejected_edit".to_string(),
+                            batch_id: None,
+                            track_index: None,
+                            batch_size: None,
+                            is_artist_processing: false,
+                        };
+                        self.emit_event(ScrubberEvent::track_skipped(
+                            track,
+                            context.unwrap_or(default_context),
+                            "Edit was previously rejected - skipped".to_string(),
+                        ));
+                        return Ok(false);
+                    }
+                    Some(EditDecision::Approved) => {
+                        log::info!(
+                            "Edit from {} for '{}' by '{}' was approved before, applying without confirmation",
+                            suggestion.provider_name,
+                            track.name,
+                            track.artist
+                        );
+                    }
+                    None => {}
+                }
+
                 // Check if global settings require confirmation (persistent state takes precedence over config)
-                let global_confirmation = settings_state.require_confirmation
-                    || settings_state.require_confirmation_for_edits
-                    || self.config.scrubber.require_confirmation;
+                let global_confirmation = remembered.is_none()
+                    && (settings_state.require_confirmation
+                        || settings_state.require_confirmation_for_edits
+                        || self.config.scrubber.require_confirmation);
+
+                // Confidence thresholds replace the provider's own confirmation setting for
+                // suggestions that report a confidence
+                let decision = suggestion
+                    .confidence
+                    .filter(|_| remembered.is_none())
+                    .and_then(|confidence| {
+                        self.config
+                            .scrubber
+                            .confidence_thresholds_for(&suggestion.contributing_providers())
+                            .map(|thresholds| {
+                                (confidence, thresholds, thresholds.decide(confidence))
+                            })
+                    });
+                let provider_confirmation = match decision {
+                    Some((confidence, thresholds, ConfidenceDecision::Drop)) => {
+                        log::info!(
+                            "Dropping edit from {} for '{}' by '{}': confidence {confidence:.2} is below {:.2}",
+                            suggestion.provider_name,
+                            track.name,
+                            track.artist,
+                            thresholds.pending
+                        );
+                        let default_context = ProcessingContext {
+                            run_id: "low_confidence".to_string(),
+                            batch_id: None,
+                            track_index: None,
+                            batch_size: None,
+                            is_artist_processing: false,
+                        };
+                        self.emit_event(ScrubberEvent::track_skipped(
+                            track,
+                            context.unwrap_or(default_context),
+                            format!(
+                                "Confidence {confidence:.2} from {} below threshold {:.2} - dropped",
+                                suggestion.provider_name, thresholds.pending
+                            ),
+                        ));
+                        return Ok(false);
+                    }
+                    Some((_, _, ConfidenceDecision::Pending)) => true,
+                    Some((_, _, ConfidenceDecision::AutoApply)) => false,
+                    None => remembered.is_none() && suggestion.requires_confirmation,
+                };
 
                 log::trace!(
-                    "Confirmation settings - Global: {}, Provider suggests confirmation: {}, Config dry_run: {}",
+                    "Confirmation settings - Global: {}, Provider suggests confirmation: {}, Confidence: {:?}, Config dry_run: {}",
                     global_confirmation,
-                    suggestion.requires_confirmation,
+                    provider_confirmation,
+                    suggestion.confidence,
                     self.config.scrubber.dry_run
                 );
 
-                let requires_confirmation = global_confirmation || suggestion.requires_confirmation;
+                let requires_confirmation = global_confirmation || provider_confirmation;
 
                 if self.config.scrubber.dry_run {
                     if requires_confirmation {
@@ -1429,8 +1864,14 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
 
                 if requires_confirmation {
                     log::trace!("Edit requires confirmation, creating pending edit");
-                    self.create_pending_edit(track, &edit, context.clone())
-                        .await?;
+                    self.create_pending_edit(
+                        track,
+                        &edit,
+                        Some(suggestion),
+                        Vec::new(),
+                        context.clone(),
+                    )
+                    .await?;
 
                     // Emit event for pending edit skip
                     let default_context = ProcessingContext {
@@ -1441,11 +1882,16 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
                         is_artist_processing: false,
                     };
                     let log_context = context.unwrap_or(default_context);
-                    self.emit_event(ScrubberEvent::track_skipped(
-                        track,
-                        log_context,
-                        "Edit requires confirmation - created as pending".to_string(),
-                    ));
+                    let reason = match (decision, global_confirmation) {
+                        (Some((confidence, thresholds, ConfidenceDecision::Pending)), false) => {
+                            format!(
+                                "Confidence {confidence:.2} from {} below auto-apply threshold {:.2} - created as pending",
+                                suggestion.provider_name, thresholds.auto_apply
+                            )
+                        }
+                        _ => "Edit requires confirmation - created as pending".to_string(),
+                    };
+                    self.emit_event(ScrubberEvent::track_skipped(track, log_context, reason));
                 } else if self.config.scrubber.dry_run {
                     // Emit event for dry run skip
                     let default_context = ProcessingContext {
@@ -1463,7 +1909,17 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
                     ));
                 } else {
                     log::trace!("Applying edit directly to track");
-                    self.apply_edit_with_context(track, &edit, context).await?;
+                    let origin = EditOrigin {
+                        run_id: context
+                            .as_ref()
+                            .map_or_else(|| "manual_edit".to_string(), |c| c.run_id.clone()),
+                        providers: suggestion.contributing_providers(),
+                        rules: rules.to_vec(),
+                        explanation: suggestion.explanation.clone(),
+                    };
+                    applied = self
+                        .apply_edit_with_context(track, &edit, context, Some(origin))
+                        .await?;
                 }
             }
             ScrubActionSuggestion::ProposeRule { rule, motivation } => {
@@ -1487,71 +1943,49 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
                 log::debug!("Provider suggested no action needed");
             }
         }
-        Ok(())
+        Ok(applied)
     }
 
-    async fn create_pending_edit(
+    /// The user's earlier decision on the change `edit` makes to `track`, if any
+    async fn remembered_decision(
         &self,
         track: &lastfm_edit::Track,
         edit: &ScrobbleEdit,
-        context: Option<ProcessingContext>,
-    ) -> Result<()> {
-        let new_track_name = if edit.track_name.as_ref() == edit.track_name_original.as_ref() {
-            None
-        } else {
-            edit.track_name.clone()
-        };
-
-        let new_artist_name = if Some(&edit.artist_name) == Some(&edit.artist_name_original) {
-            None
-        } else {
-            Some(edit.artist_name.clone())
-        };
-
-        let new_album_name = if edit.album_name.as_ref() == edit.album_name_original.as_ref() {
-            None
-        } else {
-            edit.album_name.clone()
-        };
-
-        let new_album_artist_name =
-            if edit.album_artist_name.as_ref() == edit.album_artist_name_original.as_ref() {
-                None
-            } else {
-                edit.album_artist_name.clone()
-            };
-
-        let pending_edit = PendingEdit::new(
-            track.name.clone(),
-            track.artist.clone(),
-            edit.album_name_original.clone(),
-            edit.album_artist_name_original.clone(),
-            new_track_name,
-            new_artist_name,
-            new_album_name,
-            new_album_artist_name,
-            track.timestamp,
-        );
-
-        // Load and save pending edits
-        let mut pending_edits_state = self
+    ) -> Result<Option<EditDecision>> {
+        let decisions = self
             .storage
             .lock()
             .await
-            .load_pending_edits_state()
+            .load_edit_decisions_state()
             .await
             .map_err(|e| {
                 lastfm_edit::LastFmError::Io(std::io::Error::other(format!(
-                    "Failed to load pending edits: {e}"
+                    "Failed to load edit decisions: {e}"
                 )))
             })?;
+        Ok(decisions.decision_for(&PendingEdit::from_edit(track, edit)))
+    }
 
-        pending_edits_state.pending_edits.push(pending_edit.clone());
+    async fn create_pending_edit(
+        &self,
+        track: &lastfm_edit::Track,
+        edit: &ScrobbleEdit,
+        suggestion: Option<&SuggestionWithContext>,
+        conflicts: Vec<FieldConflict>,
+        context: Option<ProcessingContext>,
+    ) -> Result<()> {
+        let pending_edit = PendingEdit::from_edit(track, edit)
+            .with_conflicts(conflicts)
+            .with_confidence(
+                suggestion.and_then(|s| s.confidence),
+                suggestion.and_then(|s| s.explanation.clone()),
+            )
+            .with_reverts(suggestion.and_then(|s| s.reverts.clone()));
 
         self.storage
             .lock()
             .await
-            .save_pending_edits_state(&pending_edits_state)
+            .add_pending_edit(&pending_edit)
             .await
             .map_err(|e| {
                 lastfm_edit::LastFmError::Io(std::io::Error::other(format!(
@@ -1597,15 +2031,22 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
 
     #[allow(dead_code)]
     async fn apply_edit(&mut self, track: &lastfm_edit::Track, edit: &ScrobbleEdit) -> Result<()> {
-        self.apply_edit_with_context(track, edit, None).await
+        self.apply_edit_with_context(track, edit, None, None)
+            .await
+            .map(|_| ())
     }
 
+    /// Apply an edit, returning whether it was applied to Last.fm (not a dry run, no timeout)
+    ///
+    /// `origin` is announced before the edit, so the edit log can record it.
     async fn apply_edit_with_context(
         &mut self,
         track: &lastfm_edit::Track,
         edit: &ScrobbleEdit,
         context: Option<ProcessingContext>,
-    ) -> Result<()> {
+        origin: Option<EditOrigin>,
+    ) -> Result<bool> {
+        let mut applied = false;
         // Log the edit using ScrobbleEdit's Display implementation
         if Self::has_changes(edit) {
             log::debug!("Applying edit: {edit}");
@@ -1619,30 +2060,33 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
                 is_artist_processing: false,
             };
             let log_context = context.unwrap_or(default_context);
+            let edit_info = LogEditInfo {
+                original_track_name: edit.track_name_original.clone(),
+                original_artist_name: Some(edit.artist_name_original.clone()),
+                original_album_name: edit.album_name_original.clone(),
+                original_album_artist_name: edit.album_artist_name_original.clone(),
+                new_track_name: edit.track_name.clone(),
+                new_artist_name: Some(edit.artist_name.clone()),
+                new_album_name: edit.album_name.clone(),
+                new_album_artist_name: edit.album_artist_name.clone(),
+            };
 
             let timeout = Duration::from_secs(30);
 
             let result = if self.config.scrubber.dry_run {
                 dry_run_edit(edit).await
             } else {
+                if let Some(origin) = origin {
+                    self.emit_event(ScrubberEvent::edit_applying(track, &edit_info, origin));
+                }
                 apply_edit_to_lastfm(self.client.as_ref(), edit, timeout).await
             };
 
             match result {
                 Ok(_response) => {
                     // Emit event for successful edit
-                    let edit_info = LogEditInfo {
-                        original_track_name: edit.track_name_original.clone(),
-                        original_artist_name: Some(edit.artist_name_original.clone()),
-                        original_album_name: edit.album_name_original.clone(),
-                        original_album_artist_name: edit.album_artist_name_original.clone(),
-                        new_track_name: edit.track_name.clone(),
-                        new_artist_name: Some(edit.artist_name.clone()),
-                        new_album_name: edit.album_name.clone(),
-                        new_album_artist_name: edit.album_artist_name.clone(),
-                    };
-
                     self.emit_event(ScrubberEvent::track_edited(track, &edit_info, log_context));
+                    applied = !self.config.scrubber.dry_run;
                 }
                 Err(e) => {
                     // Check if this is a timeout error
@@ -1653,17 +2097,6 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
                     }
 
                     // Emit event for failed edit
-                    let edit_info = LogEditInfo {
-                        original_track_name: edit.track_name_original.clone(),
-                        original_artist_name: Some(edit.artist_name_original.clone()),
-                        original_album_name: edit.album_name_original.clone(),
-                        original_album_artist_name: edit.album_artist_name_original.clone(),
-                        new_track_name: edit.track_name.clone(),
-                        new_artist_name: Some(edit.artist_name.clone()),
-                        new_album_name: edit.album_name.clone(),
-                        new_album_artist_name: edit.album_artist_name.clone(),
-                    };
-
                     self.emit_event(ScrubberEvent::track_edit_failed_from_string(
                         track,
                         Some(&edit_info),
@@ -1682,7 +2115,7 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
             }
         }
 
-        Ok(())
+        Ok(applied)
     }
 
     async fn handle_proposed_rule(
@@ -1704,10 +2137,12 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
                 )))
             })?;
 
-        // Check if confirmation is required for proposed rules (persistent state takes precedence over config)
+        // Check if confirmation is required for proposed rules (persistent state takes precedence over config).
+        // Rules can't be added to a rules directory, so proposals from it always wait for review.
         let requires_confirmation = settings_state.require_confirmation
             || settings_state.require_confirmation_for_new_rules
-            || self.config.scrubber.require_proposed_rule_confirmation;
+            || self.config.scrubber.require_proposed_rule_confirmation
+            || ensure_rules_editable(self.config.storage.rules_dir.as_ref()).is_err();
 
         if requires_confirmation {
             // Create a pending rewrite rule for approval
@@ -1782,3 +2217,11 @@ impl<S: StateStorage, P: ScrubActionProvider> ScrobbleScrubber<S, P> {
         Ok(())
     }
 }
+
+impl<S: StateStorage, P: ScrubActionProvider> Drop for ScrobbleScrubber<S, P> {
+    fn drop(&mut self) {
+        if let Some(forwarding) = self.musicbrainz_forwarding.take() {
+            forwarding.abort();
+        }
+    }
+}
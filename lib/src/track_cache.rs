@@ -150,6 +150,30 @@ impl TrackCache {
         self.update_timestamp();
     }
 
+    /// Load an artist's tracks from the Last.fm library and cache them
+    ///
+    /// Each track carries its album and the user's play count for it. At most `limit`
+    /// tracks are loaded.
+    pub async fn fetch_artist_tracks(
+        &mut self,
+        client: &(dyn LastFmEditClient + Send + Sync),
+        artist: &str,
+        limit: usize,
+    ) -> lastfm_edit::Result<Vec<Track>> {
+        let mut iterator = client.artist_tracks(artist);
+        let mut tracks = Vec::new();
+        while tracks.len() < limit {
+            match iterator.next().await? {
+                Some(track) => tracks.push(track),
+                None => break,
+            }
+        }
+
+        log::debug!("Fetched {} tracks for '{artist}'", tracks.len());
+        self.cache_artist_tracks(artist.to_string(), tracks.clone());
+        Ok(tracks)
+    }
+
     /// Clear all cached data
     pub fn clear(&mut self) {
         self.recent_tracks.clear();
@@ -0,0 +1,333 @@
+//! Reversing edits recorded in the edit log.
+//!
+//! Every edit applied to Last.fm is written to the edit log as an [`EditLogEntry`] with the
+//! full metadata before and after. [`plan_undo`] selects entries by run id, time range, rule
+//! or artist and turns each into an [`UndoEdit`] holding the inverse [`ScrobbleEdit`], which
+//! the scrubber applies like any other edit (see `ScrobbleScrubber::undo_edits`).
+
+use chrono::{DateTime, Utc};
+use lastfm_edit::{ScrobbleEdit, Track};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::json_logger::{EditLogEntry, TrackMetadata};
+use crate::persistence::PendingEdit;
+
+/// Provider name given to undo edits, so they show up as such in events and the edit log
+pub const UNDO_PROVIDER_NAME: &str = "Undo";
+
+/// Which logged edits to undo; unset criteria match every edit
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UndoFilter {
+    pub run_id: Option<String>,
+    /// Only edits made at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only edits made before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only edits produced by the rewrite rule with this name
+    pub rule: Option<String>,
+    /// Only edits of this artist's scrobbles, before or after the edit
+    pub artist: Option<String>,
+}
+
+impl UndoFilter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_run_id<S: Into<String>>(mut self, run_id: S) -> Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn with_time_range(
+        mut self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.since = since;
+        self.until = until;
+        self
+    }
+
+    #[must_use]
+    pub fn with_rule<S: Into<String>>(mut self, rule: S) -> Self {
+        self.rule = Some(rule.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_artist<S: Into<String>>(mut self, artist: S) -> Self {
+        self.artist = Some(artist.into());
+        self
+    }
+
+    /// Whether no criteria are set, which would select the whole log
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Whether `entry` is a successful edit matching every set criterion
+    #[must_use]
+    pub fn matches(&self, entry: &EditLogEntry) -> bool {
+        let origin = entry.origin.as_ref();
+        entry.success
+            && self
+                .run_id
+                .as_ref()
+                .is_none_or(|run_id| origin.is_some_and(|origin| &origin.run_id == run_id))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+            && self.rule.as_ref().is_none_or(|rule| {
+                origin.is_some_and(|origin| {
+                    origin
+                        .rules
+                        .iter()
+                        .any(|name| name.eq_ignore_ascii_case(rule))
+                })
+            })
+            && self.artist.as_ref().is_none_or(|artist| {
+                entry.edit.original.artist_name.eq_ignore_ascii_case(artist)
+                    || entry.edit.new.artist_name.eq_ignore_ascii_case(artist)
+            })
+    }
+}
+
+/// A logged edit and the edit reversing it
+#[derive(Debug, Clone)]
+pub struct UndoEdit {
+    pub entry: EditLogEntry,
+    /// Edit restoring the metadata from before the logged edit
+    pub edit: ScrobbleEdit,
+}
+
+impl UndoEdit {
+    #[must_use]
+    pub fn new(entry: EditLogEntry) -> Self {
+        let details = &entry.edit;
+        let edit = ScrobbleEdit {
+            track_name_original: Some(details.new.track_name.clone()),
+            album_name_original: Some(details.new.album_name.clone()),
+            artist_name_original: details.new.artist_name.clone(),
+            album_artist_name_original: Some(details.new.album_artist_name.clone()),
+            track_name: Some(details.original.track_name.clone()),
+            album_name: Some(details.original.album_name.clone()),
+            artist_name: details.original.artist_name.clone(),
+            album_artist_name: Some(details.original.album_artist_name.clone()),
+            timestamp: Some(details.timestamp),
+            edit_all: details.edit_all,
+        };
+        Self { entry, edit }
+    }
+
+    /// The scrobble as it looks after the logged edit
+    #[must_use]
+    pub fn track(&self) -> Track {
+        metadata_track(&self.entry.edit.new, self.entry.edit.timestamp)
+    }
+
+    /// The change the logged edit made, for remembering it as rejected once reverted
+    #[must_use]
+    pub fn reverted_edit(&self) -> PendingEdit {
+        let details = &self.entry.edit;
+        let track = metadata_track(&details.original, details.timestamp);
+        let edit = ScrobbleEdit {
+            track_name_original: Some(track.name.clone()),
+            album_name_original: track.album.clone(),
+            artist_name_original: track.artist.clone(),
+            album_artist_name_original: track.album_artist.clone(),
+            track_name: Some(details.new.track_name.clone()),
+            album_name: non_empty(&details.new.album_name),
+            artist_name: details.new.artist_name.clone(),
+            album_artist_name: non_empty(&details.new.album_artist_name),
+            timestamp: Some(details.timestamp),
+            edit_all: details.edit_all,
+        };
+        PendingEdit::from_edit(&track, &edit)
+    }
+
+    /// One-line description of the change being reverted, for previews
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let (original, new) = (&self.entry.edit.original, &self.entry.edit.new);
+        format!(
+            "{} → {}",
+            describe_metadata(new),
+            describe_metadata(original)
+        )
+    }
+}
+
+/// The edit log writes missing albums and album artists as empty strings
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.to_string()).filter(|value| !value.is_empty())
+}
+
+fn metadata_track(metadata: &TrackMetadata, timestamp: u64) -> Track {
+    Track {
+        name: metadata.track_name.clone(),
+        artist: metadata.artist_name.clone(),
+        album: non_empty(&metadata.album_name),
+        album_artist: non_empty(&metadata.album_artist_name),
+        playcount: 0,
+        timestamp: Some(timestamp),
+    }
+}
+
+fn describe_metadata(metadata: &TrackMetadata) -> String {
+    if metadata.album_name.is_empty() {
+        format!("'{}' by '{}'", metadata.track_name, metadata.artist_name)
+    } else {
+        format!(
+            "'{}' by '{}' [{}]",
+            metadata.track_name, metadata.artist_name, metadata.album_name
+        )
+    }
+}
+
+/// Read every entry of the edit log at `path`
+///
+/// Lines that can't be parsed (such as one cut short by a crash) are logged and skipped.
+pub fn read_edit_log(path: &Path) -> std::io::Result<Vec<EditLogEntry>> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut entries = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => log::warn!(
+                "Skipping invalid edit log line {} in {}: {e}",
+                index + 1,
+                path.display()
+            ),
+        }
+    }
+    Ok(entries)
+}
+
+/// The edits undoing the log entries matched by `filter`, most recent first
+///
+/// Reverting in reverse order restores the original metadata of scrobbles edited more than
+/// once. An `edit_all` edit is logged once per scrobble it changed, but a single inverse edit
+/// reverts all of them, so repeats are dropped.
+#[must_use]
+pub fn plan_undo(entries: Vec<EditLogEntry>, filter: &UndoFilter) -> Vec<UndoEdit> {
+    let mut entries: Vec<_> = entries
+        .into_iter()
+        .filter(|entry| filter.matches(entry) && entry.edit.original != entry.edit.new)
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+
+    let mut seen = HashSet::new();
+    entries
+        .into_iter()
+        .filter(|entry| {
+            let details = &entry.edit;
+            let timestamp = (!details.edit_all).then_some(details.timestamp);
+            seen.insert((details.original.clone(), details.new.clone(), timestamp))
+        })
+        .map(UndoEdit::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EditOrigin;
+    use crate::json_logger::EditDetails;
+
+    fn metadata(track_name: &str, artist_name: &str) -> TrackMetadata {
+        TrackMetadata {
+            track_name: track_name.to_string(),
+            artist_name: artist_name.to_string(),
+            album_name: "Pablo Honey".to_string(),
+            album_artist_name: artist_name.to_string(),
+        }
+    }
+
+    fn entry(minute: u32, scrobbled_at: u64, from: &str, to: &str, rule: &str) -> EditLogEntry {
+        EditLogEntry {
+            timestamp: format!("2026-10-01T12:{minute:02}:00Z").parse().unwrap(),
+            success: true,
+            error_message: None,
+            duration_ms: 100,
+            edit: EditDetails {
+                timestamp: scrobbled_at,
+                edit_all: true,
+                original: metadata(from, "Radiohead"),
+                new: metadata(to, "Radiohead"),
+            },
+            origin: Some(EditOrigin {
+                run_id: format!("run-{minute}"),
+                providers: vec!["RewriteRules".to_string()],
+                rules: vec![rule.to_string()],
+                explanation: None,
+            }),
+        }
+    }
+
+    #[test_log::test]
+    fn inverse_edit_should_restore_original_metadata() {
+        let undo = UndoEdit::new(entry(
+            0,
+            1_700_000_000,
+            "Creep - Remaster",
+            "Creep",
+            "Strip",
+        ));
+
+        assert_eq!(undo.edit.track_name_original.as_deref(), Some("Creep"));
+        assert_eq!(undo.edit.track_name.as_deref(), Some("Creep - Remaster"));
+        assert_eq!(undo.edit.timestamp, Some(1_700_000_000));
+        assert!(undo.edit.edit_all);
+        assert_eq!(undo.track().name, "Creep");
+        assert_eq!(undo.track().album.as_deref(), Some("Pablo Honey"));
+    }
+
+    #[test_log::test]
+    fn plan_should_filter_order_and_deduplicate() {
+        let mut failed = entry(
+            5,
+            1_700_000_005,
+            "Anyone Can Play Guitar",
+            "Guitar",
+            "Strip",
+        );
+        failed.success = false;
+        let entries = vec![
+            entry(1, 1_700_000_001, "Creep - Remaster", "Creep", "Strip"),
+            // The same edit_all edit logged for a second scrobble
+            entry(1, 1_700_000_002, "Creep - Remaster", "Creep", "Strip"),
+            entry(2, 1_700_000_003, "Creep", "Creep (Acoustic)", "Acoustic"),
+            failed,
+        ];
+
+        let plan = plan_undo(entries.clone(), &UndoFilter::new());
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].entry.edit.new.track_name, "Creep (Acoustic)");
+        assert_eq!(plan[1].entry.edit.new.track_name, "Creep");
+
+        let by_rule = plan_undo(entries.clone(), &UndoFilter::new().with_rule("strip"));
+        assert_eq!(by_rule.len(), 1);
+        assert_eq!(
+            by_rule[0].entry.edit.original.track_name,
+            "Creep - Remaster"
+        );
+
+        let by_run = plan_undo(entries.clone(), &UndoFilter::new().with_run_id("run-2"));
+        assert_eq!(by_run.len(), 1);
+
+        let since = "2026-10-01T12:02:00Z".parse().ok();
+        let by_time = plan_undo(entries, &UndoFilter::new().with_time_range(since, None));
+        assert_eq!(by_time.len(), 1);
+        assert_eq!(by_time[0].entry.edit.new.track_name, "Creep (Acoustic)");
+    }
+}
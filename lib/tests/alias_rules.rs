@@ -0,0 +1,59 @@
+use common::track;
+use scrobble_scrubber::alias_rules::{AliasMatching, AliasRule};
+use scrobble_scrubber::persistence::RewriteRulesState;
+use scrobble_scrubber::rewrite::{ReplacementMode, RewriteRule, RuleField, SdRule};
+use scrobble_scrubber::scrub_action_provider::{
+    RewriteRulesScrubActionProvider, ScrubActionProvider, ScrubActionSuggestion,
+};
+
+mod common;
+
+#[test_log::test(tokio::test)]
+async fn provider_should_canonicalize_with_alias_tables_after_rewrite_rules() {
+    let rules_state = RewriteRulesState {
+        rewrite_rules: vec![RewriteRule::new().with_artist_name(
+            SdRule::new(r" [Ff]t\. .*$", "").with_mode(ReplacementMode::AllMatches),
+        )],
+        alias_rules: vec![AliasRule::new(RuleField::ArtistName)
+            .with_name("Beyoncé")
+            .with_matching(AliasMatching::Normalized)
+            .with_alias("Beyoncé", "Beyonce Knowles")
+            .with_confirmation_required(true)],
+    };
+    let provider = RewriteRulesScrubActionProvider::new(&rules_state);
+
+    let tracks = vec![
+        track("Halo", "beyonce ft. Someone"),
+        track("Crazy in Love", "Beyonce Knowles"),
+        track("Formation", "Beyoncé"),
+    ];
+    let results = provider.analyze_tracks(&tracks, None, None).await.unwrap();
+
+    assert_eq!(results.len(), 2);
+    for (index, suggestions) in &results {
+        assert!(*index < 2);
+        let suggestion = &suggestions[0];
+        assert!(suggestion.requires_confirmation);
+        match &suggestion.suggestion {
+            ScrubActionSuggestion::Edit(edit) => assert_eq!(edit.artist_name, "Beyoncé"),
+            other => panic!("Expected an edit, got {other:?}"),
+        }
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn provider_should_skip_alias_tables_that_fail_to_compile() {
+    let rules_state = RewriteRulesState {
+        rewrite_rules: Vec::new(),
+        alias_rules: vec![AliasRule::new(RuleField::ArtistName)
+            .with_alias("A", "x")
+            .with_alias("B", "x")],
+    };
+    let provider = RewriteRulesScrubActionProvider::new(&rules_state);
+
+    let results = provider
+        .analyze_tracks(&[track("Song", "x")], None, None)
+        .await
+        .unwrap();
+    assert!(results.is_empty());
+}
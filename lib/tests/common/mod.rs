@@ -1,4 +1,9 @@
 /// Common test utilities and macros
+use lastfm_edit::Track;
+use scrobble_scrubber::config::{RulesDirConfig, RulesSource};
+use scrobble_scrubber::rewrite::{RewriteRule, SdRule};
+use std::path::PathBuf;
+
 /// Macro to skip live MusicBrainz tests when the environment variable is set
 #[macro_export]
 macro_rules! skip_if_live_mb_disabled {
@@ -63,3 +68,45 @@ pub fn should_skip_live_mb_tests() -> bool {
         .map(|v| v == "1" || v.to_lowercase() == "true")
         .unwrap_or(false)
 }
+
+/// A scrobble with no album, as most rule tests need
+#[allow(dead_code)]
+pub fn track(name: &str, artist: &str) -> Track {
+    Track {
+        name: name.to_string(),
+        artist: artist.to_string(),
+        album: None,
+        album_artist: None,
+        playcount: 1,
+        timestamp: Some(1_700_000_000),
+    }
+}
+
+/// A named rule stripping `- 2009 Remaster` style suffixes from track names
+#[allow(dead_code)]
+pub fn remaster_rule() -> RewriteRule {
+    RewriteRule::new()
+        .with_track_name(SdRule::new(r"(.*) - \d{4} Remaster", "$1"))
+        .with_name("Strip remaster")
+}
+
+/// A rule that matches Radiohead's scrobbles without changing anything
+#[allow(dead_code)]
+pub fn identity_rule() -> RewriteRule {
+    RewriteRule::new().with_artist_name(SdRule::new(r"^(Radiohead)$", "$1"))
+}
+
+/// A rules directory config with the default settings, pointing at a fresh temp directory
+#[allow(dead_code)]
+pub fn rules_dir_config(source: RulesSource) -> (PathBuf, RulesDirConfig) {
+    let dir = std::env::temp_dir().join(format!(
+        "scrobble-scrubber-rules-dir-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let config = serde_json::from_value(serde_json::json!({
+        "path": dir.to_string_lossy(),
+        "source": source,
+    }))
+    .unwrap();
+    (dir, config)
+}
@@ -0,0 +1,47 @@
+use scrobble_scrubber::config::{ConfidenceDecision, ConfidenceThresholds, ScrubberConfig};
+
+#[test_log::test]
+fn thresholds_should_split_confidence_into_bands() {
+    let thresholds = ConfidenceThresholds::default();
+
+    assert_eq!(thresholds.decide(1.0), ConfidenceDecision::AutoApply);
+    assert_eq!(thresholds.decide(0.95), ConfidenceDecision::AutoApply);
+    assert_eq!(thresholds.decide(0.9), ConfidenceDecision::Pending);
+    assert_eq!(thresholds.decide(0.7), ConfidenceDecision::Pending);
+    assert_eq!(thresholds.decide(0.5), ConfidenceDecision::Drop);
+}
+
+#[test_log::test]
+fn provider_overrides_should_take_precedence_over_global_thresholds() {
+    let mut config = ScrubberConfig::default();
+    assert_eq!(config.confidence_thresholds_for(&["MusicBrainz"]), None);
+
+    let global = ConfidenceThresholds::default();
+    let strict = ConfidenceThresholds {
+        auto_apply: 0.99,
+        pending: 0.6,
+    };
+    let lenient = ConfidenceThresholds {
+        auto_apply: 0.8,
+        pending: 0.75,
+    };
+    config.confidence_thresholds = Some(global);
+    config
+        .provider_confidence_thresholds
+        .insert("HTTP".to_string(), strict);
+    config
+        .provider_confidence_thresholds
+        .insert("MusicBrainz".to_string(), lenient);
+
+    assert_eq!(config.confidence_thresholds_for(&["OpenAI"]), Some(global));
+    assert_eq!(config.confidence_thresholds_for(&["HTTP"]), Some(strict));
+
+    // Merged suggestions use the strictest bounds of their providers
+    assert_eq!(
+        config.confidence_thresholds_for(&["MusicBrainz", "HTTP"]),
+        Some(ConfidenceThresholds {
+            auto_apply: 0.99,
+            pending: 0.75,
+        })
+    );
+}
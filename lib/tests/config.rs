@@ -1,4 +1,8 @@
-use scrobble_scrubber::config::ScrobbleScrubberConfig;
+use scrobble_scrubber::config::{
+    CompilationToCanonicalConfig, GreatestHitsPolicy, MusicBrainzProviderConfig, RuleFileFormat,
+    RulesSource, ScrobbleScrubberConfig, StorageBackend, StorageConfig,
+};
+use std::path::PathBuf;
 
 #[test_log::test]
 fn should_create_valid_default_configuration() {
@@ -34,3 +38,115 @@ fn should_return_preferred_config_path_when_exists() {
         assert!(path.ends_with("scrobble-scrubber/config.toml"));
     }
 }
+
+#[test_log::test]
+fn greatest_hits_policy_should_decide_remaps_by_release_gap() {
+    assert!(!GreatestHitsPolicy::Keep.allows_remap(Some(2000), Some(1975)));
+    assert!(GreatestHitsPolicy::MapToOriginal.allows_remap(None, None));
+
+    let policy = GreatestHitsPolicy::MapIfOlder { years: 10 };
+    assert!(policy.allows_remap(Some(1985), Some(1975)));
+    assert!(!policy.allows_remap(Some(1981), Some(1975)));
+    // Unknown dates keep the track where it is
+    assert!(!policy.allows_remap(None, Some(1975)));
+}
+
+#[test_log::test]
+fn greatest_hits_policy_should_default_to_mapping_when_missing() {
+    let config: CompilationToCanonicalConfig =
+        serde_json::from_str(r#"{"confidence_threshold": 0.8, "enabled": true}"#).unwrap();
+    assert_eq!(
+        config.greatest_hits_policy,
+        GreatestHitsPolicy::MapToOriginal
+    );
+
+    let config: CompilationToCanonicalConfig = serde_json::from_str(
+        r#"{"confidence_threshold": 0.8, "enabled": true,
+            "greatest_hits_policy": {"mode": "map_if_older", "years": 15}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.greatest_hits_policy,
+        GreatestHitsPolicy::MapIfOlder { years: 15 }
+    );
+}
+
+#[test_log::test]
+fn musicbrainz_config_should_load_with_the_removed_api_delay() {
+    let config: MusicBrainzProviderConfig = serde_json::from_str(
+        r#"{"confidence_threshold": 0.9, "max_results": 3, "api_delay_ms": 100}"#,
+    )
+    .unwrap();
+    assert_eq!(config.max_results, 3);
+}
+
+#[test_log::test]
+fn musicbrainz_mirror_should_ignore_the_public_server() {
+    let mut config = MusicBrainzProviderConfig::default();
+    assert_eq!(config.mirror_url(), None);
+
+    config.base_url = Some("https://musicbrainz.org/".to_string());
+    assert_eq!(config.mirror_url(), None);
+
+    config.base_url = Some("http://localhost:5000/".to_string());
+    assert_eq!(config.mirror_url(), Some("http://localhost:5000"));
+}
+
+#[test_log::test]
+fn musicbrainz_user_agent_should_include_contact() {
+    let config = MusicBrainzProviderConfig {
+        user_agent: Some("my-backfill/1.0".to_string()),
+        contact: Some("me@example.com".to_string()),
+        ..MusicBrainzProviderConfig::default()
+    };
+
+    assert_eq!(
+        config.user_agent_header(),
+        "my-backfill/1.0 ( me@example.com )"
+    );
+    assert!(MusicBrainzProviderConfig::default()
+        .user_agent_header()
+        .starts_with("scrobble-scrubber/"));
+}
+
+#[test_log::test]
+fn storage_should_default_to_pickledb_with_sqlite_next_to_state_file() {
+    let config: StorageConfig =
+        serde_json::from_str(r#"{"state_file": "/data/scrobble_state.db"}"#).unwrap();
+    assert_eq!(config.backend, StorageBackend::Pickledb);
+    assert_eq!(
+        config.sqlite_file_path(),
+        PathBuf::from("/data/scrobble_state.sqlite")
+    );
+
+    let config: StorageConfig = serde_json::from_str(
+        r#"{"state_file": "/data/scrobble_state.db", "backend": "sqlite",
+            "sqlite_file": "/other/state.sqlite"}"#,
+    )
+    .unwrap();
+    assert_eq!(config.backend, StorageBackend::Sqlite);
+    assert_eq!(
+        config.sqlite_file_path(),
+        PathBuf::from("/other/state.sqlite")
+    );
+}
+
+#[test_log::test]
+fn rules_dir_should_only_be_watched_as_source_of_truth() {
+    let config: StorageConfig = serde_json::from_str(
+        r#"{"state_file": "state.db", "rules_dir": {"path": "rules", "watch": true}}"#,
+    )
+    .unwrap();
+    let rules_dir = config.rules_dir.unwrap();
+    assert_eq!(rules_dir.source, RulesSource::Storage);
+    assert_eq!(rules_dir.format, RuleFileFormat::Toml);
+    assert_eq!(rules_dir.watch_interval_secs, 10);
+    assert!(!rules_dir.watches());
+
+    let config: StorageConfig = serde_json::from_str(
+        r#"{"state_file": "state.db",
+            "rules_dir": {"path": "rules", "source": "directory", "watch": true}}"#,
+    )
+    .unwrap();
+    assert!(config.rules_dir.unwrap().watches());
+}
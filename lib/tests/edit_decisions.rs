@@ -0,0 +1,100 @@
+use lastfm_edit::Track;
+use scrobble_scrubber::persistence::{
+    EditDecision, EditDecisionsState, MemoryStorage, PendingEdit, StateStorage,
+};
+use scrobble_scrubber::rewrite::{create_no_op_edit, RuleField};
+
+mod common;
+
+fn scrobble_at(timestamp: u64) -> Track {
+    Track {
+        album: Some("Pablo Honey".to_string()),
+        timestamp: Some(timestamp),
+        ..common::track("Creep - 2009 Remaster", "Radiohead")
+    }
+}
+
+fn renamed(track: &Track, new_name: &str) -> PendingEdit {
+    let mut edit = create_no_op_edit(track);
+    RuleField::TrackName.set_edit_value(&mut edit, new_name.to_string());
+    PendingEdit::from_edit(track, &edit)
+}
+
+#[test_log::test]
+fn pending_edit_should_only_keep_changed_fields() {
+    let edit = renamed(&scrobble_at(1_700_000_000), "Creep");
+
+    assert_eq!(edit.new_track_name.as_deref(), Some("Creep"));
+    assert_eq!(edit.new_artist_name, None);
+    assert_eq!(edit.new_album_name, None);
+    assert_eq!(edit.original_album_name.as_deref(), Some("Pablo Honey"));
+}
+
+#[test_log::test(tokio::test)]
+async fn decisions_should_apply_to_other_scrobbles_of_the_same_change() {
+    let mut storage = MemoryStorage::new();
+    storage
+        .record_edit_decision(
+            &renamed(&scrobble_at(1_700_000_000), "Creep"),
+            EditDecision::Rejected,
+        )
+        .await
+        .unwrap();
+
+    let decisions = storage.load_edit_decisions_state().await.unwrap();
+    let later_scrobble = scrobble_at(1_700_086_400);
+    assert_eq!(
+        decisions.decision_for(&renamed(&later_scrobble, "Creep")),
+        Some(EditDecision::Rejected)
+    );
+    // A different change to the same track hasn't been decided on
+    assert_eq!(
+        decisions.decision_for(&renamed(&later_scrobble, "Creep (Remastered)")),
+        None
+    );
+
+    let rejected = decisions.rejected_as_pending_edits();
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0].decision, Some(EditDecision::Rejected));
+    assert_eq!(rejected[0].new_track_name.as_deref(), Some("Creep"));
+}
+
+#[test_log::test]
+fn later_decision_should_replace_earlier_one() {
+    let edit = renamed(&scrobble_at(1_700_000_000), "Creep");
+    let mut decisions = EditDecisionsState::default();
+
+    decisions.record(&edit, EditDecision::Rejected, chrono::Utc::now());
+    decisions.record(&edit, EditDecision::Approved, chrono::Utc::now());
+
+    assert_eq!(decisions.decisions.len(), 1);
+    assert_eq!(decisions.decision_for(&edit), Some(EditDecision::Approved));
+    assert!(decisions.rejected_as_pending_edits().is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn approving_a_pending_edit_should_remove_it_and_remember_the_decision() {
+    let mut storage = MemoryStorage::new();
+    let edit = renamed(&scrobble_at(1_700_000_000), "Creep");
+    storage.add_pending_edit(&edit).await.unwrap();
+    assert_eq!(
+        storage
+            .load_pending_edit(&edit.id)
+            .await
+            .unwrap()
+            .map(|e| e.id),
+        Some(edit.id.clone())
+    );
+
+    let approved = storage.approve_pending_edit(&edit.id).await.unwrap();
+    assert_eq!(approved.map(|e| e.id), Some(edit.id.clone()));
+    assert!(storage.load_pending_edit(&edit.id).await.unwrap().is_none());
+    assert!(storage
+        .reject_pending_edit(&edit.id)
+        .await
+        .unwrap()
+        .is_none());
+
+    let decisions = storage.load_edit_decisions_state().await.unwrap();
+    assert_eq!(decisions.decision_for(&edit), Some(EditDecision::Approved));
+}
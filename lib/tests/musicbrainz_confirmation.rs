@@ -356,3 +356,34 @@ async fn weeknd_starboy_deluxe() {
     )
     .await;
 }
+
+#[test_log::test(tokio::test)]
+async fn default_deluxe_rule_keeps_bonus_tracks_on_deluxe_album() {
+    // The default "(Deluxe)" rule only strips the suffix for tracks on the standard edition
+    let rule: RewriteRule = scrobble_scrubber::default_rules::load_all_default_rules()
+        .expect("default rules should load")
+        .into_iter()
+        .find(|rule| rule.name == "Remove Deluxe")
+        .expect("default rules should include 'Remove Deluxe'")
+        .into();
+    assert!(rule.requires_musicbrainz_confirmation);
+
+    check_mb_confirmation_rule(
+        rule,
+        "The Weeknd",
+        "Starboy (Deluxe)",
+        vec![
+            TrackTestCase {
+                track_name: "Starboy".to_string(),
+                should_be_renamed: true,
+                expected_album: Some("Starboy".to_string()),
+            },
+            TrackTestCase {
+                track_name: "Stargirl Interlude (Extended)".to_string(),
+                should_be_renamed: false,
+                expected_album: Some("Starboy".to_string()),
+            },
+        ],
+    )
+    .await;
+}
@@ -0,0 +1,179 @@
+use lastfm_edit::Track;
+use scrobble_scrubber::persistence::{PendingEdit, PendingRewriteRule};
+use scrobble_scrubber::rewrite::{create_no_op_edit, RewriteRule, RuleField, SdRule};
+use scrobble_scrubber::scrub_action_provider::{
+    ActionProviderError, ChainScrubActionProvider, OrScrubActionProvider,
+    RewriteRulesScrubActionProvider, ScrubActionProvider, ScrubActionSuggestion,
+    SuggestionWithContext,
+};
+
+fn track(name: &str, artist: &str) -> Track {
+    Track {
+        name: name.to_string(),
+        artist: artist.to_string(),
+        album: Some("Pablo Honey (Remastered)".to_string()),
+        album_artist: None,
+        playcount: 1,
+        timestamp: Some(1_700_000_000),
+    }
+}
+
+fn remaster_rules() -> RewriteRulesScrubActionProvider {
+    RewriteRulesScrubActionProvider::from_rules(vec![
+        RewriteRule::new().with_track_name(SdRule::new(r"^(.+) - \d{4} Remaster$", "$1")),
+        RewriteRule::new().with_album_name(SdRule::new(r"^(.+) \(Remastered\)$", "$1")),
+    ])
+}
+
+/// Looks up exact track names, like a metadata database would
+struct LookupProvider;
+
+#[async_trait::async_trait]
+impl ScrubActionProvider for LookupProvider {
+    type Error = ActionProviderError;
+
+    async fn analyze_tracks(
+        &self,
+        tracks: &[Track],
+        _pending_edits: Option<&[PendingEdit]>,
+        _pending_rules: Option<&[PendingRewriteRule]>,
+    ) -> Result<Vec<(usize, Vec<SuggestionWithContext>)>, Self::Error> {
+        let mut results = Vec::new();
+        for (index, track) in tracks.iter().enumerate() {
+            if track.name == "Creep" {
+                let mut edit = create_no_op_edit(track);
+                RuleField::AlbumName.set_edit_value(&mut edit, "Pablo Honey".to_string());
+                RuleField::AlbumArtistName.set_edit_value(&mut edit, "Radiohead".to_string());
+                results.push((
+                    index,
+                    vec![SuggestionWithContext::edit_with_confirmation(
+                        edit,
+                        true,
+                        self.provider_name().to_string(),
+                    )],
+                ));
+            }
+        }
+        Ok(results)
+    }
+
+    fn provider_name(&self) -> &str {
+        "Lookup"
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn chain_should_pass_rewritten_tracks_to_later_providers() {
+    let chain = ChainScrubActionProvider::new()
+        .add_provider(remaster_rules())
+        .add_provider(LookupProvider);
+    let tracks = vec![
+        track("Creep - 2009 Remaster", "Radiohead"),
+        track("Anyone Can Play Guitar", "Radiohead"),
+    ];
+
+    let results = chain.analyze_tracks(&tracks, None, None).await.unwrap();
+
+    assert_eq!(results.len(), 2);
+    let (index, suggestions) = &results[0];
+    assert_eq!(*index, 0);
+    assert_eq!(suggestions.len(), 1, "expected a single merged edit");
+    let suggestion = &suggestions[0];
+    let ScrubActionSuggestion::Edit(edit) = &suggestion.suggestion else {
+        panic!("Expected an edit, got {:?}", suggestion.suggestion);
+    };
+    assert_eq!(edit.track_name.as_deref(), Some("Creep"));
+    assert_eq!(edit.album_name.as_deref(), Some("Pablo Honey"));
+    assert_eq!(edit.album_artist_name.as_deref(), Some("Radiohead"));
+    assert_eq!(
+        edit.track_name_original.as_deref(),
+        Some("Creep - 2009 Remaster")
+    );
+    assert_eq!(
+        edit.album_name_original.as_deref(),
+        Some("Pablo Honey (Remastered)")
+    );
+
+    // The lookup only confirmed the album the rewrite rules had already cleaned, so it
+    // didn't change any field and its confirmation requirement doesn't apply
+    assert_eq!(
+        suggestion.provenance.get(RuleField::TrackName),
+        Some("RewriteRules")
+    );
+    assert_eq!(
+        suggestion.provenance.get(RuleField::AlbumName),
+        Some("RewriteRules")
+    );
+    assert_eq!(suggestion.provenance.get(RuleField::AlbumArtistName), None);
+    assert!(!suggestion.requires_confirmation);
+    assert_eq!(suggestion.contributing_providers(), vec!["RewriteRules"]);
+
+    // The second track only had its album cleaned
+    let ScrubActionSuggestion::Edit(edit) = &results[1].1[0].suggestion else {
+        panic!("Expected an edit");
+    };
+    assert_eq!(edit.track_name.as_deref(), Some("Anyone Can Play Guitar"));
+    assert_eq!(edit.album_name.as_deref(), Some("Pablo Honey"));
+}
+
+#[test_log::test(tokio::test)]
+async fn chain_should_record_provenance_across_providers() {
+    let chain = ChainScrubActionProvider::new()
+        .add_provider(RewriteRulesScrubActionProvider::from_rules(vec![
+            RewriteRule::new().with_track_name(SdRule::new(r"^(.+) - \d{4} Remaster$", "$1")),
+        ]))
+        .add_provider(LookupProvider);
+
+    let results = chain
+        .analyze_tracks(&[track("Creep - 2009 Remaster", "Radiohead")], None, None)
+        .await
+        .unwrap();
+
+    let suggestion = &results[0].1[0];
+    let ScrubActionSuggestion::Edit(edit) = &suggestion.suggestion else {
+        panic!("Expected an edit");
+    };
+    assert_eq!(edit.track_name.as_deref(), Some("Creep"));
+    assert_eq!(edit.album_name.as_deref(), Some("Pablo Honey"));
+    assert_eq!(
+        suggestion.provenance.to_string(),
+        "track_name by RewriteRules, album_name by Lookup"
+    );
+    assert!(suggestion.requires_confirmation);
+    assert_eq!(
+        suggestion.contributing_providers(),
+        vec!["RewriteRules", "Lookup"]
+    );
+}
+
+#[test_log::test(tokio::test)]
+async fn or_provider_judges_original_tracks_unless_chained() {
+    let tracks = [track("Creep - 2009 Remaster", "Radiohead")];
+    let rules = || {
+        RewriteRulesScrubActionProvider::from_rules(vec![
+            RewriteRule::new().with_track_name(SdRule::new(r"^(.+) - \d{4} Remaster$", "$1"))
+        ])
+    };
+
+    let or_provider = OrScrubActionProvider::new()
+        .add_provider(rules())
+        .add_provider(LookupProvider);
+    let results = or_provider
+        .analyze_tracks(&tracks, None, None)
+        .await
+        .unwrap();
+    assert_eq!(results[0].1.len(), 1);
+    assert_eq!(results[0].1[0].provider_name, "RewriteRules");
+
+    let chained = OrScrubActionProvider::new()
+        .add_provider(rules())
+        .add_provider(LookupProvider)
+        .chained();
+    let results = chained.analyze_tracks(&tracks, None, None).await.unwrap();
+    assert_eq!(results[0].1.len(), 1);
+    assert_eq!(results[0].1[0].provider_name, "ChainProvider");
+    assert_eq!(
+        results[0].1[0].contributing_providers(),
+        vec!["RewriteRules", "Lookup"]
+    );
+}
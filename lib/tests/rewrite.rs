@@ -1,6 +1,8 @@
 use lastfm_edit::{ScrobbleEdit, Track};
 use scrobble_scrubber::rewrite::{
-    any_rules_apply, apply_all_rules, create_no_op_edit, RewriteRule, SdRule,
+    any_rules_apply, apply_all_rules, create_no_op_edit, default_rules,
+    load_comprehensive_default_rules, CompiledRuleSet, ReplacementMode, RewriteRule, RuleCondition,
+    RuleField, SdRule,
 };
 
 #[test_log::test]
@@ -58,6 +60,27 @@ fn should_use_capture_groups_in_replacement() {
     );
 }
 
+#[test_log::test]
+fn should_expand_replacements_as_before_filters_existed() {
+    // Groups that didn't participate stay literal, and `$12` is group 1 followed by "2"
+    let rule = SdRule::new(r"^(.+?)( \(Live\))?$", "$1$2 [$12]");
+    assert_eq!(rule.apply("Song").unwrap(), "Song$2 [Song2]");
+
+    // Default rules with optional groups only reference the groups that always match
+    let rules = load_comprehensive_default_rules();
+    let lp_version = rules
+        .iter()
+        .find(|rule| rule.name.as_deref() == Some("Remove Remastered LP Version"))
+        .and_then(|rule| rule.track_name.as_ref())
+        .expect("default rule should exist");
+    assert_eq!(
+        lp_version
+            .apply("They Reminisce Over You - Remastered LP Version")
+            .unwrap(),
+        "They Reminisce Over You"
+    );
+}
+
 #[test_log::test]
 fn should_leave_track_unchanged_when_no_match() {
     let track = Track {
@@ -1028,3 +1051,344 @@ fn handles_dot_star_special_case_in_scrobble_edit() {
 
     println!("✅ Dot star pattern special case tests passed!");
 }
+
+fn compiled_rule_set_test_tracks() -> Vec<Track> {
+    [
+        (
+            "Heroes - 2017 Remaster",
+            "David Bowie",
+            Some("Heroes (2017 Remaster)"),
+        ),
+        ("Yesterday", "The Beatles", Some("Help! (Remastered)")),
+        ("Clean Song", "Clean Artist", Some("Clean Album")),
+        ("Live Forever (Live at Knebworth)", "Oasis", None),
+        (
+            "song (REMASTERED 2009)",
+            "artist",
+            Some("album (Deluxe Edition)"),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, artist, album)| Track {
+        name: name.to_string(),
+        artist: artist.to_string(),
+        album: album.map(str::to_string),
+        album_artist: None,
+        playcount: 1,
+        timestamp: Some(1_700_000_000),
+    })
+    .collect()
+}
+
+#[test_log::test]
+fn compiled_rule_set_should_agree_with_uncompiled_rules() {
+    let rules = load_comprehensive_default_rules();
+    let rule_set = CompiledRuleSet::new(rules.clone()).unwrap();
+    assert_eq!(rule_set.len(), rules.len());
+
+    for track in compiled_rule_set_test_tracks() {
+        assert_eq!(
+            rule_set.any_rule_matches(&track),
+            any_rules_apply(&rules, &track).unwrap(),
+            "Match result differs for '{}'",
+            track.name
+        );
+
+        let mut expected = create_no_op_edit(&track);
+        let expected_changed = apply_all_rules(&rules, &mut expected).unwrap();
+        let mut actual = create_no_op_edit(&track);
+        let actual_changed = rule_set.apply_all(&mut actual);
+
+        assert_eq!(actual_changed, expected_changed, "for '{}'", track.name);
+        assert_eq!(actual, expected, "Edit differs for '{}'", track.name);
+    }
+}
+
+#[test_log::test]
+fn compiled_rule_set_prefilter_should_respect_flags() {
+    let rules = vec![
+        RewriteRule::new()
+            .with_track_name(SdRule::new("^clean song$", "Case Insensitive").with_flags("i")),
+        RewriteRule::new().with_track_name(SdRule::new("^clean song$", "Case Sensitive")),
+        RewriteRule::new()
+            .with_artist_name(SdRule::new("^Clean Artist$", "Artist Match"))
+            .with_album_name(SdRule::new("Deluxe", "Album Match")),
+    ];
+    let rule_set = CompiledRuleSet::new(rules).unwrap();
+    let track = &compiled_rule_set_test_tracks()[2];
+
+    assert_eq!(rule_set.matching_rules(track), vec![0]);
+    assert_eq!(rule_set.prefilter_track(track), vec![true, false, false]);
+}
+
+#[test_log::test]
+fn compiled_rule_set_should_report_invalid_rules_once() {
+    let rules = vec![
+        RewriteRule::new()
+            .with_name("Broken")
+            .with_track_name(SdRule::new("(unclosed", "")),
+        RewriteRule::new()
+            .with_name("Valid")
+            .with_track_name(SdRule::new(r"^(.*) - \d{4} Remaster$", "$1")),
+    ];
+
+    assert!(CompiledRuleSet::new(rules.clone()).is_err());
+
+    let (rule_set, invalid) = CompiledRuleSet::compile_valid(rules);
+    assert_eq!(rule_set.len(), 1);
+    assert_eq!(invalid.len(), 1);
+    assert_eq!(invalid[0].index, 0);
+    assert_eq!(invalid[0].name.as_deref(), Some("Broken"));
+
+    let mut edit = create_no_op_edit(&compiled_rule_set_test_tracks()[0]);
+    assert!(rule_set.apply_all(&mut edit));
+    assert_eq!(edit.track_name.as_deref(), Some("Heroes"));
+}
+
+#[test_log::test]
+fn should_replace_according_to_replacement_mode() {
+    let input = "Artist ft. One ft. Two";
+
+    let whole = SdRule::new(r" ft\. ", " feat. ");
+    assert_eq!(whole.apply(input).unwrap(), " feat. ");
+
+    let first = SdRule::new(r" ft\. ", " feat. ").with_mode(ReplacementMode::FirstMatch);
+    assert_eq!(first.apply(input).unwrap(), "Artist feat. One ft. Two");
+
+    let all = SdRule::new(r" ft\. ", " feat. ").with_mode(ReplacementMode::AllMatches);
+    assert_eq!(all.apply(input).unwrap(), "Artist feat. One feat. Two");
+
+    // Capture groups expand per match in substring modes
+    let swap = SdRule::new(r"(\d+)-(\d+)", "$2-$1").with_mode(ReplacementMode::AllMatches);
+    assert_eq!(swap.apply("1-2 and 3-4").unwrap(), "2-1 and 4-3");
+
+    // Non-matching input is unchanged in every mode
+    assert_eq!(all.apply("Artist").unwrap(), "Artist");
+}
+
+#[test_log::test]
+fn should_deserialize_rules_without_mode_as_whole_string() {
+    let rule: SdRule =
+        serde_json::from_str(r#"{"find": "a", "replace": "b", "flags": null}"#).unwrap();
+    assert_eq!(rule.mode, ReplacementMode::WholeString);
+
+    // Whole-string rules serialize exactly as before
+    let json = serde_json::to_string(&rule).unwrap();
+    assert!(!json.contains("mode"));
+
+    let all = rule.with_mode(ReplacementMode::AllMatches);
+    let json = serde_json::to_string(&all).unwrap();
+    assert!(json.contains(r#""mode":"all_matches""#));
+    assert_eq!(serde_json::from_str::<SdRule>(&json).unwrap(), all);
+}
+
+#[test_log::test]
+fn replacement_mode_should_display_its_serialized_name() {
+    for mode in [
+        ReplacementMode::WholeString,
+        ReplacementMode::FirstMatch,
+        ReplacementMode::AllMatches,
+    ] {
+        let json = serde_json::to_string(&mode).unwrap();
+        assert_eq!(json, format!("\"{mode}\""));
+    }
+}
+
+#[test_log::test]
+fn default_featuring_rule_should_keep_artist_name() {
+    let track = Track {
+        name: "Song".to_string(),
+        artist: "Artist ft. Someone".to_string(),
+        album: None,
+        album_artist: None,
+        playcount: 1,
+        timestamp: None,
+    };
+
+    let mut edit = create_no_op_edit(&track);
+    apply_all_rules(&default_rules(), &mut edit).unwrap();
+
+    assert_eq!(edit.artist_name, "Artist feat. Someone");
+    assert_eq!(edit.track_name.as_deref(), Some("Song"));
+}
+
+#[test_log::test]
+fn should_apply_template_filters_in_every_mode() {
+    let rule = SdRule::new(
+        r"^(?P<artist>.+) - (.+)$",
+        "${artist:title} - ${2:trim:lower}",
+    );
+    assert_eq!(
+        rule.apply("the rolling stones - ANGIE ").unwrap(),
+        "The Rolling Stones - angie"
+    );
+
+    let rule = SdRule::new(r"\b(\w)", "${1:upper}").with_mode(ReplacementMode::AllMatches);
+    assert_eq!(rule.apply("hey jude").unwrap(), "Hey Jude");
+
+    let rule = SdRule::new(r"é", "${0:strip_diacritics}").with_mode(ReplacementMode::FirstMatch);
+    assert_eq!(rule.apply("Beyoncé Café").unwrap(), "Beyonce Café");
+}
+
+#[test_log::test]
+fn should_reject_unknown_template_filters_when_compiling() {
+    let rule = SdRule::new(r"^(.+)$", "${1:shout}");
+    let error = rule.compile().unwrap_err();
+    assert!(error.to_string().contains("unknown filter 'shout'"));
+
+    let track = Track {
+        name: "Song".to_string(),
+        artist: "Artist".to_string(),
+        album: None,
+        album_artist: None,
+        playcount: 1,
+        timestamp: None,
+    };
+    let rules = vec![RewriteRule::new().with_track_name(rule)];
+    assert!(any_rules_apply(&rules, &track).is_err());
+}
+
+fn deluxe_track(artist: &str, album_artist: Option<&str>) -> Track {
+    Track {
+        name: "Song".to_string(),
+        artist: artist.to_string(),
+        album: Some("Album (Deluxe)".to_string()),
+        album_artist: album_artist.map(str::to_string),
+        playcount: 1,
+        timestamp: None,
+    }
+}
+
+#[test_log::test]
+fn should_not_match_when_unless_pattern_matches() {
+    let rule = RewriteRule::new()
+        .with_album_name(SdRule::new(r" \(Deluxe\)", "").with_mode(ReplacementMode::AllMatches))
+        .with_unless(RuleField::ArtistName, "^(Taylor Swift|Adele)$");
+
+    assert!(rule.matches(&deluxe_track("Radiohead", None)).unwrap());
+    assert!(!rule.matches(&deluxe_track("Adele", None)).unwrap());
+
+    let mut edit = create_no_op_edit(&deluxe_track("Adele", None));
+    assert!(!apply_all_rules(std::slice::from_ref(&rule), &mut edit).unwrap());
+    assert_eq!(edit.album_name.as_deref(), Some("Album (Deluxe)"));
+
+    let mut edit = create_no_op_edit(&deluxe_track("Radiohead", None));
+    assert!(apply_all_rules(&[rule], &mut edit).unwrap());
+    assert_eq!(edit.album_name.as_deref(), Some("Album"));
+}
+
+#[test_log::test]
+fn should_require_all_conditions_to_hold() {
+    let rule = RewriteRule::new()
+        .with_album_name(SdRule::new(r" \(Deluxe\)", "").with_mode(ReplacementMode::AllMatches))
+        .with_condition(
+            RuleCondition::not_matching(RuleField::AlbumArtistName, "^various artists$")
+                .with_flags("i"),
+        )
+        .with_condition(RuleCondition::matching(RuleField::TrackName, "^Song$"));
+
+    assert!(rule.matches(&deluxe_track("Radiohead", None)).unwrap());
+    assert!(!rule
+        .matches(&deluxe_track("Radiohead", Some("Various Artists")))
+        .unwrap());
+
+    let mut other_track = deluxe_track("Radiohead", None);
+    other_track.name = "Other".to_string();
+    assert!(!rule.matches(&other_track).unwrap());
+
+    // A missing field only satisfies negated conditions
+    let mut edit = create_no_op_edit(&deluxe_track("Radiohead", None));
+    edit.album_artist_name = None;
+    assert!(rule.matches_scrobble_edit(&edit).unwrap());
+    edit.track_name = None;
+    assert!(!rule.matches_scrobble_edit(&edit).unwrap());
+}
+
+#[test_log::test]
+fn should_round_trip_exclusions_and_conditions_through_json() {
+    let rule = RewriteRule::new()
+        .with_unless(RuleField::AlbumName, "(?i)live")
+        .with_condition(RuleCondition::not_matching(RuleField::ArtistName, "^$"));
+    let json = serde_json::to_string(&rule).unwrap();
+    let parsed: RewriteRule = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, rule);
+
+    // Rules stored before exclusions existed still load
+    let legacy: RewriteRule = serde_json::from_str(
+        r#"{"name": null, "track_name": null, "album_name": null, "artist_name": null,
+            "album_artist_name": null, "requires_confirmation": false}"#,
+    )
+    .unwrap();
+    assert!(legacy.unless.is_empty());
+    assert!(legacy.conditions.is_empty());
+}
+
+#[test_log::test]
+fn should_move_featured_artist_from_track_to_artist() {
+    let rule = RewriteRule::new()
+        .with_track_name(SdRule::new(
+            r"^(?P<title>.+?) \(feat\. (?P<feat>.+)\)$",
+            "${title}",
+        ))
+        .with_artist_name(SdRule::new(r"^(.+)$", "$1 feat. ${track.feat}"));
+
+    let track = Track {
+        name: "Stay (feat. Justin Bieber)".to_string(),
+        artist: "The Kid LAROI".to_string(),
+        album: None,
+        album_artist: None,
+        playcount: 1,
+        timestamp: None,
+    };
+    let mut edit = create_no_op_edit(&track);
+
+    assert!(apply_all_rules(&[rule], &mut edit).unwrap());
+    assert_eq!(edit.track_name.as_deref(), Some("Stay"));
+    assert_eq!(edit.artist_name, "The Kid LAROI feat. Justin Bieber");
+}
+
+#[test_log::test]
+fn should_split_artist_out_of_track_name() {
+    let rule = RewriteRule::new()
+        .with_track_name(SdRule::new(
+            r"^(?P<artist>.+?) - (?P<title>.+)$",
+            "${title}",
+        ))
+        .with_artist_name(SdRule::new(r"^Unknown$", "${track.artist:trim}"))
+        .with_album_name(SdRule::new(r"^(.*)$", "${track.1} ${album.1}"));
+
+    let track = Track {
+        name: "Daft Punk - Around the World".to_string(),
+        artist: "Unknown".to_string(),
+        album: Some("Homework".to_string()),
+        album_artist: None,
+        playcount: 1,
+        timestamp: None,
+    };
+    let mut edit = create_no_op_edit(&track);
+
+    assert!(apply_all_rules(&[rule], &mut edit).unwrap());
+    assert_eq!(edit.track_name.as_deref(), Some("Around the World"));
+    assert_eq!(edit.artist_name, "Daft Punk");
+    assert_eq!(edit.album_name.as_deref(), Some("Daft Punk Homework"));
+}
+
+#[test_log::test]
+fn should_keep_cross_field_references_to_unknown_fields_literal() {
+    // The album has no pattern, so there is nothing to reference
+    let rule = RewriteRule::new().with_track_name(SdRule::new(r"^(.+)$", "$1 ${album.1}"));
+    let mut edit = create_no_op_edit(&Track {
+        name: "Song".to_string(),
+        artist: "Artist".to_string(),
+        album: Some("Album".to_string()),
+        album_artist: None,
+        playcount: 1,
+        timestamp: None,
+    });
+
+    assert!(apply_all_rules(&[rule], &mut edit).unwrap());
+    assert_eq!(edit.track_name.as_deref(), Some("Song ${album.1}"));
+
+    let rule = RewriteRule::new().with_track_name(SdRule::new(r"^(.+)$", "${album.1:upper}"));
+    assert!(rule.compile().is_err());
+}
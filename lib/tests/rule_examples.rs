@@ -0,0 +1,119 @@
+use common::remaster_rule;
+use scrobble_scrubber::rewrite::{
+    load_comprehensive_default_rules, RewriteRule, RuleField, SdRule,
+};
+use scrobble_scrubber::rule_examples::{
+    check_rule_examples, example_count, ExampleFailure, FieldValues, RuleExample,
+};
+
+mod common;
+
+#[test_log::test]
+fn default_rule_examples_should_all_pass() {
+    let rules = load_comprehensive_default_rules();
+    assert!(
+        example_count(&rules) > rules.len(),
+        "default rules should carry their JSON examples"
+    );
+
+    let failures = check_rule_examples(&rules);
+    let report: Vec<String> = failures
+        .iter()
+        .map(|failure| {
+            let name = rules[failure.rule_index()].name.as_deref().unwrap_or("");
+            format!("{name}: {failure}")
+        })
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "default rule examples failed:\n{}",
+        report.join("\n")
+    );
+}
+
+#[test_log::test]
+fn should_report_examples_the_rule_does_not_produce() {
+    let rules = vec![remaster_rule()
+        .with_example(RuleExample::new(
+            RuleField::TrackName,
+            "Song - 2011 Remaster",
+            "Song",
+        ))
+        .with_example(RuleExample::unchanged(RuleField::TrackName, "Song (Live)"))
+        .with_example(RuleExample::new(
+            RuleField::TrackName,
+            "Song - Remastered",
+            "Song",
+        ))];
+
+    assert_eq!(example_count(&rules), 3);
+    assert_eq!(
+        check_rule_examples(&rules),
+        vec![ExampleFailure::Mismatch {
+            rule_index: 0,
+            example_index: 2,
+            field: RuleField::TrackName,
+            input: Some("Song - Remastered".to_string()),
+            expected: Some("Song".to_string()),
+            actual: Some("Song - Remastered".to_string()),
+        }]
+    );
+}
+
+#[test_log::test]
+fn should_check_multi_field_examples() {
+    let input = FieldValues::new()
+        .with(RuleField::TrackName, "Stay (feat. Justin Bieber)")
+        .with(RuleField::ArtistName, "The Kid LAROI");
+    let rule = RewriteRule::new()
+        .with_track_name(SdRule::new(
+            r"^(?P<title>.+?) \(feat\. (?P<feat>.+)\)$",
+            "${title}",
+        ))
+        .with_artist_name(SdRule::new(r"^(.+)$", "$1 feat. ${track.feat}"))
+        .with_example(RuleExample {
+            input: input.clone(),
+            expected: FieldValues::new()
+                .with(RuleField::TrackName, "Stay")
+                .with(RuleField::ArtistName, "The Kid LAROI feat. Justin Bieber"),
+        })
+        .with_example(RuleExample::unchanged(RuleField::TrackName, "Stay"));
+    assert_eq!(check_rule_examples(std::slice::from_ref(&rule)), Vec::new());
+
+    // Fields left out of `expected` must stay unchanged
+    let rule = rule.with_example(RuleExample {
+        input,
+        expected: FieldValues::new().with(RuleField::TrackName, "Stay"),
+    });
+    assert_eq!(
+        check_rule_examples(&[rule]),
+        vec![ExampleFailure::Mismatch {
+            rule_index: 0,
+            example_index: 2,
+            field: RuleField::ArtistName,
+            input: Some("The Kid LAROI".to_string()),
+            expected: Some("The Kid LAROI".to_string()),
+            actual: Some("The Kid LAROI feat. Justin Bieber".to_string()),
+        }]
+    );
+}
+
+#[test_log::test]
+fn should_round_trip_examples_through_json() {
+    let rule = RewriteRule::new()
+        .with_album_name(SdRule::new(r"^(.+) \(Remastered\)$", "$1"))
+        .with_example(RuleExample::new(
+            RuleField::AlbumName,
+            "Abbey Road (Remastered)",
+            "Abbey Road",
+        ));
+    let json = serde_json::to_string(&rule).unwrap();
+    assert!(json.contains(r#""examples":[{"input":{"album_name":"Abbey Road (Remastered)"}"#));
+
+    let parsed: RewriteRule = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, rule);
+
+    // Rules without examples don't serialize the field
+    let plain = serde_json::to_string(&RewriteRule::new()).unwrap();
+    assert!(!plain.contains("examples"));
+}
@@ -0,0 +1,126 @@
+use chrono::{TimeZone, Utc};
+use common::{identity_rule, remaster_rule, track};
+use scrobble_scrubber::persistence::{MemoryStorage, RuleStatsState, StateStorage};
+use scrobble_scrubber::rewrite::{ReplacementMode, RewriteRule, RuleField, SdRule};
+use scrobble_scrubber::scrub_action_provider::{
+    OrScrubActionProvider, RewriteRulesScrubActionProvider, RuleOutcome, ScrubActionProvider,
+};
+use std::collections::HashSet;
+
+mod common;
+
+#[test_log::test(tokio::test)]
+async fn provider_should_report_rule_activity_per_track() {
+    let provider =
+        RewriteRulesScrubActionProvider::from_rules(vec![remaster_rule(), identity_rule()]);
+    let tracks = vec![
+        track("Creep - 2009 Remaster", "Radiohead"),
+        track("Song", "Someone"),
+    ];
+    provider.analyze_tracks(&tracks, None, None).await.unwrap();
+
+    let activity = provider.take_rule_activity();
+    let summary: Vec<(String, usize, RuleOutcome)> = activity
+        .iter()
+        .map(|a| (a.rule_key.clone(), a.track_index, a.outcome))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (remaster_rule().stats_key(), 0, RuleOutcome::Produced),
+            (identity_rule().stats_key(), 0, RuleOutcome::Unchanged),
+        ]
+    );
+    assert!(activity.iter().all(|a| a.provider_name == "RewriteRules"));
+
+    // Activity is drained once taken
+    assert!(provider.take_rule_activity().is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn or_provider_should_forward_rule_activity() {
+    let provider = OrScrubActionProvider::new().add_provider(
+        RewriteRulesScrubActionProvider::from_rules(vec![remaster_rule()]),
+    );
+    provider
+        .analyze_tracks(&[track("Creep - 2009 Remaster", "Radiohead")], None, None)
+        .await
+        .unwrap();
+
+    let activity = provider.take_rule_activity();
+    assert_eq!(activity.len(), 1);
+    assert_eq!(activity[0].outcome, RuleOutcome::Produced);
+}
+
+#[test_log::test(tokio::test)]
+async fn should_accumulate_and_persist_rule_stats() {
+    let provider =
+        RewriteRulesScrubActionProvider::from_rules(vec![remaster_rule(), identity_rule()]);
+    let creep = track("Creep - 2009 Remaster", "Radiohead");
+    provider
+        .analyze_tracks(std::slice::from_ref(&creep), None, None)
+        .await
+        .unwrap();
+
+    let first = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+    let second = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+    let mut stats = RuleStatsState::default();
+    for activity in provider.take_rule_activity() {
+        stats.record(&activity, &creep, true, first);
+    }
+    provider
+        .analyze_tracks(std::slice::from_ref(&creep), None, None)
+        .await
+        .unwrap();
+    for activity in provider.take_rule_activity() {
+        stats.record(&activity, &creep, false, second);
+    }
+
+    let mut storage = MemoryStorage::new();
+    storage.save_rule_stats_state(&stats).await.unwrap();
+    let loaded = storage.load_rule_stats_state().await.unwrap();
+
+    let remaster = loaded.for_rule(&remaster_rule()).unwrap();
+    assert_eq!(
+        (remaster.matched, remaster.produced, remaster.applied),
+        (2, 2, 1)
+    );
+    assert_eq!(remaster.last_fired, Some(second));
+    assert_eq!(
+        remaster.last_matched_track.as_deref(),
+        Some("Radiohead - Creep - 2009 Remaster")
+    );
+
+    let identity = loaded.for_rule(&identity_rule()).unwrap();
+    assert_eq!((identity.matched, identity.produced), (2, 0));
+    assert_eq!(identity.last_fired, None);
+    assert_eq!(identity.last_matched, Some(second));
+
+    let unused = RewriteRule::new().with_album_name(SdRule::new("x", "y"));
+    assert!(loaded.for_rule(&unused).is_none());
+}
+
+#[test_log::test]
+fn stats_key_should_change_with_anything_affecting_matches() {
+    let rule = remaster_rule();
+    let track_name = rule.track_name.clone().unwrap();
+    let keys: HashSet<String> = [
+        rule.clone(),
+        rule.clone()
+            .with_track_name(track_name.clone().with_flags("i")),
+        rule.clone()
+            .with_track_name(track_name.with_mode(ReplacementMode::AllMatches)),
+        rule.clone()
+            .with_unless(RuleField::ArtistName, "^Radiohead$"),
+    ]
+    .iter()
+    .map(RewriteRule::stats_key)
+    .collect();
+    assert_eq!(keys.len(), 4);
+
+    // Renaming a rule keeps its statistics
+    assert_eq!(
+        rule.clone().with_name("Renamed").stats_key(),
+        rule.stats_key()
+    );
+}
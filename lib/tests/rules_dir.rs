@@ -0,0 +1,109 @@
+use common::{remaster_rule, rules_dir_config, track};
+use scrobble_scrubber::config::{RuleFileFormat, RulesSource};
+use scrobble_scrubber::persistence::{MemoryStorage, RewriteRulesState, StateStorage};
+use scrobble_scrubber::rules_dir::{
+    ensure_rules_editable, export_rules_dir, load_rules_dir, sync_storage_from_dir, RulesDirError,
+};
+use scrobble_scrubber::scrub_action_provider::{
+    OrScrubActionProvider, RewriteRulesScrubActionProvider, ScrubActionProvider,
+};
+
+mod common;
+
+#[test_log::test(tokio::test)]
+async fn directory_source_should_replace_stored_rules() {
+    let (dir, config) = rules_dir_config(RulesSource::Directory);
+    let rules_state = RewriteRulesState {
+        rewrite_rules: vec![remaster_rule()],
+        alias_rules: Vec::new(),
+    };
+    export_rules_dir(&dir, &rules_state, config.format, false).unwrap();
+    let mut storage = MemoryStorage::new();
+
+    let loaded = sync_storage_from_dir(&config, &mut storage).await.unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(loaded.unwrap().rewrite_rules, vec![remaster_rule()]);
+    assert_eq!(
+        storage
+            .load_rewrite_rules_state()
+            .await
+            .unwrap()
+            .rewrite_rules,
+        vec![remaster_rule()]
+    );
+}
+
+#[test_log::test]
+fn export_should_keep_the_order_of_more_than_a_thousand_rules() {
+    let (dir, _) = rules_dir_config(RulesSource::Directory);
+    let rules_state = RewriteRulesState {
+        rewrite_rules: (1..=1001)
+            .map(|n| remaster_rule().with_name(format!("Rule {n}")))
+            .collect(),
+        alias_rules: Vec::new(),
+    };
+
+    let written = export_rules_dir(&dir, &rules_state, RuleFileFormat::Json, false).unwrap();
+    let loaded = load_rules_dir(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(written[0].ends_with("0001-rule-1.json"));
+    assert_eq!(loaded.rewrite_rules, rules_state.rewrite_rules);
+}
+
+#[test_log::test(tokio::test)]
+async fn storage_source_should_leave_stored_rules_alone() {
+    let (_, config) = rules_dir_config(RulesSource::Storage);
+    let mut storage = MemoryStorage::with_initial_rules(RewriteRulesState {
+        rewrite_rules: vec![remaster_rule()],
+        alias_rules: Vec::new(),
+    });
+
+    // The directory doesn't even exist, since it isn't read
+    let loaded = sync_storage_from_dir(&config, &mut storage).await.unwrap();
+
+    assert!(loaded.is_none());
+    assert_eq!(
+        storage
+            .load_rewrite_rules_state()
+            .await
+            .unwrap()
+            .rewrite_rules,
+        vec![remaster_rule()]
+    );
+}
+
+#[test_log::test(tokio::test)]
+async fn reloaded_rules_should_apply_to_later_analysis() {
+    let provider = OrScrubActionProvider::new().add_provider(RewriteRulesScrubActionProvider::new(
+        &RewriteRulesState::default(),
+    ));
+    let tracks = vec![track("Creep - 2009 Remaster", "Radiohead")];
+    assert!(provider
+        .analyze_tracks(&tracks, None, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    provider.reload_rules(&RewriteRulesState {
+        rewrite_rules: vec![remaster_rule()],
+        alias_rules: Vec::new(),
+    });
+
+    let results = provider.analyze_tracks(&tracks, None, None).await.unwrap();
+    assert_eq!(results.len(), 1);
+}
+
+#[test_log::test]
+fn rule_changes_should_be_refused_when_the_directory_is_the_source() {
+    let (_, storage_config) = rules_dir_config(RulesSource::Storage);
+    assert!(ensure_rules_editable(None).is_ok());
+    assert!(ensure_rules_editable(Some(&storage_config)).is_ok());
+
+    let (dir, directory_config) = rules_dir_config(RulesSource::Directory);
+    match ensure_rules_editable(Some(&directory_config)) {
+        Err(RulesDirError::DirectorySource(path)) => assert_eq!(path, dir),
+        other => panic!("expected the directory to be reported, got {other:?}"),
+    }
+}
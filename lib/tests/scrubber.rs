@@ -7,7 +7,7 @@ use mockall::predicate::*;
 use scrobble_scrubber::config::ScrobbleScrubberConfig;
 use scrobble_scrubber::persistence::{MemoryStorage, StateStorage, TimestampState};
 use scrobble_scrubber::scrub_action_provider::{
-    RewriteRulesScrubActionProvider, ScrubActionSuggestion, SuggestionWithContext,
+    EditProvenance, RewriteRulesScrubActionProvider, ScrubActionSuggestion, SuggestionWithContext,
 };
 use scrobble_scrubber::scrubber::ScrobbleScrubber;
 // SerializableTrack is no longer needed - Track is now serializable
@@ -32,6 +32,7 @@ async fn should_create_scrubber_instance_successfully() {
     // Set up action provider (empty rules for this test)
     let rules_state = scrobble_scrubber::persistence::RewriteRulesState {
         rewrite_rules: vec![],
+        alias_rules: vec![],
     };
     let action_provider = RewriteRulesScrubActionProvider::new(&rules_state);
 
@@ -63,6 +64,7 @@ async fn should_not_be_running_when_first_created() {
     let storage = Arc::new(Mutex::new(MemoryStorage::new()));
     let rules_state = scrobble_scrubber::persistence::RewriteRulesState {
         rewrite_rules: vec![],
+        alias_rules: vec![],
     };
     let action_provider = RewriteRulesScrubActionProvider::new(&rules_state);
     let config = ScrobbleScrubberConfig::default();
@@ -93,6 +95,7 @@ async fn should_work_with_mock_lastfm_client() {
     let storage = Arc::new(Mutex::new(MemoryStorage::new()));
     let rules_state = scrobble_scrubber::persistence::RewriteRulesState {
         rewrite_rules: vec![],
+        alias_rules: vec![],
     };
     let action_provider = RewriteRulesScrubActionProvider::new(&rules_state);
     let config = ScrobbleScrubberConfig::default();
@@ -104,6 +107,33 @@ async fn should_work_with_mock_lastfm_client() {
     assert!(!scrubber.is_running().await);
 }
 
+#[test_log::test(tokio::test)]
+async fn dropping_the_scrubber_should_stop_its_event_forwarding() {
+    let mut mock_client = MockLastFmEditClient::new();
+    mock_client.expect_subscribe().returning(|| {
+        let (_, receiver) = tokio::sync::broadcast::channel(100);
+        receiver
+    });
+    let storage = Arc::new(Mutex::new(MemoryStorage::new()));
+    let action_provider = RewriteRulesScrubActionProvider::from_rules(vec![]);
+    let scrubber = ScrobbleScrubber::new(
+        storage,
+        Box::new(mock_client),
+        action_provider,
+        ScrobbleScrubberConfig::default(),
+    );
+    let mut events = scrubber.subscribe_events();
+
+    drop(scrubber);
+
+    // The channel only closes once no forwarding task holds a sender
+    let result = tokio::time::timeout(std::time::Duration::from_secs(5), events.recv()).await;
+    assert!(matches!(
+        result,
+        Ok(Err(tokio::sync::broadcast::error::RecvError::Closed))
+    ));
+}
+
 #[test_log::test(tokio::test)]
 async fn should_process_tracks_in_chronological_order() {
     // Create test tracks with timestamps (API returns newest first)
@@ -451,6 +481,10 @@ impl scrobble_scrubber::scrub_action_provider::ScrubActionProvider for TestActio
                 suggestion: ScrubActionSuggestion::Edit(edit),
                 provider_name: "TestActionProvider".to_string(),
                 requires_confirmation: false,
+                provenance: EditProvenance::default(),
+                confidence: None,
+                explanation: None,
+                reverts: None,
             };
             suggestions.push((index, vec![suggestion_with_context]));
         }
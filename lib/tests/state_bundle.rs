@@ -0,0 +1,143 @@
+use common::remaster_rule;
+use scrobble_scrubber::persistence::bundle::{BundleError, StateBundle};
+use scrobble_scrubber::persistence::migrations::{SchemaError, CURRENT_SCHEMA_VERSION};
+use scrobble_scrubber::persistence::{
+    MemoryStorage, PendingEdit, PendingEditsState, RewriteRulesState, StateStorage, TimestampState,
+};
+
+mod common;
+
+#[test_log::test(tokio::test)]
+async fn bundle_should_round_trip_state_between_storages() {
+    let mut source = MemoryStorage::with_initial_rules(RewriteRulesState {
+        rewrite_rules: vec![remaster_rule()],
+        alias_rules: Vec::new(),
+    });
+    let anchor = chrono::DateTime::from_timestamp(1_700_000_000, 0);
+    source
+        .save_timestamp_state(&TimestampState {
+            last_processed_timestamp: anchor,
+        })
+        .await
+        .unwrap();
+    let edit = PendingEdit::new(
+        "Creep - 2009 Remaster".to_string(),
+        "Radiohead".to_string(),
+        Some("Pablo Honey".to_string()),
+        None,
+        Some("Creep".to_string()),
+        None,
+        None,
+        None,
+        Some(1_700_000_000),
+    );
+    source
+        .save_pending_edits_state(&PendingEditsState {
+            pending_edits: vec![edit.clone()],
+        })
+        .await
+        .unwrap();
+
+    let json = StateBundle::export(&source)
+        .await
+        .unwrap()
+        .to_json()
+        .unwrap();
+    assert!(!json.contains("\"session\""));
+    assert!(!json.contains("\"track_cache\""));
+
+    let bundle = StateBundle::from_json(&json).unwrap();
+    let mut target = MemoryStorage::new();
+    bundle.restore(&mut target).await.unwrap();
+
+    assert_eq!(
+        target
+            .load_rewrite_rules_state()
+            .await
+            .unwrap()
+            .rewrite_rules,
+        vec![remaster_rule()]
+    );
+    assert_eq!(
+        target
+            .load_timestamp_state()
+            .await
+            .unwrap()
+            .last_processed_timestamp,
+        anchor
+    );
+    let pending = target.load_pending_edits_state().await.unwrap();
+    assert_eq!(pending.pending_edits[0].id, edit.id);
+}
+
+#[test_log::test(tokio::test)]
+async fn bundle_without_alias_tables_should_load() {
+    let json = StateBundle::export(&MemoryStorage::new())
+        .await
+        .unwrap()
+        .to_json()
+        .unwrap();
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["rewrite_rules_state"] = serde_json::json!({ "rewrite_rules": [] });
+
+    let bundle = StateBundle::from_json(&value.to_string()).unwrap();
+
+    assert_eq!(bundle.schema_version, CURRENT_SCHEMA_VERSION);
+    assert!(bundle.rewrite_rules_state.alias_rules.is_empty());
+}
+
+#[test_log::test(tokio::test)]
+async fn bundle_from_newer_schema_should_be_refused() {
+    let mut bundle = StateBundle::export(&MemoryStorage::new()).await.unwrap();
+    bundle.schema_version = CURRENT_SCHEMA_VERSION + 1;
+
+    let result = StateBundle::from_json(&bundle.to_json().unwrap());
+
+    assert!(matches!(
+        result,
+        Err(BundleError::Schema(SchemaError::TooNew { .. }))
+    ));
+}
+
+#[test_log::test(tokio::test)]
+async fn backup_should_keep_the_state_a_restore_replaces() {
+    let mut storage = MemoryStorage::with_initial_rules(RewriteRulesState {
+        rewrite_rules: vec![remaster_rule()],
+        alias_rules: Vec::new(),
+    });
+    let dir = std::env::temp_dir().join(format!("scrobble-scrubber-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let backup = StateBundle::backup(&storage, &dir.join("state.db"))
+        .await
+        .unwrap();
+    StateBundle::export(&MemoryStorage::new())
+        .await
+        .unwrap()
+        .restore(&mut storage)
+        .await
+        .unwrap();
+    assert!(storage
+        .load_rewrite_rules_state()
+        .await
+        .unwrap()
+        .rewrite_rules
+        .is_empty());
+
+    StateBundle::read_from(&backup)
+        .unwrap()
+        .restore(&mut storage)
+        .await
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(backup.starts_with(&dir));
+    assert_eq!(
+        storage
+            .load_rewrite_rules_state()
+            .await
+            .unwrap()
+            .rewrite_rules,
+        vec![remaster_rule()]
+    );
+}
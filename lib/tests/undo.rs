@@ -0,0 +1,171 @@
+use lastfm_edit::{EditResponse, MockLastFmEditClient};
+use scrobble_scrubber::config::ScrobbleScrubberConfig;
+use scrobble_scrubber::persistence::{EditDecision, MemoryStorage, PendingEdit, StateStorage};
+use scrobble_scrubber::rewrite::create_no_op_edit;
+use scrobble_scrubber::scrub_action_provider::RewriteRulesScrubActionProvider;
+use scrobble_scrubber::scrubber::ScrobbleScrubber;
+use scrobble_scrubber::undo::{plan_undo, read_edit_log, UndoFilter};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+mod common;
+
+const EDIT_LOG: &str = r#"{"timestamp":"2026-10-01T12:00:00Z","success":true,"error_message":null,"duration_ms":120,"edit":{"timestamp":1700000000,"edit_all":false,"original":{"track_name":"Paranoid Android","artist_name":"radiohead","album_name":"OK Computer","album_artist_name":"radiohead"},"new":{"track_name":"Paranoid Android","artist_name":"Radiohead","album_name":"OK Computer","album_artist_name":"Radiohead"}}}
+{"timestamp":"2026-10-01T12:05:00Z","success":true,"error_message":null,"duration_ms":95,"edit":{"timestamp":1700000100,"edit_all":true,"original":{"track_name":"Creep - 2009 Remaster","artist_name":"Radiohead","album_name":"Pablo Honey","album_artist_name":"Radiohead"},"new":{"track_name":"Creep","artist_name":"Radiohead","album_name":"Pablo Honey","album_artist_name":"Radiohead"}},"origin":{"run_id":"run-1","providers":["RewriteRules"],"rules":["Strip remaster"]}}
+{"timestamp":"2026-10-01T12:0
+"#;
+
+#[test_log::test]
+fn edit_log_should_be_read_and_planned_with_and_without_origins() {
+    let path = std::env::temp_dir().join(format!(
+        "scrobble-scrubber-edits-{}.jsonl",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&path, EDIT_LOG).unwrap();
+    let entries = read_edit_log(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // The truncated last line is skipped
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].origin.is_none());
+
+    let by_artist = plan_undo(entries.clone(), &UndoFilter::new().with_artist("RADIOHEAD"));
+    assert_eq!(by_artist.len(), 2);
+    assert_eq!(
+        by_artist[0].edit.track_name.as_deref(),
+        Some("Creep - 2009 Remaster")
+    );
+    assert_eq!(by_artist[1].edit.artist_name, "radiohead");
+    assert_eq!(by_artist[1].edit.timestamp, Some(1_700_000_000));
+    assert!(!by_artist[1].edit.edit_all);
+
+    // Edits logged before origins were recorded never match a run or rule
+    let by_rule = plan_undo(entries, &UndoFilter::new().with_rule("Strip remaster"));
+    assert_eq!(by_rule.len(), 1);
+    assert_eq!(by_rule[0].entry.origin.as_ref().unwrap().run_id, "run-1");
+}
+
+#[test_log::test(tokio::test)]
+async fn undone_changes_should_be_remembered_as_rejected() {
+    let path = std::env::temp_dir().join(format!(
+        "scrobble-scrubber-edits-{}.jsonl",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&path, EDIT_LOG).unwrap();
+    let entries = read_edit_log(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let plan = plan_undo(entries, &UndoFilter::new().with_run_id("run-1"));
+    assert_eq!(plan.len(), 1);
+
+    let mut client = MockLastFmEditClient::new();
+    client.expect_subscribe().returning(|| {
+        let (_, receiver) = tokio::sync::broadcast::channel(100);
+        receiver
+    });
+    client
+        .expect_username()
+        .returning(|| "test_user".to_string());
+    client.expect_edit_scrobble().times(1).returning(|_| {
+        Ok(EditResponse {
+            individual_results: vec![],
+        })
+    });
+
+    let rule = common::remaster_rule();
+    let storage = Arc::new(Mutex::new(MemoryStorage::new()));
+    let mut config = ScrobbleScrubberConfig::default();
+    config.scrubber.require_confirmation = false;
+    let mut scrubber = ScrobbleScrubber::new(
+        storage.clone(),
+        Box::new(client),
+        RewriteRulesScrubActionProvider::from_rules(vec![rule.clone()]),
+        config,
+    );
+
+    assert_eq!(scrubber.undo_edits(&plan).await.unwrap(), 1);
+
+    // The next sweep makes the same change to the restored scrobble, which is now rejected
+    let mut restored = common::track("Creep - 2009 Remaster", "Radiohead");
+    restored.album = Some("Pablo Honey".to_string());
+    restored.album_artist = Some("Radiohead".to_string());
+    let mut edit = create_no_op_edit(&restored);
+    assert!(rule.apply(&mut edit).unwrap());
+
+    let decisions = storage
+        .lock()
+        .await
+        .load_edit_decisions_state()
+        .await
+        .unwrap();
+    assert_eq!(
+        decisions.decision_for(&PendingEdit::from_edit(&restored, &edit)),
+        Some(EditDecision::Rejected)
+    );
+}
+
+#[test_log::test(tokio::test)]
+async fn approving_a_pending_undo_should_remember_the_change_as_rejected() {
+    let path = std::env::temp_dir().join(format!(
+        "scrobble-scrubber-edits-{}.jsonl",
+        uuid::Uuid::new_v4()
+    ));
+    std::fs::write(&path, EDIT_LOG).unwrap();
+    let entries = read_edit_log(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let plan = plan_undo(entries, &UndoFilter::new().with_run_id("run-1"));
+
+    let mut client = MockLastFmEditClient::new();
+    client.expect_subscribe().returning(|| {
+        let (_, receiver) = tokio::sync::broadcast::channel(100);
+        receiver
+    });
+    client
+        .expect_username()
+        .returning(|| "test_user".to_string());
+    client.expect_edit_scrobble().never();
+
+    let storage = Arc::new(Mutex::new(MemoryStorage::new()));
+    let mut config = ScrobbleScrubberConfig::default();
+    config.scrubber.require_confirmation = true;
+    let mut scrubber = ScrobbleScrubber::new(
+        storage.clone(),
+        Box::new(client),
+        RewriteRulesScrubActionProvider::from_rules(vec![common::remaster_rule()]),
+        config,
+    );
+
+    assert_eq!(scrubber.undo_edits(&plan).await.unwrap(), 0);
+
+    let mut storage = storage.lock().await;
+    let reverted = plan[0].reverted_edit();
+    assert_eq!(
+        storage
+            .load_edit_decisions_state()
+            .await
+            .unwrap()
+            .decision_for(&reverted),
+        None
+    );
+    let pending = storage
+        .load_pending_edits_state()
+        .await
+        .unwrap()
+        .pending_edits;
+    assert_eq!(pending.len(), 1);
+
+    storage
+        .approve_pending_edit(&pending[0].id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let decisions = storage.load_edit_decisions_state().await.unwrap();
+    assert_eq!(
+        decisions.decision_for(&reverted),
+        Some(EditDecision::Rejected)
+    );
+    assert_eq!(
+        decisions.decision_for(&pending[0]),
+        Some(EditDecision::Approved)
+    );
+}
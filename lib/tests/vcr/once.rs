@@ -22,7 +22,10 @@ async fn should_run_end_to_end_processing_with_default_rules() {
 
     // Load all default remaster cleanup rules using existing functionality
     let rewrite_rules = load_comprehensive_default_rules();
-    let rules_state = RewriteRulesState { rewrite_rules };
+    let rules_state = RewriteRulesState {
+        rewrite_rules,
+        alias_rules: Vec::new(),
+    };
 
     log::debug!("Loaded {} default rules", rules_state.rewrite_rules.len());
 
@@ -1,6 +1,9 @@
 use scrobble_scrubber::{
     persistence::RewriteRulesState,
-    rewrite::{RewriteRule, SdRule},
+    rewrite::{
+        CompiledRuleSet, FieldExclusions, ReplacementMode, RewriteRule, RuleCondition, SdRule,
+    },
+    rule_examples::RuleExample,
     scrub_action_provider::{RewriteRulesScrubActionProvider, ScrubActionProvider},
 };
 use serde::{Deserialize, Serialize};
@@ -64,6 +67,12 @@ pub struct JSRewriteRule {
     pub requires_confirmation: bool,
     #[serde(default)]
     pub requires_musicbrainz_confirmation: bool,
+    #[serde(default)]
+    pub unless: FieldExclusions,
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    #[serde(default)]
+    pub examples: Vec<RuleExample>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -71,6 +80,8 @@ pub struct JSSdRule {
     pub find: String,
     pub replace: String,
     pub flags: Option<String>,
+    #[serde(default)]
+    pub mode: ReplacementMode,
 }
 
 impl From<&SdRule> for JSSdRule {
@@ -79,13 +90,14 @@ impl From<&SdRule> for JSSdRule {
             find: sd_rule.find.clone(),
             replace: sd_rule.replace.clone(),
             flags: sd_rule.flags.clone(),
+            mode: sd_rule.mode,
         }
     }
 }
 
 impl From<JSSdRule> for SdRule {
     fn from(js_rule: JSSdRule) -> Self {
-        let mut sd_rule = SdRule::new(&js_rule.find, &js_rule.replace);
+        let mut sd_rule = SdRule::new(&js_rule.find, &js_rule.replace).with_mode(js_rule.mode);
         if let Some(flags) = js_rule.flags {
             sd_rule = sd_rule.with_flags(&flags);
         }
@@ -102,6 +114,9 @@ impl From<&RewriteRule> for JSRewriteRule {
             album_artist_name: rule.album_artist_name.as_ref().map(JSSdRule::from),
             requires_confirmation: rule.requires_confirmation,
             requires_musicbrainz_confirmation: rule.requires_musicbrainz_confirmation,
+            unless: rule.unless.clone(),
+            conditions: rule.conditions.clone(),
+            examples: rule.examples.clone(),
         }
     }
 }
@@ -117,6 +132,9 @@ impl From<JSRewriteRule> for RewriteRule {
             requires_confirmation: js_rule.requires_confirmation,
             requires_musicbrainz_confirmation: js_rule.requires_musicbrainz_confirmation,
             musicbrainz_release_filters: None, // WASM interface doesn't support custom filters yet
+            unless: js_rule.unless,
+            conditions: js_rule.conditions,
+            examples: js_rule.examples,
         }
     }
 }
@@ -197,7 +215,10 @@ pub fn analyze_track_with_rules(rules_json: &str, track_json: &str) -> Result<Js
     let internal_track = js_track_to_internal(&track);
 
     // Create a rewrite rules state
-    let rules_state = RewriteRulesState { rewrite_rules };
+    let rules_state = RewriteRulesState {
+        rewrite_rules,
+        alias_rules: Vec::new(),
+    };
 
     // Create a provider
     let provider = RewriteRulesScrubActionProvider::new(&rules_state);
@@ -238,6 +259,9 @@ pub fn create_simple_rule(
         requires_confirmation: false,
         requires_musicbrainz_confirmation: false,
         musicbrainz_release_filters: None, // WASM interface doesn't support custom filters yet
+        unless: FieldExclusions::new(),
+        conditions: Vec::new(),
+        examples: Vec::new(),
     };
 
     match field {
@@ -333,43 +357,36 @@ pub fn process_tracks_with_rules(tracks_json: &str, rules_json: &str) -> Result<
 
     let rewrite_rules: Vec<RewriteRule> = js_rules.into_iter().map(RewriteRule::from).collect();
 
+    // Compile once up front so invalid patterns are reported a single time
+    let rule_set = CompiledRuleSet::new(rewrite_rules)
+        .map_err(|e| JsValue::from_str(&format!("Failed to compile rules: {e}")))?;
+
     let mut results = Vec::new();
 
     for (index, track) in tracks.iter().enumerate() {
         let internal_track = js_track_to_internal(track);
 
-        // Test each rule against the track
+        // Test each matching rule against the track
         let mut track_results = Vec::new();
 
-        for (rule_index, rule) in rewrite_rules.iter().enumerate() {
-            let applies = match rule.matches(&internal_track) {
-                Ok(applies) => applies,
-                Err(_) => continue,
-            };
-
-            if applies {
-                let mut edit = scrobble_scrubber::rewrite::create_no_op_edit(&internal_track);
-                match scrobble_scrubber::rewrite::apply_all_rules(&[rule.clone()], &mut edit) {
-                    Ok(changed) => {
-                        if changed {
-                            track_results.push(serde_json::json!({
-                                "rule_index": rule_index,
-                                "changed": true,
-                                "edit": {
-                                    "track_name_original": edit.track_name_original,
-                                    "track_name": edit.track_name,
-                                    "artist_name_original": edit.artist_name_original,
-                                    "artist_name": edit.artist_name,
-                                    "album_name_original": edit.album_name_original,
-                                    "album_name": edit.album_name,
-                                    "album_artist_name_original": edit.album_artist_name_original,
-                                    "album_artist_name": edit.album_artist_name,
-                                }
-                            }));
-                        }
+        for rule_index in rule_set.matching_rules(&internal_track) {
+            let rule = &rule_set.rules()[rule_index];
+            let mut edit = scrobble_scrubber::rewrite::create_no_op_edit(&internal_track);
+            if rule.matches_scrobble_edit(&edit) && rule.apply(&mut edit) {
+                track_results.push(serde_json::json!({
+                    "rule_index": rule_index,
+                    "changed": true,
+                    "edit": {
+                        "track_name_original": edit.track_name_original,
+                        "track_name": edit.track_name,
+                        "artist_name_original": edit.artist_name_original,
+                        "artist_name": edit.artist_name,
+                        "album_name_original": edit.album_name_original,
+                        "album_name": edit.album_name,
+                        "album_artist_name_original": edit.album_artist_name_original,
+                        "album_artist_name": edit.album_artist_name,
                     }
-                    Err(_) => continue,
-                }
+                }));
             }
         }
 